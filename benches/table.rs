@@ -0,0 +1,61 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rusty_orm::model::{Column, DataType, Model, Table};
+use rusty_orm::query_builder::SelectQuery;
+use rusty_orm_macros::Model;
+use std::hint::black_box;
+
+/// A derived model, whose table is built once and borrowed by every query.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "users"]
+struct CachedUser {
+    #[column(primary_key = true, auto_increment = true)]
+    id: i32,
+    name: String,
+    email: String,
+    is_active: bool,
+}
+
+/// The same table, built anew for every query as models did before caching.
+struct UncachedUser;
+
+impl Model for UncachedUser {
+    fn table() -> Table {
+        let column = |name: &str, data_type| Column {
+            name: name.to_string(),
+            data_type,
+            is_primary_key: name == "id",
+            is_nullable: false,
+            default: None,
+            is_unique: false,
+            is_auto_increment: name == "id",
+            foreign_key: None,
+        };
+        Table {
+            name: "users".to_string(),
+            schema: None,
+            columns: vec![
+                column("id", DataType::Integer),
+                column("name", DataType::Varchar(255)),
+                column("email", DataType::Varchar(255)),
+                column("is_active", DataType::Boolean),
+            ],
+            unique_constraints: Vec::new(),
+            indexes: Vec::new(),
+        }
+    }
+}
+
+fn build<T: Model>() -> String {
+    SelectQuery::<T>::new().filter_eq("is_active", true).order_by(&["name"]).limit(20).build()
+}
+
+fn table(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build select");
+    group.bench_function("cached table", |b| b.iter(|| black_box(build::<CachedUser>())));
+    group.bench_function("uncached table", |b| b.iter(|| black_box(build::<UncachedUser>())));
+    group.finish();
+}
+
+criterion_group!(benches, table);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+use rusty_orm::{
+    dialect::Dialect, error::OrmError, executor::MockConnection, migration::MigrationRunner,
+};
+use rusty_orm_macros::embed_migrations;
+
+fn main() -> Result<(), OrmError> {
+    // The migrations are read when the example is compiled, so running it
+    // needs no migrations directory
+    let migrations = embed_migrations!("migrations")?;
+
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let report = MigrationRunner::with_migrations(&mut connection, migrations).run()?;
+    println!("Applied migrations: {:?}", report.applied);
+
+    println!("\nExecuted statements:");
+    for statement in connection.statements() {
+        println!("{}", statement);
+    }
+    Ok(())
+}
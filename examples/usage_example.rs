@@ -1,14 +1,18 @@
 use rusty_orm::{
+    dialect::Dialect,
+    executor::{Connection, MockConnection},
     migration::MigrationGenerator,
-    model::{Column, DataType, Model, Table},
+    model::Model,
     query_builder::{DeleteQuery, InsertQuery, SelectQuery, UpdateQuery},
+    row::{FromRow, Row},
 };
-use rusty_orm_macros::Model;
+use rusty_orm_macros::{FromRow, IntoInsert, Model};
 
-#[derive(Model)]
+#[derive(Model, FromRow, IntoInsert)]
 #[table_name = "users"]
+#[allow(dead_code)]
 struct User {
-    #[column(type = "Integer", primary_key = "true")]
+    #[column(type = "Integer", primary_key = "true", auto_increment = true)]
     id: i32,
     #[column(type = "Varchar(100)")]
     name: String,
@@ -33,7 +37,7 @@ fn main() {
 
 
     let select_query = SelectQuery::<User>::new()
-        .select(&["id", "name", "email"])
+        .select(&[User::ID, User::NAME, User::EMAIL])
         .filter("is_active = true")
         .order_by(&["name"])
         .limit(10)
@@ -41,21 +45,63 @@ fn main() {
 
     println!("\nGenerated SELECT Query:\n{}", select_query);
 
+    let paged_query =
+        SelectQuery::<User>::new().filter_eq("is_active", true).order_by(&["name"]).offset(20);
+
+    for dialect in [Dialect::Postgres, Dialect::MySql, Dialect::Sqlite] {
+        println!("\nGenerated SELECT Query for {}:\n{}", dialect, paged_query.build_for(dialect));
+    }
+
+    let user_row = |id: i32, name: &str, email: &str| {
+        Row::new().with("id", id).with("name", name).with("email", email).with("is_active", true)
+    };
+    let mut connection = MockConnection::new(Dialect::Postgres).with_rows(vec![
+        user_row(1, "Alice", "alice@example.com"),
+        user_row(2, "Bob", "bob@example.com"),
+    ]);
+    let (sql, params) = paged_query.build_parameterized_for(connection.dialect());
+    let rows = connection.query(&sql, &params).expect("the mock connection returns its rows");
+    let users = rows.iter().map(User::from_row).collect::<Result<Vec<_>, _>>();
+
+    println!("\nQueried through a mock connection:\n{} with {:?}", sql, params);
+    for user in users.expect("the rows have the columns of a user") {
+        println!("- {} <{}>", user.name, user.email);
+    }
+
   
     let insert_query = InsertQuery::<User>::new()
         .value("name", "Alice")
         .value("email", "alice@example.com")
-        .value("is_active", "true")
+        .value("is_active", true)
         .build();
 
     println!("\nGenerated INSERT Query:\n{}", insert_query);
 
+    let user = User {
+        id: 1,
+        name: "Alice".to_string(),
+        email: "alice@example.com".to_string(),
+        is_active: true,
+    };
+    let insert_from_model = InsertQuery::from_model(&user).build();
+
+    println!("\nGenerated INSERT Query from a model:\n{}", insert_from_model);
+
 
     let update_query =
         UpdateQuery::<User>::new().set("email", "alice_new@example.com").filter("id = 1").build();
 
     println!("\nGenerated UPDATE Query:\n{}", update_query);
 
+    let update_from_model = UpdateQuery::from_model(&user).only(&["email"]).build();
+
+    println!("\nGenerated UPDATE Query from a model:\n{}", update_from_model);
+
+    let update_with_expr =
+        UpdateQuery::<User>::new().set_expr("email", "LOWER(email)").filter("id = 1").build();
+
+    println!("\nGenerated UPDATE Query with an expression:\n{}", update_with_expr);
+
 
     let delete_query = DeleteQuery::<User>::new().filter("id = 1").build();
 
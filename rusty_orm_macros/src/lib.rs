@@ -2,102 +2,744 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::ext::IdentExt;
 use syn::{
-    parse_macro_input, Data, DeriveInput, Lit, Meta, MetaList, MetaNameValue, NestedMeta, Type,
+    parse_macro_input, Data, DeriveInput, Lit, LitStr, Meta, MetaList, MetaNameValue, NestedMeta,
+    Type,
 };
 
 /// Procedural macro to derive the `Model` trait for a struct.
 ///
 /// Usage:
-/// ```rust
+/// ```ignore
 /// #[derive(Model)]
-/// #[table_name = "users"] // Optional: specify table name
+/// #[table_name = "users"] // Optional: specify table name, or "schema.table"
 /// struct User {
-///     #[column(type = "Integer", primary_key = "true")]
+///     #[column(type = "Integer", primary_key = true)] // or primary_key = "true", or primary_key
 ///     id: i32,
-///     #[column(type = "Varchar(100)")]
+///     #[column(length = 100)] // Same as type = "Varchar(100)"
 ///     name: String,
 ///     email: String, // Defaults to Varchar(255)
+///     #[column(name = "signupDate")] // Optional: column name other than the field name
+///     signup_date: String,
+///     #[column(skip)] // Not stored in the database
+///     display_name: String,
 /// }
 /// ```
-#[proc_macro_derive(Model, attributes(table_name, column))]
+///
+/// Rows are bound and read by deriving `IntoInsert` and `FromRow` as well,
+/// which need every stored field to implement `ToValue` and `FromValue`.
+///
+/// Multi-column unique constraints and indexes are declared on the struct:
+/// `#[model(unique = "email, tenant_id")]` and
+/// `#[model(index(name = "idx_users_email", columns = "email", unique, method = "btree"))]`.
+/// A single column is indexed with `#[column(index)]`; index names default to
+/// `idx_<table>_<columns>`. Column names can be converted from the field
+/// names with `#[model(rename_all = "camelCase")]` (or `snake_case`,
+/// `SCREAMING_SNAKE_CASE`, `PascalCase`); `#[column(name = "...")]` wins.
+///
+/// Each column name is also available as a constant named after the field
+/// in uppercase, e.g. `User::EMAIL`.
+///
+/// Relationships to other models are declared on the struct with
+/// `#[belongs_to(model = "User", foreign_key = "user_id")]` and
+/// `#[has_many(model = "Post", foreign_key = "user_id")]`. Each generates a
+/// marker type named after both models, e.g. `PostUser`, to pass to
+/// `EagerLoader::with`. The foreign key defaults to `<model>_id`, after the
+/// model it points to; `related_key` names the column it references, by
+/// default the primary key or the column given by the foreign key metadata.
+///
+/// Generic structs and structs with lifetimes are supported. Fields typed
+/// after a type parameter map to `VARCHAR(255)` unless given a
+/// `#[column(type = "...")]`.
+#[proc_macro_derive(Model, attributes(table_name, schema, column, model, belongs_to, has_many))]
 pub fn derive_model(input: TokenStream) -> TokenStream {
-
     let input = parse_macro_input!(input as DeriveInput);
 
-
     let name = input.ident.clone();
 
-
     let table_name = match get_table_name(&input) {
-        Some(name) => name,
-        None => name.to_string().to_lowercase(),
+        Ok(Some(name)) => name,
+        Ok(None) => name.to_string().to_lowercase(),
+        Err(e) => return e.to_compile_error().into(),
     };
 
+    // A `schema.table` name is split into its schema and table; an explicit
+    // `#[schema = "..."]` takes precedence
+    let (schema, table_name) = match table_name.rsplit_once('.') {
+        Some((schema, table)) => (Some(schema.to_string()), table.to_string()),
+        None => (None, table_name),
+    };
+    let schema = match get_schema(&input) {
+        Ok(explicit) => match explicit.or(schema) {
+            Some(schema) => quote! { Some(#schema.to_string()) },
+            None => quote! { None },
+        },
+        Err(e) => return e.to_compile_error().into(),
+    };
 
     let columns = match get_columns(&input) {
         Ok(cols) => cols,
         Err(e) => return e.to_compile_error().into(),
     };
 
+    let options = match get_model_options(&input, &columns) {
+        Ok(options) => options,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let unique_constraints = options.unique_constraints.iter().map(|names| {
+        quote! { vec![#(#names.to_string()),*], }
+    });
+
+    // Single-column indexes from `#[column(index)]`, then those declared on the struct
+    let column_indexes = columns.iter().filter(|col| col.is_indexed).map(|col| IndexInfo {
+        name: None,
+        columns: vec![col.name.clone()],
+        is_unique: false,
+        method: None,
+    });
+    let indexes = column_indexes.chain(options.indexes).map(|index| {
+        let index_name =
+            index.name.unwrap_or_else(|| format!("idx_{}_{}", table_name, index.columns.join("_")));
+        let index_columns = &index.columns;
+        let is_unique = index.is_unique;
+        let method = match &index.method {
+            Some(method) => quote! { Some(#method) },
+            None => quote! { None },
+        };
+        quote! {
+            Index {
+                name: #index_name.to_string(),
+                columns: vec![#(#index_columns.to_string()),*],
+                is_unique: #is_unique,
+                method: #method,
+            },
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // `User::EMAIL` for the `email` field, holding its column name
+    let column_consts = columns.iter().map(|col| {
+        let col_name = &col.name;
+        let const_name = syn::Ident::new(
+            &col.field.unraw().to_string().to_uppercase(),
+            col.field.span(),
+        );
+        let doc = format!("The `{}` column.", col_name);
+        quote! {
+            #[doc = #doc]
+            pub const #const_name: &'static str = #col_name;
+        }
+    });
 
     let column_defs = columns.iter().map(|col| {
         let col_name = &col.name;
         let data_type = &col.data_type;
         let is_pk = col.is_primary_key;
+        let is_nullable = col.is_nullable;
+        let is_unique = col.is_unique;
+        let is_auto_increment = col.is_auto_increment;
+        let foreign_key = match &col.foreign_key {
+            Some(foreign_key) => quote! { Some(#foreign_key) },
+            None => quote! { None },
+        };
+        let default = match &col.default {
+            Some(default) => quote! { Some(#default) },
+            None => quote! { None },
+        };
         quote! {
             Column {
                 name: #col_name.to_string(),
                 data_type: #data_type,
                 is_primary_key: #is_pk,
+                is_nullable: #is_nullable,
+                default: #default,
+                is_unique: #is_unique,
+                is_auto_increment: #is_auto_increment,
+                foreign_key: #foreign_key,
             },
         }
     });
 
-    // Generate the implementation of the Model trait
+    let relationships = match get_relationships(&input) {
+        Ok(relationships) => relationships,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    // Generate the column name constants and the implementation of the
+    // Model trait, scoped so the generated code doesn't depend on the
+    // caller's imports
     let expanded = quote! {
-        impl Model for #name {
-            fn table() -> Table {
-                Table {
-                    name: #table_name.to_string(),
-                    columns: vec![
-                        #(#column_defs)*
-                    ],
+        const _: () = {
+            use ::rusty_orm::model::{
+                Column, ColumnDefault, DataType, ForeignKey, Index, IndexMethod, Model,
+                ReferentialAction, Table, Value,
+            };
+
+            #[allow(dead_code)]
+            impl #impl_generics #name #ty_generics #where_clause {
+                #(#column_consts)*
+            }
+
+            // Built on first use and shared by every query on the model; the
+            // schema doesn't depend on the struct's generic parameters
+            static TABLE: ::std::sync::LazyLock<Table> = ::std::sync::LazyLock::new(|| Table {
+                name: #table_name.to_string(),
+                schema: #schema,
+                columns: vec![
+                    #(#column_defs)*
+                ],
+                unique_constraints: vec![
+                    #(#unique_constraints)*
+                ],
+                indexes: vec![
+                    #(#indexes)*
+                ],
+            });
+
+            impl #impl_generics Model for #name #ty_generics #where_clause {
+                fn table() -> Table {
+                    Table::clone(&TABLE)
+                }
+
+                fn table_ref() -> ::std::borrow::Cow<'static, Table> {
+                    ::std::borrow::Cow::Borrowed(&TABLE)
                 }
             }
-        }
+        };
+
+        #(#relationships)*
     };
 
     // Convert into a TokenStream and return
     TokenStream::from(expanded)
 }
 
+/// Procedural macro to derive the `IntoInsert` trait for a struct, binding
+/// each field to the column `#[derive(Model)]` maps it to.
+///
+/// Usage:
+/// ```ignore
+/// #[derive(Model, IntoInsert)]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i32,
+///     name: String,
+/// }
+/// ```
+///
+/// Every stored field must implement `ToValue`; fields of other types can
+/// still derive `Model` alone. Generic structs get the impl for any type
+/// parameters their fields can be bound with.
+///
+/// Whether an instance is new, and saved with an INSERT, is told by
+/// `#[model(is_new = "path")]` naming a `fn(&Self) -> bool`, such as
+/// `"Self::is_draft"`; by default, instances with an auto-increment key of 0
+/// or NULL are new.
+#[proc_macro_derive(IntoInsert, attributes(column, model))]
+pub fn derive_into_insert(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let columns = match get_columns(&input) {
+        Ok(cols) => cols,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let is_new = match get_is_new(&input) {
+        Ok(Some(path)) => quote! {
+            fn is_new(&self) -> bool {
+                #path(self)
+            }
+        },
+        Ok(None) => quote! {},
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let insert_values = columns.iter().map(|col| {
+        let col_name = &col.name;
+        let field = &col.field;
+        quote! {
+            (#col_name.to_string(), ToValue::to_value(&self.#field)),
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let mut where_clause = input.generics.clone().make_where_clause().clone();
+    if !input.generics.params.is_empty() {
+        for col in &columns {
+            let ty = &col.ty;
+            where_clause.predicates.push(syn::parse_quote! { #ty: ToValue });
+        }
+    }
+
+    let expanded = quote! {
+        const _: () = {
+            use ::rusty_orm::model::{IntoInsert, ToValue, Value};
+
+            impl #impl_generics IntoInsert for #name #ty_generics #where_clause {
+                fn insert_values(&self) -> Vec<(String, Value)> {
+                    vec![
+                        #(#insert_values)*
+                    ]
+                }
+
+                #is_new
+            }
+        };
+    };
+    TokenStream::from(expanded)
+}
+
+/// Procedural macro to derive the `FromRow` trait for a struct, reading each
+/// field from the column `#[derive(Model)]` maps it to.
+///
+/// Usage:
+/// ```ignore
+/// #[derive(Model, FromRow)]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i32,
+///     name: String,
+///     #[column(skip)] // Filled in with its Default
+///     display_name: String,
+/// }
+/// ```
+///
+/// Every stored field must implement `FromValue`, and every skipped one
+/// `Default`. The struct doesn't need to be a model, so query results can
+/// also be read into structs of their own. Generic structs get the impl for
+/// any type parameters their fields can be read into.
+#[proc_macro_derive(FromRow, attributes(column, model))]
+pub fn derive_from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let columns = match get_columns(&input) {
+        Ok(cols) => cols,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    // Skipped fields are left to their Default when reading rows
+    let row_fields = columns.iter().map(|col| {
+        let col_name = &col.name;
+        let field = &col.field;
+        quote! { #field: row.try_get(#col_name)?, }
+    });
+    let (skipped_fields, skipped_types): (Vec<_>, Vec<_>) = match &input.data {
+        Data::Struct(data_struct) => data_struct
+            .fields
+            .iter()
+            .filter(|field| !columns.iter().any(|col| Some(&col.field) == field.ident.as_ref()))
+            .map(|field| (field.ident.clone().unwrap(), field.ty.clone()))
+            .unzip(),
+        _ => (Vec::new(), Vec::new()),
+    };
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let mut where_clause = input.generics.clone().make_where_clause().clone();
+    if !input.generics.params.is_empty() {
+        for col in &columns {
+            let ty = &col.ty;
+            where_clause.predicates.push(syn::parse_quote! { #ty: ::rusty_orm::row::FromValue });
+        }
+        for ty in &skipped_types {
+            where_clause.predicates.push(syn::parse_quote! { #ty: ::core::default::Default });
+        }
+    }
+
+    let expanded = quote! {
+        const _: () = {
+            use ::rusty_orm::row::{FromRow, Row, RowError};
+
+            impl #impl_generics FromRow for #name #ty_generics #where_clause {
+                fn from_row(row: &Row) -> Result<Self, RowError> {
+                    Ok(Self {
+                        #(#row_fields)*
+                        #(#skipped_fields: ::core::default::Default::default(),)*
+                    })
+                }
+            }
+        };
+    };
+    TokenStream::from(expanded)
+}
+
+/// Generates a marker type implementing `Relationship` for each
+/// `#[belongs_to(...)]` and `#[has_many(...)]` struct attribute.
+fn get_relationships(input: &DeriveInput) -> Result<Vec<proc_macro2::TokenStream>, syn::Error> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let mut relationships = Vec::new();
+    for attr in &input.attrs {
+        let has_many = attr.path.is_ident("has_many");
+        if !has_many && !attr.path.is_ident("belongs_to") {
+            continue;
+        }
+        let kind = if has_many { "has_many" } else { "belongs_to" };
+        let Meta::List(MetaList { nested, .. }) = attr.parse_meta()? else {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!("Expected #[{}(model = \"...\")]", kind),
+            ));
+        };
+
+        let mut model = None;
+        let mut foreign_key = None;
+        let mut related_key = None;
+        for nested_meta in nested.iter() {
+            let NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) = nested_meta
+            else {
+                return Err(unknown_attribute(nested_meta, kind));
+            };
+            if path.is_ident("model") {
+                let path: syn::Path = syn::parse_str(&parse_str(lit)?)
+                    .map_err(|_| syn::Error::new_spanned(lit, "Expected a model type"))?;
+                model = Some(path);
+            } else if path.is_ident("foreign_key") {
+                foreign_key = Some(parse_str(lit)?);
+            } else if path.is_ident("related_key") {
+                related_key = Some(parse_str(lit)?);
+            } else {
+                return Err(unknown_attribute(nested_meta, kind));
+            }
+        }
+        let model = model.ok_or_else(|| {
+            syn::Error::new_spanned(attr, format!("Expected model = \"...\" in {}", kind))
+        })?;
+        let model_name = model.segments.last().unwrap().ident.to_string();
+
+        let marker = syn::Ident::new(&format!("{}{}", name, model_name), name.span());
+        let doc = format!("The `{}` relationship of `{}` to `{}`.", kind, name, model_name);
+        // The foreign key is named after the model it points to: this one
+        // for has_many, the related one for belongs_to
+        let (relation_kind, referenced) = if has_many {
+            (quote! { HasMany }, name.to_string())
+        } else {
+            (quote! { BelongsTo }, model_name.clone())
+        };
+        let foreign_key = foreign_key
+            .unwrap_or_else(|| format!("{}_id", RenameRule::Snake.apply(&referenced)));
+        let related_key = match related_key {
+            Some(related_key) => quote! { Some(#related_key.to_string()) },
+            None => quote! { None },
+        };
+
+        relationships.push(quote! {
+            #[doc = #doc]
+            #vis struct #marker;
+
+            impl ::rusty_orm::eager_loading::Relationship for #marker {
+                type RelatedModel = #model;
+
+                const KIND: ::rusty_orm::eager_loading::RelationKind =
+                    ::rusty_orm::eager_loading::RelationKind::#relation_kind;
+
+                fn foreign_key() -> Option<String> {
+                    Some(#foreign_key.to_string())
+                }
+
+                fn related_key() -> Option<String> {
+                    #related_key
+                }
+            }
+        });
+    }
+    Ok(relationships)
+}
+
+/// Extracts the schema from the `#[schema = "..."]` struct attribute.
+fn get_schema(input: &DeriveInput) -> Result<Option<String>, syn::Error> {
+    get_string_attribute(input, "schema")
+}
+
 /// Extracts the table name from the struct attributes.
-fn get_table_name(input: &DeriveInput) -> Option<String> {
+fn get_table_name(input: &DeriveInput) -> Result<Option<String>, syn::Error> {
+    get_string_attribute(input, "table_name")
+}
+
+/// Extracts the value of a `#[name = "..."]` struct attribute.
+fn get_string_attribute(input: &DeriveInput, name: &str) -> Result<Option<String>, syn::Error> {
+    for attr in &input.attrs {
+        if !attr.path.is_ident(name) {
+            continue;
+        }
+        return match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue { lit, .. })) => parse_str(&lit).map(Some),
+            _ => Err(syn::Error::new_spanned(attr, format!("Expected #[{} = \"...\"]", name))),
+        };
+    }
+    Ok(None)
+}
+
+/// Reports an attribute that isn't recognized inside `#[kind(...)]`.
+fn unknown_attribute(nested_meta: &NestedMeta, kind: &str) -> syn::Error {
+    let message = match nested_meta {
+        NestedMeta::Meta(meta) => match meta.path().get_ident() {
+            Some(ident) => format!("Unknown {} attribute `{}`", kind, ident),
+            None => format!("Unknown {} attribute", kind),
+        },
+        NestedMeta::Lit(_) => format!("Expected a key in {} attribute", kind),
+    };
+    syn::Error::new_spanned(nested_meta, message)
+}
+
+/// Table-level options declared with `#[model(...)]` struct attributes.
+struct ModelOptions {
+    unique_constraints: Vec<Vec<String>>,
+    indexes: Vec<IndexInfo>,
+}
+
+/// Represents an index during macro processing.
+struct IndexInfo {
+    name: Option<String>,
+    columns: Vec<String>,
+    is_unique: bool,
+    /// Tokens constructing an `IndexMethod`, if one was given.
+    method: Option<proc_macro2::TokenStream>,
+}
+
+/// Extracts the multi-column unique constraints (`unique = "a, b"`) and
+/// indexes (`index(columns = "a, b", ...)`) from `#[model(...)]` struct
+/// attributes, checking that every named column exists.
+fn get_model_options(
+    input: &DeriveInput,
+    columns: &[ColumnInfo],
+) -> Result<ModelOptions, syn::Error> {
+    let mut options = ModelOptions { unique_constraints: Vec::new(), indexes: Vec::new() };
+    for attr in &input.attrs {
+        if !attr.path.is_ident("model") {
+            continue;
+        }
+        if let Meta::List(MetaList { nested, .. }) = attr.parse_meta()? {
+            for nested_meta in nested.iter() {
+                match nested_meta {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. }))
+                        if path.is_ident("unique") =>
+                    {
+                        options.unique_constraints.push(parse_column_list(lit, columns)?);
+                    }
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("index") => {
+                        options.indexes.push(parse_index(list, columns)?);
+                    }
+                    // Read by `get_rename_rule` and `get_is_new`
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, .. }))
+                        if path.is_ident("rename_all") || path.is_ident("is_new") => {}
+                    other => return Err(unknown_attribute(other, "model")),
+                }
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Parses `index(name = "...", columns = "a, b", unique, method = "gin")`.
+fn parse_index(list: &MetaList, columns: &[ColumnInfo]) -> Result<IndexInfo, syn::Error> {
+    let mut index = IndexInfo { name: None, columns: Vec::new(), is_unique: false, method: None };
+    for nested_meta in list.nested.iter() {
+        match nested_meta {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("unique") => {
+                index.is_unique = true;
+            }
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) => {
+                if path.is_ident("name") {
+                    index.name = Some(parse_str(lit)?);
+                } else if path.is_ident("columns") {
+                    index.columns = parse_column_list(lit, columns)?;
+                } else if path.is_ident("unique") {
+                    index.is_unique = parse_bool(lit)?;
+                } else if path.is_ident("method") {
+                    index.method = Some(parse_index_method(lit)?);
+                } else {
+                    return Err(unknown_attribute(nested_meta, "index"));
+                }
+            }
+            other => return Err(unknown_attribute(other, "index")),
+        }
+    }
+    if index.columns.is_empty() {
+        return Err(syn::Error::new_spanned(list, "Expected columns = \"a, b\" in index"));
+    }
+    Ok(index)
+}
+
+/// Parses a comma-separated list of column names, checking that each exists.
+fn parse_column_list(lit: &Lit, columns: &[ColumnInfo]) -> Result<Vec<String>, syn::Error> {
+    let Lit::Str(lit_str) = lit else {
+        return Err(syn::Error::new_spanned(lit, "Expected a comma-separated list of columns"));
+    };
+    let names: Vec<String> =
+        lit_str.value().split(',').map(|name| name.trim().to_string()).collect();
+    for name in &names {
+        if !columns.iter().any(|col| &col.name == name) {
+            return Err(syn::Error::new_spanned(lit, format!("Unknown column: {}", name)));
+        }
+    }
+    Ok(names)
+}
+
+/// Parses an index method such as `"btree"` or `"gin"`.
+fn parse_index_method(lit: &Lit) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let method = match lit {
+        Lit::Str(lit_str) => lit_str.value().to_lowercase(),
+        _ => String::new(),
+    };
+    match method.as_str() {
+        "btree" => Ok(quote! { IndexMethod::BTree }),
+        "hash" => Ok(quote! { IndexMethod::Hash }),
+        "gin" => Ok(quote! { IndexMethod::Gin }),
+        "gist" => Ok(quote! { IndexMethod::Gist }),
+        _ => Err(syn::Error::new_spanned(
+            lit,
+            "Expected one of \"btree\", \"hash\", \"gin\" or \"gist\"",
+        )),
+    }
+}
+
+/// The casing applied to every column name by `#[model(rename_all = "...")]`.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Snake,
+    ScreamingSnake,
+    Camel,
+    Pascal,
+}
+
+impl RenameRule {
+    /// Converts a field name to this casing.
+    fn apply(self, name: &str) -> String {
+        let words = split_words(name);
+        match self {
+            RenameRule::Snake => {
+                words.iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("_")
+            }
+            RenameRule::ScreamingSnake => {
+                words.iter().map(|word| word.to_uppercase()).collect::<Vec<_>>().join("_")
+            }
+            RenameRule::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word.to_lowercase() } else { capitalize(word) })
+                .collect(),
+            RenameRule::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+        }
+    }
+}
+
+/// Extracts the `#[model(rename_all = "...")]` struct attribute.
+fn get_rename_rule(input: &DeriveInput) -> Result<Option<RenameRule>, syn::Error> {
+    let mut rule = None;
     for attr in &input.attrs {
-        if let Ok(meta) = attr.parse_meta() {
-            if let Meta::NameValue(MetaNameValue { path, lit, .. }) = meta {
-                if path.is_ident("table_name") {
-                    if let Lit::Str(lit_str) = lit {
-                        return Some(lit_str.value());
+        if !attr.path.is_ident("model") {
+            continue;
+        }
+        if let Meta::List(MetaList { nested, .. }) = attr.parse_meta()? {
+            for nested_meta in nested.iter() {
+                if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) =
+                    nested_meta
+                {
+                    if path.is_ident("rename_all") {
+                        let casing = match lit {
+                            Lit::Str(lit_str) => lit_str.value(),
+                            _ => String::new(),
+                        };
+                        rule = Some(match casing.as_str() {
+                            "snake_case" => RenameRule::Snake,
+                            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+                            "camelCase" => RenameRule::Camel,
+                            "PascalCase" => RenameRule::Pascal,
+                            _ => {
+                                return Err(syn::Error::new_spanned(
+                                    lit,
+                                    "Expected one of \"snake_case\", \"SCREAMING_SNAKE_CASE\", \"camelCase\" or \"PascalCase\"",
+                                ))
+                            }
+                        });
                     }
                 }
             }
         }
     }
-    None
+    Ok(rule)
+}
+
+/// Extracts the function named by the `#[model(is_new = "...")]` struct attribute.
+fn get_is_new(input: &DeriveInput) -> Result<Option<syn::ExprPath>, syn::Error> {
+    let mut is_new = None;
+    for attr in &input.attrs {
+        if !attr.path.is_ident("model") {
+            continue;
+        }
+        if let Meta::List(MetaList { nested, .. }) = attr.parse_meta()? {
+            for nested_meta in nested.iter() {
+                if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) =
+                    nested_meta
+                {
+                    if path.is_ident("is_new") {
+                        let path = syn::parse_str(&parse_str(lit)?).map_err(|_| {
+                            syn::Error::new_spanned(lit, "Expected a path to a function")
+                        })?;
+                        is_new = Some(path);
+                    }
+                }
+            }
+        }
+    }
+    Ok(is_new)
+}
+
+/// Splits a name into words at underscores and case changes, keeping runs
+/// of capitals together: `user_ID2` and `userId2` both become `user`, `ID2`
+/// or `Id2`, and `HTTPServer` becomes `HTTP`, `Server`.
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            if i > 0 && c.is_uppercase() {
+                let prev = chars[i - 1];
+                let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+                if !prev.is_uppercase() || next_is_lower {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            word.push(c);
+        }
+        words.push(word);
+    }
+    words
+}
+
+/// Uppercases the first character of a word and lowercases the rest.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
 }
 
 /// Represents a column during macro processing.
 struct ColumnInfo {
     name: String,
+    field: syn::Ident,
+    ty: Type,
     data_type: proc_macro2::TokenStream,
     is_primary_key: bool,
+    is_nullable: bool,
+    is_unique: bool,
+    is_auto_increment: bool,
+    is_indexed: bool,
+    /// Tokens constructing a `ForeignKey`, if the column references another table.
+    foreign_key: Option<proc_macro2::TokenStream>,
+    /// Tokens constructing a `ColumnDefault`, if the column has a default.
+    default: Option<proc_macro2::TokenStream>,
 }
 
 /// Extracts column information from the struct fields.
 fn get_columns(input: &DeriveInput) -> Result<Vec<ColumnInfo>, syn::Error> {
     let mut columns = Vec::new();
+    let rename_rule = get_rename_rule(input)?;
 
     let fields = match &input.data {
         Data::Struct(data_struct) => &data_struct.fields,
@@ -107,13 +749,33 @@ fn get_columns(input: &DeriveInput) -> Result<Vec<ColumnInfo>, syn::Error> {
     };
 
     for field in fields.iter() {
-        let field_name = field.ident.as_ref().unwrap().to_string();
+        let field_ident = field.ident.clone().unwrap();
+        let mut column_name = match rename_rule {
+            Some(rule) => rule.apply(&field_ident.to_string()),
+            None => field_ident.to_string(),
+        };
+
+        // Option<T> fields are nullable and typed after T
+        let (ty, mut is_nullable) = match option_inner_type(&field.ty) {
+            Some(inner) => (inner, true),
+            None => (&field.ty, false),
+        };
 
         // Default data type based on Rust type
-        let default_data_type = map_rust_type_to_sql(&field.ty);
+        let default_data_type = map_rust_type_to_sql(ty);
 
         let mut data_type = default_data_type.clone();
         let mut is_primary_key = false;
+        let mut default = None;
+        let mut is_unique = false;
+        let mut is_auto_increment = false;
+        let mut is_indexed = false;
+        let mut references = None;
+        let mut on_delete = None;
+        let mut on_update = None;
+
+        let mut skip = false;
+        let mut other_attributes = 0;
 
         // Check for custom column attributes
         for attr in &field.attrs {
@@ -121,42 +783,257 @@ fn get_columns(input: &DeriveInput) -> Result<Vec<ColumnInfo>, syn::Error> {
                 let meta = attr.parse_meta()?;
                 if let Meta::List(MetaList { nested, .. }) = meta {
                     for nested_meta in nested.iter() {
+                        if let NestedMeta::Meta(Meta::Path(path)) = nested_meta {
+                            if path.is_ident("skip") {
+                                skip = true;
+                                continue;
+                            }
+                            // Flags are shorthand for `flag = true`
+                            if path.is_ident("index") {
+                                is_indexed = true;
+                            } else if path.is_ident("primary_key") {
+                                is_primary_key = true;
+                            } else if path.is_ident("unique") {
+                                is_unique = true;
+                            } else if path.is_ident("auto_increment") {
+                                is_auto_increment = true;
+                            } else if path.is_ident("nullable") {
+                                is_nullable = true;
+                            } else {
+                                return Err(unknown_attribute(nested_meta, "column"));
+                            }
+                            other_attributes += 1;
+                            continue;
+                        }
+                        other_attributes += 1;
                         if let NestedMeta::Meta(Meta::NameValue(MetaNameValue {
                             path, lit, ..
                         })) = nested_meta
                         {
-                            if path.is_ident("type") {
-                                if let Lit::Str(lit_str) = lit {
-                                    data_type = parse_sql_type(&lit_str.value())?;
-                                }
+                            if path.is_ident("name") {
+                                column_name = parse_str(lit)?;
+                            } else if path.is_ident("type") {
+                                data_type = parse_sql_type(&parse_str(lit)?, lit.span())?;
+                            } else if path.is_ident("raw_type") {
+                                data_type = parse_custom_type(&parse_str(lit)?, lit.span())?;
+                            } else if path.is_ident("length") {
+                                let length = parse_usize(lit)?;
+                                data_type = quote! { DataType::Varchar(#length) };
+                            } else if path.is_ident("default") {
+                                default = Some(parse_default(lit)?);
+                            } else if path.is_ident("default_expr") {
+                                let expr = parse_str(lit)?;
+                                default = Some(quote! { ColumnDefault::Expr(#expr.to_string()) });
+                            } else if path.is_ident("unique") {
+                                is_unique = parse_bool(lit)?;
+                            } else if path.is_ident("auto_increment") {
+                                is_auto_increment = parse_bool(lit)?;
+                            } else if path.is_ident("references") {
+                                references = Some(parse_references(lit)?);
+                            } else if path.is_ident("on_delete") {
+                                on_delete = Some(parse_referential_action(lit)?);
+                            } else if path.is_ident("on_update") {
+                                on_update = Some(parse_referential_action(lit)?);
+                            } else if path.is_ident("nullable") {
+                                is_nullable = parse_bool(lit)?;
                             } else if path.is_ident("primary_key") {
-                                if let Lit::Str(lit_str) = lit {
-                                    is_primary_key =
-                                        lit_str.value().parse::<bool>().unwrap_or(false);
-                                }
+                                is_primary_key = parse_bool(lit)?;
+                            } else {
+                                return Err(unknown_attribute(nested_meta, "column"));
                             }
+                        } else {
+                            return Err(unknown_attribute(nested_meta, "column"));
                         }
                     }
+                } else {
+                    return Err(syn::Error::new_spanned(attr, "Expected #[column(...)]"));
                 }
             }
         }
 
-        columns.push(ColumnInfo { name: field_name, data_type, is_primary_key });
+        if skip {
+            if other_attributes > 0 {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "A skipped field cannot have other column attributes",
+                ));
+            }
+            continue;
+        }
+
+        if references.is_none() && (on_delete.is_some() || on_update.is_some()) {
+            return Err(syn::Error::new_spanned(
+                field,
+                "on_delete and on_update require a `references` attribute",
+            ));
+        }
+        let foreign_key = references.map(|(table, column)| {
+            let on_delete = match on_delete {
+                Some(action) => quote! { Some(#action) },
+                None => quote! { None },
+            };
+            let on_update = match on_update {
+                Some(action) => quote! { Some(#action) },
+                None => quote! { None },
+            };
+            quote! {
+                ForeignKey {
+                    table: #table.to_string(),
+                    column: #column.to_string(),
+                    on_delete: #on_delete,
+                    on_update: #on_update,
+                }
+            }
+        });
+
+        columns.push(ColumnInfo {
+            name: column_name,
+            field: field_ident,
+            ty: field.ty.clone(),
+            data_type,
+            is_primary_key,
+            is_nullable,
+            is_unique,
+            is_auto_increment,
+            is_indexed,
+            foreign_key,
+            default,
+        });
     }
 
     Ok(columns)
 }
 
+/// Returns `T` if the type is `Option<T>`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parses a string attribute value.
+fn parse_str(lit: &Lit) -> Result<String, syn::Error> {
+    match lit {
+        Lit::Str(lit_str) => Ok(lit_str.value()),
+        _ => Err(syn::Error::new_spanned(lit, "Expected a string literal")),
+    }
+}
+
+/// Parses a non-negative integer attribute value, written either as `100` or `"100"`.
+fn parse_usize(lit: &Lit) -> Result<usize, syn::Error> {
+    let error = || syn::Error::new_spanned(lit, "Expected a non-negative integer");
+    match lit {
+        Lit::Int(lit_int) => lit_int.base10_parse::<usize>().map_err(|_| error()),
+        Lit::Str(lit_str) => lit_str.value().parse::<usize>().map_err(|_| error()),
+        _ => Err(error()),
+    }
+}
+
+/// Parses a boolean attribute value, written either as `true` or `"true"`.
+fn parse_bool(lit: &Lit) -> Result<bool, syn::Error> {
+    match lit {
+        Lit::Bool(lit_bool) => Ok(lit_bool.value),
+        Lit::Str(lit_str) => lit_str
+            .value()
+            .parse::<bool>()
+            .map_err(|_| syn::Error::new_spanned(lit, "Expected `true` or `false`")),
+        _ => Err(syn::Error::new_spanned(lit, "Expected `true` or `false`")),
+    }
+}
+
+/// Parses a `"table.column"` foreign key reference.
+fn parse_references(lit: &Lit) -> Result<(String, String), syn::Error> {
+    let error = || syn::Error::new_spanned(lit, "Expected references = \"table.column\"");
+    let Lit::Str(lit_str) = lit else {
+        return Err(error());
+    };
+    let value = lit_str.value();
+    match value.rsplit_once('.') {
+        Some((table, column)) if !table.is_empty() && !column.is_empty() => {
+            Ok((table.to_string(), column.to_string()))
+        }
+        _ => Err(error()),
+    }
+}
+
+/// Parses an `on_delete` or `on_update` action such as `"cascade"` or `"set null"`.
+fn parse_referential_action(lit: &Lit) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let action = match lit {
+        Lit::Str(lit_str) => lit_str.value().to_lowercase().replace('_', " "),
+        _ => String::new(),
+    };
+    match action.trim() {
+        "cascade" => Ok(quote! { ReferentialAction::Cascade }),
+        "set null" => Ok(quote! { ReferentialAction::SetNull }),
+        "set default" => Ok(quote! { ReferentialAction::SetDefault }),
+        "restrict" => Ok(quote! { ReferentialAction::Restrict }),
+        "no action" => Ok(quote! { ReferentialAction::NoAction }),
+        _ => Err(syn::Error::new_spanned(
+            lit,
+            "Expected one of \"cascade\", \"set null\", \"set default\", \"restrict\" or \"no action\"",
+        )),
+    }
+}
+
+/// Parses a literal column default: strings become text, and numbers and
+/// booleans keep their type.
+fn parse_default(lit: &Lit) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let value = match lit {
+        Lit::Str(lit_str) => {
+            let value = lit_str.value();
+            quote! { Value::Text(#value.to_string()) }
+        }
+        Lit::Int(lit_int) => {
+            let value = lit_int.base10_parse::<i64>()?;
+            quote! { Value::BigInt(#value) }
+        }
+        Lit::Float(lit_float) => {
+            let value = lit_float.base10_parse::<f64>()?;
+            quote! { Value::Float(#value) }
+        }
+        Lit::Bool(lit_bool) => {
+            let value = lit_bool.value;
+            quote! { Value::Bool(#value) }
+        }
+        _ => return Err(syn::Error::new_spanned(lit, "Unsupported default value")),
+    };
+    Ok(quote! { ColumnDefault::Value(#value) })
+}
+
 /// Maps Rust types to SQL data types.
 fn map_rust_type_to_sql(ty: &Type) -> proc_macro2::TokenStream {
     match ty {
         Type::Path(type_path) => {
-            let ident = &type_path.path.segments.last().unwrap().ident;
-            match ident.to_string().as_str() {
-                "i32" | "i64" => quote! { DataType::Integer },
+            let segment = type_path.path.segments.last().unwrap();
+            match segment.ident.to_string().as_str() {
+                // Unsigned types map to the smallest signed type holding their range;
+                // u64 and usize values above i64::MAX cannot be stored.
+                "i8" | "i16" | "u8" => quote! { DataType::SmallInt },
+                "i32" | "u16" => quote! { DataType::Integer },
+                "i64" | "u32" | "u64" | "isize" | "usize" => quote! { DataType::BigInt },
                 "String" => quote! { DataType::Varchar(255) },
                 "bool" => quote! { DataType::Boolean },
                 "f32" | "f64" => quote! { DataType::Float },
+                "Vec" if is_vec_of_u8(segment) => quote! { DataType::Blob },
+                // `serde_json::Value`, or `Value` imported from serde_json
+                "Value" => quote! { DataType::Json },
+                // chrono and uuid types, bound through the `chrono` and `uuid` features
+                "NaiveDate" => quote! { DataType::Date },
+                "NaiveTime" => quote! { DataType::Time },
+                "NaiveDateTime" => quote! { DataType::DateTime },
+                "DateTime" => quote! { DataType::TimestampTz },
+                "Uuid" => quote! { DataType::Uuid },
                 _ => quote! { DataType::Varchar(255) }, //todo better here handling 
             }
         }
@@ -164,36 +1041,134 @@ fn map_rust_type_to_sql(ty: &Type) -> proc_macro2::TokenStream {
     }
 }
 
+/// Returns whether a `Vec<...>` path segment is `Vec<u8>`.
+fn is_vec_of_u8(segment: &syn::PathSegment) -> bool {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => matches!(
+            args.args.first(),
+            Some(syn::GenericArgument::Type(Type::Path(inner))) if inner.path.is_ident("u8")
+        ),
+        _ => false,
+    }
+}
+
 /// Parses a custom SQL type from a string.
-fn parse_sql_type(type_str: &str) -> Result<proc_macro2::TokenStream, syn::Error> {
+fn parse_sql_type(
+    type_str: &str,
+    span: proc_macro2::Span,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
     if type_str.starts_with("Varchar") {
-    
-        let start = type_str.find('(').ok_or_else(|| {
-            syn::Error::new(
-                proc_macro2::Span::call_site(),
-                "Expected '(' in Varchar type definition",
-            )
-        })?;
-        let end = type_str.find(')').ok_or_else(|| {
-            syn::Error::new(
-                proc_macro2::Span::call_site(),
-                "Expected ')' in Varchar type definition",
-            )
-        })?;
+        let start = type_str
+            .find('(')
+            .ok_or_else(|| syn::Error::new(span, "Expected '(' in Varchar type definition"))?;
+        let end = type_str
+            .find(')')
+            .ok_or_else(|| syn::Error::new(span, "Expected ')' in Varchar type definition"))?;
         let size_str = &type_str[start + 1..end];
-        let size: usize = size_str.parse().map_err(|_| {
-            syn::Error::new(proc_macro2::Span::call_site(), "Failed to parse size for Varchar")
-        })?;
+        let size: usize = size_str
+            .parse()
+            .map_err(|_| syn::Error::new(span, "Failed to parse size for Varchar"))?;
         Ok(quote! { DataType::Varchar(#size) })
+    } else if type_str.starts_with("Custom") {
+        let raw = type_str
+            .strip_prefix("Custom(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| syn::Error::new(span, "Expected Custom(sql_type)"))?;
+        parse_custom_type(raw, span)
+    } else if type_str.starts_with("Decimal") {
+        let args = type_str
+            .strip_prefix("Decimal(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| syn::Error::new(span, "Expected Decimal(precision, scale)"))?;
+        let (precision, scale) = args.split_once(',').ok_or_else(|| {
+            syn::Error::new(span, "Expected both precision and scale in Decimal type definition")
+        })?;
+        let parse = |arg: &str| {
+            arg.trim().parse::<u32>().map_err(|_| {
+                syn::Error::new(span, "Failed to parse precision or scale for Decimal")
+            })
+        };
+        let (precision, scale) = (parse(precision)?, parse(scale)?);
+        Ok(quote! { DataType::Decimal(#precision, #scale) })
     } else {
         match type_str {
+            "SmallInt" => Ok(quote! { DataType::SmallInt }),
             "Integer" => Ok(quote! { DataType::Integer }),
+            "BigInt" => Ok(quote! { DataType::BigInt }),
             "Boolean" => Ok(quote! { DataType::Boolean }),
             "Float" => Ok(quote! { DataType::Float }),
-            other => Err(syn::Error::new(
-                proc_macro2::Span::call_site(),
-                format!("Unsupported data type: {}", other),
-            )),
+            "Text" => Ok(quote! { DataType::Text }),
+            "Date" => Ok(quote! { DataType::Date }),
+            "Time" => Ok(quote! { DataType::Time }),
+            "DateTime" | "Timestamp" => Ok(quote! { DataType::DateTime }),
+            "TimestampTz" => Ok(quote! { DataType::TimestampTz }),
+            "Uuid" => Ok(quote! { DataType::Uuid }),
+            "Blob" | "Bytea" => Ok(quote! { DataType::Blob }),
+            "Json" => Ok(quote! { DataType::Json }),
+            "Jsonb" => Ok(quote! { DataType::Jsonb }),
+            other => Err(syn::Error::new(span, format!("Unsupported data type: {}", other))),
+        }
+    }
+}
+
+/// Parses a database-specific type name, emitted verbatim in DDL.
+fn parse_custom_type(
+    raw: &str,
+    span: proc_macro2::Span,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(syn::Error::new(span, "Custom type name must not be empty"));
+    }
+    Ok(quote! { DataType::Custom(#raw.to_string()) })
+}
+
+/// Procedural macro to embed the migrations saved in a directory in the binary.
+///
+/// Usage:
+/// ```ignore
+/// let migrations = embed_migrations!("migrations")?;
+/// MigrationRunner::with_migrations(connection, migrations).run()?;
+/// ```
+///
+/// The path is relative to the crate's manifest directory. The `.json`,
+/// `.up.sql` and `.down.sql` files in it are read at compile time, and the
+/// macro evaluates to the result of `Migration::from_embedded` over them.
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    let files = match get_migration_files(&path) {
+        Ok(files) => files,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    // include_str! makes the crate rebuild when one of the files changes
+    let names = files.iter().map(|(name, _)| name);
+    let paths = files.iter().map(|(_, path)| path);
+    let expanded = quote! {
+        ::rusty_orm::migration::Migration::from_embedded(&[
+            #((#names, ::core::include_str!(#paths))),*
+        ])
+    };
+    TokenStream::from(expanded)
+}
+
+/// Lists the names and absolute paths of the migration files in a directory.
+fn get_migration_files(path: &LitStr) -> Result<Vec<(String, String)>, syn::Error> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let dir = std::path::Path::new(&manifest_dir).join(path.value());
+    let error = |e: std::io::Error| {
+        syn::Error::new(path.span(), format!("Cannot read migrations from {}: {}", dir.display(), e))
+    };
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(&dir).map_err(error)? {
+        let entry = entry.map_err(error)?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if [".json", ".up.sql", ".down.sql"].iter().any(|suffix| name.ends_with(suffix)) {
+            files.push((name, entry.path().to_string_lossy().into_owned()));
         }
     }
+    files.sort();
+    Ok(files)
 }
@@ -0,0 +1,249 @@
+use crate::model::{Column, ColumnDefault, DataType, Index, Table, Value};
+use crate::query_builder::format_literal;
+
+/// Words that can't be used as field names as they are.
+const KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while",
+];
+
+/// Generates the source of a struct deriving `Model` for the table.
+///
+/// Columns become public fields named after them in snake_case, typed after
+/// their data type and wrapped in `Option` if nullable. Attributes are only
+/// added where the derive's defaults would give a different table, so that
+/// the struct's `Model::table()` equals the given table. Integer defaults
+/// are the exception, as the derive always stores them as `Value::BigInt`.
+///
+/// The struct needs `rusty_orm_macros::Model` in scope, and the `chrono`,
+/// `uuid` and `serde_json` crates for tables with such columns.
+pub fn generate_model_source(table: &Table) -> String {
+    let fields = field_names(&table.columns);
+    let indexed = column_indexes(table);
+
+    let mut source = String::from("#[derive(Debug, Clone, Model)]\n");
+    source.push_str(&format!("#[table_name = {:?}]\n", table.qualified_name()));
+    for columns in &table.unique_constraints {
+        source.push_str(&format!("#[model(unique = {:?})]\n", columns.join(", ")));
+    }
+    for index in &table.indexes[indexed.len()..] {
+        source.push_str(&format!("#[model(index({}))]\n", index_attributes(table, index)));
+    }
+    source.push_str(&format!("pub struct {} {{\n", struct_name(&table.name)));
+    for (column, field) in table.columns.iter().zip(&fields) {
+        let mut attributes = column_attributes(column, field);
+        if indexed.contains(&column.name.as_str()) {
+            attributes.push("index".to_string());
+        }
+        if !attributes.is_empty() {
+            source.push_str(&format!("    #[column({})]\n", attributes.join(", ")));
+        }
+        let rust_type = rust_type(&column.data_type);
+        if column.is_nullable {
+            source.push_str(&format!("    pub {}: Option<{}>,\n", field, rust_type));
+        } else {
+            source.push_str(&format!("    pub {}: {},\n", field, rust_type));
+        }
+    }
+    source.push_str("}\n");
+    source
+}
+
+/// Returns the Rust type a column of the data type is read into.
+fn rust_type(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::SmallInt => "i16",
+        DataType::Integer => "i32",
+        DataType::BigInt => "i64",
+        DataType::Boolean => "bool",
+        DataType::Float => "f64",
+        DataType::Date => "chrono::NaiveDate",
+        DataType::Time => "chrono::NaiveTime",
+        DataType::DateTime => "chrono::NaiveDateTime",
+        DataType::TimestampTz => "chrono::DateTime<chrono::Utc>",
+        DataType::Uuid => "uuid::Uuid",
+        DataType::Blob => "Vec<u8>",
+        DataType::Json | DataType::Jsonb => "serde_json::Value",
+        // Decimals are read as text to keep their precision
+        DataType::Varchar(_) | DataType::Text | DataType::Decimal(..) | DataType::Custom(_) => {
+            "String"
+        }
+    }
+}
+
+/// Returns the `#[column(...)]` attributes the field needs to map to the column.
+fn column_attributes(column: &Column, field: &str) -> Vec<String> {
+    let mut attributes = Vec::new();
+    if field != column.name {
+        attributes.push(format!("name = {:?}", column.name));
+    }
+    match &column.data_type {
+        DataType::Varchar(255) => {}
+        DataType::Varchar(length) => attributes.push(format!("length = {}", length)),
+        DataType::Text => attributes.push("type = \"Text\"".to_string()),
+        DataType::Jsonb => attributes.push("type = \"Jsonb\"".to_string()),
+        DataType::Decimal(precision, scale) => {
+            attributes.push(format!("type = \"Decimal({}, {})\"", precision, scale))
+        }
+        DataType::Custom(sql_type) => attributes.push(format!("raw_type = {:?}", sql_type)),
+        _ => {}
+    }
+    if column.is_primary_key {
+        attributes.push("primary_key".to_string());
+    }
+    if column.is_auto_increment {
+        attributes.push("auto_increment".to_string());
+    }
+    if column.is_unique {
+        attributes.push("unique".to_string());
+    }
+    match &column.default {
+        Some(ColumnDefault::Value(Value::Text(text))) => {
+            attributes.push(format!("default = {:?}", text))
+        }
+        Some(ColumnDefault::Value(Value::Integer(i))) => {
+            attributes.push(format!("default = {}", i))
+        }
+        Some(ColumnDefault::Value(Value::BigInt(i))) => attributes.push(format!("default = {}", i)),
+        Some(ColumnDefault::Value(Value::Float(f))) if f.is_finite() => {
+            attributes.push(format!("default = {:?}", f))
+        }
+        Some(ColumnDefault::Value(Value::Bool(b))) => attributes.push(format!("default = {}", b)),
+        // Values without a Rust literal are given as SQL
+        Some(ColumnDefault::Value(value)) => {
+            attributes.push(format!("default_expr = {:?}", format_literal(value, None)))
+        }
+        Some(ColumnDefault::Expr(expr)) => attributes.push(format!("default_expr = {:?}", expr)),
+        None => {}
+    }
+    if let Some(foreign_key) = &column.foreign_key {
+        let references = format!("{}.{}", foreign_key.table, foreign_key.column);
+        attributes.push(format!("references = {:?}", references));
+        if let Some(action) = foreign_key.on_delete {
+            attributes.push(format!("on_delete = {:?}", action.as_sql().to_lowercase()));
+        }
+        if let Some(action) = foreign_key.on_update {
+            attributes.push(format!("on_update = {:?}", action.as_sql().to_lowercase()));
+        }
+    }
+    attributes
+}
+
+/// Returns the attributes of a `#[model(index(...))]`, leaving out the
+/// name if it's the default one.
+fn index_attributes(table: &Table, index: &Index) -> String {
+    let mut attributes = Vec::new();
+    if index.name != default_index_name(table, &index.columns) {
+        attributes.push(format!("name = {:?}", index.name));
+    }
+    attributes.push(format!("columns = {:?}", index.columns.join(", ")));
+    if index.is_unique {
+        attributes.push("unique".to_string());
+    }
+    if let Some(method) = index.method {
+        attributes.push(format!("method = {:?}", method.as_sql().to_lowercase()));
+    }
+    attributes.join(", ")
+}
+
+/// Returns the columns of the leading indexes that can be declared with
+/// `#[column(index)]`.
+///
+/// The derive lists those indexes first, in column order, so the run stops
+/// at the first index that needs `#[model(index(...))]` or is out of order.
+fn column_indexes(table: &Table) -> Vec<&str> {
+    let mut columns: Vec<&str> = Vec::new();
+    let mut last_position = None;
+    for index in &table.indexes {
+        let [column] = index.columns.as_slice() else { break };
+        let Some(position) = table.columns.iter().position(|col| &col.name == column) else {
+            break;
+        };
+        if index.is_unique
+            || index.method.is_some()
+            || index.name != default_index_name(table, &index.columns)
+            || last_position.is_some_and(|last| last >= position)
+        {
+            break;
+        }
+        columns.push(column);
+        last_position = Some(position);
+    }
+    columns
+}
+
+/// Returns the name the derive gives an index without an explicit one.
+fn default_index_name(table: &Table, columns: &[String]) -> String {
+    format!("idx_{}_{}", table.name, columns.join("_"))
+}
+
+/// Returns the field names of the columns: snake_case, with raw identifiers
+/// for keywords and a numeric suffix if two columns would share a name.
+fn field_names(columns: &[Column]) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    for column in columns {
+        let base = field_name(&column.name);
+        let mut field = base.clone();
+        let mut suffix = 2;
+        while fields.contains(&field) {
+            field = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        fields.push(field);
+    }
+    fields
+}
+
+/// Converts a column name into a field name.
+fn field_name(column: &str) -> String {
+    let mut name = String::new();
+    let mut prev: Option<char> = None;
+    for c in column.chars() {
+        if c.is_uppercase() && prev.is_some_and(|prev| prev.is_lowercase() || prev.is_numeric()) {
+            name.push('_');
+        }
+        if c.is_alphanumeric() || c == '_' {
+            name.extend(c.to_lowercase());
+        } else {
+            name.push('_');
+        }
+        prev = Some(c);
+    }
+    if name.is_empty() || name.starts_with(|c: char| c.is_numeric()) {
+        name.insert(0, '_');
+    }
+    match name.as_str() {
+        // Keywords that can't be raw identifiers, and the placeholder `_`
+        "self" | "super" | "crate" | "_" => format!("{}_", name),
+        _ if KEYWORDS.contains(&name.as_str()) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+/// Converts a table name into a struct name: PascalCase and, naively, singular.
+fn struct_name(table: &str) -> String {
+    let singular = if let Some(stem) = table.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if table.ends_with("sses") || table.ends_with("xes") || table.ends_with("ches") {
+        table[..table.len() - 2].to_string()
+    } else if table.ends_with('s') && !table.ends_with("ss") {
+        table[..table.len() - 1].to_string()
+    } else {
+        table.to_string()
+    };
+    let name: String = singular
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect();
+    match name.chars().next() {
+        Some(first) if !first.is_numeric() => name,
+        _ => format!("Table{}", name),
+    }
+}
@@ -0,0 +1,275 @@
+use crate::escape::BackslashMode;
+use std::fmt;
+
+/// The SQL flavor a query is rendered for.
+///
+/// Queries built without a dialect use unquoted identifiers, `?`
+/// placeholders and `true`/`false` literals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl Dialect {
+    /// Returns the character identifiers are quoted with.
+    pub fn identifier_quote(self) -> char {
+        match self {
+            Dialect::MySql => '`',
+            Dialect::Postgres | Dialect::Sqlite => '"',
+        }
+    }
+
+    /// Quotes a single identifier, doubling any embedded quote characters.
+    pub fn quote_identifier(self, name: &str) -> String {
+        quote_with(name, self.identifier_quote())
+    }
+
+    /// Quotes a possibly qualified name such as `users.id` part by part.
+    ///
+    /// Names that are not plain identifiers, such as expressions or `*`,
+    /// are returned unchanged.
+    pub(crate) fn quote_path(self, name: &str) -> String {
+        map_path(name, |part| self.quote_identifier(part))
+    }
+
+    /// Returns the placeholder style the dialect's drivers expect.
+    pub fn placeholder_style(self) -> PlaceholderStyle {
+        match self {
+            Dialect::Postgres => PlaceholderStyle::Dollar,
+            Dialect::MySql | Dialect::Sqlite => PlaceholderStyle::Question,
+        }
+    }
+
+    /// Renders for this dialect with a placeholder style other than its own.
+    pub fn with_placeholders(self, style: PlaceholderStyle) -> ParamFormat {
+        ParamFormat { dialect: Some(self), style }
+    }
+
+    /// Returns the literal for a boolean value.
+    pub fn bool_literal(self, value: bool) -> &'static str {
+        match (self, value) {
+            (Dialect::Sqlite, true) => "1",
+            (Dialect::Sqlite, false) => "0",
+            (_, true) => "TRUE",
+            (_, false) => "FALSE",
+        }
+    }
+
+    /// Returns how string literals treat backslashes.
+    pub fn backslash_mode(self) -> BackslashMode {
+        match self {
+            Dialect::MySql => BackslashMode::Escape,
+            Dialect::Postgres | Dialect::Sqlite => BackslashMode::Literal,
+        }
+    }
+
+    /// Returns the LIMIT needed before an OFFSET on databases that don't
+    /// accept OFFSET on its own.
+    pub(crate) fn unbounded_limit(self) -> Option<&'static str> {
+        match self {
+            Dialect::Postgres => None,
+            Dialect::MySql => Some("18446744073709551615"),
+            Dialect::Sqlite => Some("-1"),
+        }
+    }
+}
+
+/// How bound values are referenced in parameterized SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlaceholderStyle {
+    /// `?` for every value.
+    #[default]
+    Question,
+    /// `$1`, `$2`, ... numbered in binding order.
+    Dollar,
+    /// `:p1`, `:p2`, ... where repeated values share one name.
+    Named,
+}
+
+impl PlaceholderStyle {
+    /// Returns the placeholder for the parameter at the given 1-based index.
+    pub fn placeholder(self, index: usize) -> String {
+        match self {
+            PlaceholderStyle::Question => "?".to_string(),
+            PlaceholderStyle::Dollar => format!("${}", index),
+            PlaceholderStyle::Named => format!(":p{}", index),
+        }
+    }
+}
+
+/// The output format of a parameterized build: an optional dialect to render
+/// for, and the placeholder style to bind values with.
+///
+/// Created from a `Dialect`, which uses its own placeholder style, from a
+/// `PlaceholderStyle` alone, or from `Dialect::with_placeholders`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamFormat {
+    pub dialect: Option<Dialect>,
+    pub style: PlaceholderStyle,
+}
+
+impl From<Dialect> for ParamFormat {
+    fn from(dialect: Dialect) -> Self {
+        dialect.with_placeholders(dialect.placeholder_style())
+    }
+}
+
+impl From<PlaceholderStyle> for ParamFormat {
+    fn from(style: PlaceholderStyle) -> Self {
+        ParamFormat { dialect: None, style }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Dialect::Postgres => "PostgreSQL",
+            Dialect::MySql => "MySQL",
+            Dialect::Sqlite => "SQLite",
+        })
+    }
+}
+
+/// Keywords reserved by at least one supported database, which cannot be
+/// used as bare table or column names.
+const RESERVED_WORDS: &[&str] = &[
+    "all",
+    "alter",
+    "and",
+    "any",
+    "as",
+    "asc",
+    "between",
+    "by",
+    "case",
+    "check",
+    "column",
+    "constraint",
+    "create",
+    "cross",
+    "current_date",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "delete",
+    "desc",
+    "distinct",
+    "drop",
+    "else",
+    "end",
+    "except",
+    "exists",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "in",
+    "index",
+    "inner",
+    "insert",
+    "intersect",
+    "into",
+    "is",
+    "join",
+    "key",
+    "left",
+    "like",
+    "limit",
+    "natural",
+    "not",
+    "null",
+    "offset",
+    "on",
+    "or",
+    "order",
+    "outer",
+    "primary",
+    "references",
+    "right",
+    "select",
+    "set",
+    "table",
+    "then",
+    "to",
+    "true",
+    "union",
+    "unique",
+    "update",
+    "user",
+    "using",
+    "values",
+    "when",
+    "where",
+    "with",
+];
+
+/// Returns whether a name is a reserved SQL keyword, ignoring case.
+pub fn is_reserved_word(name: &str) -> bool {
+    RESERVED_WORDS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+/// Quotes an identifier with ANSI double quotes if it would not survive
+/// being emitted bare: reserved words, names with uppercase letters (which
+/// unquoted would be case-folded) and names that aren't plain identifiers.
+pub(crate) fn quote_if_needed(name: &str) -> String {
+    if !is_identifier(name)
+        || is_reserved_word(name)
+        || name.chars().any(|c| c.is_ascii_uppercase())
+    {
+        quote_with(name, '"')
+    } else {
+        name.to_string()
+    }
+}
+
+/// Like `quote_if_needed`, for possibly qualified names such as `users.id`.
+/// Names that are not plain identifiers, such as expressions, are returned unchanged.
+pub(crate) fn quote_path_if_needed(name: &str) -> String {
+    map_path(name, quote_if_needed)
+}
+
+/// Applies `quote` to each part of a qualified name, leaving a trailing `*`
+/// alone. Anything else that isn't a dotted list of plain identifiers is
+/// assumed to be an expression and returned unchanged.
+fn map_path(name: &str, quote: impl Fn(&str) -> String) -> String {
+    let parts: Vec<&str> = name.split('.').collect();
+    let (last, qualifiers) = parts.split_last().unwrap();
+    if !qualifiers.iter().all(|part| is_identifier(part))
+        || !(is_identifier(last) || *last == "*" && !qualifiers.is_empty())
+    {
+        return name.to_string();
+    }
+    parts
+        .iter()
+        .map(|part| if *part == "*" { part.to_string() } else { quote(part) })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn quote_with(name: &str, quote: char) -> String {
+    let mut quoted = String::with_capacity(name.len() + 2);
+    quoted.push(quote);
+    for c in name.chars() {
+        if c == quote {
+            quoted.push(quote);
+        }
+        quoted.push(c);
+    }
+    quoted.push(quote);
+    quoted
+}
+
+/// Returns whether a name is a plain SQL identifier.
+pub(crate) fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+}
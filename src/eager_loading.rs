@@ -1,48 +1,596 @@
-use crate::{model::Model, query_builder::SelectQuery};
+use crate::{
+    dialect::Dialect,
+    error::OrmError,
+    executor::Connection,
+    expr::Expr,
+    model::{Model, Table, ToValue, Value},
+    query_builder::{JoinType, QueryError, SelectQuery},
+    row::Row,
+};
+use std::{
+    any::{type_name, TypeId},
+    fmt,
+    marker::PhantomData,
+};
+
+/// Which side of a relationship holds the foreign key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationKind {
+    /// The current model references the related model, e.g. a post belongs to its user.
+    BelongsTo,
+    /// The related model references the current model, e.g. a user has many posts.
+    HasMany,
+}
 
 /// Represents a relationship between two models.
+///
+/// Keys left unspecified are derived from the foreign key metadata of the
+/// model holding the foreign key: the column referencing the other table,
+/// and the column it references.
 pub trait Relationship {
     type RelatedModel: Model;
 
-    /// The foreign key in the current model.
-    fn foreign_key() -> String;
+    /// Which model holds the foreign key.
+    const KIND: RelationKind = RelationKind::BelongsTo;
+
+    /// The foreign key: on the current model for `BelongsTo`, on the related
+    /// model for `HasMany`.
+    fn foreign_key() -> Option<String> {
+        None
+    }
+
+    /// The key the foreign key references, usually a primary key: on the
+    /// related model for `BelongsTo`, on the current model for `HasMany`.
+    fn related_key() -> Option<String> {
+        None
+    }
 
-    /// The primary key in the related model.
-    fn related_key() -> String;
+    /// The (foreign key, referenced key) column pairs of a relationship over
+    /// several columns, such as `[("tenant_id", "tenant_id"), ("user_id", "id")]`,
+    /// joined with AND. Sided as `foreign_key` and `related_key` are.
+    ///
+    /// If not empty, the pairs are used instead of `foreign_key` and
+    /// `related_key`; single-key relationships leave it empty.
+    fn key_pairs() -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+/// A many-to-many relationship between two models through a join table,
+/// such as posts and tags through `post_tags`.
+///
+/// The join table only needs a name and its key columns, not a `Model`.
+pub trait ManyToMany {
+    type RelatedModel: Model;
+
+    /// The join table, optionally qualified with its schema.
+    fn join_table() -> String;
+
+    /// The column of the join table referencing the current model, and the
+    /// column of the current model it references.
+    fn base_keys() -> (String, String);
+
+    /// The column of the join table referencing the related model, and the
+    /// column of the related model it references.
+    fn related_keys() -> (String, String);
 }
 
 ///  eager loading of related entities.
 pub struct EagerLoader<T: Model> {
     base_query: SelectQuery<T>,
+    /// The tables in the query, in join order.
+    tables: Vec<QueryTable>,
+    /// The separator of the aliases of the expanded select list, if the
+    /// columns of the joined tables are selected one by one.
+    related_columns: Option<String>,
+    /// The first invalid call, reported by `try_build`.
+    error: Option<QueryError>,
+}
+
+/// A table in an eager loading query.
+#[derive(Debug, Clone)]
+struct QueryTable {
+    /// The name the table's columns are qualified with: its alias or name.
+    qualifier: String,
+    /// The table's columns, if it's the table of a model.
+    columns: Vec<String>,
+    /// The relationship the table was joined for.
+    relation: Option<TypeId>,
+}
+
+impl<T: Model> Clone for EagerLoader<T> {
+    fn clone(&self) -> Self {
+        EagerLoader {
+            base_query: self.base_query.clone(),
+            tables: self.tables.clone(),
+            related_columns: self.related_columns.clone(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+impl<T: Model> fmt::Debug for EagerLoader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EagerLoader")
+            .field("base_query", &self.base_query)
+            .field("tables", &self.tables)
+            .field("related_columns", &self.related_columns)
+            .field("error", &self.error)
+            .finish()
+    }
 }
 
 impl<T: Model> EagerLoader<T> {
     /// Creates a new EagerLoader with the provided base query.
     pub fn new(base_query: SelectQuery<T>) -> Self {
-        EagerLoader { base_query }
+        let tables = vec![QueryTable {
+            qualifier: base_query.qualifier(),
+            columns: column_names(&T::table_ref()),
+            relation: None,
+        }];
+        EagerLoader { base_query, tables, related_columns: None, error: None }
+    }
+
+    /// Selects the columns of the base and joined tables one by one, each
+    /// aliased with its table's name or alias, as in `users.id AS users_id`,
+    /// so that columns of the same name don't collide.
+    ///
+    /// Only applies if the base query has an empty select list, which would
+    /// select `*`. Join tables of many-to-many relationships are left out.
+    pub fn select_related_columns(self) -> Self {
+        self.select_related_columns_with("_")
+    }
+
+    /// Selects the columns of the base and joined tables one by one, with
+    /// the given separator between the table and column in their aliases.
+    pub fn select_related_columns_with(mut self, separator: &str) -> Self {
+        self.related_columns = Some(separator.to_string());
+        self
+    }
+
+    /// Adds an INNER JOIN for the specified relationship, which leaves out
+    /// base rows without a related row.
+    pub fn with<R: Relationship + 'static>(self) -> Self {
+        self.with_join::<R>(JoinType::Inner)
+    }
+
+    /// Adds a LEFT JOIN for the specified relationship, which keeps base rows
+    /// without a related row.
+    pub fn with_left<R: Relationship + 'static>(self) -> Self {
+        self.with_join::<R>(JoinType::Left)
+    }
+
+    /// Adds a join of the given type for the specified relationship.
+    ///
+    /// A related table that is already part of the query is joined under an
+    /// alias, its name followed by `_2`, `_3` and so on, so that a
+    /// self-referencing relationship renders as
+    /// `users INNER JOIN users AS users_2 ON users.manager_id = users_2.id`.
+    /// `qualifier_of` returns the alias a relationship was joined under.
+    pub fn with_join<R: Relationship + 'static>(self, join_type: JoinType) -> Self {
+        let base = self.tables[0].qualifier.clone();
+        self.join_relation::<T, R>(&base, join_type, None)
+    }
+
+    /// Adds an INNER JOIN for the specified relationship, and a WHERE
+    /// condition on the related table.
+    ///
+    /// Unqualified columns of the condition are qualified with the related
+    /// table's name or alias, so `Expr::col("is_active").eq(true)` tests the
+    /// joined row whatever the table was joined as.
+    pub fn with_filtered<R: Relationship + 'static>(self, condition: Expr) -> Self {
+        self.with_filtered_join::<R>(JoinType::Inner, condition)
+    }
+
+    /// Adds a join of the given type for the specified relationship, and a
+    /// WHERE condition on the related table, as `with_filtered` does.
+    ///
+    /// With a LEFT JOIN, the condition applies to the joined rows, so base
+    /// rows without a matching related row are left out as with an INNER
+    /// JOIN. Use `with_on_extra_join` to keep them.
+    pub fn with_filtered_join<R: Relationship + 'static>(
+        self,
+        join_type: JoinType,
+        condition: Expr,
+    ) -> Self {
+        let mut loader = self.with_join::<R>(join_type);
+        let qualifier = &loader.tables[loader.tables.len() - 1].qualifier;
+        let condition = condition.qualify(qualifier);
+        loader.base_query = loader.base_query.filter_expr(condition);
+        loader
+    }
+
+    /// Adds an INNER JOIN for the specified relationship, with a condition
+    /// on the related table added to its ON clause with AND.
+    ///
+    /// Unqualified columns of the condition are qualified as in `with_filtered`.
+    pub fn with_on_extra<R: Relationship + 'static>(self, condition: Expr) -> Self {
+        self.with_on_extra_join::<R>(JoinType::Inner, condition)
+    }
+
+    /// Adds a join of the given type for the specified relationship, with a
+    /// condition on the related table added to its ON clause.
+    ///
+    /// With a LEFT JOIN, the condition only decides which related rows are
+    /// joined: base rows without a matching related row are kept, with NULL
+    /// related columns.
+    pub fn with_on_extra_join<R: Relationship + 'static>(
+        self,
+        join_type: JoinType,
+        condition: Expr,
+    ) -> Self {
+        let base = self.tables[0].qualifier.clone();
+        self.join_relation::<T, R>(&base, join_type, Some(condition))
+    }
+
+    /// Adds an INNER JOIN for relationship `R` of the related model of `P`,
+    /// which must have been joined already: the companies of the users of
+    /// posts are joined with `with::<PostUser>().with_nested::<PostUser, UserCompany>()`.
+    ///
+    /// The join condition refers to the table joined for `P`, or the last
+    /// one if `P` was joined several times. Nesting off a relationship that
+    /// wasn't joined adds no join, and makes `try_build` fail with
+    /// `QueryError::RelationNotJoined`.
+    pub fn with_nested<P: Relationship + 'static, R: Relationship + 'static>(self) -> Self {
+        self.with_nested_join::<P, R>(JoinType::Inner)
+    }
+
+    /// Adds a join of the given type for relationship `R` of the related
+    /// model of `P`, as `with_nested` does.
+    pub fn with_nested_join<P: Relationship + 'static, R: Relationship + 'static>(
+        mut self,
+        join_type: JoinType,
+    ) -> Self {
+        let parent =
+            self.tables.iter().rev().find(|table| table.relation == Some(TypeId::of::<P>()));
+        match parent {
+            Some(parent) => {
+                let parent = parent.qualifier.clone();
+                self.join_relation::<P::RelatedModel, R>(&parent, join_type, None)
+            }
+            None => {
+                self.error.get_or_insert(QueryError::RelationNotJoined {
+                    relationship: type_name::<P>(),
+                });
+                self
+            }
+        }
+    }
+
+    /// Joins relationship `R` of model `P`, whose table is qualified with
+    /// `parent`, adding the extra condition on the related table to the ON clause.
+    fn join_relation<P: Model, R: Relationship + 'static>(
+        mut self,
+        parent: &str,
+        join_type: JoinType,
+        extra: Option<Expr>,
+    ) -> Self {
+        let related_table = R::RelatedModel::table_ref();
+        let keys = relation_keys::<P, R>();
+
+        let related_name = related_table.qualified_name();
+        let alias =
+            self.claim(&related_name, column_names(&related_table), Some(TypeId::of::<R>()));
+        let qualifier = alias.clone().unwrap_or_else(|| related_name.clone());
+        let join_condition = keys
+            .iter()
+            .map(|(parent_key, related_key)| {
+                Expr::col(&format!("{}.{}", parent, parent_key))
+                    .eq(Expr::col(&format!("{}.{}", qualifier, related_key)))
+            })
+            .chain(extra.map(|extra| extra.qualify(&qualifier)))
+            .reduce(Expr::and)
+            .expect("a relationship has at least one key pair");
+        self.join(join_type, &related_name, alias, join_condition)
+    }
+
+    /// Adds INNER JOINs for the specified many-to-many relationship: one from
+    /// the base table to the join table, and one from there to the related table.
+    pub fn with_many_to_many<R: ManyToMany>(self) -> Self {
+        self.with_many_to_many_join::<R>(JoinType::Inner)
+    }
+
+    /// Adds joins of the given type for the specified many-to-many relationship.
+    ///
+    /// A join table or related table that is already part of the query is
+    /// joined under an alias, its name followed by `_2`, `_3` and so on.
+    pub fn with_many_to_many_join<R: ManyToMany>(mut self, join_type: JoinType) -> Self {
+        let (base_column, base_key) = R::base_keys();
+        let (related_column, related_key) = R::related_keys();
+
+        let join_table = R::join_table();
+        let join_alias = self.claim(&join_table, Vec::new(), None);
+        let join_name = join_alias.clone().unwrap_or_else(|| join_table.clone());
+        let join_condition = Expr::col(&format!("{}.{}", self.base_query.qualifier(), base_key))
+            .eq(Expr::col(&format!("{}.{}", join_name, base_column)));
+        self = self.join(join_type, &join_table, join_alias, join_condition);
+
+        let related_model = R::RelatedModel::table_ref();
+        let related_table = related_model.qualified_name();
+        let related_alias = self.claim(&related_table, column_names(&related_model), None);
+        let related_name = related_alias.clone().unwrap_or_else(|| related_table.clone());
+        let join_condition = Expr::col(&format!("{}.{}", join_name, related_column))
+            .eq(Expr::col(&format!("{}.{}", related_name, related_key)));
+        self.join(join_type, &related_table, related_alias, join_condition)
     }
 
-    /// Adds a join for the specified relationship.
-    pub fn with<R: Relationship>(mut self) -> Self {
-        let base_table = &self.base_query.table;
-        let related_table = R::RelatedModel::table();
+    /// Plans loading the specified relationship with a second query rather
+    /// than a join, so that base rows aren't repeated for every related row.
+    ///
+    /// The base query is this loader's query, joins included; the related
+    /// query selects the related rows whose key is among those of the base rows.
+    ///
+    /// Rows are matched on a single key: a composite-key relationship is
+    /// matched on its first key pair only.
+    pub fn preload<R: Relationship>(&self) -> PreloadPlan<T, R::RelatedModel> {
+        let (base_key, related_key) = relation_keys::<T, R>().swap_remove(0);
+        // With an expanded select list, the base key is read under its alias
+        let base_column = match &self.related_columns {
+            Some(separator) if self.base_query.selects_all() => format!(
+                "{}{}{}",
+                self.tables[0].qualifier.replace('.', separator),
+                separator,
+                base_key
+            ),
+            _ => base_key,
+        };
+        PreloadPlan {
+            base_query: self.query(),
+            base_key: base_column,
+            related_key,
+            _marker: PhantomData,
+        }
+    }
 
-        let join_condition = format!(
-            "{}.{} = {}.{}",
-            base_table.name,
-            R::foreign_key(),
-            related_table.name,
-            R::related_key()
-        );
+    /// Returns the name the related table of relationship `R` is qualified
+    /// with in the query: its alias if it was joined under one, as a
+    /// self-referencing relationship is, or else its name.
+    ///
+    /// If `R` was joined several times, the last join is the one returned;
+    /// `None` if it wasn't joined. Many-to-many relationships aren't tracked.
+    pub fn qualifier_of<R: Relationship + 'static>(&self) -> Option<&str> {
+        self.tables
+            .iter()
+            .rev()
+            .find(|table| table.relation == Some(TypeId::of::<R>()))
+            .map(|table| table.qualifier.as_str())
+    }
 
-        let join_clause = format!("INNER JOIN {} ON {}", related_table.name, join_condition);
-        self.base_query.joins.push(join_clause);
+    /// Records a table about to be joined, returning the alias it must be
+    /// joined under if its name is already taken.
+    fn claim(
+        &mut self,
+        table: &str,
+        columns: Vec<String>,
+        relation: Option<TypeId>,
+    ) -> Option<String> {
+        let taken = |name: &str| self.tables.iter().any(|table| table.qualifier == name);
+        let alias = taken(table).then(|| {
+            let name = table.rsplit('.').next().unwrap_or(table);
+            (2..).map(|n| format!("{}_{}", name, n)).find(|alias| !taken(alias)).unwrap()
+        });
+        let qualifier = alias.clone().unwrap_or_else(|| table.to_string());
+        self.tables.push(QueryTable { qualifier, columns, relation });
+        alias
+    }
 
+    fn join(mut self, join_type: JoinType, table: &str, alias: Option<String>, on: Expr) -> Self {
+        self.base_query = match alias {
+            Some(alias) => self.base_query.join_expr_as(join_type, table, &alias, on),
+            None => self.base_query.join_expr(join_type, table, on),
+        };
         self
     }
 
     /// Builds the final SQL query string with joins.
-    pub fn build(self) -> String {
+    pub fn build(&self) -> String {
+        self.query().build()
+    }
+
+    /// Builds the final SQL query string with joins for the given dialect.
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        self.query().build_for(dialect)
+    }
+
+    /// Validates the joins and the query, and builds the final SQL query string.
+    pub fn try_build(&self) -> Result<String, QueryError> {
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => self.query().try_build(),
+        }
+    }
+
+    /// Validates the joins and the query, including features the dialect
+    /// lacks, and builds the final SQL query string for it.
+    pub fn try_build_for(&self, dialect: Dialect) -> Result<String, QueryError> {
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => self.query().try_build_for(dialect),
+        }
+    }
+
+    /// Returns the query with joins, its select list expanded if asked for.
+    fn query(&self) -> SelectQuery<T> {
+        let query = self.base_query.clone();
+        let Some(separator) = &self.related_columns else { return query };
+        if !query.selects_all() {
+            return query;
+        }
+        let columns: Vec<(String, String)> = self
+            .tables
+            .iter()
+            .flat_map(|table| {
+                let prefix = table.qualifier.replace('.', separator);
+                table.columns.iter().map(move |column| {
+                    (
+                        format!("{}.{}", table.qualifier, column),
+                        format!("{}{}{}", prefix, separator, column),
+                    )
+                })
+            })
+            .collect();
+        let columns: Vec<(&str, &str)> =
+            columns.iter().map(|(column, alias)| (column.as_str(), alias.as_str())).collect();
+        query.select_as(&columns)
+    }
+}
+
+/// Two queries loading base rows and their related rows, planned by
+/// `EagerLoader::preload`.
+pub struct PreloadPlan<T: Model, R: Model> {
+    /// The query loading the base rows.
+    pub base_query: SelectQuery<T>,
+    /// The column of the base rows whose values the related rows are matched on.
+    pub base_key: String,
+    /// The column of the related rows matched against the base key values.
+    pub related_key: String,
+    _marker: PhantomData<R>,
+}
+
+impl<T: Model, R: Model> Clone for PreloadPlan<T, R> {
+    fn clone(&self) -> Self {
+        PreloadPlan {
+            base_query: self.base_query.clone(),
+            base_key: self.base_key.clone(),
+            related_key: self.related_key.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Model, R: Model> fmt::Debug for PreloadPlan<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreloadPlan")
+            .field("base_query", &self.base_query)
+            .field("base_key", &self.base_key)
+            .field("related_key", &self.related_key)
+            .finish()
+    }
+}
+
+impl<T: Model, R: Model> PreloadPlan<T, R> {
+    /// Builds the SQL of the base query.
+    pub fn base_sql(&self) -> String {
         self.base_query.build()
     }
+
+    /// Returns the query loading the related rows whose key is among the
+    /// given values, typically the base key values of the base rows:
+    /// `SELECT * FROM related WHERE related.key IN (...)`.
+    ///
+    /// Without values, the condition is the constant-false `1 = 0`.
+    pub fn related_query<V: ToValue>(&self, keys: &[V]) -> SelectQuery<R> {
+        let column = format!("{}.{}", R::table_ref().qualified_name(), self.related_key);
+        SelectQuery::<R>::new().filter_in(&column, keys)
+    }
+
+    /// Runs both queries on the connection, returning each base row with
+    /// its related rows, in the order the queries return them.
+    ///
+    /// The related query is skipped if no base row has a key value.
+    pub fn load<C: Connection>(
+        &self,
+        connection: &mut C,
+    ) -> Result<Vec<(Row, Vec<Row>)>, OrmError> {
+        let dialect = connection.dialect();
+        let (sql, params) = self.base_query.build_parameterized_for(dialect);
+        let base_rows = connection.query(&sql, &params)?;
+
+        // The distinct key values, each with the related rows having it
+        let mut groups: Vec<(Value, Vec<Row>)> = Vec::new();
+        for row in &base_rows {
+            match row.get(&self.base_key) {
+                None | Some(Value::Null) => {}
+                Some(key) if groups.iter().any(|(other, _)| other == key) => {}
+                Some(key) => groups.push((key.clone(), Vec::new())),
+            }
+        }
+        if !groups.is_empty() {
+            let keys: Vec<&Value> = groups.iter().map(|(key, _)| key).collect();
+            let (sql, params) = self.related_query(&keys).build_parameterized_for(dialect);
+            for row in connection.query(&sql, &params)? {
+                let group = row
+                    .get(&self.related_key)
+                    .and_then(|key| groups.iter_mut().find(|(other, _)| other == key));
+                if let Some((_, related)) = group {
+                    related.push(row);
+                }
+            }
+        }
+
+        Ok(base_rows
+            .into_iter()
+            .map(|row| {
+                let related = row
+                    .get(&self.base_key)
+                    .and_then(|key| groups.iter().find(|(other, _)| other == key))
+                    .map_or_else(Vec::new, |(_, related)| related.clone());
+                (row, related)
+            })
+            .collect())
+    }
+}
+
+/// Returns the names of the table's columns.
+fn column_names(table: &Table) -> Vec<String> {
+    table.columns.iter().map(|column| column.name.clone()).collect()
+}
+
+/// Returns the keys joining `T` to the related model of `R`, as pairs of a
+/// column of the base table and a column of the related table.
+fn relation_keys<T: Model, R: Relationship>() -> Vec<(String, String)> {
+    let base_table = T::table_ref();
+    let related_table = R::RelatedModel::table_ref();
+    match R::KIND {
+        RelationKind::BelongsTo => join_keys::<R>(&base_table, &related_table),
+        RelationKind::HasMany => join_keys::<R>(&related_table, &base_table)
+            .into_iter()
+            .map(|(foreign_key, referenced)| (referenced, foreign_key))
+            .collect(),
+    }
+}
+
+/// Resolves the foreign keys of a relationship and the keys they reference:
+/// its key pairs if it has several, or else its single foreign key and
+/// referenced key, filling in those the `Relationship` impl leaves out from
+/// the foreign keys declared on the `referencing` table.
+///
+/// Without matching metadata, the foreign key falls back to
+/// `<referenced table>_id` and the referenced key to its primary key.
+fn join_keys<R: Relationship>(referencing: &Table, referenced: &Table) -> Vec<(String, String)> {
+    let key_pairs = R::key_pairs();
+    if !key_pairs.is_empty() {
+        return key_pairs;
+    }
+
+    // (column, referenced column) pairs of the foreign keys to the referenced table
+    let references: Vec<(&str, &str)> = referencing
+        .columns
+        .iter()
+        .filter_map(|column| {
+            let foreign_key = column.foreign_key.as_ref()?;
+            (foreign_key.table == referenced.name
+                || foreign_key.table == referenced.qualified_name())
+            .then_some((column.name.as_str(), foreign_key.column.as_str()))
+        })
+        .collect();
+
+    let foreign_key = R::foreign_key();
+    let related_key = R::related_key();
+    let reference = references.iter().find(|(column, referenced)| {
+        foreign_key.as_deref().is_none_or(|key| key == *column)
+            && related_key.as_deref().is_none_or(|key| key == *referenced)
+    });
+    let foreign_key = foreign_key.or_else(|| reference.map(|(column, _)| column.to_string()));
+    let related_key =
+        related_key.or_else(|| reference.map(|(_, referenced)| referenced.to_string()));
+
+    let foreign_key = foreign_key.unwrap_or_else(|| format!("{}_id", referenced.name));
+    let related_key = related_key.unwrap_or_else(|| {
+        referenced
+            .primary_key()
+            .first()
+            .map_or_else(|| "id".to_string(), |column| column.name.clone())
+    });
+    vec![(foreign_key, related_key)]
 }
@@ -0,0 +1,41 @@
+use crate::executor::{DatabaseError, FetchError};
+use crate::migration::MigrationError;
+use crate::model::SchemaError;
+use crate::query_builder::QueryError;
+use crate::row::RowError;
+use crate::schema::ImportError;
+use thiserror::Error;
+
+/// Any error reported by the crate, for applications that handle them alike.
+///
+/// Methods running statements, such as the `fetch_*` methods of
+/// `SelectQuery`, those of `ModelExt` and `MigrationRunner`, report this, as
+/// several steps of them can fail. Methods failing in one way only report
+/// errors of their own, such as `QueryError` from the builders, which
+/// convert into this with `?`. The error they wrap is kept as the variant's
+/// source.
+#[derive(Debug, Error)]
+pub enum OrmError {
+    /// A query builder is in a state that doesn't make a valid query.
+    #[error("invalid query: {0}")]
+    Query(#[from] QueryError),
+    /// A table cannot be created as declared.
+    #[error("invalid schema: {0}")]
+    Schema(#[from] SchemaError),
+    /// A schema document could not be read by `schema::import`.
+    #[error(transparent)]
+    Import(#[from] ImportError),
+    /// Migrations could not be read, generated or applied.
+    #[error(transparent)]
+    Migration(#[from] MigrationError),
+    /// The database or its driver failed to run a statement.
+    #[error("database error: {0}")]
+    Execution(#[from] DatabaseError),
+    /// A result row could not be read into a Rust value.
+    #[error("failed to decode a row: {0}")]
+    Decode(#[from] RowError),
+    /// A query returned a number of rows other than expected, or was given
+    /// a primary key of the wrong length.
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+}
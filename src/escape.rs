@@ -0,0 +1,46 @@
+/// How a database treats backslashes inside string literals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackslashMode {
+    /// Backslashes are ordinary characters (ANSI SQL, Postgres, SQLite).
+    #[default]
+    Literal,
+    /// Backslashes start escape sequences and must be doubled (MySQL).
+    Escape,
+}
+
+/// Escapes a string for use inside a single-quoted SQL literal.
+///
+/// Single quotes are doubled, and backslashes are doubled as well when
+/// the target database treats them as escape characters.
+pub fn escape_string(value: &str, mode: BackslashMode) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\'' => escaped.push_str("''"),
+            '\\' if mode == BackslashMode::Escape => escaped.push_str("\\\\"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a string and wraps it in single quotes.
+pub fn quote_string(value: &str, mode: BackslashMode) -> String {
+    format!("'{}'", escape_string(value, mode))
+}
+
+/// Escapes the LIKE wildcards `%` and `_`, and the `\` escape character
+/// itself, so user input can be embedded in a pattern and match literally.
+///
+/// The builders declare `\` as the escape character of every LIKE pattern
+/// with an `ESCAPE` clause; a pattern written by hand needs one as well.
+pub fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
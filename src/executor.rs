@@ -0,0 +1,336 @@
+use crate::dialect::Dialect;
+use crate::error::OrmError;
+use crate::model::Value;
+use crate::observer::{ObserverSlot, QueryObserver};
+use crate::row::Row;
+use std::collections::VecDeque;
+use std::future::Future;
+use thiserror::Error;
+
+/// A connection to a database that statements can be run on.
+///
+/// Statements are given with placeholders in the style of the connection's
+/// dialect, along with their bound values in placeholder order. The database
+/// or its driver failing to run one is reported as `OrmError::Execution`.
+pub trait Connection {
+    /// Returns the SQL flavor the database speaks.
+    fn dialect(&self) -> Dialect;
+
+    /// Runs a statement, returning the number of rows it affected.
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError>;
+
+    /// Runs a query, returning its result rows.
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError>;
+
+    /// Sets the observer told of the statements run on the connection from
+    /// now on, replacing any set before.
+    fn set_observer(&mut self, observer: Box<dyn QueryObserver>);
+
+    /// Returns the number of transactions the connection is in, 0 outside
+    /// of any.
+    fn transaction_depth(&self) -> usize {
+        0
+    }
+
+    /// Begins a transaction, returning a guard that statements of the
+    /// transaction are run on, or a savepoint if the connection is in a
+    /// transaction already.
+    ///
+    /// The transaction is committed by `Transaction::commit`, and rolled back
+    /// if the guard is dropped first, such as on an early return or a panic.
+    fn begin(&mut self) -> Result<Transaction<'_, Self>, OrmError>
+    where
+        Self: Sized,
+    {
+        let depth = self.transaction_depth();
+        Transaction::start(self, depth)
+    }
+}
+
+impl<C: Connection + ?Sized> Connection for &mut C {
+    fn dialect(&self) -> Dialect {
+        (**self).dialect()
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError> {
+        (**self).execute(sql, params)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError> {
+        (**self).query(sql, params)
+    }
+
+    fn set_observer(&mut self, observer: Box<dyn QueryObserver>) {
+        (**self).set_observer(observer)
+    }
+
+    fn transaction_depth(&self) -> usize {
+        (**self).transaction_depth()
+    }
+}
+
+/// A transaction begun by `Connection::begin`, rolled back when dropped
+/// unless committed.
+///
+/// A transaction is a connection itself, so that the builders and models run
+/// on it; beginning a transaction on it nests one in a savepoint, which
+/// commits or rolls back without ending the outer transaction.
+pub struct Transaction<'c, C: Connection> {
+    connection: &'c mut C,
+    depth: usize,
+    finished: bool,
+}
+
+impl<'c, C: Connection> Transaction<'c, C> {
+    /// Begins a transaction on the connection, in a savepoint if nested at
+    /// a depth above 0.
+    fn start(connection: &'c mut C, depth: usize) -> Result<Self, OrmError> {
+        let sql = match depth {
+            0 => "BEGIN;".to_string(),
+            _ => format!("SAVEPOINT sp_{};", depth),
+        };
+        connection.execute(&sql, &[])?;
+        Ok(Transaction { connection, depth, finished: false })
+    }
+
+    /// Returns the number of transactions this one is nested in, 0 for one
+    /// begun on a connection.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Commits the transaction, or releases its savepoint if nested.
+    ///
+    /// If the commit fails, the transaction is rolled back when dropped.
+    pub fn commit(mut self) -> Result<(), OrmError> {
+        let sql = match self.depth {
+            0 => "COMMIT;".to_string(),
+            depth => format!("RELEASE SAVEPOINT sp_{};", depth),
+        };
+        self.connection.execute(&sql, &[])?;
+        self.finished = true;
+        Ok(())
+    }
+
+    /// Rolls back the transaction, or to its savepoint if nested, reporting
+    /// an error that dropping it would ignore.
+    pub fn rollback(mut self) -> Result<(), OrmError> {
+        self.finished = true;
+        self.roll_back()
+    }
+
+    fn roll_back(&mut self) -> Result<(), OrmError> {
+        let sql = match self.depth {
+            0 => "ROLLBACK;".to_string(),
+            depth => format!("ROLLBACK TO SAVEPOINT sp_{};", depth),
+        };
+        self.connection.execute(&sql, &[]).map(drop)
+    }
+}
+
+impl<C: Connection> Connection for Transaction<'_, C> {
+    fn dialect(&self) -> Dialect {
+        self.connection.dialect()
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError> {
+        self.connection.execute(sql, params)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError> {
+        self.connection.query(sql, params)
+    }
+
+    fn set_observer(&mut self, observer: Box<dyn QueryObserver>) {
+        self.connection.set_observer(observer)
+    }
+
+    fn transaction_depth(&self) -> usize {
+        self.depth + 1
+    }
+}
+
+impl<C: Connection> Drop for Transaction<'_, C> {
+    fn drop(&mut self) {
+        if !self.finished {
+            // There's no caller to report the error to; a failed rollback
+            // leaves the database to roll back when the connection closes
+            let _ = self.roll_back();
+        }
+    }
+}
+
+/// A connection to a database that statements can be run on asynchronously,
+/// as `Connection` runs them.
+pub trait AsyncConnection {
+    /// Returns the SQL flavor the database speaks.
+    fn dialect(&self) -> Dialect;
+
+    /// Runs a statement, returning the number of rows it affected.
+    fn execute(
+        &mut self,
+        sql: &str,
+        params: &[Value],
+    ) -> impl Future<Output = Result<u64, OrmError>> + Send;
+
+    /// Runs a query, returning its result rows.
+    fn query(
+        &mut self,
+        sql: &str,
+        params: &[Value],
+    ) -> impl Future<Output = Result<Vec<Row>, OrmError>> + Send;
+}
+
+impl<C: AsyncConnection + Send + ?Sized> AsyncConnection for &mut C {
+    fn dialect(&self) -> Dialect {
+        (**self).dialect()
+    }
+
+    fn execute(
+        &mut self,
+        sql: &str,
+        params: &[Value],
+    ) -> impl Future<Output = Result<u64, OrmError>> + Send {
+        (**self).execute(sql, params)
+    }
+
+    fn query(
+        &mut self,
+        sql: &str,
+        params: &[Value],
+    ) -> impl Future<Output = Result<Vec<Row>, OrmError>> + Send {
+        (**self).query(sql, params)
+    }
+}
+
+/// An error reported by the database or its driver, reported as
+/// `OrmError::Execution`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message}")]
+pub struct DatabaseError {
+    pub message: String,
+    /// The SQLSTATE code of the error, if the database reports one.
+    pub code: Option<String>,
+}
+
+impl DatabaseError {
+    /// Creates an error with the given message and no code.
+    pub fn new(message: impl Into<String>) -> Self {
+        DatabaseError { message: message.into(), code: None }
+    }
+}
+
+/// Errors of queries run by the `fetch_*` methods of `SelectQuery` and the
+/// methods of `ModelExt` returning other rows than expected, reported as
+/// `OrmError::Fetch`.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum FetchError {
+    #[error("query returned no rows")]
+    NoRows,
+    #[error("query returned more than one row")]
+    TooManyRows,
+    #[error(
+        "table `{table}` has a primary key of {expected} columns, but {found} values were given"
+    )]
+    KeyMismatch { table: String, expected: usize, found: usize },
+}
+
+/// An in-memory connection that records the statements run on it, usable
+/// as a `Connection` or an `AsyncConnection`.
+///
+/// Queries return the result sets queued with `push_rows` in order, then
+/// no rows; statements containing a fragment given to `fail_on` fail.
+#[derive(Debug, Clone)]
+pub struct MockConnection {
+    dialect: Dialect,
+    executed: Vec<(String, Vec<Value>)>,
+    results: VecDeque<Vec<Row>>,
+    failures: Vec<String>,
+    observer: ObserverSlot,
+}
+
+impl MockConnection {
+    /// Creates a connection for the given dialect.
+    pub fn new(dialect: Dialect) -> Self {
+        MockConnection {
+            dialect,
+            executed: Vec::new(),
+            results: VecDeque::new(),
+            failures: Vec::new(),
+            observer: ObserverSlot::default(),
+        }
+    }
+
+    /// Queues the rows returned by the next query without queued rows.
+    pub fn push_rows(&mut self, rows: Vec<Row>) {
+        self.results.push_back(rows);
+    }
+
+    /// Queues the rows returned by the next query without queued rows.
+    pub fn with_rows(mut self, rows: Vec<Row>) -> Self {
+        self.push_rows(rows);
+        self
+    }
+
+    /// Makes statements containing the fragment fail.
+    pub fn fail_on(mut self, fragment: &str) -> Self {
+        self.failures.push(fragment.to_string());
+        self
+    }
+
+    /// Returns the statements run so far with their bound values, in order.
+    pub fn executed(&self) -> &[(String, Vec<Value>)] {
+        &self.executed
+    }
+
+    /// Returns the SQL of the statements run so far, in order.
+    pub fn statements(&self) -> Vec<&str> {
+        self.executed.iter().map(|(sql, _)| sql.as_str()).collect()
+    }
+
+    fn record(&mut self, sql: &str, params: &[Value]) -> Result<(), DatabaseError> {
+        self.executed.push((sql.to_string(), params.to_vec()));
+        match self.failures.iter().find(|fragment| sql.contains(fragment.as_str())) {
+            Some(fragment) => Err(DatabaseError::new(format!("mock failure on `{}`", fragment))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Connection for MockConnection {
+    fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError> {
+        let observer = self.observer.clone();
+        Ok(observer.observe(sql, params, || self.record(sql, params).map(|()| 0))?)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError> {
+        let observer = self.observer.clone();
+        let rows = observer.observe(sql, params, || {
+            self.record(sql, params)?;
+            Ok(self.results.pop_front().unwrap_or_default())
+        })?;
+        Ok(rows)
+    }
+
+    fn set_observer(&mut self, observer: Box<dyn QueryObserver>) {
+        self.observer.set(observer);
+    }
+}
+
+impl AsyncConnection for MockConnection {
+    fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    async fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError> {
+        Connection::execute(self, sql, params)
+    }
+
+    async fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError> {
+        Connection::query(self, sql, params)
+    }
+}
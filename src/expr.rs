@@ -0,0 +1,343 @@
+use crate::model::{Model, ToValue, Value};
+use crate::query_builder::{SelectQuery, Subquery};
+
+/// Binding strengths of expressions; higher binds tighter.
+pub(crate) mod precedence {
+    /// Raw fragments may contain anything, so they always get parenthesized.
+    pub const RAW: u8 = 0;
+    pub const OR: u8 = 1;
+    pub const AND: u8 = 2;
+    pub const NOT: u8 = 3;
+    pub const COMPARISON: u8 = 4;
+    /// Columns and values never need parentheses.
+    pub const ATOM: u8 = 5;
+}
+
+/// Binary operators usable in expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    /// Returns the SQL spelling of the operator.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "<>",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::And => "AND",
+            BinaryOp::Or => "OR",
+        }
+    }
+
+    /// Returns the binding strength of the operator; higher binds tighter.
+    pub(crate) fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => precedence::OR,
+            BinaryOp::And => precedence::AND,
+            _ => precedence::COMPARISON,
+        }
+    }
+}
+
+/// A SQL expression, used to build WHERE conditions without raw strings.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    /// A column reference.
+    Column(String),
+    /// A bound value, rendered as a literal or a placeholder.
+    Value(Value),
+    /// A raw SQL fragment, emitted as is.
+    Raw(String),
+    /// A binary operation.
+    Binary { left: Box<Expr>, op: BinaryOp, right: Box<Expr> },
+    /// A negated expression.
+    Not(Box<Expr>),
+    /// An `IN (...)` or `NOT IN (...)` membership test.
+    In { expr: Box<Expr>, list: Vec<Expr>, negated: bool },
+    /// A `BETWEEN ... AND ...` or `NOT BETWEEN ... AND ...` range test.
+    Between { expr: Box<Expr>, low: Box<Expr>, high: Box<Expr>, negated: bool },
+    /// A `LIKE` pattern match, optionally negated or case-insensitive.
+    Like { expr: Box<Expr>, pattern: Box<Expr>, negated: bool, case_insensitive: bool },
+    /// An `IN (subquery)` or `NOT IN (subquery)` membership test.
+    InSubquery { expr: Box<Expr>, subquery: Box<Subquery>, negated: bool },
+    /// An `EXISTS (subquery)` or `NOT EXISTS (subquery)` test.
+    Exists { subquery: Box<Subquery>, negated: bool },
+    /// A row value such as `(a, b)`, comparable with another row value.
+    Tuple(Vec<Expr>),
+}
+
+impl Expr {
+    /// Creates a column reference.
+    pub fn col(name: &str) -> Self {
+        Expr::Column(name.to_string())
+    }
+
+    /// Creates a bound value.
+    pub fn val(value: impl ToValue) -> Self {
+        Expr::Value(value.to_value())
+    }
+
+    /// Creates a raw SQL fragment.
+    pub fn raw(sql: &str) -> Self {
+        Expr::Raw(sql.to_string())
+    }
+
+    /// Creates a row value from several expressions.
+    pub fn tuple(items: impl IntoIterator<Item = Expr>) -> Self {
+        Expr::Tuple(items.into_iter().collect())
+    }
+
+    fn binary(self, op: BinaryOp, right: impl Into<Expr>) -> Self {
+        Expr::Binary { left: Box::new(self), op, right: Box::new(right.into()) }
+    }
+
+    /// `self = right`
+    pub fn eq(self, right: impl Into<Expr>) -> Self {
+        self.binary(BinaryOp::Eq, right)
+    }
+
+    /// `self <> right`
+    pub fn ne(self, right: impl Into<Expr>) -> Self {
+        self.binary(BinaryOp::Ne, right)
+    }
+
+    /// `self < right`
+    pub fn lt(self, right: impl Into<Expr>) -> Self {
+        self.binary(BinaryOp::Lt, right)
+    }
+
+    /// `self <= right`
+    pub fn le(self, right: impl Into<Expr>) -> Self {
+        self.binary(BinaryOp::Le, right)
+    }
+
+    /// `self > right`
+    pub fn gt(self, right: impl Into<Expr>) -> Self {
+        self.binary(BinaryOp::Gt, right)
+    }
+
+    /// `self >= right`
+    pub fn ge(self, right: impl Into<Expr>) -> Self {
+        self.binary(BinaryOp::Ge, right)
+    }
+
+    /// `self AND right`
+    pub fn and(self, right: Expr) -> Self {
+        self.binary(BinaryOp::And, right)
+    }
+
+    /// `self OR right`
+    pub fn or(self, right: Expr) -> Self {
+        self.binary(BinaryOp::Or, right)
+    }
+
+    /// `self IN (values...)`; an empty list renders as the constant-false `1 = 0`.
+    pub fn in_list<V: ToValue>(self, values: &[V]) -> Self {
+        Expr::In {
+            expr: Box::new(self),
+            list: values.iter().map(Expr::val).collect(),
+            negated: false,
+        }
+    }
+
+    /// `self NOT IN (values...)`; an empty list renders as the constant-true `1 = 1`.
+    pub fn not_in_list<V: ToValue>(self, values: &[V]) -> Self {
+        Expr::In {
+            expr: Box::new(self),
+            list: values.iter().map(Expr::val).collect(),
+            negated: true,
+        }
+    }
+
+    /// `self BETWEEN low AND high`; the bounds are emitted as given.
+    pub fn between(self, low: impl Into<Expr>, high: impl Into<Expr>) -> Self {
+        Expr::Between {
+            expr: Box::new(self),
+            low: Box::new(low.into()),
+            high: Box::new(high.into()),
+            negated: false,
+        }
+    }
+
+    /// `self NOT BETWEEN low AND high`; the bounds are emitted as given.
+    pub fn not_between(self, low: impl Into<Expr>, high: impl Into<Expr>) -> Self {
+        Expr::Between {
+            expr: Box::new(self),
+            low: Box::new(low.into()),
+            high: Box::new(high.into()),
+            negated: true,
+        }
+    }
+
+    /// `self IN (subquery)`
+    pub fn in_subquery<R: Model>(self, subquery: SelectQuery<R>) -> Self {
+        Expr::InSubquery {
+            expr: Box::new(self),
+            subquery: Box::new(subquery.into()),
+            negated: false,
+        }
+    }
+
+    /// `self NOT IN (subquery)`
+    pub fn not_in_subquery<R: Model>(self, subquery: SelectQuery<R>) -> Self {
+        Expr::InSubquery {
+            expr: Box::new(self),
+            subquery: Box::new(subquery.into()),
+            negated: true,
+        }
+    }
+
+    /// `EXISTS (subquery)`
+    pub fn exists<R: Model>(subquery: SelectQuery<R>) -> Self {
+        Expr::Exists { subquery: Box::new(subquery.into()), negated: false }
+    }
+
+    /// `NOT EXISTS (subquery)`
+    pub fn not_exists<R: Model>(subquery: SelectQuery<R>) -> Self {
+        Expr::Exists { subquery: Box::new(subquery.into()), negated: true }
+    }
+
+    /// `self LIKE pattern`
+    pub fn like(self, pattern: impl Into<Expr>) -> Self {
+        self.pattern_match(pattern, false, false)
+    }
+
+    /// `self NOT LIKE pattern`
+    pub fn not_like(self, pattern: impl Into<Expr>) -> Self {
+        self.pattern_match(pattern, true, false)
+    }
+
+    /// `self ILIKE pattern`, a case-insensitive `LIKE`.
+    pub fn ilike(self, pattern: impl Into<Expr>) -> Self {
+        self.pattern_match(pattern, false, true)
+    }
+
+    fn pattern_match(
+        self,
+        pattern: impl Into<Expr>,
+        negated: bool,
+        case_insensitive: bool,
+    ) -> Self {
+        Expr::Like {
+            expr: Box::new(self),
+            pattern: Box::new(pattern.into()),
+            negated,
+            case_insensitive,
+        }
+    }
+
+    /// `NOT self`
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Expr::Not(Box::new(self))
+    }
+
+    /// Collects the columns referenced by the expression. Subqueries are
+    /// not descended into, as their columns belong to other tables.
+    pub(crate) fn collect_columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Expr::Column(name) => columns.push(name),
+            Expr::Value(_) | Expr::Raw(_) | Expr::Exists { .. } => {}
+            Expr::Binary { left, right, .. } => {
+                left.collect_columns(columns);
+                right.collect_columns(columns);
+            }
+            Expr::Not(inner) => inner.collect_columns(columns),
+            Expr::In { expr, list, .. } => {
+                expr.collect_columns(columns);
+                list.iter().for_each(|item| item.collect_columns(columns));
+            }
+            Expr::Between { expr, low, high, .. } => {
+                expr.collect_columns(columns);
+                low.collect_columns(columns);
+                high.collect_columns(columns);
+            }
+            Expr::Like { expr, pattern, .. } => {
+                expr.collect_columns(columns);
+                pattern.collect_columns(columns);
+            }
+            Expr::InSubquery { expr, .. } => expr.collect_columns(columns),
+            Expr::Tuple(items) => items.iter().for_each(|item| item.collect_columns(columns)),
+        }
+    }
+
+    /// Qualifies the unqualified column references of the expression with
+    /// the given table name or alias. Subqueries are left as they are.
+    pub(crate) fn qualify(self, qualifier: &str) -> Self {
+        let qualify = |expr: Box<Expr>| Box::new(expr.qualify(qualifier));
+        match self {
+            Expr::Column(name) if !name.contains('.') => {
+                Expr::Column(format!("{}.{}", qualifier, name))
+            }
+            Expr::Column(_) | Expr::Value(_) | Expr::Raw(_) | Expr::Exists { .. } => self,
+            Expr::Binary { left, op, right } => {
+                Expr::Binary { left: qualify(left), op, right: qualify(right) }
+            }
+            Expr::Not(inner) => Expr::Not(qualify(inner)),
+            Expr::In { expr, list, negated } => Expr::In {
+                expr: qualify(expr),
+                list: list.into_iter().map(|item| item.qualify(qualifier)).collect(),
+                negated,
+            },
+            Expr::Between { expr, low, high, negated } => Expr::Between {
+                expr: qualify(expr),
+                low: qualify(low),
+                high: qualify(high),
+                negated,
+            },
+            Expr::Like { expr, pattern, negated, case_insensitive } => Expr::Like {
+                expr: qualify(expr),
+                pattern: qualify(pattern),
+                negated,
+                case_insensitive,
+            },
+            Expr::InSubquery { expr, subquery, negated } => {
+                Expr::InSubquery { expr: qualify(expr), subquery, negated }
+            }
+            Expr::Tuple(items) => {
+                Expr::Tuple(items.into_iter().map(|item| item.qualify(qualifier)).collect())
+            }
+        }
+    }
+
+    /// Returns the binding strength of the expression's outermost operator.
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary { op, .. } => op.precedence(),
+            Expr::Not(_) => precedence::NOT,
+            Expr::In { .. }
+            | Expr::Between { .. }
+            | Expr::Like { .. }
+            | Expr::InSubquery { .. } => precedence::COMPARISON,
+            Expr::Exists { .. } => precedence::ATOM,
+            Expr::Column(_) | Expr::Value(_) | Expr::Tuple(_) => precedence::ATOM,
+            Expr::Raw(_) => precedence::RAW,
+        }
+    }
+}
+
+impl std::ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+impl<T: ToValue> From<T> for Expr {
+    fn from(value: T) -> Self {
+        Expr::Value(value.to_value())
+    }
+}
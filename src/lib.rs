@@ -1,5 +1,22 @@
+pub mod codegen;
+pub mod dialect;
 pub mod eager_loading;
+pub mod error;
+pub mod escape;
+pub mod executor;
+pub mod expr;
 
 pub mod migration;
 pub mod model;
+pub mod observer;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod query_builder;
+pub mod row;
+pub mod schema;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub mod statement_cache;
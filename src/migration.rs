@@ -1,16 +1,88 @@
-use crate::model::Model;
-use serde::{Deserialize, Serialize};
+use crate::dialect::{quote_if_needed, Dialect};
+use crate::error::OrmError;
+use crate::executor::Connection;
+use crate::model::{
+    Column, ColumnDefault, ForeignKey, Index, IndexMethod, Model, SchemaError, Table, Value,
+};
+use crate::query_builder::{format_literal, SelectQuery, Subquery};
+use crate::row::RowError;
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
-    fs::{self, File},
+    fs::{self, File, OpenOptions},
     io::Write,
     path::Path,
 };
+use thiserror::Error;
 
 /// Represents a migration with up and down SQL statements.
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Each list runs in order. An entry may hold several statements separated
+/// by semicolons, as migrations saved with a single string for each list
+/// do; those still deserialize, as a list of one entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Migration {
-    pub up: String,
-    pub down: String,
+    #[serde(deserialize_with = "statements")]
+    pub up: Vec<String>,
+    #[serde(deserialize_with = "statements")]
+    pub down: Vec<String>,
+    /// The checksum of the statements when the migration was saved, so that
+    /// later edits can be detected. Migrations saved without one, or as SQL
+    /// files, are not verified.
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+impl Migration {
+    /// Appends a statement to `up`, such as a data fix accompanying the
+    /// generated schema changes.
+    pub fn with_up_statement(mut self, sql: impl Into<String>) -> Self {
+        self.up.push(sql.into());
+        self
+    }
+
+    /// Adds a statement to the start of `down`, so that it runs before the
+    /// statements undoing the ones it was added after.
+    pub fn with_down_statement(mut self, sql: impl Into<String>) -> Self {
+        self.down.insert(0, sql.into());
+        self
+    }
+
+    /// Returns the `up` statements on separate lines.
+    pub fn up_sql(&self) -> String {
+        self.up.join("\n")
+    }
+
+    /// Returns the `down` statements on separate lines.
+    pub fn down_sql(&self) -> String {
+        self.down.join("\n")
+    }
+
+    /// Computes the checksum of the statements: the hex SHA-256 digest of
+    /// `up` and `down`, each on separate lines, so that migrations saved
+    /// with a single string for each keep their checksum.
+    pub fn compute_checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.up_sql().as_bytes());
+        // Separates the statements, so that moving SQL between them changes the digest
+        hasher.update([0]);
+        hasher.update(self.down_sql().as_bytes());
+        hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+/// Deserializes a list of statements, or a single string as a list of one.
+fn statements<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Statements {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Statements::deserialize(deserializer)? {
+        Statements::One(sql) => vec![sql],
+        Statements::Many(statements) => statements,
+    })
 }
 
 /// Generates a migration based on current and previous schemas.
@@ -19,52 +91,1431 @@ pub struct MigrationGenerator;
 impl MigrationGenerator {
     /// Generates a migration by comparing current and previous tables.
     ///
-    /// 
+    ///
     /// TODO: ameliorate this
     pub fn generate<T: Model>() -> Migration {
-        let table = T::table();
+        Self::generate_with_options::<T>(&MigrationOptions::new())
+    }
+
+    /// Generates a migration for the given dialect, quoting identifiers.
+    pub fn generate_for<T: Model>(dialect: Dialect) -> Migration {
+        Self::generate_with_options::<T>(&MigrationOptions::new().dialect(dialect))
+    }
+
+    /// Generates a migration with the given options.
+    pub fn generate_with_options<T: Model>(options: &MigrationOptions) -> Migration {
+        Self::generate_with(&T::table_ref(), options, quoter(options.dialect))
+    }
+
+    /// Validates the model's table and generates a migration for it.
+    pub fn try_generate<T: Model>() -> Result<Migration, SchemaError> {
+        T::table_ref().validate()?;
+        Ok(Self::generate::<T>())
+    }
+
+    /// Validates the model's table and generates a migration for the given dialect.
+    pub fn try_generate_for<T: Model>(dialect: Dialect) -> Result<Migration, SchemaError> {
+        T::table_ref().validate()?;
+        Ok(Self::generate_for::<T>(dialect))
+    }
+
+    /// Generates a single migration creating all the tables.
+    ///
+    /// Tables are created after the tables their foreign keys reference, and
+    /// dropped in the reverse order. Foreign keys to tables outside the set
+    /// are assumed to be satisfied already. Fails if the tables don't
+    /// validate or if their foreign keys form a cycle.
+    pub fn generate_all(tables: &[Table]) -> Result<Migration, SchemaError> {
+        Self::generate_all_with_options(tables, &MigrationOptions::new())
+    }
+
+    /// Generates a single migration creating all the tables, for the given dialect.
+    pub fn generate_all_for(tables: &[Table], dialect: Dialect) -> Result<Migration, SchemaError> {
+        Self::generate_all_with_options(tables, &MigrationOptions::new().dialect(dialect))
+    }
+
+    /// Generates a single migration creating all the tables, with the given options.
+    pub fn generate_all_with_options(
+        tables: &[Table],
+        options: &MigrationOptions,
+    ) -> Result<Migration, SchemaError> {
+        for table in tables {
+            table.validate()?;
+        }
+        let ident = quoter(options.dialect);
+        let migrations: Vec<Migration> = dependency_order(tables)?
+            .into_iter()
+            .map(|table| Self::generate_with(table, options, &ident))
+            .collect();
+        let up = migrations.iter().flat_map(|m| m.up.iter().cloned()).collect();
+        let down = migrations.iter().rev().flat_map(|m| m.down.iter().cloned()).collect();
+        Ok(Migration { up, down, checksum: None })
+    }
+
+    /// Generates a migration creating the view, and dropping it in down.
+    pub fn generate_view(view: &View) -> Migration {
+        Self::generate_view_with_options(view, &MigrationOptions::new())
+    }
+
+    /// Generates a migration creating the view, for the given dialect.
+    pub fn generate_view_for(view: &View, dialect: Dialect) -> Migration {
+        Self::generate_view_with_options(view, &MigrationOptions::new().dialect(dialect))
+    }
+
+    /// Generates a migration creating the view, with the given options.
+    ///
+    /// Clauses a dialect lacks are worked around or left out: views are only
+    /// materialized on Postgres, OR REPLACE becomes a DROP of the old view
+    /// where it isn't supported, and IF NOT EXISTS is dropped on Postgres
+    /// plain views and on MySQL. OR REPLACE takes precedence over IF NOT EXISTS.
+    pub fn generate_view_with_options(view: &View, options: &MigrationOptions) -> Migration {
+        let dialect = options.dialect;
+        let ident = quoter(dialect);
+        let name = quote_path(&view.name, &ident);
+        let materialized = view.materialized && matches!(dialect, Some(Dialect::Postgres) | None);
+        let kind = if materialized { "MATERIALIZED VIEW" } else { "VIEW" };
+        let drop = format!("DROP {} IF EXISTS {};", kind, name);
+
+        let mut up = Vec::new();
+        let mut modifier = "";
+        if view.or_replace {
+            // Neither SQLite nor materialized views can be replaced in place
+            if materialized || dialect == Some(Dialect::Sqlite) {
+                up.push(drop.clone());
+            } else {
+                modifier = "OR REPLACE ";
+            }
+        }
+        let if_not_exists = options.if_not_exists
+            && !view.or_replace
+            && match dialect {
+                Some(Dialect::Postgres) => materialized,
+                Some(Dialect::MySql) => false,
+                Some(Dialect::Sqlite) | None => true,
+            };
+        up.push(format!(
+            "CREATE {}{} {}{} AS {};",
+            modifier,
+            kind,
+            if if_not_exists { "IF NOT EXISTS " } else { "" },
+            name,
+            view.query.build_inline(dialect)
+        ));
+        Migration { up, down: vec![drop], checksum: None }
+    }
+
+    fn generate_with(
+        table: &Table,
+        options: &MigrationOptions,
+        ident: impl Fn(&str) -> String,
+    ) -> Migration {
+        let dialect = options.dialect;
+        // A composite primary key is declared as a table constraint
+        let primary_key = table.primary_key();
+        let composite_key = (primary_key.len() > 1).then(|| {
+            format!(
+                "PRIMARY KEY ({})",
+                primary_key.iter().map(|col| ident(&col.name)).collect::<Vec<String>>().join(", ")
+            )
+        });
 
         // TODO BETTER
-      // Generate simple CREATE TABLE and DROP TABLE statements
-        let up = format!(
-            "CREATE TABLE {} ({});",
-            table.name,
-            table
-                .columns
-                .iter()
-                .map(|col| format!(
-                    "{} {}{}",
-                    col.name,
-                    map_data_type_to_sql(&col.data_type),
-                    if col.is_primary_key { " PRIMARY KEY" } else { "" }
+        // Generate simple CREATE TABLE and DROP TABLE statements, with the
+        // table constraints after the columns
+        let inline_primary_key = composite_key.is_none();
+        let definitions = table
+            .columns
+            .iter()
+            .map(|col| column_definition(col, inline_primary_key, dialect, &ident))
+            .chain(composite_key)
+            .chain(table.unique_constraints.iter().map(|columns| {
+                format!(
+                    "UNIQUE ({})",
+                    columns.iter().map(|col| ident(col)).collect::<Vec<String>>().join(", ")
+                )
+            }))
+            .chain(table.columns.iter().filter_map(|col| {
+                let foreign_key = col.foreign_key.as_ref()?;
+                Some(format!(
+                    "FOREIGN KEY ({}) {}",
+                    ident(&col.name),
+                    references(foreign_key, &ident)
                 ))
-                .collect::<Vec<String>>()
-                .join(", ")
-        );
+            }))
+            .collect::<Vec<String>>();
+        let table_name = quote_path(&table.qualified_name(), &ident);
+        let mut up = vec![format!(
+            "CREATE TABLE {}{} ({});",
+            if options.if_not_exists { "IF NOT EXISTS " } else { "" },
+            table_name,
+            definitions.join(", ")
+        )];
+        up.extend(table.indexes.iter().map(|index| create_index(table, index, options, &ident)));
+
+        // Indexes are dropped in reverse order before their table
+        let mut down = table
+            .indexes
+            .iter()
+            .rev()
+            .map(|index| drop_index(table, index, dialect, &ident))
+            .collect::<Vec<String>>();
+        down.push(format!("DROP TABLE IF EXISTS {};", table_name));
 
-        let down = format!("DROP TABLE IF EXISTS {};", table.name);
+        Migration { up, down, checksum: None }
+    }
 
-        Migration { up, down }
+    /// Generates a migration altering the `old` table into the `new` one.
+    ///
+    /// Added and removed columns are added and dropped, and columns whose
+    /// type, nullability or default changed are altered in place; the order
+    /// of columns is ignored. `down` undoes each statement in reverse order.
+    /// Tables that are equal give a migration with empty statements.
+    pub fn diff(old: &Table, new: &Table) -> Migration {
+        Self::diff_with(old, new, None, quote_if_needed)
     }
 
-    /// Saves the migration to the specified directory with the given name.
-    pub fn save_migration(migration: &Migration, name: &str, path: &str) -> std::io::Result<()> {
+    /// Generates a migration altering the `old` table into the `new` one, for
+    /// the given dialect.
+    ///
+    /// MySQL redefines altered columns with `MODIFY COLUMN`. SQLite can't
+    /// alter columns, so unless columns are only added, the table is rebuilt:
+    /// created anew, filled with the rows of the old table, and renamed.
+    pub fn diff_for(old: &Table, new: &Table, dialect: Dialect) -> Migration {
+        Self::diff_with(old, new, Some(dialect), |name| dialect.quote_identifier(name))
+    }
+
+    fn diff_with(
+        old: &Table,
+        new: &Table,
+        dialect: Option<Dialect>,
+        ident: impl Fn(&str) -> String,
+    ) -> Migration {
+        let table_name = quote_path(&new.qualified_name(), &ident);
+        let alter = |action: String| format!("ALTER TABLE {} {};", table_name, action);
+        let inline_primary_key = new.primary_key().len() <= 1;
+        let is_altered = |old: &Column, new: &Column| {
+            old.data_type != new.data_type
+                || old.is_nullable != new.is_nullable
+                || old.default != new.default
+        };
+
+        if dialect == Some(Dialect::Sqlite)
+            && (old.columns.iter().any(|col| new.column(&col.name).is_none())
+                || new.columns.iter().any(|col| {
+                    old.column(&col.name).is_some_and(|old_col| is_altered(old_col, col))
+                }))
+        {
+            return Migration {
+                up: rebuild_table(old, new, &ident),
+                down: rebuild_table(new, old, &ident),
+                checksum: None,
+            };
+        }
+
+        // (up, down) statement pairs, in the order they are applied
+        let mut steps = Vec::new();
+        for column in &old.columns {
+            if new.column(&column.name).is_none() {
+                steps.push((
+                    alter(format!("DROP COLUMN {}", ident(&column.name))),
+                    alter(format!(
+                        "ADD COLUMN {}",
+                        added_column_definition(column, inline_primary_key, dialect, &ident)
+                    )),
+                ));
+            }
+        }
+        for column in &new.columns {
+            let Some(old_column) = old.column(&column.name) else {
+                steps.push((
+                    alter(format!(
+                        "ADD COLUMN {}",
+                        added_column_definition(column, inline_primary_key, dialect, &ident)
+                    )),
+                    alter(format!("DROP COLUMN {}", ident(&column.name))),
+                ));
+                continue;
+            };
+            if dialect == Some(Dialect::MySql) {
+                if is_altered(old_column, column) {
+                    let modify = |column: &Column| {
+                        alter(format!("MODIFY COLUMN {}", modify_definition(column, &ident)))
+                    };
+                    steps.push((modify(column), modify(old_column)));
+                }
+                continue;
+            }
+            // The type changes first, so that a new default is set on the new type
+            let name = ident(&column.name);
+            if old_column.data_type != column.data_type {
+                let retype = |column: &Column| {
+                    alter(format!(
+                        "ALTER COLUMN {} TYPE {}",
+                        name,
+                        map_data_type_to_sql(&column.data_type, dialect)
+                    ))
+                };
+                steps.push((retype(column), retype(old_column)));
+            }
+            if old_column.is_nullable != column.is_nullable {
+                let nullability = |column: &Column| {
+                    let action = if column.is_nullable { "DROP" } else { "SET" };
+                    alter(format!("ALTER COLUMN {} {} NOT NULL", name, action))
+                };
+                steps.push((nullability(column), nullability(old_column)));
+            }
+            if old_column.default != column.default {
+                let default = |column: &Column| match &column.default {
+                    Some(default) => alter(format!(
+                        "ALTER COLUMN {} SET DEFAULT {}",
+                        name,
+                        default_sql(default, dialect)
+                    )),
+                    None => alter(format!("ALTER COLUMN {} DROP DEFAULT", name)),
+                };
+                steps.push((default(column), default(old_column)));
+            }
+        }
+
+        let down = steps.iter().rev().map(|(_, down)| down.clone()).collect();
+        let up = steps.into_iter().map(|(up, _)| up).collect();
+        Migration { up, down, checksum: None }
+    }
+
+    /// Generates a migration for the model against its last snapshot.
+    ///
+    /// The table as of the previous migration is read from
+    /// `<snapshot_dir>/<table>.json`; without one, the migration creates the
+    /// table, otherwise it alters the snapshot into the current table. The
+    /// current table is then saved as the new snapshot.
+    pub fn generate_with_snapshot<T: Model>(snapshot_dir: &str) -> Result<Migration, OrmError> {
+        let table = T::table_ref();
+        let snapshot_dir = Path::new(snapshot_dir);
+        let snapshot = snapshot_dir.join(format!("{}.json", table.qualified_name()));
+        let migration = match fs::read_to_string(&snapshot) {
+            Ok(json) => {
+                let previous = serde_json::from_str(&json).map_err(|source| {
+                    MigrationError::InvalidSnapshot { path: snapshot.display().to_string(), source }
+                })?;
+                Self::diff(&previous, &table)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::generate::<T>(),
+            Err(e) => return Err(MigrationError::Io(e).into()),
+        };
+
+        fs::create_dir_all(snapshot_dir).map_err(MigrationError::Io)?;
+        let json = serde_json::to_string_pretty(&*table).expect("tables always serialize to JSON");
+        fs::write(&snapshot, json).map_err(MigrationError::Io)?;
+        Ok(migration)
+    }
+
+    /// Saves the migration to the specified directory with the given name,
+    /// along with the checksum of its statements.
+    pub fn save_migration(migration: &Migration, name: &str, path: &str) -> Result<(), OrmError> {
         let migration_dir = Path::new(path);
-        fs::create_dir_all(migration_dir)?;
+        fs::create_dir_all(migration_dir).map_err(MigrationError::Io)?;
         let migration_file = migration_dir.join(format!("{}.json", name));
-        let serialized = serde_json::to_string_pretty(migration)?;
-        let mut file = File::create(migration_file)?;
-        file.write_all(serialized.as_bytes())?;
+        let serialized = checksummed_json(migration);
+        let mut file = File::create(migration_file).map_err(MigrationError::Io)?;
+        file.write_all(serialized.as_bytes()).map_err(MigrationError::Io)?;
         Ok(())
     }
+
+    /// Saves the migration as `{name}.up.sql` and `{name}.down.sql`, one
+    /// statement per line, for tools that apply plain SQL files.
+    ///
+    /// Fails without writing anything if either file already exists.
+    pub fn save_migration_sql(
+        migration: &Migration,
+        name: &str,
+        path: &str,
+    ) -> Result<(), OrmError> {
+        Self::save_migration_as(migration, name, path, MigrationFormat::Sql, false)
+    }
+
+    /// Saves the migration in the given format.
+    ///
+    /// Unless `overwrite` is set, fails without writing anything if any of
+    /// the files already exists.
+    pub fn save_migration_as(
+        migration: &Migration,
+        name: &str,
+        path: &str,
+        format: MigrationFormat,
+        overwrite: bool,
+    ) -> Result<(), OrmError> {
+        let migration_dir = Path::new(path);
+        let mut files = Vec::new();
+        if matches!(format, MigrationFormat::Json | MigrationFormat::Both) {
+            let serialized = checksummed_json(migration);
+            files.push((migration_dir.join(format!("{}.json", name)), serialized));
+        }
+        if matches!(format, MigrationFormat::Sql | MigrationFormat::Both) {
+            files.push((
+                migration_dir.join(format!("{}.up.sql", name)),
+                sql_file(&migration.up_sql()),
+            ));
+            files.push((
+                migration_dir.join(format!("{}.down.sql", name)),
+                sql_file(&migration.down_sql()),
+            ));
+        }
+
+        if !overwrite {
+            if let Some((file, _)) = files.iter().find(|(file, _)| file.exists()) {
+                return Err(MigrationError::Io(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("migration file {} already exists", file.display()),
+                ))
+                .into());
+            }
+        }
+        fs::create_dir_all(migration_dir).map_err(MigrationError::Io)?;
+        for (file, contents) in files {
+            let mut options = OpenOptions::new();
+            options.write(true);
+            if overwrite {
+                options.create(true).truncate(true);
+            } else {
+                options.create_new(true);
+            }
+            options
+                .open(file)
+                .and_then(|mut file| file.write_all(contents.as_bytes()))
+                .map_err(MigrationError::Io)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the name prefixed so that it sorts after the migrations
+    /// already saved in the directory.
+    ///
+    /// A timestamp prefix such as `20240501123045_create_users_table` is
+    /// bumped past the last saved migration if the clock is behind it or two
+    /// migrations are generated within a second.
+    pub fn prefixed_name(
+        name: &str,
+        path: &str,
+        naming: MigrationNaming,
+    ) -> Result<String, OrmError> {
+        let last = match fs::read_dir(path) {
+            Ok(entries) => entries
+                .map(|entry| Ok(migration_prefix(&entry?.file_name().to_string_lossy())))
+                .collect::<std::io::Result<Vec<Option<u64>>>>()
+                .map_err(MigrationError::Io)?
+                .into_iter()
+                .flatten()
+                .max(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(MigrationError::Io(e).into()),
+        };
+        let prefix = match naming {
+            MigrationNaming::Timestamp => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_secs());
+                // The bump goes through the seconds it stands for, so that it
+                // rolls over into the next minute, day or year
+                let secs = match last {
+                    Some(last) if last >= utc_timestamp(now) => timestamp_secs(last) + 1,
+                    _ => now,
+                };
+                utc_timestamp(secs).to_string()
+            }
+            MigrationNaming::Sequence => format!("{:04}", last.map_or(1, |last| last + 1)),
+        };
+        Ok(format!("{}_{}", prefix, name))
+    }
+
+    /// Loads the migrations saved in the directory, ordered by their prefix.
+    ///
+    /// Migrations are read from `{name}.json` files, or from
+    /// `{name}.up.sql` and `{name}.down.sql` files if there is no JSON file.
+    pub fn load_migrations(path: &str) -> Result<Vec<(String, Migration)>, OrmError> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path).map_err(MigrationError::Io)? {
+            let file_name =
+                entry.map_err(MigrationError::Io)?.file_name().to_string_lossy().into_owned();
+            if MIGRATION_SUFFIXES.iter().any(|suffix| file_name.ends_with(suffix)) {
+                let contents = fs::read_to_string(Path::new(path).join(&file_name))
+                    .map_err(MigrationError::Io)?;
+                files.push((file_name, contents));
+            }
+        }
+        let files: Vec<(&str, &str)> =
+            files.iter().map(|(name, contents)| (name.as_str(), contents.as_str())).collect();
+        Migration::from_embedded(&files)
+    }
+}
+
+/// The suffixes of the files migrations are saved in.
+const MIGRATION_SUFFIXES: [&str; 3] = [".json", ".up.sql", ".down.sql"];
+
+impl Migration {
+    /// Reads migrations from the names and contents of the files they were
+    /// saved in, ordered by their prefix, as `MigrationGenerator::load_migrations`
+    /// does for a directory.
+    ///
+    /// The files are typically embedded in the binary with
+    /// `rusty_orm_macros::embed_migrations!`. Other files are ignored.
+    pub fn from_embedded(files: &[(&str, &str)]) -> Result<Vec<(String, Migration)>, OrmError> {
+        let file = |name: &str| files.iter().find(|(file, _)| *file == name).map(|(_, c)| *c);
+        let mut migrations: Vec<(String, Migration)> = Vec::new();
+        for (file_name, contents) in files {
+            if let Some(name) = file_name.strip_suffix(".json") {
+                let migration = serde_json::from_str(contents).map_err(|source| {
+                    MigrationError::InvalidMigration { name: name.to_string(), source }
+                })?;
+                migrations.push((name.to_string(), migration));
+            }
+        }
+        for (file_name, up) in files {
+            let Some(name) = file_name.strip_suffix(".up.sql") else { continue };
+            if migrations.iter().any(|(other, _)| other == name) {
+                continue;
+            }
+            let Some(down) = file(&format!("{}.down.sql", name)) else {
+                return Err(MigrationError::MissingDownFile { name: name.to_string() }.into());
+            };
+            let migration = Migration {
+                up: vec![up.trim_end().to_string()],
+                down: vec![down.trim_end().to_string()],
+                checksum: None,
+            };
+            migrations.push((name.to_string(), migration));
+        }
+        migrations
+            .sort_by(|(a, _), (b, _)| (migration_prefix(a), a).cmp(&(migration_prefix(b), b)));
+        Ok(migrations)
+    }
+}
+
+/// How `MigrationGenerator::prefixed_name` orders migration names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationNaming {
+    /// A UTC timestamp, `YYYYMMDDHHMMSS`.
+    Timestamp,
+    /// A sequence number, `0001`, `0002`, ...
+    ///
+    /// Numbers past `9999` widen, and migrations load in numeric order.
+    Sequence,
+}
+
+/// Returns the numeric prefix of a migration name, e.g. 1 for `0001_create_users`.
+fn migration_prefix(name: &str) -> Option<u64> {
+    name.split_once('_').and_then(|(prefix, _)| prefix.parse().ok())
+}
+
+/// Returns a UTC time, in seconds since the Unix epoch, as a `YYYYMMDDHHMMSS` number.
+fn utc_timestamp(secs: u64) -> u64 {
+    let (days, time) = (secs / 86400, secs % 86400);
+
+    // Converts days since 1970-01-01 to a civil date, in eras of 400 years
+    // starting on March 1st so that leap days come last
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    let date = year * 10000 + month * 100 + day;
+    date * 1_000_000 + (time / 3600) * 10000 + (time / 60 % 60) * 100 + time % 60
+}
+
+/// Returns the seconds since the Unix epoch of a `YYYYMMDDHHMMSS` number,
+/// the inverse of `utc_timestamp`.
+///
+/// Out of range fields carry over, so a second of 60 is the next minute.
+fn timestamp_secs(timestamp: u64) -> u64 {
+    let (date, time) = (timestamp / 1_000_000, timestamp % 1_000_000);
+    let (year, month, day) = (date / 10000, date / 100 % 100, date % 100);
+    let time = (time / 10000) * 3600 + (time / 100 % 100) * 60 + time % 100;
+
+    // Converts a civil date to days since 1970-01-01, in the same eras
+    let year = year - u64::from(month <= 2);
+    let (era, year_of_era) = (year / 400, year % 400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146097 + day_of_era).saturating_sub(719469);
+    days * 86400 + time
+}
+
+/// Options for the migrations generated by `MigrationGenerator`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationOptions {
+    /// The dialect to generate for, or `None` for standard SQL.
+    pub dialect: Option<Dialect>,
+    /// Whether tables, indexes and views are only created if they don't exist yet.
+    ///
+    /// MySQL indexes and views are always created, as it doesn't support the
+    /// check; neither does Postgres for views that aren't materialized.
+    pub if_not_exists: bool,
+}
+
+impl MigrationOptions {
+    /// Creates options for standard SQL, creating tables unconditionally.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates for the given dialect, quoting identifiers.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
+    /// Creates tables, indexes and views with `IF NOT EXISTS`.
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+}
+
+/// A view over a SELECT query, created with `MigrationGenerator::generate_view`.
+#[derive(Debug, Clone)]
+pub struct View {
+    /// The name of the view, optionally qualified with a schema.
+    pub name: String,
+    /// The query the view selects; its values are inlined into the definition.
+    pub query: Subquery,
+    /// Whether an existing view of the same name is replaced.
+    pub or_replace: bool,
+    /// Whether the view is materialized, which only Postgres supports.
+    pub materialized: bool,
+}
+
+/// Builds a `View` from a name and a query.
+#[derive(Debug, Clone)]
+pub struct ViewBuilder {
+    view: View,
+}
+
+impl ViewBuilder {
+    /// Starts a plain view selecting the query.
+    pub fn new<T: Model>(name: &str, query: SelectQuery<T>) -> Self {
+        ViewBuilder {
+            view: View {
+                name: name.to_string(),
+                query: query.into(),
+                or_replace: false,
+                materialized: false,
+            },
+        }
+    }
+
+    /// Replaces an existing view of the same name.
+    pub fn or_replace(mut self) -> Self {
+        self.view.or_replace = true;
+        self
+    }
+
+    /// Materializes the view on Postgres; other dialects create a plain view.
+    pub fn materialized(mut self) -> Self {
+        self.view.materialized = true;
+        self
+    }
+
+    /// Returns the view.
+    pub fn build(self) -> View {
+        self.view
+    }
+}
+
+/// Returns the function quoting identifiers for the dialect: always with one,
+/// and only where needed without.
+fn quoter(dialect: Option<Dialect>) -> impl Fn(&str) -> String {
+    move |name| match dialect {
+        Some(dialect) => dialect.quote_identifier(name),
+        None => quote_if_needed(name),
+    }
+}
+
+/// The file format migrations are saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationFormat {
+    /// A single `{name}.json` file holding both statements.
+    Json,
+    /// `{name}.up.sql` and `{name}.down.sql` files.
+    Sql,
+    /// Both the JSON and the SQL files.
+    Both,
+}
+
+/// Serializes the migration with the checksum of its statements.
+fn checksummed_json(migration: &Migration) -> String {
+    serde_json::to_string_pretty(&Migration {
+        up: migration.up.clone(),
+        down: migration.down.clone(),
+        checksum: Some(migration.compute_checksum()),
+    })
+    .expect("migrations always serialize to JSON")
+}
+
+/// Returns the contents of a SQL file for the statements, ending with a newline.
+fn sql_file(statements: &str) -> String {
+    if statements.is_empty() || statements.ends_with('\n') {
+        statements.to_string()
+    } else {
+        format!("{}\n", statements)
+    }
+}
+
+/// Orders tables so that each comes after the tables it references.
+///
+/// Tables are kept in their given order where their foreign keys allow.
+fn dependency_order(tables: &[Table]) -> Result<Vec<&Table>, SchemaError> {
+    // Whether `table` has a foreign key to another table, `other`
+    let references = |table: &Table, other: &Table| {
+        !std::ptr::eq(table, other)
+            && table.columns.iter().filter_map(|col| col.foreign_key.as_ref()).any(|foreign_key| {
+                foreign_key.table == other.name || foreign_key.table == other.qualified_name()
+            })
+    };
+
+    let mut remaining: Vec<&Table> = tables.iter().collect();
+    let mut ordered = Vec::new();
+    while !remaining.is_empty() {
+        let ready = remaining
+            .iter()
+            .position(|table| !remaining.iter().any(|other| references(table, other)));
+        match ready {
+            Some(i) => ordered.push(remaining.remove(i)),
+            None => {
+                // Leave out the tables that only depend on the cycle to report it
+                while let Some(i) = remaining
+                    .iter()
+                    .position(|table| !remaining.iter().any(|other| references(other, table)))
+                {
+                    remaining.remove(i);
+                }
+                return Err(SchemaError::CircularReference {
+                    tables: remaining.iter().map(|table| table.qualified_name()).collect(),
+                });
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+/// Renders the definition of a column in a CREATE TABLE statement for the
+/// given dialect, or for standard SQL without one.
+///
+/// The definition holds the column's name and type, followed by its
+/// constraints: auto-increment, then `PRIMARY KEY` or `NOT NULL`, `DEFAULT`
+/// and `UNIQUE`. Foreign keys are declared as table constraints instead.
+pub fn render_column_def(column: &Column, dialect: Option<Dialect>) -> String {
+    column_definition(column, true, dialect, &quoter(dialect))
+}
+
+/// Renders a column definition of a CREATE TABLE statement, marking it as the
+/// primary key inline if `inline_primary_key` is set.
+fn column_definition(
+    column: &Column,
+    inline_primary_key: bool,
+    dialect: Option<Dialect>,
+    ident: &impl Fn(&str) -> String,
+) -> String {
+    // SQLite only generates keys for an INTEGER PRIMARY KEY, an alias of the rowid
+    let is_primary_key = column.is_primary_key && inline_primary_key;
+    let sqlite_rowid = column.is_auto_increment && dialect == Some(Dialect::Sqlite);
+    let data_type = if sqlite_rowid {
+        "INTEGER".to_string()
+    } else {
+        map_data_type_to_sql(&column.data_type, dialect)
+    };
+    let mut definition = format!("{} {}", ident(&column.name), data_type);
+    if column.is_auto_increment {
+        match dialect {
+            Some(Dialect::Postgres) | None => definition.push_str(" GENERATED ALWAYS AS IDENTITY"),
+            Some(Dialect::MySql) => definition.push_str(" AUTO_INCREMENT"),
+            Some(Dialect::Sqlite) => {}
+        }
+    }
+    if is_primary_key {
+        definition.push_str(" PRIMARY KEY");
+        if sqlite_rowid {
+            definition.push_str(" AUTOINCREMENT");
+        }
+    } else if !column.is_nullable {
+        definition.push_str(" NOT NULL");
+    }
+    if let Some(default) = &column.default {
+        definition.push_str(&format!(" DEFAULT {}", default_sql(default, dialect)));
+    }
+    if column.is_unique && !is_primary_key {
+        definition.push_str(" UNIQUE");
+    }
+    definition
+}
+
+/// Renders the REFERENCES clause of a foreign key, with its referential actions.
+fn references(foreign_key: &ForeignKey, ident: &impl Fn(&str) -> String) -> String {
+    let mut sql = format!(
+        "REFERENCES {}({})",
+        quote_path(&foreign_key.table, ident),
+        ident(&foreign_key.column)
+    );
+    if let Some(action) = foreign_key.on_delete {
+        sql.push_str(&format!(" ON DELETE {}", action.as_sql()));
+    }
+    if let Some(action) = foreign_key.on_update {
+        sql.push_str(&format!(" ON UPDATE {}", action.as_sql()));
+    }
+    sql
+}
+
+/// Renders a column definition for ADD COLUMN, where a foreign key can only
+/// be declared inline.
+fn added_column_definition(
+    column: &Column,
+    inline_primary_key: bool,
+    dialect: Option<Dialect>,
+    ident: &impl Fn(&str) -> String,
+) -> String {
+    let mut definition = column_definition(column, inline_primary_key, dialect, ident);
+    if let Some(foreign_key) = &column.foreign_key {
+        definition.push(' ');
+        definition.push_str(&references(foreign_key, ident));
+    }
+    definition
+}
+
+/// Renders a column as redefined by MySQL's MODIFY COLUMN: its type,
+/// nullability and default, leaving its key and constraints as they are.
+fn modify_definition(column: &Column, ident: &impl Fn(&str) -> String) -> String {
+    let dialect = Some(Dialect::MySql);
+    let mut definition =
+        format!("{} {}", ident(&column.name), map_data_type_to_sql(&column.data_type, dialect));
+    if column.is_auto_increment {
+        definition.push_str(" AUTO_INCREMENT");
+    }
+    if !column.is_nullable || column.is_primary_key {
+        definition.push_str(" NOT NULL");
+    }
+    if let Some(default) = &column.default {
+        definition.push_str(&format!(" DEFAULT {}", default_sql(default, dialect)));
+    }
+    definition
+}
+
+/// Renders the statements rebuilding a SQLite table `from` as the table
+/// `to`, keeping the values of the columns they share.
+fn rebuild_table(from: &Table, to: &Table, ident: &impl Fn(&str) -> String) -> Vec<String> {
+    let options = MigrationOptions::new().dialect(Dialect::Sqlite);
+    let rebuilt = Table { name: format!("new_{}", to.name), indexes: Vec::new(), ..to.clone() };
+    let rebuilt_name = quote_path(&rebuilt.qualified_name(), ident);
+    let columns = to
+        .columns
+        .iter()
+        .filter(|col| from.column(&col.name).is_some())
+        .map(|col| ident(&col.name))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let mut sql = MigrationGenerator::generate_with(&rebuilt, &options, ident).up;
+    sql.extend([
+        format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {};",
+            rebuilt_name,
+            columns,
+            columns,
+            quote_path(&from.qualified_name(), ident)
+        ),
+        format!("DROP TABLE {};", quote_path(&from.qualified_name(), ident)),
+        format!("ALTER TABLE {} RENAME TO {};", rebuilt_name, ident(&to.name)),
+    ]);
+    sql.extend(to.indexes.iter().map(|index| create_index(to, index, &options, ident)));
+    sql
+}
+
+/// Renders a column default: a literal quoted as needed, or an expression verbatim.
+fn default_sql(default: &ColumnDefault, dialect: Option<Dialect>) -> String {
+    match default {
+        ColumnDefault::Value(value) => format_literal(value, dialect),
+        ColumnDefault::Expr(expr) => expr.clone(),
+    }
+}
+
+/// Quotes a possibly schema-qualified name such as `crm.users` part by part.
+fn quote_path(name: &str, ident: &impl Fn(&str) -> String) -> String {
+    name.split('.').map(ident).collect::<Vec<String>>().join(".")
+}
+
+/// Renders the CREATE INDEX statement of an index on the given table.
+///
+/// An index lives in the schema of its table; SQLite names that schema on
+/// the index rather than on the table.
+fn create_index(
+    table: &Table,
+    index: &Index,
+    options: &MigrationOptions,
+    ident: &impl Fn(&str) -> String,
+) -> String {
+    let dialect = options.dialect;
+    let (index_name, table_name) = match dialect {
+        Some(Dialect::Sqlite) => (qualified_index_name(table, index, ident), ident(&table.name)),
+        _ => (ident(&index.name), quote_path(&table.qualified_name(), ident)),
+    };
+    let columns = index.columns.iter().map(|col| ident(col)).collect::<Vec<String>>().join(", ");
+    let method = index.method.filter(|method| match dialect {
+        Some(Dialect::Postgres) | None => true,
+        Some(Dialect::MySql) => matches!(method, IndexMethod::BTree | IndexMethod::Hash),
+        Some(Dialect::Sqlite) => false,
+    });
+    // MySQL has no IF NOT EXISTS for indexes
+    let if_not_exists = options.if_not_exists && dialect != Some(Dialect::MySql);
+    let mut sql = format!(
+        "CREATE {}INDEX {}{} ON {}",
+        if index.is_unique { "UNIQUE " } else { "" },
+        if if_not_exists { "IF NOT EXISTS " } else { "" },
+        index_name,
+        table_name
+    );
+    match (method, dialect) {
+        (Some(method), Some(Dialect::MySql)) => {
+            sql.push_str(&format!(" ({}) USING {};", columns, method.as_sql()))
+        }
+        (Some(method), _) => sql.push_str(&format!(" USING {} ({});", method.as_sql(), columns)),
+        (None, _) => sql.push_str(&format!(" ({});", columns)),
+    }
+    sql
+}
+
+/// Renders the DROP INDEX statement of an index on the given table.
+fn drop_index(
+    table: &Table,
+    index: &Index,
+    dialect: Option<Dialect>,
+    ident: &impl Fn(&str) -> String,
+) -> String {
+    match dialect {
+        // MySQL indexes are scoped to their table
+        Some(Dialect::MySql) => format!(
+            "DROP INDEX {} ON {};",
+            ident(&index.name),
+            quote_path(&table.qualified_name(), ident)
+        ),
+        _ => format!("DROP INDEX IF EXISTS {};", qualified_index_name(table, index, ident)),
+    }
+}
+
+/// Returns the quoted index name, qualified with the schema of its table.
+fn qualified_index_name(table: &Table, index: &Index, ident: &impl Fn(&str) -> String) -> String {
+    match &table.schema {
+        Some(schema) => format!("{}.{}", ident(schema), ident(&index.name)),
+        None => ident(&index.name),
+    }
 }
 
 /// Maps the ORM's DataType to actual SQL data types.
-fn map_data_type_to_sql(data_type: &crate::model::DataType) -> String {
+///
+/// Without a dialect, the standard or most widely understood name is used.
+fn map_data_type_to_sql(data_type: &crate::model::DataType, dialect: Option<Dialect>) -> String {
     match data_type {
+        crate::model::DataType::SmallInt => "SMALLINT".to_string(),
         crate::model::DataType::Integer => "INTEGER".to_string(),
+        crate::model::DataType::BigInt => "BIGINT".to_string(),
         crate::model::DataType::Varchar(size) => format!("VARCHAR({})", size),
         crate::model::DataType::Boolean => "BOOLEAN".to_string(),
         crate::model::DataType::Float => "FLOAT".to_string(),
+        crate::model::DataType::Text => "TEXT".to_string(),
+        crate::model::DataType::Date => "DATE".to_string(),
+        crate::model::DataType::Time => "TIME".to_string(),
+        crate::model::DataType::DateTime => "TIMESTAMP".to_string(),
+        crate::model::DataType::TimestampTz => "TIMESTAMPTZ".to_string(),
+        crate::model::DataType::Uuid => match dialect {
+            Some(Dialect::MySql | Dialect::Sqlite) => "CHAR(36)".to_string(),
+            Some(Dialect::Postgres) | None => "UUID".to_string(),
+        },
+        crate::model::DataType::Decimal(precision, scale) => {
+            format!("NUMERIC({}, {})", precision, scale)
+        }
+        crate::model::DataType::Blob => match dialect {
+            Some(Dialect::Postgres) => "BYTEA".to_string(),
+            Some(Dialect::MySql | Dialect::Sqlite) | None => "BLOB".to_string(),
+        },
+        crate::model::DataType::Json => match dialect {
+            Some(Dialect::Sqlite) => "TEXT".to_string(),
+            Some(Dialect::Postgres | Dialect::MySql) | None => "JSON".to_string(),
+        },
+        crate::model::DataType::Jsonb => match dialect {
+            Some(Dialect::Sqlite) => "TEXT".to_string(),
+            Some(Dialect::MySql) => "JSON".to_string(),
+            Some(Dialect::Postgres) | None => "JSONB".to_string(),
+        },
+        crate::model::DataType::Custom(sql_type) => sql_type.clone(),
+    }
+}
+
+/// The table applied migrations are recorded in.
+const MIGRATIONS_TABLE: &str = "_rusty_orm_migrations";
+
+/// Applies the migrations saved in a directory to a database.
+///
+/// Applied migrations are recorded by name in the `_rusty_orm_migrations`
+/// table, so that each is applied once, along with their checksum, so that
+/// edits to them are detected.
+pub struct MigrationRunner<C: Connection> {
+    connection: C,
+    source: MigrationSource,
+}
+
+/// Where a `MigrationRunner` finds its migrations.
+enum MigrationSource {
+    Directory(String),
+    Loaded(Vec<(String, Migration)>),
+}
+
+impl<C: Connection> MigrationRunner<C> {
+    /// Creates a runner for the migrations in the directory, as saved by
+    /// `MigrationGenerator`.
+    pub fn new(connection: C, path: &str) -> Self {
+        MigrationRunner { connection, source: MigrationSource::Directory(path.to_string()) }
+    }
+
+    /// Creates a runner for the given migrations, such as those embedded in
+    /// the binary, rather than reading them from a directory.
+    pub fn with_migrations(connection: C, migrations: Vec<(String, Migration)>) -> Self {
+        MigrationRunner { connection, source: MigrationSource::Loaded(migrations) }
+    }
+
+    /// Returns the connection migrations are applied on.
+    pub fn connection(&self) -> &C {
+        &self.connection
+    }
+
+    /// Returns the connection, consuming the runner.
+    pub fn into_connection(self) -> C {
+        self.connection
+    }
+
+    /// Applies the migrations that haven't been applied yet, in order.
+    ///
+    /// First checks that the applied migrations haven't been edited since,
+    /// failing with `MigrationError::Drift` otherwise. Each migration then
+    /// runs in its own transaction along with its record in the tracking
+    /// table. Stops at the first migration that fails, which is rolled back;
+    /// those applied before it stay applied.
+    pub fn run(&mut self) -> Result<MigrationReport, OrmError> {
+        let migrations = self.migrations()?;
+        self.create_tracking_table()?;
+        let applied = self.applied()?;
+        let drifted = drifted(&migrations, &applied);
+        if !drifted.is_empty() {
+            return Err(MigrationError::Drift { names: drifted }.into());
+        }
+
+        let record = format!(
+            "INSERT INTO {} (name, checksum) VALUES ({}, {});",
+            MIGRATIONS_TABLE,
+            self.placeholder(1),
+            self.placeholder(2)
+        );
+        let mut report = MigrationReport::default();
+        for (name, migration) in migrations {
+            if applied.iter().any(|(applied, _)| *applied == name) {
+                report.skipped.push(name);
+                continue;
+            }
+            // Migrations saved without a checksum are recorded as unverified
+            let checksum = match migration.checksum {
+                Some(_) => Value::Text(migration.compute_checksum()),
+                None => Value::Null,
+            };
+            let params = [Value::Text(name.clone()), checksum];
+            if let Err(source) = self.transaction(&migration.up, &record, &params) {
+                let source = Box::new(source);
+                return Err(
+                    MigrationError::Failed { name, completed: report.applied, source }.into()
+                );
+            }
+            report.applied.push(name);
+        }
+        Ok(report)
+    }
+
+    /// Rolls back the last `n` applied migrations, most recent first,
+    /// returning their names.
+    ///
+    /// Each migration's `down` statements run in its own transaction along
+    /// with the removal of its record. Fails before rolling back anything if
+    /// one of the migrations is missing.
+    pub fn rollback(&mut self, n: usize) -> Result<Vec<String>, OrmError> {
+        self.create_tracking_table()?;
+        let applied = self.applied_names()?;
+        let names = applied.into_iter().rev().take(n).collect();
+        self.revert(names)
+    }
+
+    /// Rolls back the migrations applied after the named one, most recent
+    /// first, returning their names. The named migration stays applied.
+    pub fn rollback_to(&mut self, name: &str) -> Result<Vec<String>, OrmError> {
+        self.create_tracking_table()?;
+        let applied = self.applied_names()?;
+        let Some(position) = applied.iter().position(|applied| applied == name) else {
+            return Err(MigrationError::NotApplied { name: name.to_string() }.into());
+        };
+        let names = applied.into_iter().skip(position + 1).rev().collect();
+        self.revert(names)
+    }
+
+    /// Returns the statements `run` would apply, without running anything.
+    ///
+    /// The tracking table is only read, and not created if it doesn't exist.
+    /// Fails with `MigrationError::Drift` as `run` would.
+    pub fn plan(&mut self) -> Result<MigrationPlan, OrmError> {
+        let migrations = self.migrations()?;
+        let applied = if self.tracking_table_exists()? { self.applied()? } else { Vec::new() };
+        let drifted = drifted(&migrations, &applied);
+        if !drifted.is_empty() {
+            return Err(MigrationError::Drift { names: drifted }.into());
+        }
+
+        let migrations = migrations
+            .into_iter()
+            .filter(|(name, _)| !applied.iter().any(|(applied, _)| applied == name))
+            .map(|(name, migration)| {
+                let statements = migration
+                    .up
+                    .iter()
+                    .flat_map(|sql| split_statements(sql))
+                    .map(|sql| PlannedStatement {
+                        sql: sql.to_string(),
+                        destructive: is_destructive(sql),
+                    })
+                    .collect();
+                PlannedMigration { name, statements }
+            })
+            .collect();
+        Ok(MigrationPlan { migrations })
+    }
+
+    /// Returns the applied and pending migrations.
+    pub fn status(&mut self) -> Result<MigrationStatus, OrmError> {
+        let migrations = self.migrations()?;
+        self.create_tracking_table()?;
+        let applied = self.applied_names()?;
+        let pending = migrations
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| !applied.contains(name))
+            .collect();
+        Ok(MigrationStatus { applied, pending })
+    }
+
+    /// Records the current checksums of applied migrations that have been
+    /// edited since they were applied, returning their names.
+    ///
+    /// Overrides the check made by `run`, for migrations that were edited
+    /// on purpose, e.g. to repair them.
+    pub fn repair_checksums(&mut self) -> Result<Vec<String>, OrmError> {
+        let migrations = self.migrations()?;
+        self.create_tracking_table()?;
+        let applied = self.applied()?;
+        let drifted = drifted(&migrations, &applied);
+
+        let sql = format!(
+            "UPDATE {} SET checksum = {} WHERE name = {};",
+            MIGRATIONS_TABLE,
+            self.placeholder(1),
+            self.placeholder(2)
+        );
+        for name in &drifted {
+            let (_, migration) = migrations.iter().find(|(other, _)| other == name).unwrap();
+            let params = [Value::Text(migration.compute_checksum()), Value::Text(name.clone())];
+            self.connection.execute(&sql, &params)?;
+        }
+        Ok(drifted)
+    }
+
+    /// Rolls back the named migrations in the given order.
+    fn revert(&mut self, names: Vec<String>) -> Result<Vec<String>, OrmError> {
+        let mut migrations = self.migrations()?;
+        let mut downs = Vec::new();
+        for name in &names {
+            let Some(i) = migrations.iter().position(|(other, _)| other == name) else {
+                return Err(MigrationError::Missing { name: name.clone() }.into());
+            };
+            downs.push(migrations.swap_remove(i).1.down);
+        }
+
+        let record =
+            format!("DELETE FROM {} WHERE name = {};", MIGRATIONS_TABLE, self.placeholder(1));
+        let mut rolled_back = Vec::new();
+        for (name, down) in names.into_iter().zip(downs) {
+            if let Err(source) = self.transaction(&down, &record, &[Value::Text(name.clone())]) {
+                let source = Box::new(source);
+                return Err(MigrationError::Failed { name, completed: rolled_back, source }.into());
+            }
+            rolled_back.push(name);
+        }
+        Ok(rolled_back)
+    }
+
+    /// Returns the migrations, in order.
+    fn migrations(&self) -> Result<Vec<(String, Migration)>, OrmError> {
+        match &self.source {
+            MigrationSource::Directory(path) => MigrationGenerator::load_migrations(path),
+            MigrationSource::Loaded(migrations) => Ok(migrations.clone()),
+        }
+    }
+
+    /// Returns the placeholder of a value bound by tracking statements.
+    fn placeholder(&self, index: usize) -> String {
+        self.connection.dialect().placeholder_style().placeholder(index)
+    }
+
+    /// Returns whether the tracking table exists, looking it up in the catalog.
+    fn tracking_table_exists(&mut self) -> Result<bool, OrmError> {
+        let sql = match self.connection.dialect() {
+            Dialect::Sqlite => format!(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = {};",
+                self.placeholder(1)
+            ),
+            Dialect::Postgres => format!(
+                "SELECT table_name FROM information_schema.tables \
+                 WHERE table_schema = current_schema() AND table_name = {};",
+                self.placeholder(1)
+            ),
+            Dialect::MySql => format!(
+                "SELECT table_name FROM information_schema.tables \
+                 WHERE table_schema = DATABASE() AND table_name = {};",
+                self.placeholder(1)
+            ),
+        };
+        let rows = self.connection.query(&sql, &[Value::Text(MIGRATIONS_TABLE.to_string())])?;
+        Ok(!rows.is_empty())
+    }
+
+    fn create_tracking_table(&mut self) -> Result<(), OrmError> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (name VARCHAR(255) PRIMARY KEY, checksum VARCHAR(64), \
+             applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP);",
+            MIGRATIONS_TABLE
+        );
+        self.connection.execute(&sql, &[])?;
+        Ok(())
+    }
+
+    /// Returns the names and recorded checksums of the applied migrations,
+    /// in the order they were applied.
+    fn applied(&mut self) -> Result<Vec<(String, Option<String>)>, OrmError> {
+        let sql =
+            format!("SELECT name, checksum FROM {} ORDER BY applied_at, name;", MIGRATIONS_TABLE);
+        let applied = self
+            .connection
+            .query(&sql, &[])?
+            .iter()
+            .map(|row| Ok((row.try_get("name")?, row.try_get("checksum")?)))
+            .collect::<Result<_, RowError>>()?;
+        Ok(applied)
+    }
+
+    /// Returns the names of the applied migrations, in the order they were applied.
+    fn applied_names(&mut self) -> Result<Vec<String>, OrmError> {
+        Ok(self.applied()?.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// Runs the statements of a migration in order, then the statement
+    /// updating its record with the given values bound, in a transaction.
+    fn transaction(
+        &mut self,
+        sql: &[String],
+        record: &str,
+        params: &[Value],
+    ) -> Result<(), OrmError> {
+        let mut transaction = self.connection.begin()?;
+        for statement in sql.iter().flat_map(|sql| split_statements(sql)) {
+            transaction.execute(statement, &[])?;
+        }
+        transaction.execute(record, params)?;
+        transaction.commit()
+    }
+}
+
+/// The outcome of `MigrationRunner::run`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The migrations applied by the run, in order.
+    pub applied: Vec<String>,
+    /// The migrations that had already been applied.
+    pub skipped: Vec<String>,
+}
+
+/// The migrations returned by `MigrationRunner::status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// The applied migrations, in the order they were applied.
+    pub applied: Vec<String>,
+    /// The migrations in the directory that haven't been applied, in order.
+    pub pending: Vec<String>,
+}
+
+/// The migrations `MigrationRunner::plan` found pending, in the order they
+/// would be applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationPlan {
+    pub migrations: Vec<PlannedMigration>,
+}
+
+impl MigrationPlan {
+    /// Returns whether there is nothing to apply.
+    pub fn is_empty(&self) -> bool {
+        self.migrations.is_empty()
+    }
+
+    /// Returns the names of the migrations and their statements that drop
+    /// tables or columns or truncate tables, in order.
+    pub fn destructive(&self) -> Vec<(&str, &str)> {
+        self.migrations
+            .iter()
+            .flat_map(|migration| {
+                migration
+                    .statements
+                    .iter()
+                    .filter(|statement| statement.destructive)
+                    .map(|statement| (migration.name.as_str(), statement.sql.as_str()))
+            })
+            .collect()
+    }
+
+    /// Renders the statements as a SQL script, each migration preceded by a
+    /// `-- migration: {name}` comment and each destructive statement by a
+    /// `-- destructive` comment.
+    pub fn render(&self) -> String {
+        let mut sql = String::new();
+        for (i, migration) in self.migrations.iter().enumerate() {
+            if i > 0 {
+                sql.push('\n');
+            }
+            sql.push_str(&format!("-- migration: {}\n", migration.name));
+            for statement in &migration.statements {
+                if statement.destructive {
+                    sql.push_str("-- destructive\n");
+                }
+                sql.push_str(&statement.sql);
+                if !statement.sql.ends_with(';') {
+                    sql.push(';');
+                }
+                sql.push('\n');
+            }
+        }
+        sql
+    }
+}
+
+/// A pending migration in a `MigrationPlan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedMigration {
+    pub name: String,
+    /// The `up` statements, split one per entry.
+    pub statements: Vec<PlannedStatement>,
+}
+
+/// A statement of a `PlannedMigration`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStatement {
+    pub sql: String,
+    /// Whether the statement may lose data, as flagged by `is_destructive`.
+    pub destructive: bool,
+}
+
+/// Returns whether the statement may lose data: `DROP TABLE`, `TRUNCATE`,
+/// or an `ALTER TABLE` dropping a column.
+///
+/// Leading comments are skipped and keywords are matched in any case.
+pub fn is_destructive(statement: &str) -> bool {
+    let words = statement
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .flat_map(str::split_whitespace)
+        .map(|word| word.trim_end_matches(';').to_ascii_uppercase())
+        .collect::<Vec<String>>();
+    match words.first().map(String::as_str) {
+        Some("TRUNCATE") => true,
+        Some("DROP") => words.get(1).is_some_and(|word| word == "TABLE"),
+        Some("ALTER") => words.windows(2).any(|pair| pair[0] == "DROP" && pair[1] == "COLUMN"),
+        _ => false,
+    }
+}
+
+/// Errors of reading, saving and applying migrations, reported as
+/// `OrmError::Migration`; the database failing to run the tracking
+/// statements is reported as `OrmError::Execution`.
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("failed to read migrations: {0}")]
+    Io(#[from] std::io::Error),
+    /// A migration failed to apply or roll back, and its transaction was
+    /// rolled back; `completed` lists the migrations applied or rolled back
+    /// before it.
+    #[error("migration `{name}` failed: {source}")]
+    Failed { name: String, completed: Vec<String>, source: Box<OrmError> },
+    /// A migration saved as JSON failed to parse.
+    #[error("failed to parse migration `{name}`: {source}")]
+    InvalidMigration { name: String, source: serde_json::Error },
+    /// The snapshot of a table read by `generate_with_snapshot` failed to parse.
+    #[error("failed to parse snapshot {path}: {source}")]
+    InvalidSnapshot { path: String, source: serde_json::Error },
+    /// A migration saved as `{name}.up.sql` has no `{name}.down.sql` file.
+    #[error("migration `{name}` has no {name}.down.sql file")]
+    MissingDownFile { name: String },
+    #[error("migration `{name}` is applied but missing from the migrations")]
+    Missing { name: String },
+    #[error("migration `{name}` has not been applied")]
+    NotApplied { name: String },
+    /// Applied migrations were edited since; `repair_checksums` accepts the edits.
+    #[error("migrations `{}` were edited after being applied", names.join("`, `"))]
+    Drift { names: Vec<String> },
+}
+
+/// Returns the names of the applied migrations whose statements no longer
+/// match the checksum recorded when they were applied.
+fn drifted(
+    migrations: &[(String, Migration)],
+    applied: &[(String, Option<String>)],
+) -> Vec<String> {
+    applied
+        .iter()
+        .filter(|(name, checksum)| {
+            let Some(checksum) = checksum else { return false };
+            migrations.iter().any(|(other, migration)| {
+                other == name
+                    && migration.checksum.is_some()
+                    && migration.compute_checksum() != *checksum
+            })
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Splits SQL into its statements, on semicolons outside of quotes and comments.
+fn split_statements(sql: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut chars = sql.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '-') if chars.peek().is_some_and(|(_, next)| *next == '-') => {
+                // Skip to the end of the line comment
+                while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+            }
+            (None, ';') => {
+                statements.push(&sql[start..=i]);
+                start = i + 1;
+            }
+            (None, _) => {}
+        }
     }
+    statements.push(&sql[start..]);
+    statements.into_iter().map(str::trim).filter(|s| !s.is_empty() && *s != ";").collect()
 }
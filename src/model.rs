@@ -1,32 +1,780 @@
+use crate::dialect::Dialect;
+use crate::error::OrmError;
+use crate::executor::{AsyncConnection, Connection, FetchError};
+use crate::expr::Expr;
+use crate::query_builder::{DeleteQuery, InsertQuery, QueryError, SelectQuery, UpdateQuery};
+use crate::row::{FromRow, Row};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use thiserror::Error;
 
 /// Represents a column in a database table.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
     pub is_primary_key: bool,
+    #[serde(default)]
+    pub is_nullable: bool,
+    #[serde(default)]
+    pub default: Option<ColumnDefault>,
+    #[serde(default)]
+    pub is_unique: bool,
+    /// Whether the database generates the column's value, typically a surrogate key.
+    #[serde(default)]
+    pub is_auto_increment: bool,
+    #[serde(default)]
+    pub foreign_key: Option<ForeignKey>,
+}
+
+/// A reference from a column to a column of another table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForeignKey {
+    /// The referenced table.
+    pub table: String,
+    /// The referenced column.
+    pub column: String,
+    pub on_delete: Option<ReferentialAction>,
+    pub on_update: Option<ReferentialAction>,
+}
+
+/// What happens to referencing rows when the referenced row is deleted or updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReferentialAction {
+    Cascade,
+    SetNull,
+    SetDefault,
+    Restrict,
+    NoAction,
+}
+
+impl ReferentialAction {
+    /// Returns the SQL keywords for the action.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::SetNull => "SET NULL",
+            ReferentialAction::SetDefault => "SET DEFAULT",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::NoAction => "NO ACTION",
+        }
+    }
+}
+
+/// The default value of a column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ColumnDefault {
+    /// A literal value, quoted as needed in DDL.
+    Value(Value),
+    /// A SQL expression such as `now()`, emitted verbatim.
+    Expr(String),
 }
 
 /// Enum for various SQL data types.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
+    SmallInt,
     Integer,
+    BigInt,
     Varchar(usize),
     Boolean,
     Float,
+    /// Unbounded text.
+    Text,
+    Date,
+    Time,
+    /// A timestamp without time zone.
+    DateTime,
+    /// A timestamp with time zone.
+    TimestampTz,
+    Uuid,
+    /// A fixed-point number with the given precision and scale.
+    Decimal(u32, u32),
+    /// Binary data.
+    Blob,
+    Json,
+    /// Binary JSON, Postgres only; other databases fall back to their JSON type.
+    Jsonb,
+    /// A database-specific type, such as an enum or extension type, emitted verbatim.
+    Custom(String),
     // todo add more
 }
 
+/// Represents a value bound to a SQL statement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Integer(i32),
+    BigInt(i64),
+    Float(f64),
+    Text(String),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    Null,
+}
+
+/// Trait for Rust types that can be converted into a SQL value.
+pub trait ToValue {
+    /// Converts the value into its SQL representation.
+    fn to_value(&self) -> Value;
+}
+
+impl ToValue for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl ToValue for i8 {
+    fn to_value(&self) -> Value {
+        Value::Integer(i32::from(*self))
+    }
+}
+
+impl ToValue for i16 {
+    fn to_value(&self) -> Value {
+        Value::Integer(i32::from(*self))
+    }
+}
+
+impl ToValue for i32 {
+    fn to_value(&self) -> Value {
+        Value::Integer(*self)
+    }
+}
+
+impl ToValue for u8 {
+    fn to_value(&self) -> Value {
+        Value::Integer(i32::from(*self))
+    }
+}
+
+impl ToValue for u16 {
+    fn to_value(&self) -> Value {
+        Value::Integer(i32::from(*self))
+    }
+}
+
+impl ToValue for u32 {
+    fn to_value(&self) -> Value {
+        Value::BigInt(i64::from(*self))
+    }
+}
+
+/// Values above `i64::MAX` don't fit a BIGINT column and are bound as text,
+/// so the database rejects them instead of storing a wrapped number.
+impl ToValue for u64 {
+    fn to_value(&self) -> Value {
+        i64::try_from(*self).map(Value::BigInt).unwrap_or_else(|_| Value::Text(self.to_string()))
+    }
+}
+
+impl ToValue for isize {
+    fn to_value(&self) -> Value {
+        Value::BigInt(*self as i64)
+    }
+}
+
+impl ToValue for usize {
+    fn to_value(&self) -> Value {
+        (*self as u64).to_value()
+    }
+}
+
+impl ToValue for i64 {
+    fn to_value(&self) -> Value {
+        Value::BigInt(*self)
+    }
+}
+
+impl ToValue for f32 {
+    fn to_value(&self) -> Value {
+        Value::Float(f64::from(*self))
+    }
+}
+
+impl ToValue for f64 {
+    fn to_value(&self) -> Value {
+        Value::Float(*self)
+    }
+}
+
+impl ToValue for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl ToValue for str {
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value {
+        Value::Text(self.clone())
+    }
+}
+
+impl ToValue for [u8] {
+    fn to_value(&self) -> Value {
+        Value::Bytes(self.to_vec())
+    }
+}
+
+impl ToValue for Vec<u8> {
+    fn to_value(&self) -> Value {
+        Value::Bytes(self.clone())
+    }
+}
+
+/// JSON documents are bound as their serialized text.
+impl ToValue for serde_json::Value {
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn to_value(&self) -> Value {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: ToValue + ?Sized> ToValue for &T {
+    fn to_value(&self) -> Value {
+        (**self).to_value()
+    }
+}
+
+/// Dates and times are bound as ISO 8601 text.
+#[cfg(feature = "chrono")]
+impl ToValue for chrono::NaiveDate {
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToValue for chrono::NaiveTime {
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToValue for chrono::NaiveDateTime {
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> ToValue for chrono::DateTime<Tz>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_rfc3339())
+    }
+}
+
+/// UUIDs are bound in their hyphenated text form.
+#[cfg(feature = "uuid")]
+impl ToValue for uuid::Uuid {
+    fn to_value(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
+
 /// Represents a database table.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct Table {
     pub name: String,
+    /// The schema (namespace) the table lives in, if not the default one.
+    #[serde(default)]
+    pub schema: Option<String>,
     pub columns: Vec<Column>,
+    /// Sets of columns whose combined values must be unique.
+    #[serde(default)]
+    pub unique_constraints: Vec<Vec<String>>,
+    #[serde(default)]
+    pub indexes: Vec<Index>,
+}
+
+impl Table {
+    /// Returns the table name, qualified with its schema if it has one.
+    pub fn qualified_name(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}", schema, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Returns the column with the given name.
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Returns the column names, in declaration order.
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns.iter().map(|column| column.name.as_str()).collect()
+    }
+
+    /// Returns the primary key columns, in declaration order.
+    pub fn primary_key(&self) -> Vec<&Column> {
+        self.columns.iter().filter(|column| column.is_primary_key).collect()
+    }
+
+    /// Checks that the table can be created: it must have a name and at
+    /// least one column, and its columns must have distinct names and
+    /// non-zero VARCHAR lengths.
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        if self.name.is_empty() {
+            return Err(SchemaError::EmptyTableName);
+        }
+        if self.columns.is_empty() {
+            return Err(SchemaError::NoColumns { table: self.name.clone() });
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            if self.columns[..i].iter().any(|other| other.name == column.name) {
+                return Err(SchemaError::DuplicateColumn {
+                    table: self.name.clone(),
+                    column: column.name.clone(),
+                });
+            }
+            if column.data_type == DataType::Varchar(0) {
+                return Err(SchemaError::EmptyVarchar {
+                    table: self.name.clone(),
+                    column: column.name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors reported by `Table::validate` and `MigrationGenerator::generate_all`
+/// for schemas that cannot be created.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SchemaError {
+    #[error("table has no name")]
+    EmptyTableName,
+    #[error("table `{table}` has no columns")]
+    NoColumns { table: String },
+    #[error("column `{column}` is declared more than once on table `{table}`")]
+    DuplicateColumn { table: String, column: String },
+    #[error("column `{column}` on table `{table}` is a VARCHAR of length 0")]
+    EmptyVarchar { table: String, column: String },
+    #[error("foreign keys between tables `{}` form a cycle", tables.join("`, `"))]
+    CircularReference { tables: Vec<String> },
+}
+
+/// An index on one or more columns of a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Index {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub is_unique: bool,
+    /// The index method, or the database's default (usually a B-tree) if `None`.
+    pub method: Option<IndexMethod>,
+}
+
+/// The data structure backing an index.
+///
+/// Gin and Gist are Postgres only; MySQL supports BTree and Hash, and SQLite
+/// none, so unsupported methods are left out of the DDL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndexMethod {
+    BTree,
+    Hash,
+    Gin,
+    Gist,
+}
+
+impl IndexMethod {
+    /// Returns the SQL name of the method.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            IndexMethod::BTree => "BTREE",
+            IndexMethod::Hash => "HASH",
+            IndexMethod::Gin => "GIN",
+            IndexMethod::Gist => "GIST",
+        }
+    }
 }
 
 /// Trait for entities that can be mapped to a database table.
 pub trait Model {
     /// Returns the table schema associated with the model.
     fn table() -> Table;
+
+    /// Returns the table schema, borrowed if the model caches it.
+    ///
+    /// Query builders use this rather than `table`, so that building a query
+    /// doesn't allocate the schema anew. Derived models build their table
+    /// once; the default implementation calls `table`.
+    fn table_ref() -> Cow<'static, Table> {
+        Cow::Owned(Self::table())
+    }
+}
+
+/// Trait for model instances whose field values can be inserted into their
+/// table, implemented with `#[derive(IntoInsert)]`.
+pub trait IntoInsert: Model {
+    /// Returns `(column, value)` pairs for every column of the model.
+    fn insert_values(&self) -> Vec<(String, Value)>;
+
+    /// Returns whether the instance has yet to be inserted, which
+    /// `ModelExt::save` inserts rather than updates.
+    ///
+    /// By default, whether an auto-increment primary key column holds NULL
+    /// or 0, as a key the database hasn't generated yet does. Models with
+    /// other keys can tell with `#[model(is_new = "path")]`, naming a
+    /// `fn(&Self) -> bool`.
+    fn is_new(&self) -> bool {
+        let table = Self::table_ref();
+        self.insert_values().iter().any(|(column, value)| {
+            table
+                .column(column)
+                .is_some_and(|column| column.is_primary_key && column.is_auto_increment)
+                && matches!(value, Value::Null | Value::Integer(0) | Value::BigInt(0))
+        })
+    }
+}
+
+/// Shortcuts for common queries of a model, implemented for every model
+/// that can be read from a row, such as those deriving `FromRow`.
+///
+/// Writing an instance with `save`, `insert`, `update` or `delete` also
+/// takes its values, which models provide by deriving `IntoInsert`.
+#[allow(async_fn_in_trait)]
+pub trait ModelExt: Model + FromRow {
+    /// Returns the row whose primary key is the given value, if any.
+    ///
+    /// Fails with `QueryError::MissingPrimaryKey` if the table has no primary
+    /// key, and with `FetchError::KeyMismatch` if it has several columns;
+    /// use `find_by_key` for those.
+    fn find<C: Connection>(
+        connection: &mut C,
+        key: impl ToValue,
+    ) -> Result<Option<Self>, OrmError> {
+        Self::find_by_key(connection, &[key.to_value()])
+    }
+
+    /// Returns the row whose primary key columns hold the given values, in
+    /// the order of the columns, if any.
+    fn find_by_key<C: Connection>(
+        connection: &mut C,
+        key: &[Value],
+    ) -> Result<Option<Self>, OrmError> {
+        primary_key_query::<Self>(key)?.fetch_optional(connection)
+    }
+
+    /// Returns every row of the table.
+    fn all<C: Connection>(connection: &mut C) -> Result<Vec<Self>, OrmError> {
+        SelectQuery::<Self>::new().fetch_all(connection)
+    }
+
+    /// Returns the number of rows of the table.
+    fn count<C: Connection>(connection: &mut C) -> Result<i64, OrmError> {
+        let (sql, params) = count_query::<Self>().build_parameterized_for(connection.dialect());
+        read_count(connection.query(&sql, &params)?)
+    }
+
+    /// Returns whether a row matches the condition.
+    fn exists<C: Connection>(connection: &mut C, condition: Expr) -> Result<bool, OrmError> {
+        let (sql, params) =
+            exists_query::<Self>(condition).build_parameterized_for(connection.dialect());
+        Ok(!connection.query(&sql, &params)?.is_empty())
+    }
+
+    /// Returns the row whose primary key is the given value, as `find` does.
+    async fn find_async<C: AsyncConnection>(
+        connection: &mut C,
+        key: impl ToValue,
+    ) -> Result<Option<Self>, OrmError> {
+        Self::find_by_key_async(connection, &[key.to_value()]).await
+    }
+
+    /// Returns the row whose primary key columns hold the given values, as
+    /// `find_by_key` does.
+    async fn find_by_key_async<C: AsyncConnection>(
+        connection: &mut C,
+        key: &[Value],
+    ) -> Result<Option<Self>, OrmError> {
+        primary_key_query::<Self>(key)?.fetch_optional_async(connection).await
+    }
+
+    /// Returns every row of the table, as `all` does.
+    async fn all_async<C: AsyncConnection>(connection: &mut C) -> Result<Vec<Self>, OrmError> {
+        SelectQuery::<Self>::new().fetch_all_async(connection).await
+    }
+
+    /// Returns the number of rows of the table, as `count` does.
+    async fn count_async<C: AsyncConnection>(connection: &mut C) -> Result<i64, OrmError> {
+        let (sql, params) = count_query::<Self>().build_parameterized_for(connection.dialect());
+        read_count(connection.query(&sql, &params).await?)
+    }
+
+    /// Returns whether a row matches the condition, as `exists` does.
+    async fn exists_async<C: AsyncConnection>(
+        connection: &mut C,
+        condition: Expr,
+    ) -> Result<bool, OrmError> {
+        let (sql, params) =
+            exists_query::<Self>(condition).build_parameterized_for(connection.dialect());
+        Ok(!connection.query(&sql, &params).await?.is_empty())
+    }
+
+    /// Inserts the instance if it `is_new`, or else updates its row, or
+    /// inserts it with its key if it has none.
+    ///
+    /// An UPDATE affecting no rows is followed by a lookup of the key before
+    /// inserting, as MySQL only counts the rows an UPDATE changed. Fails with
+    /// `QueryError::MissingPrimaryKey` when updating a model without a
+    /// primary key.
+    fn save<C: Connection>(&mut self, connection: &mut C) -> Result<(), OrmError>
+    where
+        Self: IntoInsert,
+    {
+        if self.is_new() {
+            return self.insert(connection);
+        }
+        if self.update(connection)? == 0 {
+            let (sql, params) =
+                key_exists_query(self)?.build_parameterized_for(connection.dialect());
+            if connection.query(&sql, &params)?.is_empty() {
+                return insert_model(self, connection, true);
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts the instance, leaving out its auto-increment columns, then
+    /// reads back the inserted row into it, generated key included.
+    ///
+    /// The row is read with RETURNING, or on MySQL, which lacks it, looked
+    /// up by the key `LAST_INSERT_ID()` returns.
+    fn insert<C: Connection>(&mut self, connection: &mut C) -> Result<(), OrmError>
+    where
+        Self: IntoInsert,
+    {
+        insert_model(self, connection, false)
+    }
+
+    /// Updates the row of the instance, found by its primary key, with its
+    /// other columns, returning the number of rows updated.
+    fn update<C: Connection>(&self, connection: &mut C) -> Result<u64, OrmError>
+    where
+        Self: IntoInsert,
+    {
+        let (sql, params) =
+            UpdateQuery::from_model(self).try_build_parameterized_for(connection.dialect())?;
+        connection.execute(&sql, &params)
+    }
+
+    /// Deletes the row of the instance, found by its primary key, returning
+    /// the number of rows deleted.
+    fn delete<C: Connection>(&self, connection: &mut C) -> Result<u64, OrmError>
+    where
+        Self: IntoInsert,
+    {
+        let (sql, params) =
+            DeleteQuery::from_model(self).try_build_parameterized_for(connection.dialect())?;
+        connection.execute(&sql, &params)
+    }
+
+    /// Inserts or updates the instance, as `save` does.
+    async fn save_async<C: AsyncConnection>(&mut self, connection: &mut C) -> Result<(), OrmError>
+    where
+        Self: IntoInsert,
+    {
+        if self.is_new() {
+            return self.insert_async(connection).await;
+        }
+        if self.update_async(connection).await? == 0 {
+            let (sql, params) =
+                key_exists_query(self)?.build_parameterized_for(connection.dialect());
+            if connection.query(&sql, &params).await?.is_empty() {
+                return insert_model_async(self, connection, true).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts the instance and reads back the inserted row, as `insert` does.
+    async fn insert_async<C: AsyncConnection>(&mut self, connection: &mut C) -> Result<(), OrmError>
+    where
+        Self: IntoInsert,
+    {
+        insert_model_async(self, connection, false).await
+    }
+
+    /// Updates the row of the instance, as `update` does.
+    async fn update_async<C: AsyncConnection>(&self, connection: &mut C) -> Result<u64, OrmError>
+    where
+        Self: IntoInsert,
+    {
+        let (sql, params) =
+            UpdateQuery::from_model(self).try_build_parameterized_for(connection.dialect())?;
+        connection.execute(&sql, &params).await
+    }
+
+    /// Deletes the row of the instance, as `delete` does.
+    async fn delete_async<C: AsyncConnection>(&self, connection: &mut C) -> Result<u64, OrmError>
+    where
+        Self: IntoInsert,
+    {
+        let (sql, params) =
+            DeleteQuery::from_model(self).try_build_parameterized_for(connection.dialect())?;
+        connection.execute(&sql, &params).await
+    }
+}
+
+impl<T: Model + FromRow> ModelExt for T {}
+
+/// Returns the query selecting the row of `T` with the given primary key.
+fn primary_key_query<T: Model>(key: &[Value]) -> Result<SelectQuery<T>, OrmError> {
+    let table = T::table_ref();
+    let columns = table.primary_key();
+    if columns.is_empty() {
+        return Err(QueryError::MissingPrimaryKey { table: table.name.clone() }.into());
+    }
+    if columns.len() != key.len() {
+        return Err(FetchError::KeyMismatch {
+            table: table.name.clone(),
+            expected: columns.len(),
+            found: key.len(),
+        }
+        .into());
+    }
+    Ok(columns
+        .iter()
+        .zip(key)
+        .fold(SelectQuery::new(), |query, (column, value)| query.filter_eq(&column.name, value)))
+}
+
+/// The query reading the key MySQL generated for the last inserted row.
+const LAST_INSERT_ID: &str = "SELECT LAST_INSERT_ID() AS id";
+
+/// Inserts the model and reads back the inserted row into it. Its
+/// auto-increment columns are left out for the database to generate, unless
+/// `with_key` is set.
+fn insert_model<T, C>(model: &mut T, connection: &mut C, with_key: bool) -> Result<(), OrmError>
+where
+    T: IntoInsert + FromRow,
+    C: Connection,
+{
+    let dialect = connection.dialect();
+    let (sql, params) = insert_statement(model, dialect, with_key)?;
+    if dialect != Dialect::MySql {
+        let rows = connection.query(&sql, &params)?;
+        *model = T::from_row(rows.first().ok_or(FetchError::NoRows)?)?;
+        return Ok(());
+    }
+    connection.execute(&sql, &params)?;
+    if !with_key && has_generated_key::<T>() {
+        let rows = connection.query(LAST_INSERT_ID, &[])?;
+        let key = generated_key(model, rows.first().ok_or(FetchError::NoRows)?)?;
+        *model = primary_key_query::<T>(&key)?.fetch_one(connection)?;
+    }
+    Ok(())
+}
+
+/// Inserts the model and reads back the inserted row, as `insert_model` does.
+async fn insert_model_async<T, C>(
+    model: &mut T,
+    connection: &mut C,
+    with_key: bool,
+) -> Result<(), OrmError>
+where
+    T: IntoInsert + FromRow,
+    C: AsyncConnection,
+{
+    let dialect = connection.dialect();
+    let (sql, params) = insert_statement(model, dialect, with_key)?;
+    if dialect != Dialect::MySql {
+        let rows = connection.query(&sql, &params).await?;
+        *model = T::from_row(rows.first().ok_or(FetchError::NoRows)?)?;
+        return Ok(());
+    }
+    connection.execute(&sql, &params).await?;
+    if !with_key && has_generated_key::<T>() {
+        let rows = connection.query(LAST_INSERT_ID, &[]).await?;
+        let key = generated_key(model, rows.first().ok_or(FetchError::NoRows)?)?;
+        *model = primary_key_query::<T>(&key)?.fetch_one_async(connection).await?;
+    }
+    Ok(())
+}
+
+/// Returns the INSERT statement of the model, reading back the inserted row
+/// on dialects with RETURNING.
+fn insert_statement<T: IntoInsert>(
+    model: &T,
+    dialect: Dialect,
+    with_key: bool,
+) -> Result<(String, Vec<Value>), QueryError> {
+    let mut query = InsertQuery::from_model(model);
+    if with_key {
+        let table = T::table_ref();
+        for (column, value) in model.insert_values() {
+            if table.column(&column).is_some_and(|column| column.is_auto_increment) {
+                query = query.value(&column, value);
+            }
+        }
+    }
+    let query = if dialect == Dialect::MySql { query } else { query.returning_all() };
+    query.try_build_parameterized_for(dialect)
+}
+
+/// Returns whether the database generates a primary key column of `T`.
+fn has_generated_key<T: Model>() -> bool {
+    T::table_ref().primary_key().iter().any(|column| column.is_auto_increment)
+}
+
+/// Returns the primary key of an inserted model: the generated key read
+/// from `LAST_INSERT_ID()` for the auto-increment column, and the model's
+/// values for the others.
+fn generated_key<T: IntoInsert>(model: &T, row: &Row) -> Result<Vec<Value>, OrmError> {
+    let generated: i64 = row.try_get("id")?;
+    let values = model.insert_values();
+    let table = T::table_ref();
+    Ok(table
+        .primary_key()
+        .iter()
+        .map(|column| match values.iter().find(|(name, _)| *name == column.name) {
+            Some((_, value)) if !column.is_auto_increment => value.clone(),
+            _ => Value::BigInt(generated),
+        })
+        .collect())
+}
+
+/// Returns the query selecting a row if one has the primary key of the model.
+fn key_exists_query<T: IntoInsert>(model: &T) -> Result<SelectQuery<T>, OrmError> {
+    let table = T::table_ref();
+    let values = model.insert_values();
+    let key: Vec<Value> = table
+        .primary_key()
+        .iter()
+        .filter_map(|column| values.iter().find(|(name, _)| *name == column.name))
+        .map(|(_, value)| value.clone())
+        .collect();
+    Ok(primary_key_query::<T>(&key)?.select(&["1"]).limit(1))
+}
+
+fn count_query<T: Model>() -> SelectQuery<T> {
+    SelectQuery::new().select_count("*", Some("count"))
+}
+
+fn exists_query<T: Model>(condition: Expr) -> SelectQuery<T> {
+    SelectQuery::new().select(&["1"]).filter_expr(condition).limit(1)
+}
+
+fn read_count(rows: Vec<Row>) -> Result<i64, OrmError> {
+    let row = rows.first().ok_or(FetchError::NoRows)?;
+    Ok(row.try_get("count")?)
 }
@@ -0,0 +1,222 @@
+use crate::executor::DatabaseError;
+use crate::model::Value;
+use crate::row::Row;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Callbacks around the statements a connection runs, set with
+/// `Connection::set_observer`.
+///
+/// `on_query_start` is called before each statement with its bound values,
+/// and `on_query_end` after it with the time it took and the number of rows
+/// it affected or returned, or its error. Statements run by the crate itself,
+/// such as those beginning and ending transactions, are observed as well.
+pub trait QueryObserver: Send + Sync {
+    /// Called before a statement runs.
+    fn on_query_start(&self, sql: &str, params: &[Value]);
+
+    /// Called after a statement ran, with the number of rows it affected or
+    /// returned, or the error it failed with.
+    fn on_query_end(&self, sql: &str, duration: Duration, result: Result<u64, &DatabaseError>);
+}
+
+/// The observer set on a connection, if any, which its statements run through.
+#[derive(Clone, Default)]
+pub(crate) struct ObserverSlot(Option<Arc<dyn QueryObserver>>);
+
+impl ObserverSlot {
+    pub(crate) fn set(&mut self, observer: Box<dyn QueryObserver>) {
+        self.0 = Some(Arc::from(observer));
+    }
+
+    /// Runs a statement, reporting it to the observer.
+    pub(crate) fn observe<T: RowCount>(
+        &self,
+        sql: &str,
+        params: &[Value],
+        run: impl FnOnce() -> Result<T, DatabaseError>,
+    ) -> Result<T, DatabaseError> {
+        let Some(observer) = &self.0 else { return run() };
+        observer.on_query_start(sql, params);
+        let start = Instant::now();
+        let result = run();
+        observer.on_query_end(sql, start.elapsed(), result.as_ref().map(RowCount::row_count));
+        result
+    }
+}
+
+/// The result of a statement, as the number of rows reported to observers.
+pub(crate) trait RowCount {
+    fn row_count(&self) -> u64;
+}
+
+/// The number of rows a statement affected.
+impl RowCount for u64 {
+    fn row_count(&self) -> u64 {
+        *self
+    }
+}
+
+/// The rows a query returned.
+impl RowCount for Vec<Row> {
+    fn row_count(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+impl fmt::Debug for ObserverSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(QueryObserver)" } else { "None" })
+    }
+}
+
+/// Renders bound values for a log, or a placeholder for each if redacted.
+fn describe_params(params: &[Value], redact: bool) -> String {
+    if redact {
+        format!("[{}]", vec!["?"; params.len()].join(", "))
+    } else {
+        format!("{:?}", params)
+    }
+}
+
+/// An observer logging statements through the `log` crate, at the debug
+/// level, or the warn level for failed ones.
+///
+/// Bound values are logged unless `redact_params` is set, for deployments
+/// where they may hold personal data.
+#[derive(Debug, Clone, Default)]
+pub struct LogObserver {
+    redact_params: bool,
+}
+
+impl LogObserver {
+    /// Creates an observer logging bound values.
+    pub fn new() -> Self {
+        LogObserver::default()
+    }
+
+    /// Logs a `?` in place of each bound value.
+    pub fn redact_params(mut self, redact: bool) -> Self {
+        self.redact_params = redact;
+        self
+    }
+}
+
+impl QueryObserver for LogObserver {
+    fn on_query_start(&self, sql: &str, params: &[Value]) {
+        log::debug!("running `{}` with {}", sql, describe_params(params, self.redact_params));
+    }
+
+    fn on_query_end(&self, sql: &str, duration: Duration, result: Result<u64, &DatabaseError>) {
+        match result {
+            Ok(rows) => log::debug!("ran `{}` in {:?}: {} rows", sql, duration, rows),
+            Err(error) => log::warn!("`{}` failed after {:?}: {}", sql, duration, error),
+        }
+    }
+}
+
+/// An observer emitting `tracing` events for statements, with the `tracing`
+/// feature.
+///
+/// Each statement gets a `query` span, entered for an event when it starts
+/// and one when it ends; spans can't stay entered across the callbacks, so
+/// the time a statement took is a field of the second event. Bound values
+/// are recorded unless `redact_params` is set.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Default)]
+pub struct TracingObserver {
+    redact_params: bool,
+}
+
+#[cfg(feature = "tracing")]
+impl TracingObserver {
+    /// Creates an observer recording bound values.
+    pub fn new() -> Self {
+        TracingObserver::default()
+    }
+
+    /// Records a `?` in place of each bound value.
+    pub fn redact_params(mut self, redact: bool) -> Self {
+        self.redact_params = redact;
+        self
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl QueryObserver for TracingObserver {
+    fn on_query_start(&self, sql: &str, params: &[Value]) {
+        let params = describe_params(params, self.redact_params);
+        tracing::debug_span!("query", sql)
+            .in_scope(|| tracing::debug!(params = params.as_str(), "query started"));
+    }
+
+    fn on_query_end(&self, sql: &str, duration: Duration, result: Result<u64, &DatabaseError>) {
+        let span = tracing::debug_span!("query", sql);
+        let _entered = span.enter();
+        match result {
+            Ok(rows) => tracing::debug!(?duration, rows, "query finished"),
+            Err(error) => tracing::warn!(?duration, %error, "query failed"),
+        }
+    }
+}
+
+/// A statement reported to a `CollectingObserver`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryEvent {
+    /// A statement started, with its bound values, or `None` if redacted.
+    Start { sql: String, params: Option<Vec<Value>> },
+    /// A statement ended with the number of rows it affected or returned, or
+    /// its error.
+    End { sql: String, duration: Duration, result: Result<u64, DatabaseError> },
+}
+
+/// An observer collecting the statements it's told of, to inspect in tests.
+///
+/// Clones share their events, so a clone kept before the observer is set on
+/// a connection sees the connection's statements.
+#[derive(Debug, Clone, Default)]
+pub struct CollectingObserver {
+    events: Arc<Mutex<Vec<QueryEvent>>>,
+    redact_params: bool,
+}
+
+impl CollectingObserver {
+    /// Creates an observer with no events, collecting bound values.
+    pub fn new() -> Self {
+        CollectingObserver::default()
+    }
+
+    /// Collects the start of statements without their bound values.
+    pub fn redact_params(mut self, redact: bool) -> Self {
+        self.redact_params = redact;
+        self
+    }
+
+    /// Returns the events collected so far, in order.
+    pub fn events(&self) -> Vec<QueryEvent> {
+        self.lock().clone()
+    }
+
+    /// Removes the events collected so far.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<QueryEvent>> {
+        // Events pushed before a panic elsewhere are still whole
+        self.events.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl QueryObserver for CollectingObserver {
+    fn on_query_start(&self, sql: &str, params: &[Value]) {
+        let params = (!self.redact_params).then(|| params.to_vec());
+        self.lock().push(QueryEvent::Start { sql: sql.to_string(), params });
+    }
+
+    fn on_query_end(&self, sql: &str, duration: Duration, result: Result<u64, &DatabaseError>) {
+        let result = result.map_err(DatabaseError::clone);
+        self.lock().push(QueryEvent::End { sql: sql.to_string(), duration, result });
+    }
+}
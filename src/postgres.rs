@@ -0,0 +1,328 @@
+use crate::dialect::Dialect;
+use crate::error::OrmError;
+use crate::executor::{Connection, DatabaseError};
+use crate::model::{Model, ToValue, Value};
+use crate::observer::{ObserverSlot, QueryObserver};
+use crate::query_builder::{InsertQuery, QueryError};
+use crate::row::{FromValue, Row};
+use crate::statement_cache::{
+    is_ddl, CacheStats, StatementCache, DEFAULT_STATEMENT_CACHE_CAPACITY,
+};
+use ::postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+use ::postgres::{Client, NoTls, Statement};
+use bytes::BytesMut;
+use std::error::Error;
+
+/// A connection to a PostgreSQL database through the `postgres` crate, with
+/// the `postgres` feature.
+///
+/// Statements are given with `$n` placeholders, as the builders render them
+/// for `Dialect::Postgres`. Values are bound after the types the server
+/// infers for the parameters, so that text is bound to a `date`, `timestamp`
+/// or `uuid` parameter with the `chrono` and `uuid` features, and to a
+/// `json` one. Result columns are read into the values the builders bind,
+/// dates and times and UUIDs as text; columns of other types, such as
+/// `numeric`, must be cast to text in the query.
+///
+/// Statements are prepared once and kept in a cache keyed by their SQL,
+/// which is cleared when a statement changes the schema, as the server
+/// rejects statements prepared against tables that changed since.
+pub struct PostgresConnection {
+    client: Client,
+    observer: ObserverSlot,
+    statements: StatementCache<Statement>,
+}
+
+impl PostgresConnection {
+    /// Connects without TLS with a connection string, such as
+    /// `host=localhost user=postgres` or `postgresql://postgres@localhost/db`.
+    pub fn connect(params: &str) -> Result<Self, OrmError> {
+        Ok(PostgresConnection::from_client(Client::connect(params, NoTls).map_err(error)?))
+    }
+
+    /// Wraps a connected `postgres` client.
+    pub fn from_client(client: Client) -> Self {
+        PostgresConnection {
+            client,
+            observer: ObserverSlot::default(),
+            statements: StatementCache::new(DEFAULT_STATEMENT_CACHE_CAPACITY),
+        }
+    }
+
+    /// Sets the number of prepared statements kept, 0 to prepare every
+    /// statement anew.
+    pub fn with_statement_cache_capacity(mut self, capacity: usize) -> Self {
+        self.statements.set_capacity(capacity);
+        self
+    }
+
+    /// Returns how often statements were found prepared in the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.statements.stats()
+    }
+
+    /// Removes the prepared statements from the cache.
+    pub fn clear_statement_cache(&mut self) {
+        self.statements.clear();
+    }
+
+    /// Runs the insert with a RETURNING clause for the model's primary key,
+    /// returning the key of the inserted row, such as one generated by a
+    /// serial column; `None` if no row was inserted, as with `ON CONFLICT DO NOTHING`.
+    pub fn insert_returning_id<T: Model>(
+        &mut self,
+        query: InsertQuery<T>,
+    ) -> Result<Option<Value>, OrmError> {
+        let table = T::table_ref();
+        let Some(key) = table.primary_key().first().map(|column| column.name.clone()) else {
+            return Err(QueryError::MissingPrimaryKey { table: table.name.clone() }.into());
+        };
+        let sql = query.returning(&[&key]).try_build_for(Dialect::Postgres)?;
+        let rows = self.query(&sql, &[])?;
+        Ok(rows.first().and_then(|row| row.get(&key)).cloned())
+    }
+
+    /// Returns the underlying `postgres` client.
+    pub fn client(&mut self) -> &mut Client {
+        &mut self.client
+    }
+}
+
+impl Connection for PostgresConnection {
+    fn dialect(&self) -> Dialect {
+        Dialect::Postgres
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError> {
+        let count = self.observer.observe(sql, params, || {
+            let statement =
+                self.statements.get_or_prepare(sql, || self.client.prepare(sql)).map_err(error)?;
+            let params: Vec<Param> = params.iter().map(Param).collect();
+            let params: Vec<&(dyn ToSql + Sync)> =
+                params.iter().map(|param| param as &(dyn ToSql + Sync)).collect();
+            self.client.execute(&statement, &params).map_err(error)
+        });
+        if is_ddl(sql) {
+            self.clear_statement_cache();
+        }
+        Ok(count?)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError> {
+        let rows = self.observer.observe(sql, params, || {
+            let statement =
+                self.statements.get_or_prepare(sql, || self.client.prepare(sql)).map_err(error)?;
+            let params: Vec<Param> = params.iter().map(Param).collect();
+            let params: Vec<&(dyn ToSql + Sync)> =
+                params.iter().map(|param| param as &(dyn ToSql + Sync)).collect();
+            let rows = self.client.query(&statement, &params).map_err(error)?;
+
+            let mut result = Vec::new();
+            for postgres_row in &rows {
+                let mut row = Row::new();
+                for (index, column) in postgres_row.columns().iter().enumerate() {
+                    row.push(column.name(), read(postgres_row, index, column.type_())?);
+                }
+                result.push(row);
+            }
+            Ok(result)
+        });
+        if is_ddl(sql) {
+            self.clear_statement_cache();
+        }
+        Ok(rows?)
+    }
+
+    fn set_observer(&mut self, observer: Box<dyn QueryObserver>) {
+        self.observer.set(observer);
+    }
+}
+
+/// A value bound as a parameter of the type the server expects.
+#[derive(Debug)]
+struct Param<'a>(&'a Value);
+
+impl ToSql for Param<'_> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        if *self.0 == Value::Null {
+            return Ok(IsNull::Yes);
+        }
+        match *ty {
+            Type::BOOL => bind::<bool>(self.0, ty, out),
+            Type::INT2 => bind::<i16>(self.0, ty, out),
+            Type::INT4 => bind::<i32>(self.0, ty, out),
+            Type::INT8 => bind::<i64>(self.0, ty, out),
+            Type::FLOAT4 => bind::<f32>(self.0, ty, out),
+            Type::FLOAT8 => bind::<f64>(self.0, ty, out),
+            Type::BYTEA => bind::<Vec<u8>>(self.0, ty, out),
+            Type::JSON | Type::JSONB => bind::<serde_json::Value>(self.0, ty, out),
+            #[cfg(feature = "chrono")]
+            Type::DATE => bind::<chrono::NaiveDate>(self.0, ty, out),
+            #[cfg(feature = "chrono")]
+            Type::TIME => bind::<chrono::NaiveTime>(self.0, ty, out),
+            #[cfg(feature = "chrono")]
+            Type::TIMESTAMP => bind::<chrono::NaiveDateTime>(self.0, ty, out),
+            #[cfg(feature = "chrono")]
+            Type::TIMESTAMPTZ => bind::<chrono::DateTime<chrono::FixedOffset>>(self.0, ty, out),
+            #[cfg(feature = "uuid")]
+            Type::UUID => bind::<uuid::Uuid>(self.0, ty, out),
+            _ => bind::<String>(self.0, ty, out),
+        }
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+/// Binds the value as a `T`, failing if it can't be read as one or `T`
+/// can't be bound to the parameter type.
+fn bind<T: FromValue + ToSql>(
+    value: &Value,
+    ty: &Type,
+    out: &mut BytesMut,
+) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+    let value = T::from_value(value)
+        .ok_or_else(|| format!("cannot bind {:?} to a parameter of type {}", value, ty))?;
+    value.to_sql_checked(ty, out)
+}
+
+/// Reads a column of a result row into a value.
+fn read(row: &::postgres::Row, index: usize, ty: &Type) -> Result<Value, DatabaseError> {
+    match *ty {
+        Type::BOOL => get::<bool>(row, index),
+        Type::INT2 => get::<i16>(row, index),
+        Type::INT4 => get::<i32>(row, index),
+        Type::INT8 => get::<i64>(row, index),
+        Type::OID => get::<u32>(row, index),
+        Type::FLOAT4 => get::<f32>(row, index),
+        Type::FLOAT8 => get::<f64>(row, index),
+        Type::BYTEA => get::<Vec<u8>>(row, index),
+        Type::JSON | Type::JSONB => get::<serde_json::Value>(row, index),
+        #[cfg(feature = "chrono")]
+        Type::DATE => get::<chrono::NaiveDate>(row, index),
+        #[cfg(feature = "chrono")]
+        Type::TIME => get::<chrono::NaiveTime>(row, index),
+        #[cfg(feature = "chrono")]
+        Type::TIMESTAMP => get::<chrono::NaiveDateTime>(row, index),
+        #[cfg(feature = "chrono")]
+        Type::TIMESTAMPTZ => get::<chrono::DateTime<chrono::Utc>>(row, index),
+        #[cfg(feature = "uuid")]
+        Type::UUID => get::<uuid::Uuid>(row, index),
+        _ => get::<String>(row, index),
+    }
+}
+
+fn get<T>(row: &::postgres::Row, index: usize) -> Result<Value, DatabaseError>
+where
+    T: for<'a> FromSql<'a> + ToValue,
+{
+    Ok(row.try_get::<_, Option<T>>(index).map_err(error)?.to_value())
+}
+
+/// Wraps a driver error, keeping the message and SQLSTATE code of errors
+/// reported by the server.
+fn error(error: ::postgres::Error) -> DatabaseError {
+    match error.as_db_error() {
+        Some(db_error) => DatabaseError {
+            message: db_error.to_string(),
+            code: Some(db_error.code().code().to_string()),
+        },
+        None => match error.source() {
+            Some(source) => DatabaseError::new(format!("{}: {}", error, source)),
+            None => DatabaseError::new(error.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Binds the value to a parameter of the type, returning its wire bytes.
+    fn bound(value: Value, ty: &Type) -> Result<Option<Vec<u8>>, String> {
+        let mut out = BytesMut::new();
+        match Param(&value).to_sql(ty, &mut out).map_err(|e| e.to_string())? {
+            IsNull::Yes => Ok(None),
+            IsNull::No => Ok(Some(out.to_vec())),
+        }
+    }
+
+    #[test]
+    fn integers_bind_to_the_parameter_width() {
+        assert_eq!(bound(Value::BigInt(5), &Type::INT2), Ok(Some(vec![0, 5])));
+        assert_eq!(bound(Value::Integer(5), &Type::INT4), Ok(Some(vec![0, 0, 0, 5])));
+        assert_eq!(bound(Value::Integer(-1), &Type::INT8), Ok(Some(vec![255; 8])));
+        assert_eq!(
+            bound(Value::BigInt(1 << 40), &Type::INT4),
+            Err("cannot bind BigInt(1099511627776) to a parameter of type int4".to_string())
+        );
+    }
+
+    #[test]
+    fn null_binds_to_any_type() {
+        for ty in [Type::BOOL, Type::INT4, Type::TEXT, Type::BYTEA, Type::JSONB] {
+            assert_eq!(bound(Value::Null, &ty), Ok(None));
+        }
+    }
+
+    #[test]
+    fn values_bind_after_the_parameter_type() {
+        assert_eq!(bound(Value::Bool(true), &Type::BOOL), Ok(Some(vec![1])));
+        assert_eq!(
+            bound(Value::Float(1.5), &Type::FLOAT8),
+            Ok(Some(1.5f64.to_be_bytes().to_vec()))
+        );
+        assert_eq!(bound(Value::Bytes(vec![0, 255]), &Type::BYTEA), Ok(Some(vec![0, 255])));
+        assert_eq!(bound(Value::Text("Ada".to_string()), &Type::TEXT), Ok(Some(b"Ada".to_vec())));
+        // Other parameter types are bound as text
+        assert_eq!(
+            bound(Value::Text("Ada".to_string()), &Type::VARCHAR),
+            Ok(Some(b"Ada".to_vec()))
+        );
+        assert_eq!(
+            bound(Value::Integer(7), &Type::VARCHAR),
+            Err("cannot bind Integer(7) to a parameter of type varchar".to_string())
+        );
+        // JSONB is sent with its version byte
+        assert_eq!(
+            bound(Value::Text("{\"a\": 1}".to_string()), &Type::JSONB),
+            Ok(Some(b"\x01{\"a\":1}".to_vec()))
+        );
+        assert_eq!(
+            bound(Value::Text("yes".to_string()), &Type::BOOL),
+            Err("cannot bind Text(\"yes\") to a parameter of type bool".to_string())
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn text_binds_to_dates_and_timestamps() {
+        // Days since 2000-01-01
+        assert_eq!(
+            bound(Value::Text("2000-01-31".to_string()), &Type::DATE),
+            Ok(Some(30i32.to_be_bytes().to_vec()))
+        );
+        // Microseconds since 2000-01-01 00:00:00
+        assert_eq!(
+            bound(Value::Text("2000-01-01 00:00:01".to_string()), &Type::TIMESTAMP),
+            Ok(Some(1_000_000i64.to_be_bytes().to_vec()))
+        );
+        assert!(bound(Value::Text("yesterday".to_string()), &Type::DATE).is_err());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn text_binds_to_uuids() {
+        assert_eq!(
+            bound(Value::Text("00000000-0000-0000-0000-0000000000ff".to_string()), &Type::UUID),
+            Ok(Some([0; 15].into_iter().chain([255]).collect()))
+        );
+    }
+}
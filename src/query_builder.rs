@@ -1,209 +1,2672 @@
-use crate::model::{Model, Table};
+use crate::dialect::{
+    is_identifier, quote_if_needed, quote_path_if_needed, Dialect, ParamFormat, PlaceholderStyle,
+};
+use crate::error::OrmError;
+use crate::escape::{quote_string, BackslashMode};
+use crate::executor::{AsyncConnection, Connection, FetchError};
+use crate::expr::{precedence, BinaryOp, Expr};
+use crate::model::{IntoInsert, Model, Table, ToValue, Value};
+use crate::row::{FromRow, Row};
+use std::borrow::Cow;
+use std::fmt;
 use std::marker::PhantomData;
+use thiserror::Error;
+
+/// Errors reported by `try_build` when a query builder is in an invalid state.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum QueryError {
+    #[error("UPDATE on table `{table}` has no SET clause")]
+    EmptySetClause { table: String },
+    #[error("INSERT into table `{table}` has no values")]
+    NoValues { table: String },
+    #[error("column `{column}` does not exist on table `{table}`")]
+    UnknownColumn { table: String, column: String },
+    #[error("OFFSET without LIMIT is not supported by all databases")]
+    OffsetWithoutLimit,
+    #[error("row {row} has {found} values but {expected} columns were declared")]
+    RowLength { row: usize, expected: usize, found: usize },
+    #[error("table `{table}` has no primary key")]
+    MissingPrimaryKey { table: String },
+    #[error("INSERT cannot combine VALUES rows with a SELECT source")]
+    MixedInsertSource,
+    #[error("INSERT declares {expected} columns but its SELECT returns {found}")]
+    SelectColumnCount { expected: usize, found: usize },
+    #[error("keyset pagination cannot be combined with OFFSET")]
+    KeysetWithOffset,
+    #[error("{feature} is not supported by {dialect}")]
+    Unsupported { feature: &'static str, dialect: Dialect },
+    #[error("query on table `{table}` has no WHERE clause; call `all_rows()` to affect every row")]
+    MissingWhereClause { table: String },
+    #[error("relationship `{relationship}` must be joined before relations are nested off it")]
+    RelationNotJoined { relationship: &'static str },
+}
+
+/// How a condition is combined with the conditions before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Connective {
+    And,
+    Or,
+}
+
+impl Connective {
+    fn as_sql(self) -> &'static str {
+        match self {
+            Connective::And => " AND ",
+            Connective::Or => " OR ",
+        }
+    }
+}
+
+/// The conditions of a WHERE or HAVING clause, combined from left to right.
+#[derive(Debug, Clone, Default)]
+struct Conditions {
+    conditions: Vec<(Connective, Expr)>,
+}
+
+impl Conditions {
+    fn and(&mut self, condition: Expr) {
+        self.conditions.push((Connective::And, condition));
+    }
+
+    fn or(&mut self, condition: Expr) {
+        self.conditions.push((Connective::Or, condition));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Returns the columns referenced by the conditions.
+    fn columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        for (_, condition) in &self.conditions {
+            condition.collect_columns(&mut columns);
+        }
+        columns
+    }
+}
+
+/// Checks that every plain (unqualified) column name exists on the table.
+///
+/// Qualified names and expressions are left to the database, since they
+/// may refer to other tables or be computed.
+fn check_columns<'a>(
+    table: &Table,
+    columns: impl IntoIterator<Item = &'a str>,
+) -> Result<(), QueryError> {
+    for column in columns {
+        if is_identifier(column) && table.column(column).is_none() {
+            return Err(QueryError::UnknownColumn {
+                table: table.name.clone(),
+                column: column.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The right-hand side of a column assignment in INSERT or UPDATE.
+#[derive(Debug, Clone)]
+enum Operand {
+    /// A typed value, formatted according to its kind.
+    Value(Value),
+    /// A pre-formatted SQL fragment, emitted as is.
+    Raw(String),
+    /// A column plus or minus a value, such as `counter + 1`.
+    Offset { column: String, op: char, amount: Value },
+}
+
+/// Accumulates SQL text, either inlining values as literals or
+/// replacing them with placeholders and collecting them separately.
+///
+/// Without a dialect, identifiers are only quoted where needed (reserved
+/// words and mixed case) and values use `?` placeholders and generic literals.
+struct SqlWriter {
+    sql: String,
+    params: Option<Vec<Value>>,
+    dialect: Option<Dialect>,
+    placeholders: PlaceholderStyle,
+}
+
+impl SqlWriter {
+    /// Creates a writer that inlines values as SQL literals.
+    fn inline() -> Self {
+        SqlWriter {
+            sql: String::new(),
+            params: None,
+            dialect: None,
+            placeholders: PlaceholderStyle::Question,
+        }
+    }
+
+    /// Creates a writer that emits `?` placeholders and collects the values.
+    fn parameterized() -> Self {
+        SqlWriter { params: Some(Vec::new()), ..SqlWriter::inline() }
+    }
+
+    /// Creates a writer that emits placeholders in the given format.
+    fn parameterized_for(format: ParamFormat) -> Self {
+        SqlWriter {
+            dialect: format.dialect,
+            placeholders: format.style,
+            ..SqlWriter::parameterized()
+        }
+    }
+
+    /// Renders for the given dialect instead of the generic flavor.
+    fn for_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.sql.push_str(s);
+    }
+
+    /// Returns a table or column name, quoted for the dialect. Expressions
+    /// are returned unchanged.
+    fn ident(&self, name: &str) -> String {
+        match self.dialect {
+            Some(dialect) => dialect.quote_path(name),
+            None => quote_path_if_needed(name),
+        }
+    }
+
+    /// Returns an alias, quoted for the dialect. Unlike names, aliases are
+    /// never expressions, so those that aren't plain identifiers are quoted
+    /// as well.
+    fn alias(&self, alias: &str) -> String {
+        match self.dialect {
+            Some(dialect) => dialect.quote_identifier(alias),
+            None => quote_if_needed(alias),
+        }
+    }
+
+    /// Returns a comma-separated list of table or column names.
+    fn idents(&self, names: &[String]) -> String {
+        names.iter().map(|name| self.ident(name)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn push_ident(&mut self, name: &str) {
+        let ident = self.ident(name);
+        self.push_str(&ident);
+    }
+
+    /// Writes a value, either as a placeholder or as a literal.
+    fn push_value(&mut self, value: &Value) {
+        match &mut self.params {
+            Some(params) => {
+                // Named placeholders can be referenced repeatedly, so equal values share one.
+                let existing = match self.placeholders {
+                    PlaceholderStyle::Named => params.iter().position(|param| param == value),
+                    _ => None,
+                };
+                let index = existing.unwrap_or_else(|| {
+                    params.push(value.clone());
+                    params.len() - 1
+                });
+                self.sql.push_str(&self.placeholders.placeholder(index + 1));
+            }
+            None => self.sql.push_str(&format_literal(value, self.dialect)),
+        }
+    }
+
+    /// Writes `(column, expr)` pairs as `column = expr, ...`.
+    fn push_assignments(&mut self, assignments: &[(String, String)]) {
+        for (i, (column, expr)) in assignments.iter().enumerate() {
+            if i > 0 {
+                self.push_str(", ");
+            }
+            self.push_ident(column);
+            self.push_str(&format!(" = {}", expr));
+        }
+    }
+
+    fn push_operand(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Value(value) => self.push_value(value),
+            Operand::Raw(sql) => self.push_str(sql),
+            Operand::Offset { column, op, amount } => {
+                self.push_ident(column);
+                self.push_str(&format!(" {} ", op));
+                self.push_value(amount);
+            }
+        }
+    }
+
+    /// Writes an expression, parenthesizing operands that bind looser than
+    /// their parent operator.
+    fn push_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Column(name) => self.push_ident(name),
+            Expr::Value(value) => self.push_value(value),
+            Expr::Raw(sql) => self.push_str(sql),
+            Expr::Binary { left, op, right } => {
+                let op_precedence = op.precedence();
+                // Comparisons don't chain, so an equally binding operand needs parentheses too.
+                let strict = matches!(op, BinaryOp::And | BinaryOp::Or);
+                self.push_operand_expr(left, op_precedence, strict);
+                self.push_str(&format!(" {} ", op.as_sql()));
+                self.push_operand_expr(right, op_precedence, strict);
+            }
+            Expr::Not(inner) => {
+                self.push_str("NOT ");
+                self.push_operand_expr(inner, precedence::ATOM, true);
+            }
+            Expr::In { list, negated, .. } if list.is_empty() => {
+                // `IN ()` is invalid SQL, so fall back to a constant condition.
+                self.push_str(if *negated { "1 = 1" } else { "1 = 0" });
+            }
+            Expr::In { expr, list, negated } => {
+                self.push_operand_expr(expr, precedence::COMPARISON, false);
+                self.push_str(if *negated { " NOT IN (" } else { " IN (" });
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
+                        self.push_str(", ");
+                    }
+                    self.push_expr(item);
+                }
+                self.push_str(")");
+            }
+            Expr::Between { expr, low, high, negated } => {
+                self.push_operand_expr(expr, precedence::COMPARISON, false);
+                self.push_str(if *negated { " NOT BETWEEN " } else { " BETWEEN " });
+                self.push_operand_expr(low, precedence::COMPARISON, false);
+                self.push_str(" AND ");
+                self.push_operand_expr(high, precedence::COMPARISON, false);
+            }
+            Expr::Like { expr, pattern, negated, case_insensitive: true }
+                if matches!(self.dialect, Some(Dialect::MySql | Dialect::Sqlite)) =>
+            {
+                // Only Postgres has ILIKE, so compare both sides lowercased.
+                self.push_str("LOWER(");
+                self.push_expr(expr);
+                self.push_str(if *negated { ") NOT LIKE LOWER(" } else { ") LIKE LOWER(" });
+                self.push_expr(pattern);
+                self.push_str(")");
+                self.push_like_escape();
+            }
+            Expr::Like { expr, pattern, negated, case_insensitive } => {
+                self.push_operand_expr(expr, precedence::COMPARISON, false);
+                self.push_str(match (negated, case_insensitive) {
+                    (false, false) => " LIKE ",
+                    (true, false) => " NOT LIKE ",
+                    (false, true) => " ILIKE ",
+                    (true, true) => " NOT ILIKE ",
+                });
+                self.push_operand_expr(pattern, precedence::COMPARISON, false);
+                self.push_like_escape();
+            }
+            Expr::InSubquery { expr, subquery, negated } => {
+                self.push_operand_expr(expr, precedence::COMPARISON, false);
+                self.push_str(if *negated { " NOT IN (" } else { " IN (" });
+                subquery.0.write_sql(self);
+                self.push_str(")");
+            }
+            Expr::Exists { subquery, negated } => {
+                self.push_str(if *negated { "NOT EXISTS (" } else { "EXISTS (" });
+                subquery.0.write_sql(self);
+                self.push_str(")");
+            }
+            Expr::Tuple(items) => {
+                self.push_str("(");
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.push_str(", ");
+                    }
+                    self.push_expr(item);
+                }
+                self.push_str(")");
+            }
+        }
+    }
+
+    /// Writes the `ESCAPE '\'` clause of a LIKE pattern, which `escape_like`
+    /// escapes wildcards for. SQLite has no default escape character, so it's
+    /// always given.
+    fn push_like_escape(&mut self) {
+        let mode = self.dialect.map_or(BackslashMode::Literal, Dialect::backslash_mode);
+        self.push_str(&format!(" ESCAPE {}", quote_string("\\", mode)));
+    }
+
+    fn push_operand_expr(&mut self, expr: &Expr, parent_precedence: u8, strict: bool) {
+        let precedence = expr.precedence();
+        if precedence < parent_precedence || (!strict && precedence == parent_precedence) {
+            self.push_str("(");
+            self.push_expr(expr);
+            self.push_str(")");
+        } else {
+            self.push_expr(expr);
+        }
+    }
+
+    /// Writes a WHERE clause.
+    fn push_where(&mut self, where_clause: &Conditions) {
+        self.push_conditions(" WHERE ", where_clause);
+    }
+
+    /// Writes a keyword followed by a list of conditions, if there are any.
+    ///
+    /// A single condition is written as is. Otherwise raw and AND/OR
+    /// conditions are parenthesized, and the accumulated conditions are grouped whenever
+    /// the connective changes, so `a AND b OR c` becomes `((a) AND (b)) OR (c)`.
+    fn push_conditions(&mut self, keyword: &str, conditions: &Conditions) {
+        let conditions = &conditions.conditions;
+        if conditions.is_empty() {
+            return;
+        }
+
+        self.push_str(keyword);
+        if conditions.len() == 1 {
+            self.push_expr(&conditions[0].1);
+            return;
+        }
+
+        let groups = conditions[1..].windows(2).filter(|pair| pair[0].0 != pair[1].0).count();
+        self.push_str(&"(".repeat(groups));
+
+        for (i, (connective, condition)) in conditions.iter().enumerate() {
+            if i > 1 && conditions[i - 1].0 != *connective {
+                self.push_str(")");
+            }
+            if i > 0 {
+                self.push_str(connective.as_sql());
+            }
+            self.push_operand_expr(condition, precedence::NOT, true);
+        }
+    }
+
+    /// Writes an ORDER BY clause, if there are any columns to order by.
+    fn push_order_by(&mut self, order_by: &[OrderItem]) {
+        if order_by.is_empty() {
+            return;
+        }
+
+        let items: Vec<String> = order_by
+            .iter()
+            .map(|item| {
+                let column = self.ident(&item.column);
+                let mut sql = String::new();
+                if let (Some(Dialect::MySql), Some(nulls)) = (self.dialect, item.nulls) {
+                    // MySQL has no NULLS FIRST/LAST, so sort on the IS NULL flag first.
+                    sql.push_str(&format!("{} IS NULL", column));
+                    if nulls == NullsOrder::First {
+                        sql.push_str(" DESC");
+                    }
+                    sql.push_str(", ");
+                }
+                sql.push_str(&column);
+                if let Some(direction) = item.direction {
+                    sql.push_str(&format!(" {}", direction.as_sql()));
+                }
+                if let Some(nulls) = item.nulls.filter(|_| self.dialect != Some(Dialect::MySql)) {
+                    sql.push_str(&format!(" {}", nulls.as_sql()));
+                }
+                sql
+            })
+            .collect();
+        self.push_str(&format!(" ORDER BY {}", items.join(", ")));
+    }
+
+    /// Writes a RETURNING clause, if there are any columns to return.
+    fn push_returning(&mut self, columns: &[String]) {
+        if !columns.is_empty() {
+            let columns = self.idents(columns);
+            self.push_str(&format!(" RETURNING {}", columns));
+        }
+    }
+
+    /// Writes LIMIT and OFFSET clauses.
+    fn push_limit_offset(&mut self, limit: Option<usize>, offset: Option<usize>) {
+        if let Some(limit) = limit {
+            self.push_str(&format!(" LIMIT {}", limit));
+        } else if let (Some(_), Some(unbounded)) =
+            (offset, self.dialect.and_then(Dialect::unbounded_limit))
+        {
+            self.push_str(&format!(" LIMIT {}", unbounded));
+        }
+        if let Some(offset) = offset {
+            self.push_str(&format!(" OFFSET {}", offset));
+        }
+    }
+
+    fn finish(self) -> (String, Vec<Value>) {
+        (self.sql, self.params.unwrap_or_default())
+    }
+}
+
+/// Formats a value as an inline SQL literal, for the given dialect if any.
+pub(crate) fn format_literal(value: &Value, dialect: Option<Dialect>) -> String {
+    match (dialect, value) {
+        (_, Value::Integer(i)) => i.to_string(),
+        (_, Value::BigInt(i)) => i.to_string(),
+        (_, Value::Float(f)) => f.to_string(),
+        (Some(dialect), Value::Text(s)) => quote_string(s, dialect.backslash_mode()),
+        (None, Value::Text(s)) => quote_string(s, BackslashMode::Literal),
+        (Some(dialect), Value::Bool(b)) => dialect.bool_literal(*b).to_string(),
+        (None, Value::Bool(b)) => b.to_string(),
+        (Some(Dialect::Postgres), Value::Bytes(bytes)) => format!("'\\x{}'", hex(bytes)),
+        (_, Value::Bytes(bytes)) => format!("X'{}'", hex(bytes)),
+        (_, Value::Null) => "NULL".to_string(),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The DISTINCT mode of a SELECT query.
+#[derive(Debug, Clone)]
+enum Distinct {
+    /// `SELECT DISTINCT`
+    Rows,
+    /// `SELECT DISTINCT ON (columns)`, Postgres only.
+    On(Vec<String>),
+}
+
+/// Sort direction of an ORDER BY column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    fn as_sql(self) -> &'static str {
+        match self {
+            Order::Asc => "ASC",
+            Order::Desc => "DESC",
+        }
+    }
+}
+
+/// Placement of NULL values in an ORDER BY column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+impl NullsOrder {
+    fn as_sql(self) -> &'static str {
+        match self {
+            NullsOrder::First => "NULLS FIRST",
+            NullsOrder::Last => "NULLS LAST",
+        }
+    }
+}
+
+/// A column in an ORDER BY clause. Without an explicit direction or nulls
+/// placement the database defaults apply and nothing is emitted.
+#[derive(Debug, Clone)]
+struct OrderItem {
+    column: String,
+    direction: Option<Order>,
+    nulls: Option<NullsOrder>,
+}
+
+/// The kind of a JOIN clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Right,
+    /// A FULL OUTER JOIN, which MySQL doesn't support.
+    Full,
+    Cross,
+}
+
+impl JoinType {
+    fn as_sql(self) -> &'static str {
+        match self {
+            JoinType::Inner => "INNER JOIN",
+            JoinType::Left => "LEFT JOIN",
+            JoinType::Right => "RIGHT JOIN",
+            JoinType::Full => "FULL JOIN",
+            JoinType::Cross => "CROSS JOIN",
+        }
+    }
+}
+
+/// A JOIN clause of a SELECT query.
+#[derive(Debug, Clone)]
+struct Join {
+    join_type: JoinType,
+    table: String,
+    alias: Option<String>,
+    on: Option<Expr>,
+}
+
+/// A column or expression in a SELECT list, with an optional alias.
+#[derive(Debug, Clone)]
+struct SelectItem {
+    expr: String,
+    alias: Option<String>,
+}
+
+/// Stand-in model for queries whose model type has been erased.
+#[derive(Debug, Clone)]
+struct Untyped;
+
+impl Model for Untyped {
+    fn table() -> Table {
+        Table::default()
+    }
+}
+
+/// A SELECT query nested inside another statement.
+///
+/// Created from any `SelectQuery` and rendered with the enclosing
+/// statement, so its bound values are merged in placeholder order.
+#[derive(Debug, Clone)]
+pub struct Subquery(SelectQuery<Untyped>);
+
+impl<T: Model> From<SelectQuery<T>> for Subquery {
+    fn from(query: SelectQuery<T>) -> Self {
+        Subquery(SelectQuery {
+            table: query.table,
+            alias: query.alias,
+            distinct: query.distinct,
+            selected_columns: query.selected_columns,
+            where_clause: query.where_clause,
+            joins: query.joins,
+            group_by: query.group_by,
+            having: query.having,
+            order_by: query.order_by,
+            limit: query.limit,
+            offset: query.offset,
+            keyset: query.keyset,
+            source: query.source,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl Subquery {
+    /// Builds the query on its own with inlined values, for the dialect if
+    /// one is given.
+    pub(crate) fn build_inline(&self, dialect: Option<Dialect>) -> String {
+        match dialect {
+            Some(dialect) => self.0.build_for(dialect),
+            None => self.0.build(),
+        }
+    }
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|s| s.to_string()).collect()
+}
+
+fn to_string_pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+}
 
 /// Represents a SQL SELECT query.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct SelectQuery<T: Model> {
-    pub table: Table,
-    selected_columns: Vec<String>,
-    where_clause: Option<String>,
-    pub joins: Vec<String>,
-    order_by: Vec<String>,
+    pub table: Cow<'static, Table>,
+    alias: Option<String>,
+    distinct: Option<Distinct>,
+    selected_columns: Vec<SelectItem>,
+    where_clause: Conditions,
+    joins: Vec<Join>,
+    group_by: Vec<String>,
+    having: Conditions,
+    order_by: Vec<OrderItem>,
     limit: Option<usize>,
     offset: Option<usize>,
+    /// Set by `after` and `before`, which page by key rather than by offset.
+    keyset: bool,
+    /// Set by `count_query` to count the rows of a grouped or distinct
+    /// query, selected from in place of the table.
+    source: Option<Box<Subquery>>,
+    _marker: PhantomData<T>,
+}
+
+// Clone and Debug are implemented by hand for the query builders, as
+// deriving them would require the model type itself to be Clone and Debug.
+impl<T: Model> Clone for SelectQuery<T> {
+    fn clone(&self) -> Self {
+        SelectQuery {
+            table: self.table.clone(),
+            alias: self.alias.clone(),
+            distinct: self.distinct.clone(),
+            selected_columns: self.selected_columns.clone(),
+            where_clause: self.where_clause.clone(),
+            joins: self.joins.clone(),
+            group_by: self.group_by.clone(),
+            having: self.having.clone(),
+            order_by: self.order_by.clone(),
+            limit: self.limit,
+            offset: self.offset,
+            keyset: self.keyset,
+            source: self.source.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Model> fmt::Debug for SelectQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectQuery")
+            .field("table", &self.table)
+            .field("alias", &self.alias)
+            .field("distinct", &self.distinct)
+            .field("selected_columns", &self.selected_columns)
+            .field("where_clause", &self.where_clause)
+            .field("joins", &self.joins)
+            .field("group_by", &self.group_by)
+            .field("having", &self.having)
+            .field("order_by", &self.order_by)
+            .field("limit", &self.limit)
+            .field("offset", &self.offset)
+            .field("keyset", &self.keyset)
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<T: Model> SelectQuery<T> {
+    /// Creates a new SelectQuery for the given model.
+    pub fn new() -> Self {
+        SelectQuery {
+            table: T::table_ref(),
+            alias: None,
+            distinct: None,
+            selected_columns: Vec::new(),
+            where_clause: Conditions::default(),
+            joins: Vec::new(),
+            group_by: Vec::new(),
+            having: Conditions::default(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+            keyset: false,
+            source: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns whether the select list is empty, so that the query selects `*`.
+    pub fn selects_all(&self) -> bool {
+        self.selected_columns.is_empty()
+    }
+
+    /// Adds columns or expressions to the select list.
+    pub fn select(mut self, columns: &[&str]) -> Self {
+        self.selected_columns
+            .extend(columns.iter().map(|s| SelectItem { expr: s.to_string(), alias: None }));
+        self
+    }
+
+    /// Adds `(column, alias)` pairs to the select list, rendered as `column AS alias`.
+    pub fn select_as(mut self, columns: &[(&str, &str)]) -> Self {
+        self.selected_columns.extend(columns.iter().map(|(expr, alias)| SelectItem {
+            expr: expr.to_string(),
+            alias: Some(alias.to_string()),
+        }));
+        self
+    }
+
+    /// Adds `COUNT(column)` to the select list, with an optional alias.
+    pub fn select_count(self, column: &str, alias: Option<&str>) -> Self {
+        self.select_aggregate("COUNT", column, alias)
+    }
+
+    /// Adds `SUM(column)` to the select list, with an optional alias.
+    pub fn select_sum(self, column: &str, alias: Option<&str>) -> Self {
+        self.select_aggregate("SUM", column, alias)
+    }
+
+    /// Adds `AVG(column)` to the select list, with an optional alias.
+    pub fn select_avg(self, column: &str, alias: Option<&str>) -> Self {
+        self.select_aggregate("AVG", column, alias)
+    }
+
+    /// Adds `MIN(column)` to the select list, with an optional alias.
+    pub fn select_min(self, column: &str, alias: Option<&str>) -> Self {
+        self.select_aggregate("MIN", column, alias)
+    }
+
+    /// Adds `MAX(column)` to the select list, with an optional alias.
+    pub fn select_max(self, column: &str, alias: Option<&str>) -> Self {
+        self.select_aggregate("MAX", column, alias)
+    }
+
+    fn select_aggregate(mut self, function: &str, column: &str, alias: Option<&str>) -> Self {
+        self.selected_columns.push(SelectItem {
+            expr: format!("{}({})", function, column),
+            alias: alias.map(|s| s.to_string()),
+        });
+        self
+    }
+
+    /// Turns the query into a `SELECT COUNT(*)` over the same rows.
+    ///
+    /// The select list is replaced and ORDER BY is dropped, since ordering
+    /// is meaningless when counting.
+    pub fn count(mut self) -> Self {
+        self.selected_columns = vec![SelectItem { expr: "COUNT(*)".to_string(), alias: None }];
+        self.order_by.clear();
+        self
+    }
+
+    /// Selects only distinct rows.
+    ///
+    /// `distinct` and `distinct_on` are mutually exclusive; the last call wins.
+    pub fn distinct(mut self) -> Self {
+        self.distinct = Some(Distinct::Rows);
+        self
+    }
+
+    /// Selects the first row of each group of rows with equal values in the
+    /// given columns (Postgres `DISTINCT ON`).
+    ///
+    /// `distinct` and `distinct_on` are mutually exclusive; the last call wins.
+    pub fn distinct_on(mut self, columns: &[&str]) -> Self {
+        self.distinct = Some(Distinct::On(columns.iter().map(|s| s.to_string()).collect()));
+        self
+    }
+
+    /// Adds a raw WHERE condition, combined with previous conditions using AND.
+    pub fn filter(mut self, condition: &str) -> Self {
+        self.where_clause.and(Expr::raw(condition));
+        self
+    }
+
+    /// Adds a raw WHERE condition, combined with previous conditions using OR.
+    pub fn or_filter(mut self, condition: &str) -> Self {
+        self.where_clause.or(Expr::raw(condition));
+        self
+    }
+
+    /// Adds a WHERE condition built from an expression, combined using AND.
+    pub fn filter_expr(mut self, expr: Expr) -> Self {
+        self.where_clause.and(expr);
+        self
+    }
+
+    /// Adds a `column = value` WHERE condition, combined using AND.
+    pub fn filter_eq(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).eq(value.to_value()));
+        self
+    }
+
+    /// Adds a `column <> value` WHERE condition, combined using AND.
+    pub fn filter_ne(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).ne(value.to_value()));
+        self
+    }
+
+    /// Adds a `column > value` WHERE condition, combined using AND.
+    pub fn filter_gt(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).gt(value.to_value()));
+        self
+    }
+
+    /// Adds a `column < value` WHERE condition, combined using AND.
+    pub fn filter_lt(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).lt(value.to_value()));
+        self
+    }
+
+    /// Adds a `column >= value` WHERE condition, combined using AND.
+    pub fn filter_ge(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).ge(value.to_value()));
+        self
+    }
+
+    /// Adds a `column <= value` WHERE condition, combined using AND.
+    pub fn filter_le(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).le(value.to_value()));
+        self
+    }
+
+    /// Adds a `column IN (...)` WHERE condition, combined using AND.
+    pub fn filter_in<V: ToValue>(mut self, column: &str, values: &[V]) -> Self {
+        self.where_clause.and(Expr::col(column).in_list(values));
+        self
+    }
+
+    /// Adds a `column NOT IN (...)` WHERE condition, combined using AND.
+    pub fn filter_not_in<V: ToValue>(mut self, column: &str, values: &[V]) -> Self {
+        self.where_clause.and(Expr::col(column).not_in_list(values));
+        self
+    }
+
+    /// Adds a `column BETWEEN low AND high` WHERE condition, combined using AND.
+    pub fn filter_between(mut self, column: &str, low: impl ToValue, high: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).between(low.to_value(), high.to_value()));
+        self
+    }
+
+    /// Adds a `column NOT BETWEEN low AND high` WHERE condition, combined using AND.
+    pub fn filter_not_between(
+        mut self,
+        column: &str,
+        low: impl ToValue,
+        high: impl ToValue,
+    ) -> Self {
+        self.where_clause.and(Expr::col(column).not_between(low.to_value(), high.to_value()));
+        self
+    }
+
+    /// Adds a `column LIKE pattern` WHERE condition, combined using AND.
+    ///
+    /// Use [`escape_like`](crate::escape::escape_like) to embed user input in the pattern.
+    pub fn filter_like(mut self, column: &str, pattern: &str) -> Self {
+        self.where_clause.and(Expr::col(column).like(pattern));
+        self
+    }
+
+    /// Adds a `column NOT LIKE pattern` WHERE condition, combined using AND.
+    pub fn filter_not_like(mut self, column: &str, pattern: &str) -> Self {
+        self.where_clause.and(Expr::col(column).not_like(pattern));
+        self
+    }
+
+    /// Adds a case-insensitive `column ILIKE pattern` WHERE condition, combined using AND.
+    pub fn filter_ilike(mut self, column: &str, pattern: &str) -> Self {
+        self.where_clause.and(Expr::col(column).ilike(pattern));
+        self
+    }
+
+    /// Adds a `column IN (subquery)` WHERE condition, combined using AND.
+    pub fn filter_in_subquery<R: Model>(mut self, column: &str, subquery: SelectQuery<R>) -> Self {
+        self.where_clause.and(Expr::col(column).in_subquery(subquery));
+        self
+    }
+
+    /// Adds an `EXISTS (subquery)` WHERE condition, combined using AND.
+    pub fn filter_exists<R: Model>(mut self, subquery: SelectQuery<R>) -> Self {
+        self.where_clause.and(Expr::exists(subquery));
+        self
+    }
+
+    /// Adds a `NOT EXISTS (subquery)` WHERE condition, combined using AND.
+    pub fn filter_not_exists<R: Model>(mut self, subquery: SelectQuery<R>) -> Self {
+        self.where_clause.and(Expr::not_exists(subquery));
+        self
+    }
+
+    /// Aliases the FROM table, rendered as `FROM table AS alias`.
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.alias = Some(alias.to_string());
+        self
+    }
+
+    /// Returns the name that columns of the FROM table are qualified with:
+    /// its alias if one was set, otherwise the schema-qualified table name.
+    pub fn qualifier(&self) -> String {
+        self.alias.clone().unwrap_or_else(|| self.table.qualified_name())
+    }
+
+    /// Adds an `INNER JOIN table ON condition` clause.
+    pub fn inner_join(self, table: &str, on: &str) -> Self {
+        self.join(JoinType::Inner, table, None, Some(Expr::raw(on)))
+    }
+
+    /// Adds an `INNER JOIN table AS alias ON condition` clause.
+    pub fn inner_join_as(self, table: &str, alias: &str, on: &str) -> Self {
+        self.join(JoinType::Inner, table, Some(alias), Some(Expr::raw(on)))
+    }
+
+    /// Adds an INNER JOIN on the table of another model.
+    pub fn inner_join_model<R: Model>(self, on: &str) -> Self {
+        self.join(JoinType::Inner, &R::table_ref().qualified_name(), None, Some(Expr::raw(on)))
+    }
+
+    /// Adds an INNER JOIN whose condition is built from an expression.
+    pub fn inner_join_expr(self, table: &str, on: Expr) -> Self {
+        self.join(JoinType::Inner, table, None, Some(on))
+    }
+
+    /// Adds a `LEFT JOIN table ON condition` clause.
+    pub fn left_join(self, table: &str, on: &str) -> Self {
+        self.join(JoinType::Left, table, None, Some(Expr::raw(on)))
+    }
+
+    /// Adds a `LEFT JOIN table AS alias ON condition` clause.
+    pub fn left_join_as(self, table: &str, alias: &str, on: &str) -> Self {
+        self.join(JoinType::Left, table, Some(alias), Some(Expr::raw(on)))
+    }
+
+    /// Adds a LEFT JOIN whose condition is built from an expression.
+    pub fn left_join_expr(self, table: &str, on: Expr) -> Self {
+        self.join(JoinType::Left, table, None, Some(on))
+    }
+
+    /// Adds a `RIGHT JOIN table ON condition` clause.
+    pub fn right_join(self, table: &str, on: &str) -> Self {
+        self.join(JoinType::Right, table, None, Some(Expr::raw(on)))
+    }
+
+    /// Adds a `RIGHT JOIN table AS alias ON condition` clause.
+    pub fn right_join_as(self, table: &str, alias: &str, on: &str) -> Self {
+        self.join(JoinType::Right, table, Some(alias), Some(Expr::raw(on)))
+    }
+
+    /// Adds a `FULL JOIN table ON condition` clause.
+    pub fn full_join(self, table: &str, on: &str) -> Self {
+        self.join(JoinType::Full, table, None, Some(Expr::raw(on)))
+    }
+
+    /// Adds a join of the given type whose condition is built from an
+    /// expression. A CROSS JOIN takes no condition, so it's left out.
+    pub fn join_expr(self, join_type: JoinType, table: &str, on: Expr) -> Self {
+        let on = (join_type != JoinType::Cross).then_some(on);
+        self.join(join_type, table, None, on)
+    }
+
+    /// Adds a join of the given type to the table under an alias, whose
+    /// condition is built from an expression.
+    pub fn join_expr_as(self, join_type: JoinType, table: &str, alias: &str, on: Expr) -> Self {
+        let on = (join_type != JoinType::Cross).then_some(on);
+        self.join(join_type, table, Some(alias), on)
+    }
+
+    /// Adds a `CROSS JOIN table` clause.
+    pub fn cross_join(self, table: &str) -> Self {
+        self.join(JoinType::Cross, table, None, None)
+    }
+
+    fn join(
+        mut self,
+        join_type: JoinType,
+        table: &str,
+        alias: Option<&str>,
+        on: Option<Expr>,
+    ) -> Self {
+        self.joins.push(Join {
+            join_type,
+            table: table.to_string(),
+            alias: alias.map(|s| s.to_string()),
+            on,
+        });
+        self
+    }
+
+    /// Adds columns to the GROUP BY clause.
+    pub fn group_by(mut self, columns: &[&str]) -> Self {
+        self.group_by.extend(columns.iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Adds a raw HAVING condition, combined with previous conditions using AND.
+    pub fn having(mut self, condition: &str) -> Self {
+        self.having.and(Expr::raw(condition));
+        self
+    }
+
+    /// Adds a HAVING condition built from an expression, combined using AND.
+    pub fn having_expr(mut self, expr: Expr) -> Self {
+        self.having.and(expr);
+        self
+    }
+
+    /// Adds columns to the ORDER BY clause, using the default direction.
+    pub fn order_by(mut self, columns: &[&str]) -> Self {
+        self.order_by.extend(columns.iter().map(|s| OrderItem {
+            column: s.to_string(),
+            direction: None,
+            nulls: None,
+        }));
+        self
+    }
+
+    /// Adds columns to the ORDER BY clause in descending order.
+    pub fn order_by_desc(mut self, columns: &[&str]) -> Self {
+        self.order_by.extend(columns.iter().map(|s| OrderItem {
+            column: s.to_string(),
+            direction: Some(Order::Desc),
+            nulls: None,
+        }));
+        self
+    }
+
+    /// Adds `(column, direction)` pairs to the ORDER BY clause.
+    pub fn order_by_dir(mut self, columns: &[(&str, Order)]) -> Self {
+        self.order_by.extend(columns.iter().map(|(column, direction)| OrderItem {
+            column: column.to_string(),
+            direction: Some(*direction),
+            nulls: None,
+        }));
+        self
+    }
+
+    /// Adds `(column, direction, nulls placement)` triples to the ORDER BY clause,
+    /// e.g. `("last_login", Order::Desc, Some(NullsOrder::Last))`.
+    pub fn order_by_with(mut self, columns: &[(&str, Order, Option<NullsOrder>)]) -> Self {
+        self.order_by.extend(columns.iter().map(|(column, direction, nulls)| OrderItem {
+            column: column.to_string(),
+            direction: Some(*direction),
+            nulls: *nulls,
+        }));
+        self
+    }
+
+    /// Adds a LIMIT clause.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Adds an OFFSET clause.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets LIMIT and OFFSET to return the given 1-based page of results.
+    ///
+    /// Page 0 is treated as page 1.
+    pub fn paginate(self, page: u64, per_page: u64) -> Self {
+        let offset = page.max(1).saturating_sub(1).saturating_mul(per_page);
+        let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+        let limit = usize::try_from(per_page).unwrap_or(usize::MAX);
+        self.limit(limit).offset(offset)
+    }
+
+    /// Starts the page after the given cursor, e.g.
+    /// `after(&[("created_at", last_created_at), ("id", last_id)])`.
+    ///
+    /// Adds a `(created_at, id) > (?, ?)` condition and replaces the ORDER BY
+    /// with the cursor columns in ascending order. `try_build` rejects the
+    /// query if an OFFSET is also set.
+    pub fn after<V: ToValue>(self, cursor: &[(&str, V)]) -> Self {
+        self.keyset_page(cursor, Order::Asc)
+    }
+
+    /// Starts the page before the given cursor, the descending counterpart of
+    /// `after`: the comparison is `<` and the cursor columns are ordered DESC.
+    pub fn before<V: ToValue>(self, cursor: &[(&str, V)]) -> Self {
+        self.keyset_page(cursor, Order::Desc)
+    }
+
+    fn keyset_page<V: ToValue>(mut self, cursor: &[(&str, V)], direction: Order) -> Self {
+        let (columns, values): (Vec<Expr>, Vec<Expr>) =
+            cursor.iter().map(|(column, value)| (Expr::col(column), Expr::val(value))).unzip();
+        let (left, right) = if cursor.len() == 1 {
+            (columns.into_iter().next().unwrap(), values.into_iter().next().unwrap())
+        } else {
+            (Expr::tuple(columns), Expr::tuple(values))
+        };
+        if !cursor.is_empty() {
+            self.where_clause.and(match direction {
+                Order::Asc => left.gt(right),
+                Order::Desc => left.lt(right),
+            });
+        }
+        self.order_by = cursor
+            .iter()
+            .map(|(column, _)| OrderItem {
+                column: column.to_string(),
+                direction: Some(direction),
+                nulls: None,
+            })
+            .collect();
+        self.keyset = true;
+        self
+    }
+
+    /// Returns a `SELECT COUNT(*)` query over the same FROM, JOIN and WHERE
+    /// clauses, for computing the total number of pages.
+    ///
+    /// The select list, ORDER BY, LIMIT and OFFSET are dropped. A query with
+    /// GROUP BY, HAVING or DISTINCT returns one row per group or distinct
+    /// row, so it's counted as a whole instead:
+    /// `SELECT COUNT(*) FROM (<query>) AS t`.
+    pub fn count_query(&self) -> Self {
+        let mut query = self.clone();
+        query.order_by.clear();
+        query.limit = None;
+        query.offset = None;
+        if query.group_by.is_empty() && query.having.is_empty() && query.distinct.is_none() {
+            return query.count();
+        }
+        let mut count = Self::new().count();
+        count.alias = Some("t".to_string());
+        count.source = Some(Box::new(query.into()));
+        count
+    }
+
+    /// Combines this query with another using UNION, which removes duplicate rows.
+    pub fn union<R: Model>(self, other: SelectQuery<R>) -> CompoundQuery {
+        CompoundQuery::new(self.into(), SetOperation::Union, other.into())
+    }
+
+    /// Combines this query with another using UNION ALL, which keeps duplicate rows.
+    pub fn union_all<R: Model>(self, other: SelectQuery<R>) -> CompoundQuery {
+        CompoundQuery::new(self.into(), SetOperation::UnionAll, other.into())
+    }
+
+    /// Builds the final SQL query string.
+    ///
+    /// The query is not validated; use `try_build` to catch invalid states.
+    pub fn build(&self) -> String {
+        let mut writer = SqlWriter::inline();
+        self.write_sql(&mut writer);
+        writer.sql
+    }
+
+    /// Validates the query and builds the final SQL query string.
+    pub fn try_build(&self) -> Result<String, QueryError> {
+        self.validate()?;
+        Ok(self.build())
+    }
+
+    /// Builds the SQL query string for the given dialect.
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        let mut writer = SqlWriter::inline().for_dialect(dialect);
+        self.write_sql(&mut writer);
+        writer.sql
+    }
+
+    /// Validates the query, including features the dialect lacks, and builds
+    /// the SQL query string for it.
+    pub fn try_build_for(&self, dialect: Dialect) -> Result<String, QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_for(dialect))
+    }
+
+    /// Builds the SQL query string with `?` placeholders, returning
+    /// the bound values in placeholder order.
+    pub fn build_parameterized(&self) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized();
+        self.write_sql(&mut writer);
+        writer.finish()
+    }
+
+    /// Builds the SQL query string with placeholders in the given format, a
+    /// `Dialect` or a `PlaceholderStyle`, returning the bound values in
+    /// placeholder order.
+    pub fn build_parameterized_for(&self, format: impl Into<ParamFormat>) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized_for(format.into());
+        self.write_sql(&mut writer);
+        writer.finish()
+    }
+
+    /// Validates the query as `try_build_for` does, and builds it with
+    /// placeholders for the dialect, returning the bound values in
+    /// placeholder order.
+    pub fn try_build_parameterized_for(
+        &self,
+        dialect: Dialect,
+    ) -> Result<(String, Vec<Value>), QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_parameterized_for(dialect))
+    }
+
+    fn validate_dialect(&self, dialect: Dialect) -> Result<(), QueryError> {
+        if let Some(source) = &self.source {
+            source.0.validate_dialect(dialect)?;
+        }
+        if matches!(self.distinct, Some(Distinct::On(_))) && dialect != Dialect::Postgres {
+            return Err(QueryError::Unsupported { feature: "DISTINCT ON", dialect });
+        }
+        if dialect == Dialect::MySql
+            && self.joins.iter().any(|join| join.join_type == JoinType::Full)
+        {
+            return Err(QueryError::Unsupported { feature: "FULL JOIN", dialect });
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), QueryError> {
+        if let Some(source) = &self.source {
+            source.0.validate()?;
+        }
+        if self.offset.is_some() && self.limit.is_none() {
+            return Err(QueryError::OffsetWithoutLimit);
+        }
+        if self.keyset && self.offset.is_some() {
+            return Err(QueryError::KeysetWithOffset);
+        }
+        // Unqualified columns may belong to a joined table.
+        if self.joins.is_empty() {
+            check_columns(&self.table, self.where_clause.columns())?;
+        }
+        Ok(())
+    }
+
+    fn write_sql(&self, writer: &mut SqlWriter) {
+        // SELECT clause
+        writer.push_str("SELECT");
+        match &self.distinct {
+            Some(Distinct::Rows) => writer.push_str(" DISTINCT"),
+            Some(Distinct::On(columns)) => {
+                let columns = writer.idents(columns);
+                writer.push_str(&format!(" DISTINCT ON ({})", columns))
+            }
+            None => {}
+        }
+        if self.selected_columns.is_empty() {
+            writer.push_str(" *");
+        } else {
+            let items: Vec<String> = self
+                .selected_columns
+                .iter()
+                .map(|item| match &item.alias {
+                    Some(alias) => {
+                        format!("{} AS {}", writer.ident(&item.expr), writer.alias(alias))
+                    }
+                    None => writer.ident(&item.expr),
+                })
+                .collect();
+            writer.push_str(" ");
+            writer.push_str(&items.join(", "));
+        }
+
+        // FROM clause
+        writer.push_str(" FROM ");
+        match &self.source {
+            Some(source) => {
+                writer.push_str("(");
+                source.0.write_sql(writer);
+                writer.push_str(")");
+            }
+            None => writer.push_ident(&self.table.qualified_name()),
+        }
+        if let Some(alias) = &self.alias {
+            writer.push_str(" AS ");
+            let alias = writer.alias(alias);
+            writer.push_str(&alias);
+        }
+
+        // JOIN clauses
+        for join in &self.joins {
+            writer.push_str(&format!(" {} ", join.join_type.as_sql()));
+            writer.push_ident(&join.table);
+            if let Some(alias) = &join.alias {
+                writer.push_str(" AS ");
+                let alias = writer.alias(alias);
+                writer.push_str(&alias);
+            }
+            if let Some(on) = &join.on {
+                writer.push_str(" ON ");
+                writer.push_expr(on);
+            }
+        }
+
+        // WHERE clause
+        writer.push_where(&self.where_clause);
+
+        // GROUP BY clause
+        if !self.group_by.is_empty() {
+            let columns = writer.idents(&self.group_by);
+            writer.push_str(&format!(" GROUP BY {}", columns));
+        }
+
+        // HAVING clause
+        writer.push_conditions(" HAVING ", &self.having);
+
+        // ORDER BY clause
+        writer.push_order_by(&self.order_by);
+
+        // LIMIT and OFFSET clauses
+        writer.push_limit_offset(self.limit, self.offset);
+    }
+}
+
+impl<T: Model + FromRow> SelectQuery<T> {
+    /// Runs the query on the connection, returning every row as a `T`.
+    ///
+    /// The query is validated and built with placeholders for the
+    /// connection's dialect, its values bound as parameters.
+    pub fn fetch_all<C: Connection>(&self, connection: &mut C) -> Result<Vec<T>, OrmError> {
+        let (sql, params) = self.try_build_parameterized_for(connection.dialect())?;
+        decode_all(connection.query(&sql, &params)?)
+    }
+
+    /// Runs the query on the connection, returning its first row as a `T`,
+    /// or `FetchError::NoRows` if it returned none.
+    ///
+    /// The query is limited to one row unless it has a limit already.
+    pub fn fetch_one<C: Connection>(&self, connection: &mut C) -> Result<T, OrmError> {
+        let (sql, params) = self.limited(1).try_build_parameterized_for(connection.dialect())?;
+        decode_one(connection.query(&sql, &params)?)
+    }
+
+    /// Runs the query on the connection, returning its row as a `T`, `None`
+    /// if it returned none, or `FetchError::TooManyRows` if it returned several.
+    ///
+    /// The query is limited to two rows unless it has a limit already, which
+    /// is enough to tell one row from several.
+    pub fn fetch_optional<C: Connection>(&self, connection: &mut C) -> Result<Option<T>, OrmError> {
+        let (sql, params) = self.limited(2).try_build_parameterized_for(connection.dialect())?;
+        decode_optional(connection.query(&sql, &params)?)
+    }
+
+    /// Runs the query on the asynchronous connection, as `fetch_all` does.
+    pub async fn fetch_all_async<C: AsyncConnection>(
+        &self,
+        connection: &mut C,
+    ) -> Result<Vec<T>, OrmError> {
+        let (sql, params) = self.try_build_parameterized_for(connection.dialect())?;
+        decode_all(connection.query(&sql, &params).await?)
+    }
+
+    /// Runs the query on the asynchronous connection, as `fetch_one` does.
+    pub async fn fetch_one_async<C: AsyncConnection>(
+        &self,
+        connection: &mut C,
+    ) -> Result<T, OrmError> {
+        let (sql, params) = self.limited(1).try_build_parameterized_for(connection.dialect())?;
+        decode_one(connection.query(&sql, &params).await?)
+    }
+
+    /// Runs the query on the asynchronous connection, as `fetch_optional` does.
+    pub async fn fetch_optional_async<C: AsyncConnection>(
+        &self,
+        connection: &mut C,
+    ) -> Result<Option<T>, OrmError> {
+        let (sql, params) = self.limited(2).try_build_parameterized_for(connection.dialect())?;
+        decode_optional(connection.query(&sql, &params).await?)
+    }
+
+    /// Returns the query with the given limit, unless it has one already.
+    fn limited(&self, limit: usize) -> Cow<'_, Self> {
+        match self.limit {
+            Some(_) => Cow::Borrowed(self),
+            None => Cow::Owned(self.clone().limit(limit)),
+        }
+    }
+}
+
+fn decode_all<T: FromRow>(rows: Vec<Row>) -> Result<Vec<T>, OrmError> {
+    Ok(rows.iter().map(T::from_row).collect::<Result<_, _>>()?)
+}
+
+fn decode_one<T: FromRow>(rows: Vec<Row>) -> Result<T, OrmError> {
+    let row = rows.first().ok_or(FetchError::NoRows)?;
+    Ok(T::from_row(row)?)
+}
+
+fn decode_optional<T: FromRow>(rows: Vec<Row>) -> Result<Option<T>, OrmError> {
+    match rows.as_slice() {
+        [] => Ok(None),
+        [row] => Ok(Some(T::from_row(row)?)),
+        _ => Err(FetchError::TooManyRows.into()),
+    }
+}
+
+/// A set operation combining the results of two SELECT queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetOperation {
+    Union,
+    UnionAll,
+}
+
+impl SetOperation {
+    fn as_sql(self) -> &'static str {
+        match self {
+            SetOperation::Union => " UNION ",
+            SetOperation::UnionAll => " UNION ALL ",
+        }
+    }
+}
+
+/// Represents SELECT queries combined with UNION or UNION ALL, with an
+/// optional ORDER BY, LIMIT and OFFSET applied to the combined result.
+#[derive(Debug, Clone)]
+pub struct CompoundQuery {
+    first: Subquery,
+    rest: Vec<(SetOperation, Subquery)>,
+    order_by: Vec<OrderItem>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+impl CompoundQuery {
+    fn new(first: Subquery, operation: SetOperation, second: Subquery) -> Self {
+        CompoundQuery {
+            first,
+            rest: vec![(operation, second)],
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Appends another query with UNION, which removes duplicate rows.
+    pub fn union<R: Model>(mut self, other: SelectQuery<R>) -> Self {
+        self.rest.push((SetOperation::Union, other.into()));
+        self
+    }
+
+    /// Appends another query with UNION ALL, which keeps duplicate rows.
+    pub fn union_all<R: Model>(mut self, other: SelectQuery<R>) -> Self {
+        self.rest.push((SetOperation::UnionAll, other.into()));
+        self
+    }
+
+    /// Adds columns to the ORDER BY clause of the combined result.
+    pub fn order_by(mut self, columns: &[&str]) -> Self {
+        self.order_by.extend(columns.iter().map(|s| OrderItem {
+            column: s.to_string(),
+            direction: None,
+            nulls: None,
+        }));
+        self
+    }
+
+    /// Adds `(column, direction)` pairs to the ORDER BY clause of the combined result.
+    pub fn order_by_dir(mut self, columns: &[(&str, Order)]) -> Self {
+        self.order_by.extend(columns.iter().map(|(column, direction)| OrderItem {
+            column: column.to_string(),
+            direction: Some(*direction),
+            nulls: None,
+        }));
+        self
+    }
+
+    /// Limits the number of rows of the combined result.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skips rows of the combined result.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Builds the final SQL query string.
+    pub fn build(&self) -> String {
+        let mut writer = SqlWriter::inline();
+        self.write_sql(&mut writer);
+        writer.sql
+    }
+
+    /// Builds the SQL query string for the given dialect.
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        let mut writer = SqlWriter::inline().for_dialect(dialect);
+        self.write_sql(&mut writer);
+        writer.sql
+    }
+
+    /// Builds the SQL query string with `?` placeholders, returning
+    /// the bound values of all combined queries in placeholder order.
+    pub fn build_parameterized(&self) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized();
+        self.write_sql(&mut writer);
+        writer.finish()
+    }
+
+    /// Builds the SQL query string with placeholders in the given format,
+    /// returning the bound values of all combined queries in placeholder order.
+    pub fn build_parameterized_for(&self, format: impl Into<ParamFormat>) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized_for(format.into());
+        self.write_sql(&mut writer);
+        writer.finish()
+    }
+
+    fn write_sql(&self, writer: &mut SqlWriter) {
+        // SQLite rejects parenthesized members of a compound SELECT.
+        let (open, close) =
+            if writer.dialect == Some(Dialect::Sqlite) { ("", "") } else { ("(", ")") };
+        writer.push_str(open);
+        self.first.0.write_sql(writer);
+        writer.push_str(close);
+        for (operation, query) in &self.rest {
+            writer.push_str(operation.as_sql());
+            writer.push_str(open);
+            query.0.write_sql(writer);
+            writer.push_str(close);
+        }
+
+        writer.push_order_by(&self.order_by);
+        writer.push_limit_offset(self.limit, self.offset);
+    }
+}
+
+/// What an INSERT does when it conflicts with an existing row.
+#[derive(Debug, Clone)]
+enum OnConflict {
+    /// Postgres/SQLite `ON CONFLICT (target) DO NOTHING`.
+    DoNothing { target: Vec<String> },
+    /// Postgres/SQLite `ON CONFLICT (target) DO UPDATE SET ...`.
+    Update { target: Vec<String>, assignments: Vec<(String, String)> },
+    /// MySQL `ON DUPLICATE KEY UPDATE ...`.
+    DuplicateKeyUpdate { assignments: Vec<(String, String)> },
+}
+
+/// Represents a SQL INSERT query.
+pub struct InsertQuery<T: Model> {
+    table: Cow<'static, Table>,
+    columns: Vec<String>,
+    rows: Vec<Vec<Operand>>,
+    select: Option<Subquery>,
+    on_conflict: Option<OnConflict>,
+    returning: Vec<String>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Model> Clone for InsertQuery<T> {
+    fn clone(&self) -> Self {
+        InsertQuery {
+            table: self.table.clone(),
+            columns: self.columns.clone(),
+            rows: self.rows.clone(),
+            select: self.select.clone(),
+            on_conflict: self.on_conflict.clone(),
+            returning: self.returning.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Model> fmt::Debug for InsertQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InsertQuery")
+            .field("table", &self.table)
+            .field("columns", &self.columns)
+            .field("rows", &self.rows)
+            .field("select", &self.select)
+            .field("on_conflict", &self.on_conflict)
+            .field("returning", &self.returning)
+            .finish()
+    }
+}
+
+impl<T: Model> InsertQuery<T> {
+    /// Creates a new InsertQuery for the given model.
+    pub fn new() -> Self {
+        InsertQuery {
+            table: T::table_ref(),
+            columns: Vec::new(),
+            rows: Vec::new(),
+            select: None,
+            on_conflict: None,
+            returning: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates an InsertQuery with a row holding the field values of a model instance.
+    ///
+    /// Auto-increment columns are left out so the database generates them;
+    /// use `from_model_with_keys` to insert the model's own keys.
+    pub fn from_model(model: &T) -> Self
+    where
+        T: IntoInsert,
+    {
+        Self::model_row(model, false)
+    }
+
+    /// Creates an InsertQuery with a row holding every field value of a model
+    /// instance, auto-increment keys included, in field order.
+    ///
+    /// On PostgreSQL the statement overrides the generated identity values.
+    pub fn from_model_with_keys(model: &T) -> Self
+    where
+        T: IntoInsert,
+    {
+        Self::model_row(model, true)
+    }
+
+    fn model_row(model: &T, with_keys: bool) -> Self
+    where
+        T: IntoInsert,
+    {
+        let mut query = Self::new();
+        for (column, value) in model.insert_values() {
+            if with_keys || !query.is_auto_increment(&column) {
+                query = query.push_value(&column, Operand::Value(value));
+            }
+        }
+        query
+    }
+
+    fn is_auto_increment(&self, column: &str) -> bool {
+        self.table.column(column).is_some_and(|col| col.is_auto_increment)
+    }
+
+    /// Adds a column-value pair to the (single) row of the INSERT statement.
+    pub fn value(self, column: &str, value: impl ToValue) -> Self {
+        self.push_value(column, Operand::Value(value.to_value()))
+    }
+
+    /// Adds a column with a pre-formatted SQL fragment, emitted without quoting.
+    pub fn value_raw(self, column: &str, sql: &str) -> Self {
+        self.push_value(column, Operand::Raw(sql.to_string()))
+    }
+
+    fn push_value(mut self, column: &str, operand: Operand) -> Self {
+        self.columns.push(column.to_string());
+        match self.rows.first_mut() {
+            Some(row) => row.push(operand),
+            None => self.rows.push(vec![operand]),
+        }
+        self
+    }
+
+    /// Declares the columns of the rows added with `row`.
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns.extend(columns.iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Adds a row of values, in the order of the declared columns.
+    ///
+    /// Rows of mixed types can be passed as `&[&dyn ToValue]`.
+    pub fn row<V: ToValue>(mut self, values: &[V]) -> Self {
+        self.rows.push(values.iter().map(|v| Operand::Value(v.to_value())).collect());
+        self
+    }
+
+    /// Inserts the rows returned by a SELECT query into the given columns
+    /// (`INSERT INTO table (columns) SELECT ...`) instead of VALUES rows.
+    pub fn from_select<R: Model>(mut self, columns: &[&str], select: SelectQuery<R>) -> Self {
+        self.columns.extend(to_strings(columns));
+        self.select = Some(select.into());
+        self
+    }
+
+    /// Ignores rows that conflict on the given columns
+    /// (`ON CONFLICT (target) DO NOTHING`).
+    ///
+    /// An empty target defaults to the primary key columns of the model.
+    pub fn on_conflict_do_nothing(mut self, target: &[&str]) -> Self {
+        self.on_conflict = Some(OnConflict::DoNothing { target: to_strings(target) });
+        self
+    }
+
+    /// Updates rows that conflict on the given columns
+    /// (`ON CONFLICT (target) DO UPDATE SET column = expr, ...`).
+    ///
+    /// The expressions are emitted as is, e.g. `("email", "excluded.email")`.
+    /// An empty target defaults to the primary key columns of the model.
+    pub fn on_conflict_update(mut self, target: &[&str], assignments: &[(&str, &str)]) -> Self {
+        self.on_conflict = Some(OnConflict::Update {
+            target: to_strings(target),
+            assignments: to_string_pairs(assignments),
+        });
+        self
+    }
+
+    /// Updates rows that conflict on any unique key, using the MySQL syntax
+    /// (`ON DUPLICATE KEY UPDATE column = expr, ...`).
+    ///
+    /// The expressions are emitted as is, e.g. `("email", "VALUES(email)")`.
+    pub fn on_duplicate_key_update(mut self, assignments: &[(&str, &str)]) -> Self {
+        self.on_conflict =
+            Some(OnConflict::DuplicateKeyUpdate { assignments: to_string_pairs(assignments) });
+        self
+    }
+
+    /// Returns the conflict target, falling back to the primary key columns.
+    fn conflict_target<'a>(&'a self, target: &'a [String]) -> Vec<&'a str> {
+        if target.is_empty() {
+            self.table.primary_key().into_iter().map(|column| column.name.as_str()).collect()
+        } else {
+            target.iter().map(|s| s.as_str()).collect()
+        }
+    }
+
+    /// Adds a RETURNING clause with the given columns.
+    pub fn returning(mut self, columns: &[&str]) -> Self {
+        self.returning.extend(to_strings(columns));
+        self
+    }
+
+    /// Adds a `RETURNING *` clause.
+    pub fn returning_all(mut self) -> Self {
+        self.returning = vec!["*".to_string()];
+        self
+    }
+
+    /// Validates the query and builds the final SQL query string.
+    pub fn try_build(&self) -> Result<String, QueryError> {
+        self.validate()?;
+        Ok(self.build())
+    }
+
+    fn validate(&self) -> Result<(), QueryError> {
+        if let Some(OnConflict::DoNothing { target } | OnConflict::Update { target, .. }) =
+            &self.on_conflict
+        {
+            if self.conflict_target(target).is_empty() {
+                return Err(QueryError::MissingPrimaryKey { table: self.table.name.clone() });
+            }
+        }
+        if let Some(select) = &self.select {
+            if !self.rows.is_empty() {
+                return Err(QueryError::MixedInsertSource);
+            }
+            let selected = select.0.selected_columns.len();
+            if selected > 0 && selected != self.columns.len() {
+                return Err(QueryError::SelectColumnCount {
+                    expected: self.columns.len(),
+                    found: selected,
+                });
+            }
+        } else if self.columns.is_empty() || self.rows.is_empty() {
+            return Err(QueryError::NoValues { table: self.table.name.clone() });
+        }
+        for (index, row) in self.rows.iter().enumerate() {
+            if row.len() != self.columns.len() {
+                return Err(QueryError::RowLength {
+                    row: index,
+                    expected: self.columns.len(),
+                    found: row.len(),
+                });
+            }
+        }
+        check_columns(&self.table, self.columns.iter().map(|column| column.as_str()))
+    }
+
+    /// Builds the final SQL query string.
+    ///
+    /// The query is not validated; use `try_build` to catch invalid states.
+    pub fn build(&self) -> String {
+        let mut writer = SqlWriter::inline();
+        self.write_sql(&mut writer, &self.rows);
+        writer.sql
+    }
+
+    /// Builds the final SQL query string for the given dialect.
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        let mut writer = SqlWriter::inline().for_dialect(dialect);
+        self.write_sql(&mut writer, &self.rows);
+        writer.sql
+    }
+
+    /// Builds the SQL query string with `?` placeholders, returning
+    /// the bound values in placeholder order.
+    pub fn build_parameterized(&self) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized();
+        self.write_sql(&mut writer, &self.rows);
+        writer.finish()
+    }
+
+    /// Builds the SQL query string with placeholders in the given format, a
+    /// `Dialect` or a `PlaceholderStyle`, returning the bound values in
+    /// placeholder order.
+    pub fn build_parameterized_for(&self, format: impl Into<ParamFormat>) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized_for(format.into());
+        self.write_sql(&mut writer, &self.rows);
+        writer.finish()
+    }
+
+    /// Validates the query, including features the dialect lacks, and builds
+    /// the SQL query string for it.
+    pub fn try_build_for(&self, dialect: Dialect) -> Result<String, QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_for(dialect))
+    }
+
+    /// Validates the query as `try_build_for` does, and builds it with
+    /// placeholders for the dialect, returning the bound values in
+    /// placeholder order.
+    pub fn try_build_parameterized_for(
+        &self,
+        dialect: Dialect,
+    ) -> Result<(String, Vec<Value>), QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_parameterized_for(dialect))
+    }
+
+    fn validate_dialect(&self, dialect: Dialect) -> Result<(), QueryError> {
+        let feature = match (&self.on_conflict, dialect) {
+            (Some(OnConflict::DoNothing { .. } | OnConflict::Update { .. }), Dialect::MySql) => {
+                Some("ON CONFLICT")
+            }
+            (Some(OnConflict::DuplicateKeyUpdate { .. }), Dialect::Postgres | Dialect::Sqlite) => {
+                Some("ON DUPLICATE KEY UPDATE")
+            }
+            _ if !self.returning.is_empty() && dialect == Dialect::MySql => Some("RETURNING"),
+            _ => None,
+        };
+        match feature {
+            Some(feature) => Err(QueryError::Unsupported { feature, dialect }),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates the query and builds one INSERT statement per chunk of at
+    /// most `chunk_size` rows, for servers that cap the size of a single
+    /// statement.
+    ///
+    /// An INSERT from a SELECT has no rows to split and builds one statement.
+    pub fn rows_chunked(&self, chunk_size: usize) -> Result<Vec<String>, QueryError> {
+        self.validate()?;
+        Ok(self
+            .write_chunks(chunk_size, SqlWriter::inline)
+            .into_iter()
+            .map(|writer| writer.sql)
+            .collect())
+    }
+
+    /// Validates the query as `try_build_for` does, and builds one INSERT
+    /// statement for the dialect per chunk of at most `chunk_size` rows.
+    pub fn rows_chunked_for(
+        &self,
+        chunk_size: usize,
+        dialect: Dialect,
+    ) -> Result<Vec<String>, QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        let writer = || SqlWriter::inline().for_dialect(dialect);
+        Ok(self.write_chunks(chunk_size, writer).into_iter().map(|writer| writer.sql).collect())
+    }
+
+    /// Validates the query as `try_build_for` does, and builds one INSERT
+    /// statement with placeholders for the dialect per chunk of at most
+    /// `chunk_size` rows, each returned with its bound values in placeholder
+    /// order.
+    ///
+    /// The placeholders of each statement are numbered from the first.
+    pub fn rows_chunked_parameterized_for(
+        &self,
+        chunk_size: usize,
+        dialect: Dialect,
+    ) -> Result<Vec<(String, Vec<Value>)>, QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        let writer = || SqlWriter::parameterized_for(dialect.into());
+        Ok(self.write_chunks(chunk_size, writer).into_iter().map(SqlWriter::finish).collect())
+    }
+
+    /// Writes one statement per chunk of at most `chunk_size` rows with a
+    /// writer of its own.
+    fn write_chunks(&self, chunk_size: usize, writer: impl Fn() -> SqlWriter) -> Vec<SqlWriter> {
+        let chunks: Vec<&[Vec<Operand>]> = match self.select {
+            Some(_) => vec![&self.rows],
+            None => self.rows.chunks(chunk_size.max(1)).collect(),
+        };
+        chunks
+            .into_iter()
+            .map(|rows| {
+                let mut writer = writer();
+                self.write_sql(&mut writer, rows);
+                writer
+            })
+            .collect()
+    }
+
+    fn write_sql(&self, writer: &mut SqlWriter, rows: &[Vec<Operand>]) {
+        let columns = writer.idents(&self.columns);
+        writer.push_str("INSERT INTO ");
+        writer.push_ident(&self.table.qualified_name());
+        writer.push_str(&format!(" ({})", columns));
+        // Identity columns are GENERATED ALWAYS and reject explicit values otherwise.
+        if writer.dialect == Some(Dialect::Postgres)
+            && self.columns.iter().any(|column| self.is_auto_increment(column))
+        {
+            writer.push_str(" OVERRIDING SYSTEM VALUE");
+        }
+        if let Some(select) = &self.select {
+            writer.push_str(" ");
+            select.0.write_sql(writer);
+        } else {
+            writer.push_str(" VALUES ");
+        }
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                writer.push_str(", ");
+            }
+            writer.push_str("(");
+            for (j, operand) in row.iter().enumerate() {
+                if j > 0 {
+                    writer.push_str(", ");
+                }
+                writer.push_operand(operand);
+            }
+            writer.push_str(")");
+        }
+
+        match &self.on_conflict {
+            Some(OnConflict::DoNothing { target }) => {
+                writer.push_str(" ON CONFLICT");
+                self.push_conflict_target(writer, target);
+                writer.push_str(" DO NOTHING");
+            }
+            Some(OnConflict::Update { target, assignments }) => {
+                writer.push_str(" ON CONFLICT");
+                self.push_conflict_target(writer, target);
+                writer.push_str(" DO UPDATE SET ");
+                writer.push_assignments(assignments);
+            }
+            Some(OnConflict::DuplicateKeyUpdate { assignments }) => {
+                writer.push_str(" ON DUPLICATE KEY UPDATE ");
+                writer.push_assignments(assignments);
+            }
+            None => {}
+        }
+
+        writer.push_returning(&self.returning);
+        writer.push_str(";");
+    }
+
+    fn push_conflict_target(&self, writer: &mut SqlWriter, target: &[String]) {
+        let target = self.conflict_target(target);
+        if !target.is_empty() {
+            let target: Vec<String> = target.iter().map(|column| writer.ident(column)).collect();
+            writer.push_str(&format!(" ({})", target.join(", ")));
+        }
+    }
+}
+
+impl<T: Model> Default for InsertQuery<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Represents a SQL UPDATE query.
+pub struct UpdateQuery<T: Model> {
+    table: Cow<'static, Table>,
+    set_clauses: Vec<(String, Operand)>,
+    where_clause: Conditions,
+    returning: Vec<String>,
+    /// Set by `from_model`, whose WHERE clause relies on the primary key.
+    keyed_by_model: bool,
+    all_rows: bool,
     _marker: PhantomData<T>,
 }
 
-impl<T: Model> SelectQuery<T> {
-    /// Creates a new SelectQuery for the given model.
+impl<T: Model> Clone for UpdateQuery<T> {
+    fn clone(&self) -> Self {
+        UpdateQuery {
+            table: self.table.clone(),
+            set_clauses: self.set_clauses.clone(),
+            where_clause: self.where_clause.clone(),
+            returning: self.returning.clone(),
+            keyed_by_model: self.keyed_by_model,
+            all_rows: self.all_rows,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Model> fmt::Debug for UpdateQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpdateQuery")
+            .field("table", &self.table)
+            .field("set_clauses", &self.set_clauses)
+            .field("where_clause", &self.where_clause)
+            .field("returning", &self.returning)
+            .field("keyed_by_model", &self.keyed_by_model)
+            .field("all_rows", &self.all_rows)
+            .finish()
+    }
+}
+
+impl<T: Model> UpdateQuery<T> {
+    /// Creates a new UpdateQuery for the given model.
     pub fn new() -> Self {
-        SelectQuery {
-            table: T::table(),
-            selected_columns: Vec::new(),
-            where_clause: None,
-            joins: Vec::new(),
-            order_by: Vec::new(),
-            limit: None,
-            offset: None,
+        UpdateQuery {
+            table: T::table_ref(),
+            set_clauses: Vec::new(),
+            where_clause: Conditions::default(),
+            returning: Vec::new(),
+            keyed_by_model: false,
+            all_rows: false,
             _marker: PhantomData,
         }
     }
 
-    /// Specifies the columns to select.
-    pub fn select(mut self, columns: &[&str]) -> Self {
-        self.selected_columns = columns.iter().map(|s| s.to_string()).collect();
+    /// Creates an UpdateQuery setting every non-primary-key column from a model
+    /// instance, filtered on its primary key column(s).
+    ///
+    /// `try_build` fails with `MissingPrimaryKey` if the table has no primary key.
+    pub fn from_model(model: &T) -> Self
+    where
+        T: IntoInsert,
+    {
+        let mut query = Self::new();
+        query.keyed_by_model = true;
+        for (column, value) in model.insert_values() {
+            let is_primary_key = query.table.column(&column).is_some_and(|c| c.is_primary_key);
+            if is_primary_key {
+                query.where_clause.and(Expr::col(&column).eq(value));
+            } else {
+                query.set_clauses.push((column, Operand::Value(value)));
+            }
+        }
+        query
+    }
+
+    /// Restricts the SET clauses added so far to the given columns.
+    pub fn only(mut self, columns: &[&str]) -> Self {
+        self.set_clauses.retain(|(column, _)| columns.contains(&column.as_str()));
+        self
+    }
+
+    /// Adds a SET clause.
+    pub fn set(mut self, column: &str, value: impl ToValue) -> Self {
+        self.set_clauses.push((column.to_string(), Operand::Value(value.to_value())));
+        self
+    }
+
+    /// Adds a `column = NULL` SET clause.
+    ///
+    /// Passing `Value::Null` or `None` to `set` has the same effect.
+    pub fn set_null(self, column: &str) -> Self {
+        self.set(column, Value::Null)
+    }
+
+    /// Adds a SET clause whose right-hand side is a SQL expression emitted
+    /// verbatim, such as `NOW()` or `counter * 2`.
+    pub fn set_expr(mut self, column: &str, expr: &str) -> Self {
+        self.set_clauses.push((column.to_string(), Operand::Raw(expr.to_string())));
+        self
+    }
+
+    /// Adds a `column = column + amount` SET clause.
+    pub fn increment(self, column: &str, amount: impl ToValue) -> Self {
+        self.offset(column, '+', amount.to_value())
+    }
+
+    /// Adds a `column = column - amount` SET clause.
+    pub fn decrement(self, column: &str, amount: impl ToValue) -> Self {
+        self.offset(column, '-', amount.to_value())
+    }
+
+    fn offset(mut self, column: &str, op: char, amount: Value) -> Self {
+        let operand = Operand::Offset { column: column.to_string(), op, amount };
+        self.set_clauses.push((column.to_string(), operand));
         self
     }
 
-    /// Adds a WHERE clause.
+    /// Adds a WHERE condition, combined with previous conditions using AND.
     pub fn filter(mut self, condition: &str) -> Self {
-        self.where_clause = Some(condition.to_string());
+        self.where_clause.and(Expr::raw(condition));
         self
     }
 
-    /// Adds an ORDER BY clause.
-    pub fn order_by(mut self, columns: &[&str]) -> Self {
-        self.order_by = columns.iter().map(|s| s.to_string()).collect();
+    /// Adds a WHERE condition, combined with previous conditions using OR.
+    pub fn or_filter(mut self, condition: &str) -> Self {
+        self.where_clause.or(Expr::raw(condition));
         self
     }
 
-    /// Adds a LIMIT clause.
-    pub fn limit(mut self, limit: usize) -> Self {
-        self.limit = Some(limit);
+    /// Adds a WHERE condition built from an expression, combined using AND.
+    pub fn filter_expr(mut self, expr: Expr) -> Self {
+        self.where_clause.and(expr);
         self
     }
 
-    /// Adds an OFFSET clause.
-    pub fn offset(mut self, offset: usize) -> Self {
-        self.offset = Some(offset);
+    /// Adds a `column = value` WHERE condition, combined using AND.
+    pub fn filter_eq(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).eq(value.to_value()));
         self
     }
 
-    /// Builds the final SQL query string.
-    pub fn build(self) -> String {
-        let mut query = String::new();
+    /// Adds a `column <> value` WHERE condition, combined using AND.
+    pub fn filter_ne(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).ne(value.to_value()));
+        self
+    }
 
-        // SELECT clause
-        if self.selected_columns.is_empty() {
-            query.push_str("SELECT *");
-        } else {
-            query.push_str("SELECT ");
-            query.push_str(&self.selected_columns.join(", "));
-        }
+    /// Adds a `column > value` WHERE condition, combined using AND.
+    pub fn filter_gt(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).gt(value.to_value()));
+        self
+    }
 
-        // FROM clause
-        query.push_str(&format!(" FROM {}", self.table.name));
+    /// Adds a `column < value` WHERE condition, combined using AND.
+    pub fn filter_lt(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).lt(value.to_value()));
+        self
+    }
 
-        // WHERE clause
-        if let Some(where_clause) = self.where_clause {
-            query.push_str(&format!(" WHERE {}", where_clause));
-        }
+    /// Adds a `column >= value` WHERE condition, combined using AND.
+    pub fn filter_ge(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).ge(value.to_value()));
+        self
+    }
 
-        // ORDER BY clause
-        if !self.order_by.is_empty() {
-            query.push_str(&format!(" ORDER BY {}", self.order_by.join(", ")));
-        }
+    /// Adds a `column <= value` WHERE condition, combined using AND.
+    pub fn filter_le(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).le(value.to_value()));
+        self
+    }
+
+    /// Adds a `column IN (...)` WHERE condition, combined using AND.
+    pub fn filter_in<V: ToValue>(mut self, column: &str, values: &[V]) -> Self {
+        self.where_clause.and(Expr::col(column).in_list(values));
+        self
+    }
+
+    /// Adds a `column NOT IN (...)` WHERE condition, combined using AND.
+    pub fn filter_not_in<V: ToValue>(mut self, column: &str, values: &[V]) -> Self {
+        self.where_clause.and(Expr::col(column).not_in_list(values));
+        self
+    }
+
+    /// Allows the query to run without a WHERE clause, affecting every row.
+    ///
+    /// Without it, `try_build` rejects an UPDATE that has no conditions.
+    pub fn all_rows(mut self) -> Self {
+        self.all_rows = true;
+        self
+    }
+
+    /// Adds a RETURNING clause with the given columns.
+    pub fn returning(mut self, columns: &[&str]) -> Self {
+        self.returning.extend(to_strings(columns));
+        self
+    }
+
+    /// Adds a `RETURNING *` clause.
+    pub fn returning_all(mut self) -> Self {
+        self.returning = vec!["*".to_string()];
+        self
+    }
+
+    /// Validates the query and builds the final SQL query string.
+    pub fn try_build(&self) -> Result<String, QueryError> {
+        self.validate()?;
+        Ok(self.build())
+    }
 
-        // LIMIT clause
-        if let Some(limit) = self.limit {
-            query.push_str(&format!(" LIMIT {}", limit));
+    /// Validates the query, including features the dialect lacks, and builds
+    /// the SQL query string for it.
+    pub fn try_build_for(&self, dialect: Dialect) -> Result<String, QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_for(dialect))
+    }
+
+    /// Validates the query as `try_build_for` does, and builds it with
+    /// placeholders for the dialect, returning the bound values in
+    /// placeholder order.
+    pub fn try_build_parameterized_for(
+        &self,
+        dialect: Dialect,
+    ) -> Result<(String, Vec<Value>), QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_parameterized_for(dialect))
+    }
+
+    fn validate_dialect(&self, dialect: Dialect) -> Result<(), QueryError> {
+        if !self.returning.is_empty() && dialect == Dialect::MySql {
+            return Err(QueryError::Unsupported { feature: "RETURNING", dialect });
         }
+        Ok(())
+    }
 
-        // OFFSET clause
-        if let Some(offset) = self.offset {
-            query.push_str(&format!(" OFFSET {}", offset));
+    fn validate(&self) -> Result<(), QueryError> {
+        if self.keyed_by_model && self.table.primary_key().is_empty() {
+            return Err(QueryError::MissingPrimaryKey { table: self.table.name.clone() });
+        }
+        if self.set_clauses.is_empty() {
+            return Err(QueryError::EmptySetClause { table: self.table.name.clone() });
         }
+        if self.where_clause.is_empty() && !self.all_rows {
+            return Err(QueryError::MissingWhereClause { table: self.table.name.clone() });
+        }
+        check_columns(&self.table, self.set_clauses.iter().map(|(column, _)| column.as_str()))?;
+        check_columns(&self.table, self.where_clause.columns())
+    }
 
-        query
+    /// Builds the final SQL query string.
+    ///
+    /// The query is not validated; use `try_build` to catch invalid states.
+    /// A query without a WHERE clause is still built even if `all_rows` was
+    /// not called; this will be rejected in a future release.
+    pub fn build(&self) -> String {
+        let mut writer = SqlWriter::inline();
+        self.write_sql(&mut writer);
+        writer.sql
     }
-}
 
-/// Represents a SQL INSERT query.
-pub struct InsertQuery<T: Model> {
-    table: Table,
-    values: Vec<(String, String)>,
-    _marker: PhantomData<T>,
-}
+    /// Builds the final SQL query string for the given dialect.
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        let mut writer = SqlWriter::inline().for_dialect(dialect);
+        self.write_sql(&mut writer);
+        writer.sql
+    }
 
-impl<T: Model> InsertQuery<T> {
-    /// Creates a new InsertQuery for the given model.
-    pub fn new() -> Self {
-        InsertQuery { table: T::table(), values: Vec::new(), _marker: PhantomData }
+    /// Builds the SQL query string with `?` placeholders, returning
+    /// the bound values in placeholder order.
+    pub fn build_parameterized(&self) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized();
+        self.write_sql(&mut writer);
+        writer.finish()
     }
 
-    /// Adds a column-value pair to the INSERT statement.
-    pub fn value(mut self, column: &str, value: &str) -> Self {
-        self.values.push((column.to_string(), value.to_string()));
-        self
+    /// Builds the SQL query string with placeholders in the given format, a
+    /// `Dialect` or a `PlaceholderStyle`, returning the bound values in
+    /// placeholder order.
+    pub fn build_parameterized_for(&self, format: impl Into<ParamFormat>) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized_for(format.into());
+        self.write_sql(&mut writer);
+        writer.finish()
     }
 
-    /// Builds the final SQL query string.
-    pub fn build(self) -> String {
-        let columns: Vec<String> = self.values.iter().map(|(col, _)| col.clone()).collect();
-        let values: Vec<String> = self.values.iter().map(|(_, val)| format!("'{}'", val)).collect();
+    fn write_sql(&self, writer: &mut SqlWriter) {
+        writer.push_str("UPDATE ");
+        writer.push_ident(&self.table.qualified_name());
+        writer.push_str(" SET ");
+        for (i, (column, operand)) in self.set_clauses.iter().enumerate() {
+            if i > 0 {
+                writer.push_str(", ");
+            }
+            writer.push_ident(column);
+            writer.push_str(" = ");
+            writer.push_operand(operand);
+        }
 
-        format!(
-            "INSERT INTO {} ({}) VALUES ({});",
-            self.table.name,
-            columns.join(", "),
-            values.join(", ")
-        )
+        writer.push_where(&self.where_clause);
+        writer.push_returning(&self.returning);
     }
 }
 
-/// Represents a SQL UPDATE query.
-pub struct UpdateQuery<T: Model> {
-    table: Table,
-    set_clauses: Vec<(String, String)>,
-    where_clause: Option<String>,
+impl<T: Model> Default for UpdateQuery<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Represents a SQL DELETE query.
+pub struct DeleteQuery<T: Model> {
+    table: Cow<'static, Table>,
+    using: Vec<String>,
+    where_clause: Conditions,
+    returning: Vec<String>,
+    all_rows: bool,
+    /// Set by `from_model`, whose WHERE clause relies on the primary key.
+    keyed_by_model: bool,
     _marker: PhantomData<T>,
 }
 
-impl<T: Model> UpdateQuery<T> {
-    /// Creates a new UpdateQuery for the given model.
+impl<T: Model> Clone for DeleteQuery<T> {
+    fn clone(&self) -> Self {
+        DeleteQuery {
+            table: self.table.clone(),
+            using: self.using.clone(),
+            where_clause: self.where_clause.clone(),
+            returning: self.returning.clone(),
+            all_rows: self.all_rows,
+            keyed_by_model: self.keyed_by_model,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Model> fmt::Debug for DeleteQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeleteQuery")
+            .field("table", &self.table)
+            .field("using", &self.using)
+            .field("where_clause", &self.where_clause)
+            .field("returning", &self.returning)
+            .field("all_rows", &self.all_rows)
+            .field("keyed_by_model", &self.keyed_by_model)
+            .finish()
+    }
+}
+
+impl<T: Model> DeleteQuery<T> {
+    /// Creates a new DeleteQuery for the given model.
     pub fn new() -> Self {
-        UpdateQuery {
-            table: T::table(),
-            set_clauses: Vec::new(),
-            where_clause: None,
+        DeleteQuery {
+            table: T::table_ref(),
+            using: Vec::new(),
+            where_clause: Conditions::default(),
+            returning: Vec::new(),
+            all_rows: false,
+            keyed_by_model: false,
             _marker: PhantomData,
         }
     }
 
-    /// Adds a SET clause.
-    pub fn set(mut self, column: &str, value: &str) -> Self {
-        self.set_clauses.push((column.to_string(), value.to_string()));
+    /// Creates a DeleteQuery removing the row of a model instance, filtered on
+    /// its primary key column(s).
+    ///
+    /// `try_build` fails with `MissingPrimaryKey` if the table has no primary key.
+    pub fn from_model(model: &T) -> Self
+    where
+        T: IntoInsert,
+    {
+        let mut query = Self::new();
+        query.keyed_by_model = true;
+        for (column, value) in model.insert_values() {
+            if query.table.column(&column).is_some_and(|c| c.is_primary_key) {
+                query.where_clause.and(Expr::col(&column).eq(value));
+            }
+        }
+        query
+    }
+
+    /// Adds a table to a `USING` clause, joined to the deleted rows by the
+    /// given condition, which is combined with the filters using AND.
+    pub fn using(mut self, table: &str, condition: &str) -> Self {
+        self.using.push(table.to_string());
+        self.where_clause.and(Expr::raw(condition));
         self
     }
 
-    /// Adds a WHERE clause.
+    /// Adds the table of the given model to a `USING` clause.
+    pub fn using_model<U: Model>(self, condition: &str) -> Self {
+        self.using(&U::table_ref().qualified_name(), condition)
+    }
+
+    /// Adds a WHERE condition, combined with previous conditions using AND.
     pub fn filter(mut self, condition: &str) -> Self {
-        self.where_clause = Some(condition.to_string());
+        self.where_clause.and(Expr::raw(condition));
+        self
+    }
+
+    /// Adds a WHERE condition, combined with previous conditions using OR.
+    pub fn or_filter(mut self, condition: &str) -> Self {
+        self.where_clause.or(Expr::raw(condition));
+        self
+    }
+
+    /// Adds a WHERE condition built from an expression, combined using AND.
+    pub fn filter_expr(mut self, expr: Expr) -> Self {
+        self.where_clause.and(expr);
+        self
+    }
+
+    /// Adds a `column = value` WHERE condition, combined using AND.
+    pub fn filter_eq(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).eq(value.to_value()));
+        self
+    }
+
+    /// Adds a `column <> value` WHERE condition, combined using AND.
+    pub fn filter_ne(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).ne(value.to_value()));
+        self
+    }
+
+    /// Adds a `column > value` WHERE condition, combined using AND.
+    pub fn filter_gt(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).gt(value.to_value()));
+        self
+    }
+
+    /// Adds a `column < value` WHERE condition, combined using AND.
+    pub fn filter_lt(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).lt(value.to_value()));
+        self
+    }
+
+    /// Adds a `column >= value` WHERE condition, combined using AND.
+    pub fn filter_ge(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).ge(value.to_value()));
+        self
+    }
+
+    /// Adds a `column <= value` WHERE condition, combined using AND.
+    pub fn filter_le(mut self, column: &str, value: impl ToValue) -> Self {
+        self.where_clause.and(Expr::col(column).le(value.to_value()));
+        self
+    }
+
+    /// Adds a `column IN (...)` WHERE condition, combined using AND.
+    pub fn filter_in<V: ToValue>(mut self, column: &str, values: &[V]) -> Self {
+        self.where_clause.and(Expr::col(column).in_list(values));
+        self
+    }
+
+    /// Adds a `column NOT IN (...)` WHERE condition, combined using AND.
+    pub fn filter_not_in<V: ToValue>(mut self, column: &str, values: &[V]) -> Self {
+        self.where_clause.and(Expr::col(column).not_in_list(values));
         self
     }
 
+    /// Allows the query to run without a WHERE clause, affecting every row.
+    ///
+    /// Without it, `try_build` rejects a DELETE that has no conditions.
+    pub fn all_rows(mut self) -> Self {
+        self.all_rows = true;
+        self
+    }
+
+    /// Adds a RETURNING clause with the given columns.
+    pub fn returning(mut self, columns: &[&str]) -> Self {
+        self.returning.extend(to_strings(columns));
+        self
+    }
+
+    /// Adds a `RETURNING *` clause.
+    pub fn returning_all(mut self) -> Self {
+        self.returning = vec!["*".to_string()];
+        self
+    }
+
+    /// Validates the query and builds the final SQL query string.
+    pub fn try_build(&self) -> Result<String, QueryError> {
+        self.validate()?;
+        Ok(self.build())
+    }
+
+    /// Validates the query, including features the dialect lacks, and builds
+    /// the SQL query string for it.
+    pub fn try_build_for(&self, dialect: Dialect) -> Result<String, QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_for(dialect))
+    }
+
+    /// Validates the query as `try_build_for` does, and builds it with
+    /// placeholders for the dialect, returning the bound values in
+    /// placeholder order.
+    pub fn try_build_parameterized_for(
+        &self,
+        dialect: Dialect,
+    ) -> Result<(String, Vec<Value>), QueryError> {
+        self.validate()?;
+        self.validate_dialect(dialect)?;
+        Ok(self.build_parameterized_for(dialect))
+    }
+
+    fn validate_dialect(&self, dialect: Dialect) -> Result<(), QueryError> {
+        if !self.returning.is_empty() && dialect == Dialect::MySql {
+            return Err(QueryError::Unsupported { feature: "RETURNING", dialect });
+        }
+        if !self.using.is_empty() && dialect == Dialect::Sqlite {
+            return Err(QueryError::Unsupported { feature: "DELETE ... USING", dialect });
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), QueryError> {
+        if self.keyed_by_model && self.table.primary_key().is_empty() {
+            return Err(QueryError::MissingPrimaryKey { table: self.table.name.clone() });
+        }
+        if self.where_clause.is_empty() && !self.all_rows {
+            return Err(QueryError::MissingWhereClause { table: self.table.name.clone() });
+        }
+        check_columns(&self.table, self.where_clause.columns())
+    }
+
     /// Builds the final SQL query string.
-    pub fn build(self) -> String {
-        let set_clause: Vec<String> =
-            self.set_clauses.iter().map(|(col, val)| format!("{} = '{}'", col, val)).collect();
+    ///
+    /// The query is not validated; use `try_build` to catch invalid states.
+    /// A query without a WHERE clause is still built even if `all_rows` was
+    /// not called; this will be rejected in a future release.
+    pub fn build(&self) -> String {
+        let mut writer = SqlWriter::inline();
+        self.write_sql(&mut writer);
+        writer.sql
+    }
 
-        let mut query = format!("UPDATE {} SET {}", self.table.name, set_clause.join(", "));
+    /// Builds the final SQL query string for the given dialect.
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        let mut writer = SqlWriter::inline().for_dialect(dialect);
+        self.write_sql(&mut writer);
+        writer.sql
+    }
+
+    /// Builds the SQL query string with `?` placeholders, returning
+    /// the bound values in placeholder order.
+    pub fn build_parameterized(&self) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized();
+        self.write_sql(&mut writer);
+        writer.finish()
+    }
+
+    /// Builds the SQL query string with placeholders in the given format, a
+    /// `Dialect` or a `PlaceholderStyle`, returning the bound values in
+    /// placeholder order.
+    pub fn build_parameterized_for(&self, format: impl Into<ParamFormat>) -> (String, Vec<Value>) {
+        let mut writer = SqlWriter::parameterized_for(format.into());
+        self.write_sql(&mut writer);
+        writer.finish()
+    }
 
-        if let Some(where_clause) = self.where_clause {
-            query.push_str(&format!(" WHERE {}", where_clause));
+    fn write_sql(&self, writer: &mut SqlWriter) {
+        writer.push_str("DELETE FROM ");
+        writer.push_ident(&self.table.qualified_name());
+        if !self.using.is_empty() {
+            let mut using = self.using.clone();
+            // MySQL's multi-table form lists the target table in USING as well.
+            if writer.dialect == Some(Dialect::MySql) {
+                using.insert(0, self.table.qualified_name());
+            }
+            let using = writer.idents(&using);
+            writer.push_str(&format!(" USING {}", using));
         }
+        writer.push_where(&self.where_clause);
+        writer.push_returning(&self.returning);
+    }
+}
 
-        query
+impl<T: Model> Default for DeleteQuery<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-/// Represents a SQL DELETE query.
-pub struct DeleteQuery<T: Model> {
-    table: Table,
-    where_clause: Option<String>,
+/// Represents a SQL TRUNCATE TABLE query.
+pub struct TruncateQuery<T: Model> {
+    table: Cow<'static, Table>,
+    only: bool,
+    restart_identity: bool,
+    cascade: bool,
     _marker: PhantomData<T>,
 }
 
-impl<T: Model> DeleteQuery<T> {
-    /// Creates a new DeleteQuery for the given model.
+impl<T: Model> Clone for TruncateQuery<T> {
+    fn clone(&self) -> Self {
+        TruncateQuery {
+            table: self.table.clone(),
+            only: self.only,
+            restart_identity: self.restart_identity,
+            cascade: self.cascade,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Model> fmt::Debug for TruncateQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TruncateQuery")
+            .field("table", &self.table)
+            .field("only", &self.only)
+            .field("restart_identity", &self.restart_identity)
+            .field("cascade", &self.cascade)
+            .finish()
+    }
+}
+
+impl<T: Model> TruncateQuery<T> {
+    /// Creates a new TruncateQuery for the given model.
     pub fn new() -> Self {
-        DeleteQuery { table: T::table(), where_clause: None, _marker: PhantomData }
+        TruncateQuery {
+            table: T::table_ref(),
+            only: false,
+            restart_identity: false,
+            cascade: false,
+            _marker: PhantomData,
+        }
     }
 
-    /// Adds a WHERE clause.
-    pub fn filter(mut self, condition: &str) -> Self {
-        self.where_clause = Some(condition.to_string());
+    /// Truncates only the named table, not tables inheriting from it (`ONLY`).
+    pub fn only(mut self) -> Self {
+        self.only = true;
+        self
+    }
+
+    /// Resets sequences owned by the table's columns (`RESTART IDENTITY`).
+    pub fn restart_identity(mut self) -> Self {
+        self.restart_identity = true;
+        self
+    }
+
+    /// Also truncates tables with foreign keys referencing this one (`CASCADE`).
+    pub fn cascade(mut self) -> Self {
+        self.cascade = true;
         self
     }
 
     /// Builds the final SQL query string.
-    pub fn build(self) -> String {
-        let mut query = format!("DELETE FROM {}", self.table.name);
+    pub fn build(&self) -> String {
+        let mut sql = String::from("TRUNCATE TABLE ");
+        if self.only {
+            sql.push_str("ONLY ");
+        }
+        sql.push_str(&quote_path_if_needed(&self.table.qualified_name()));
+        if self.restart_identity {
+            sql.push_str(" RESTART IDENTITY");
+        }
+        if self.cascade {
+            sql.push_str(" CASCADE");
+        }
+        sql
+    }
+
+    /// Builds the final SQL query string; a truncate has no invalid states,
+    /// so this never fails.
+    pub fn try_build(&self) -> Result<String, QueryError> {
+        Ok(self.build())
+    }
 
-        if let Some(where_clause) = self.where_clause {
-            query.push_str(&format!(" WHERE {}", where_clause));
+    /// Builds the final SQL query string for the given dialect.
+    ///
+    /// SQLite has no TRUNCATE, so it falls back to `DELETE FROM table`.
+    /// MySQL's TRUNCATE always resets the auto-increment counter and has no
+    /// table inheritance, so `only` and `restart_identity` need no
+    /// translation there, but it cannot cascade: a cascade falls back to
+    /// `DELETE FROM table` as well, which removes the rows referencing the
+    /// table through foreign keys declared `ON DELETE CASCADE`.
+    ///
+    /// The DELETE fallbacks keep auto-increment counters, so
+    /// `restart_identity` has no effect with them.
+    pub fn build_for(&self, dialect: Dialect) -> String {
+        let table = dialect.quote_path(&self.table.qualified_name());
+        match dialect {
+            Dialect::Postgres => {
+                let mut sql = String::from("TRUNCATE TABLE ");
+                if self.only {
+                    sql.push_str("ONLY ");
+                }
+                sql.push_str(&table);
+                if self.restart_identity {
+                    sql.push_str(" RESTART IDENTITY");
+                }
+                if self.cascade {
+                    sql.push_str(" CASCADE");
+                }
+                sql
+            }
+            Dialect::MySql if !self.cascade => format!("TRUNCATE TABLE {}", table),
+            Dialect::MySql | Dialect::Sqlite => format!("DELETE FROM {}", table),
         }
+    }
 
-        query
+    /// Builds the SQL query string for the given dialect, as `build_for`
+    /// does; every dialect has a statement for each option.
+    pub fn try_build_for(&self, dialect: Dialect) -> Result<String, QueryError> {
+        Ok(self.build_for(dialect))
+    }
+}
+
+impl<T: Model> Default for TruncateQuery<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
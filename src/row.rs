@@ -0,0 +1,307 @@
+use crate::model::{ToValue, Value};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A result row: column names mapped to their values, in select order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Row {
+    columns: Vec<(String, Value)>,
+}
+
+impl Row {
+    /// Creates an empty row.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column to the row.
+    pub fn with(mut self, column: &str, value: impl ToValue) -> Self {
+        self.push(column, value.to_value());
+        self
+    }
+
+    /// Adds a column to the row.
+    pub fn push(&mut self, column: &str, value: Value) {
+        self.columns.push((column.to_string(), value));
+    }
+
+    /// Returns the value of a column, or the first of several with the same name.
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.columns.iter().find(|(name, _)| name == column).map(|(_, value)| value)
+    }
+
+    /// Reads a column as the given type.
+    pub fn try_get<T: FromValue>(&self, column: &str) -> Result<T, RowError> {
+        let value = self
+            .get(column)
+            .ok_or_else(|| RowError::MissingColumn { column: column.to_string() })?;
+        T::from_value(value).ok_or_else(|| RowError::TypeMismatch {
+            column: column.to_string(),
+            expected: std::any::type_name::<T>(),
+            found: value.clone(),
+        })
+    }
+
+    /// Returns the column names, in select order.
+    pub fn columns(&self) -> Vec<&str> {
+        self.columns.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Returns the number of columns.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns whether the row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+impl FromIterator<(String, Value)> for Row {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        Row { columns: iter.into_iter().collect() }
+    }
+}
+
+impl From<Vec<(String, Value)>> for Row {
+    fn from(columns: Vec<(String, Value)>) -> Self {
+        Row { columns }
+    }
+}
+
+/// Errors reported when a row cannot be mapped into a Rust value.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RowError {
+    #[error("row has no column `{column}`")]
+    MissingColumn { column: String },
+    #[error("column `{column}` holds {found:?}, which cannot be read as `{expected}`")]
+    TypeMismatch { column: String, expected: &'static str, found: Value },
+}
+
+/// Trait for Rust types that can be read from a SQL value.
+///
+/// The counterpart of `ToValue`: anything it binds reads back, including
+/// integers bound as text because they don't fit a BIGINT.
+pub trait FromValue: Sized {
+    /// Converts the value, or returns `None` if it has an incompatible type
+    /// or is out of range.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+/// Trait for types that can be built from a result row, implemented with
+/// `#[derive(FromRow)]`.
+pub trait FromRow: Sized {
+    /// Builds a value from the columns of the row.
+    fn from_row(row: &Row) -> Result<Self, RowError>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+/// Reads an integer value, converting it to a narrower type if it's in range.
+fn integer<T: TryFrom<i64> + FromStr>(value: &Value) -> Option<T> {
+    match value {
+        Value::Integer(i) => T::try_from(i64::from(*i)).ok(),
+        Value::BigInt(i) => T::try_from(*i).ok(),
+        Value::Text(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+impl FromValue for i8 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for i16 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for i32 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for isize {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for u8 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for u16 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for u32 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for u64 {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for usize {
+    fn from_value(value: &Value) -> Option<Self> {
+        integer(value)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Float(f) => Some(*f),
+            Value::Integer(i) => Some(f64::from(*i)),
+            Value::BigInt(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: &Value) -> Option<Self> {
+        f64::from_value(value).map(|f| f as f32)
+    }
+}
+
+/// Booleans also read from 0 and 1, as stored by databases without a boolean type.
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            Value::Integer(0) | Value::BigInt(0) => Some(false),
+            Value::Integer(1) | Value::BigInt(1) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bytes(bytes) => Some(bytes.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// JSON documents are parsed from their text.
+impl FromValue for serde_json::Value {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => serde_json::from_str(s).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// `NULL` reads as `None`; anything else must convert to `T`.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+/// Dates and times are parsed from ISO 8601 text.
+#[cfg(feature = "chrono")]
+impl FromValue for chrono::NaiveDate {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromValue for chrono::NaiveTime {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromValue for chrono::NaiveDateTime {
+    fn from_value(value: &Value) -> Option<Self> {
+        // Bound with a space between date and time, but databases may use a `T`
+        match value {
+            Value::Text(s) => chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .or_else(|| s.parse().ok()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromValue for chrono::DateTime<chrono::Utc> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => chrono::DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|datetime| datetime.with_timezone(&chrono::Utc)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromValue for chrono::DateTime<chrono::FixedOffset> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => chrono::DateTime::parse_from_rfc3339(s).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl FromValue for uuid::Uuid {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => s.parse().ok(),
+            Value::Bytes(bytes) => uuid::Uuid::from_slice(bytes).ok(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::model::Table;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The version of the schema file format written by `export`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The text format a schema is exported to or imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    Json,
+    /// YAML, with the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+/// The top-level document of a schema file.
+#[derive(Serialize, Deserialize)]
+struct SchemaFile<T> {
+    version: u32,
+    tables: T,
+}
+
+/// Errors reported by `import` for input that isn't a schema it can read.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("invalid schema JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "yaml")]
+    #[error("invalid schema YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("schema format version {found} is not supported; expected version {expected}")]
+    UnsupportedVersion { found: u32, expected: u32 },
+}
+
+/// Exports the tables as a schema document in the given format.
+///
+/// The tables are wrapped in an envelope recording the format version,
+/// `{"version": 1, "tables": [...]}`, so that tools can tell which format
+/// they read.
+pub fn export(tables: &[Table], format: SchemaFormat) -> String {
+    let file = SchemaFile { version: SCHEMA_VERSION, tables };
+    match format {
+        SchemaFormat::Json => {
+            serde_json::to_string_pretty(&file).expect("tables always serialize to JSON")
+        }
+        // serde_yaml can't serialize nested enums such as column defaults,
+        // so the tables go through the JSON data model, as maps
+        #[cfg(feature = "yaml")]
+        SchemaFormat::Yaml => {
+            let file = serde_json::to_value(file).expect("tables always serialize to JSON");
+            serde_yaml::to_string(&file).expect("JSON values always serialize to YAML")
+        }
+    }
+}
+
+/// Imports the tables of a schema document written by `export`.
+///
+/// The version is checked before the tables are read, so that a document
+/// of another version fails with `ImportError::UnsupportedVersion` rather
+/// than with an error about its contents.
+pub fn import(input: &str, format: SchemaFormat) -> Result<Vec<Table>, ImportError> {
+    match format {
+        SchemaFormat::Json => {
+            let file: SchemaFile<serde_json::Value> = serde_json::from_str(input)?;
+            check_version(file.version)?;
+            Ok(serde_json::from_value(file.tables)?)
+        }
+        #[cfg(feature = "yaml")]
+        SchemaFormat::Yaml => {
+            let file: SchemaFile<serde_json::Value> = serde_yaml::from_str(input)?;
+            check_version(file.version)?;
+            serde_json::from_value(file.tables)
+                .map_err(|e| ImportError::Yaml(serde::de::Error::custom(e)))
+        }
+    }
+}
+
+fn check_version(version: u32) -> Result<(), ImportError> {
+    if version != SCHEMA_VERSION {
+        return Err(ImportError::UnsupportedVersion { found: version, expected: SCHEMA_VERSION });
+    }
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use crate::dialect::Dialect;
+use crate::error::OrmError;
+use crate::executor::{Connection, DatabaseError};
+use crate::model::Value;
+use crate::observer::{ObserverSlot, QueryObserver};
+use crate::row::Row;
+use crate::statement_cache::{
+    is_ddl, CacheStats, StatementCache, DEFAULT_STATEMENT_CACHE_CAPACITY,
+};
+use rusqlite::types::{Value as SqliteValue, ValueRef};
+use std::path::Path;
+
+/// A connection to a SQLite database through `rusqlite`, with the `sqlite` feature.
+///
+/// SQLite's dynamic typing is bridged on reads: booleans are bound as 0 and
+/// 1 and come back as integers, which `bool` fields accept, and whole reals
+/// stored in columns of numeric affinity come back as integers, which float
+/// fields accept. Integers are read as `Value::BigInt`.
+///
+/// Statements are prepared once and kept in `rusqlite`'s cache of prepared
+/// statements, keyed by their SQL, which is cleared when a statement changes
+/// the schema. As `rusqlite` doesn't count the statements it finds there,
+/// the connection tracks the SQL of those it holds, evicted in the same
+/// order, for `stats`.
+pub struct SqliteConnection {
+    connection: rusqlite::Connection,
+    observer: ObserverSlot,
+    statements: StatementCache<()>,
+}
+
+impl SqliteConnection {
+    /// Opens the database file at the path, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, OrmError> {
+        Ok(SqliteConnection::from_rusqlite(rusqlite::Connection::open(path).map_err(error)?))
+    }
+
+    /// Opens a new database in memory, dropped with the connection.
+    pub fn open_in_memory() -> Result<Self, OrmError> {
+        Ok(SqliteConnection::from_rusqlite(rusqlite::Connection::open_in_memory().map_err(error)?))
+    }
+
+    /// Wraps an open `rusqlite` connection.
+    pub fn from_rusqlite(connection: rusqlite::Connection) -> Self {
+        connection.set_prepared_statement_cache_capacity(DEFAULT_STATEMENT_CACHE_CAPACITY);
+        SqliteConnection {
+            connection,
+            observer: ObserverSlot::default(),
+            statements: StatementCache::new(DEFAULT_STATEMENT_CACHE_CAPACITY),
+        }
+    }
+
+    /// Sets the number of prepared statements kept, 0 to prepare every
+    /// statement anew.
+    pub fn with_statement_cache_capacity(mut self, capacity: usize) -> Self {
+        self.statements.set_capacity(capacity);
+        self.connection.set_prepared_statement_cache_capacity(capacity);
+        self
+    }
+
+    /// Returns how often statements were found prepared in the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.statements.stats()
+    }
+
+    /// Removes the prepared statements from the cache.
+    pub fn clear_statement_cache(&mut self) {
+        self.statements.clear();
+        self.connection.flush_prepared_statement_cache();
+    }
+
+    /// Returns the rowid of the last row inserted on this connection, which
+    /// is the value of an INTEGER PRIMARY KEY column; 0 if none was.
+    pub fn last_insert_rowid(&self) -> i64 {
+        self.connection.last_insert_rowid()
+    }
+
+    /// Returns the underlying `rusqlite` connection.
+    pub fn as_rusqlite(&self) -> &rusqlite::Connection {
+        &self.connection
+    }
+}
+
+impl Connection for SqliteConnection {
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
+    }
+
+    fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError> {
+        let count = self.observer.observe(sql, params, || {
+            let mut statement = prepare(&self.connection, &mut self.statements, sql)?;
+            let count = statement
+                .execute(rusqlite::params_from_iter(params.iter().map(to_sqlite)))
+                .map_err(error)?;
+            Ok(count as u64)
+        });
+        if is_ddl(sql) {
+            self.clear_statement_cache();
+        }
+        Ok(count?)
+    }
+
+    fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError> {
+        let rows = self.observer.observe(sql, params, || {
+            let mut statement = prepare(&self.connection, &mut self.statements, sql)?;
+            let columns: Vec<String> =
+                statement.column_names().into_iter().map(|name| name.to_string()).collect();
+            let mut rows = statement
+                .query(rusqlite::params_from_iter(params.iter().map(to_sqlite)))
+                .map_err(error)?;
+
+            let mut result = Vec::new();
+            while let Some(sqlite_row) = rows.next().map_err(error)? {
+                let mut row = Row::new();
+                for (index, column) in columns.iter().enumerate() {
+                    let value = sqlite_row.get_ref(index).map_err(error)?;
+                    row.push(column, from_sqlite(value));
+                }
+                result.push(row);
+            }
+            Ok(result)
+        });
+        if is_ddl(sql) {
+            self.clear_statement_cache();
+        }
+        Ok(rows?)
+    }
+
+    fn set_observer(&mut self, observer: Box<dyn QueryObserver>) {
+        self.observer.set(observer);
+    }
+}
+
+/// Prepares the statement through `rusqlite`'s cache, counting whether it
+/// was found there.
+///
+/// The SQL is only tracked once the statement is prepared, as `rusqlite`
+/// doesn't cache statements that fail to prepare.
+fn prepare<'c>(
+    connection: &'c rusqlite::Connection,
+    statements: &mut StatementCache<()>,
+    sql: &str,
+) -> Result<rusqlite::CachedStatement<'c>, DatabaseError> {
+    let statement = connection.prepare_cached(sql).map_err(error)?;
+    statements.get_or_prepare(sql, || Ok::<_, DatabaseError>(()))?;
+    Ok(statement)
+}
+
+/// Converts a bound value into a SQLite value.
+fn to_sqlite(value: &Value) -> SqliteValue {
+    match value {
+        Value::Integer(i) => SqliteValue::Integer(i64::from(*i)),
+        Value::BigInt(i) => SqliteValue::Integer(*i),
+        Value::Float(f) => SqliteValue::Real(*f),
+        Value::Text(text) => SqliteValue::Text(text.clone()),
+        Value::Bool(b) => SqliteValue::Integer(i64::from(*b)),
+        Value::Bytes(bytes) => SqliteValue::Blob(bytes.clone()),
+        Value::Null => SqliteValue::Null,
+    }
+}
+
+/// Converts a SQLite value of a result row into a value.
+fn from_sqlite(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::BigInt(i),
+        ValueRef::Real(f) => Value::Float(f),
+        ValueRef::Text(text) => Value::Text(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::Bytes(bytes.to_vec()),
+    }
+}
+
+fn error(error: rusqlite::Error) -> DatabaseError {
+    DatabaseError::new(error.to_string())
+}
@@ -0,0 +1,135 @@
+use crate::dialect::Dialect;
+use crate::error::OrmError;
+use crate::executor::{AsyncConnection, DatabaseError};
+use crate::model::Value;
+use crate::row::Row;
+use ::sqlx::any::{AnyArguments, AnyPoolOptions, AnyRow, AnyTypeInfoKind};
+use ::sqlx::query::Query;
+use ::sqlx::{Any, AnyPool, Column, Row as _, ValueRef as _};
+
+/// A pool of connections to a database through `sqlx`'s `Any` driver, with
+/// the `sqlx` feature.
+///
+/// The drivers are those enabled on the `sqlx` dependency, such as its
+/// `sqlite` or `postgres` features. Statements are given with placeholders
+/// in the style of the pool's dialect. Null values are bound as null text,
+/// which databases with typed parameters may reject for columns of other types.
+///
+/// Result columns are read as the `Any` driver maps them, which its SQLite
+/// driver only does for columns declared with a storage class such as
+/// INTEGER or TEXT: reading a BOOLEAN or DATETIME column fails, unless it's
+/// cast in the query.
+#[derive(Debug, Clone)]
+pub struct SqlxConnection {
+    pool: AnyPool,
+    dialect: Dialect,
+}
+
+impl SqlxConnection {
+    /// Connects a pool to the database at the URL, such as
+    /// `sqlite::memory:` or `postgres://postgres@localhost/db`, with the
+    /// dialect named by its scheme.
+    ///
+    /// As every connection to an in-memory SQLite database opens a database
+    /// of its own, the pool of such a database holds a single connection.
+    pub async fn connect(url: &str) -> Result<Self, OrmError> {
+        let dialect = match url.split(':').next() {
+            Some("sqlite") => Dialect::Sqlite,
+            Some("postgres" | "postgresql") => Dialect::Postgres,
+            Some("mysql" | "mariadb") => Dialect::MySql,
+            _ => {
+                return Err(DatabaseError::new(format!("unsupported database URL `{}`", url)).into())
+            }
+        };
+        ::sqlx::any::install_default_drivers();
+        let in_memory = url.contains(":memory:") || url.contains("mode=memory");
+        let options = match dialect {
+            Dialect::Sqlite if in_memory => AnyPoolOptions::new().max_connections(1),
+            _ => AnyPoolOptions::new(),
+        };
+        let pool = options.connect(url).await.map_err(error)?;
+        Ok(SqlxConnection { pool, dialect })
+    }
+
+    /// Wraps a connected pool of a database of the given dialect.
+    pub fn from_pool(pool: AnyPool, dialect: Dialect) -> Self {
+        SqlxConnection { pool, dialect }
+    }
+
+    /// Returns the underlying pool.
+    pub fn pool(&self) -> &AnyPool {
+        &self.pool
+    }
+}
+
+impl AsyncConnection for SqlxConnection {
+    fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    async fn execute(&mut self, sql: &str, params: &[Value]) -> Result<u64, OrmError> {
+        let result = bind(::sqlx::query(sql), params).execute(&self.pool).await.map_err(error)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn query(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>, OrmError> {
+        let rows = bind(::sqlx::query(sql), params).fetch_all(&self.pool).await.map_err(error)?;
+        Ok(rows.iter().map(read).collect::<Result<_, _>>()?)
+    }
+}
+
+/// Binds the values to the query's placeholders, in order.
+fn bind<'q>(
+    mut query: Query<'q, Any, AnyArguments<'q>>,
+    params: &[Value],
+) -> Query<'q, Any, AnyArguments<'q>> {
+    for param in params {
+        query = match param {
+            Value::Integer(i) => query.bind(*i),
+            Value::BigInt(i) => query.bind(*i),
+            Value::Float(f) => query.bind(*f),
+            Value::Text(text) => query.bind(text.clone()),
+            Value::Bool(b) => query.bind(*b),
+            Value::Bytes(bytes) => query.bind(bytes.clone()),
+            Value::Null => query.bind(None::<String>),
+        };
+    }
+    query
+}
+
+/// Reads a result row into a row of values.
+fn read(any_row: &AnyRow) -> Result<Row, DatabaseError> {
+    let mut row = Row::new();
+    for column in any_row.columns() {
+        let index = column.ordinal();
+        let raw = any_row.try_get_raw(index).map_err(error)?;
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().kind() {
+                AnyTypeInfoKind::Null => Value::Null,
+                AnyTypeInfoKind::Bool => Value::Bool(any_row.try_get(index).map_err(error)?),
+                AnyTypeInfoKind::SmallInt => {
+                    Value::Integer(i32::from(any_row.try_get::<i16, _>(index).map_err(error)?))
+                }
+                AnyTypeInfoKind::Integer => Value::Integer(any_row.try_get(index).map_err(error)?),
+                AnyTypeInfoKind::BigInt => Value::BigInt(any_row.try_get(index).map_err(error)?),
+                AnyTypeInfoKind::Real => {
+                    Value::Float(f64::from(any_row.try_get::<f32, _>(index).map_err(error)?))
+                }
+                AnyTypeInfoKind::Double => Value::Float(any_row.try_get(index).map_err(error)?),
+                AnyTypeInfoKind::Text => Value::Text(any_row.try_get(index).map_err(error)?),
+                AnyTypeInfoKind::Blob => Value::Bytes(any_row.try_get(index).map_err(error)?),
+            }
+        };
+        row.push(column.name(), value);
+    }
+    Ok(row)
+}
+
+/// Wraps a driver error, keeping the SQLSTATE code of errors reported by the database.
+fn error(error: ::sqlx::Error) -> DatabaseError {
+    let code =
+        error.as_database_error().and_then(|error| error.code()).map(|code| code.to_string());
+    DatabaseError { message: error.to_string(), code }
+}
@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+
+/// The number of statements a connection keeps prepared unless configured
+/// otherwise.
+pub const DEFAULT_STATEMENT_CACHE_CAPACITY: usize = 16;
+
+/// How often a connection found a statement in its cache of prepared statements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Statements run with a statement prepared before.
+    pub hits: u64,
+    /// Statements prepared to run, as they weren't in the cache.
+    pub misses: u64,
+}
+
+/// The statements a connection prepared, keyed by their SQL and evicted
+/// least recently used first.
+///
+/// The entries are scanned in order of use, as a cache holds a few dozen
+/// statements at most.
+#[derive(Debug)]
+pub(crate) struct StatementCache<S> {
+    capacity: usize,
+    entries: VecDeque<(String, S)>,
+    stats: CacheStats,
+}
+
+impl<S: Clone> StatementCache<S> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        StatementCache { capacity, entries: VecDeque::new(), stats: CacheStats::default() }
+    }
+
+    /// Returns the statement cached for the SQL, or prepares and caches it.
+    pub(crate) fn get_or_prepare<E>(
+        &mut self,
+        sql: &str,
+        prepare: impl FnOnce() -> Result<S, E>,
+    ) -> Result<S, E> {
+        if let Some(index) = self.entries.iter().position(|(cached, _)| cached == sql) {
+            self.stats.hits += 1;
+            let entry = self.entries.remove(index).expect("the index was just found");
+            self.entries.push_back(entry);
+            return Ok(self.entries[self.entries.len() - 1].1.clone());
+        }
+        self.stats.misses += 1;
+        let statement = prepare()?;
+        if self.capacity > 0 {
+            if self.entries.len() == self.capacity {
+                self.entries.pop_front();
+            }
+            self.entries.push_back((sql.to_string(), statement.clone()));
+        }
+        Ok(statement)
+    }
+
+    /// Sets the number of statements kept, evicting the least recently used
+    /// ones beyond it.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Returns whether the statement changes the schema, after which statements
+/// prepared before may no longer be valid.
+pub(crate) fn is_ddl(statement: &str) -> bool {
+    let first = statement
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .flat_map(str::split_whitespace)
+        .next()
+        .map(|word| word.trim_end_matches(';').to_ascii_uppercase());
+    matches!(first.as_deref(), Some("CREATE" | "ALTER" | "DROP"))
+}
@@ -0,0 +1,62 @@
+//! Counts the allocations made while building queries, to check derived
+//! models borrow their cached table instead of building it each time.
+
+mod common;
+
+use common::User;
+use rusty_orm::model::{Model, Table};
+use rusty_orm::query_builder::SelectQuery;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// The system allocator, counting the allocations of the current thread.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Returns the number of allocations `f` makes.
+fn allocations<R>(f: impl FnOnce() -> R) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    drop(f());
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// The `users` table of `User`, without the cache derived models have.
+struct UncachedUser;
+
+impl Model for UncachedUser {
+    fn table() -> Table {
+        User::table()
+    }
+}
+
+#[test]
+fn derived_models_do_not_rebuild_their_table() {
+    // The first use builds the cached table
+    let _ = SelectQuery::<User>::new();
+    assert_eq!(allocations(SelectQuery::<User>::new), 0);
+
+    let uncached = allocations(SelectQuery::<UncachedUser>::new);
+    // The table name, the column vector and each column name
+    assert!(uncached > User::table().columns.len() + 1);
+
+    let build = || SelectQuery::<User>::new().filter_eq("is_active", true).build();
+    let build_uncached = || SelectQuery::<UncachedUser>::new().filter_eq("is_active", true).build();
+    assert_eq!(allocations(build_uncached) - allocations(build), uncached);
+}
@@ -0,0 +1,127 @@
+use rusty_orm::codegen::generate_model_source;
+use rusty_orm::model::{
+    Column, ColumnDefault, DataType, ForeignKey, Index, IndexMethod, Model, ReferentialAction,
+    Table, Value,
+};
+
+/// The generated source of `orders()`, compiled here and by the ui tests.
+#[allow(dead_code)]
+mod generated {
+    use rusty_orm_macros::Model;
+
+    include!("codegen/order.rs");
+}
+
+fn column(name: &str, data_type: DataType) -> Column {
+    Column {
+        name: name.to_string(),
+        data_type,
+        is_primary_key: false,
+        is_nullable: false,
+        default: None,
+        is_unique: false,
+        is_auto_increment: false,
+        foreign_key: None,
+    }
+}
+
+/// A table with a column of each kind the generated attributes cover.
+fn orders() -> Table {
+    Table {
+        name: "orders".to_string(),
+        schema: Some("shop".to_string()),
+        columns: vec![
+            Column {
+                is_primary_key: true,
+                is_auto_increment: true,
+                ..column("id", DataType::BigInt)
+            },
+            Column {
+                foreign_key: Some(ForeignKey {
+                    table: "customers".to_string(),
+                    column: "id".to_string(),
+                    on_delete: Some(ReferentialAction::Cascade),
+                    on_update: None,
+                }),
+                ..column("customer_id", DataType::Integer)
+            },
+            // Keywords and names that aren't snake_case need a rename
+            Column {
+                default: Some(ColumnDefault::Value(Value::Text("standard".to_string()))),
+                ..column("type", DataType::Varchar(20))
+            },
+            column("displayName", DataType::Varchar(255)),
+            column("total", DataType::Decimal(10, 2)),
+            Column { is_nullable: true, ..column("note", DataType::Text) },
+            Column { is_unique: true, ..column("reference", DataType::Varchar(64)) },
+            Column {
+                default: Some(ColumnDefault::Value(Value::Bool(false))),
+                ..column("is_paid", DataType::Boolean)
+            },
+            Column {
+                default: Some(ColumnDefault::Expr("'{}'".to_string())),
+                ..column("metadata", DataType::Jsonb)
+            },
+            column("position", DataType::Custom("POINT".to_string())),
+        ],
+        unique_constraints: vec![vec!["customer_id".to_string(), "reference".to_string()]],
+        indexes: vec![
+            Index {
+                name: "idx_orders_customer_id".to_string(),
+                columns: vec!["customer_id".to_string()],
+                is_unique: false,
+                method: None,
+            },
+            Index {
+                name: "orders_by_metadata".to_string(),
+                columns: vec!["metadata".to_string()],
+                is_unique: false,
+                method: Some(IndexMethod::Gin),
+            },
+        ],
+    }
+}
+
+#[test]
+fn generated_source_is_the_checked_in_model() {
+    assert_eq!(generate_model_source(&orders()), include_str!("codegen/order.rs"));
+}
+
+#[test]
+fn generated_model_round_trips_to_the_table() {
+    assert_eq!(generated::Order::table(), orders());
+}
+
+#[test]
+fn field_names_are_sanitized() {
+    let table = Table {
+        name: "categories".to_string(),
+        columns: vec![
+            Column { is_primary_key: true, ..column("self", DataType::Integer) },
+            column("match", DataType::Boolean),
+            column("2fa", DataType::Boolean),
+            column("user-name", DataType::Varchar(255)),
+            column("userName", DataType::Varchar(255)),
+        ],
+        ..Table::default()
+    };
+    assert_eq!(
+        generate_model_source(&table).lines().collect::<Vec<&str>>(),
+        [
+            "#[derive(Debug, Clone, Model)]",
+            r#"#[table_name = "categories"]"#,
+            "pub struct Category {",
+            r#"    #[column(name = "self", primary_key)]"#,
+            "    pub self_: i32,",
+            r#"    #[column(name = "match")]"#,
+            "    pub r#match: bool,",
+            r#"    #[column(name = "2fa")]"#,
+            "    pub _2fa: bool,",
+            r#"    #[column(name = "user-name")]"#,
+            "    pub user_name: String,",
+            r#"    #[column(name = "userName")]"#,
+            "    pub user_name_2: String,",
+            "}",
+        ]
+    );
+}
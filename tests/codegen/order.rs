@@ -0,0 +1,26 @@
+#[derive(Debug, Clone, Model)]
+#[table_name = "shop.orders"]
+#[model(unique = "customer_id, reference")]
+#[model(index(name = "orders_by_metadata", columns = "metadata", method = "gin"))]
+pub struct Order {
+    #[column(primary_key, auto_increment)]
+    pub id: i64,
+    #[column(references = "customers.id", on_delete = "cascade", index)]
+    pub customer_id: i32,
+    #[column(name = "type", length = 20, default = "standard")]
+    pub r#type: String,
+    #[column(name = "displayName")]
+    pub display_name: String,
+    #[column(type = "Decimal(10, 2)")]
+    pub total: String,
+    #[column(type = "Text")]
+    pub note: Option<String>,
+    #[column(length = 64, unique)]
+    pub reference: String,
+    #[column(default = false)]
+    pub is_paid: bool,
+    #[column(type = "Jsonb", default_expr = "'{}'")]
+    pub metadata: serde_json::Value,
+    #[column(raw_type = "POINT")]
+    pub position: String,
+}
@@ -0,0 +1,17 @@
+//! Models shared by the integration tests.
+
+#![allow(dead_code)]
+
+use rusty_orm_macros::{FromRow, IntoInsert, Model};
+
+#[derive(Model, FromRow, IntoInsert, Debug, Clone, PartialEq)]
+#[table_name = "users"]
+pub struct User {
+    #[column(type = "Integer", primary_key = true, auto_increment = true)]
+    pub id: i32,
+    #[column(type = "Varchar(100)")]
+    pub name: String,
+    #[column(type = "Varchar(150)")]
+    pub email: String,
+    pub is_active: bool,
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use common::User;
+use rusty_orm::dialect::Dialect;
+use rusty_orm::migration::MigrationGenerator;
+use rusty_orm::model::{DataType, IntoInsert, Model, Value};
+use rusty_orm::query_builder::{InsertQuery, SelectQuery, UpdateQuery};
+use rusty_orm::row::{FromRow, Row, RowError};
+use rusty_orm_macros::{FromRow, IntoInsert, Model};
+
+fn user_row() -> Row {
+    Row::new()
+        .with("id", 7)
+        .with("name", "Ann")
+        .with("email", "ann@example.com")
+        .with("is_active", true)
+}
+
+#[test]
+fn user_round_trips_through_a_row() {
+    let user = User::from_row(&user_row()).unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 7,
+            name: "Ann".to_string(),
+            email: "ann@example.com".to_string(),
+            is_active: true
+        }
+    );
+
+    let mut row = Row::new();
+    for (column, value) in user.insert_values() {
+        row.push(&column, value);
+    }
+    assert_eq!(row, user_row());
+    assert_eq!(User::from_row(&row).unwrap(), user);
+}
+
+#[test]
+fn missing_column_is_reported() {
+    let row = Row::new().with("id", 7).with("name", "Ann").with("is_active", true);
+
+    assert_eq!(User::from_row(&row), Err(RowError::MissingColumn { column: "email".to_string() }));
+}
+
+#[test]
+fn type_mismatch_is_reported() {
+    let row = Row::new()
+        .with("id", 7)
+        .with("name", "Ann")
+        .with("email", "ann@example.com")
+        .with("is_active", "yes");
+
+    let error = User::from_row(&row).unwrap_err();
+    assert!(matches!(
+        &error,
+        RowError::TypeMismatch { column, found: Value::Text(_), .. } if column == "is_active"
+    ));
+    assert_eq!(
+        error.to_string(),
+        "column `is_active` holds Text(\"yes\"), which cannot be read as `bool`"
+    );
+}
+
+#[derive(FromRow, Debug, PartialEq)]
+#[model(rename_all = "camelCase")]
+struct Profile {
+    user_id: i64,
+    nick_name: Option<String>,
+    #[column(name = "bio_text")]
+    bio: Option<String>,
+    #[column(skip)]
+    cached: Vec<String>,
+}
+
+#[test]
+fn option_fields_accept_null() {
+    let row =
+        Row::new().with("userId", 3i64).with("nickName", Value::Null).with("bio_text", "Hello");
+
+    assert_eq!(
+        Profile::from_row(&row).unwrap(),
+        Profile { user_id: 3, nick_name: None, bio: Some("Hello".to_string()), cached: Vec::new() }
+    );
+}
+
+/// A field type with neither `ToValue` nor `FromValue`.
+#[derive(Debug, Clone, Copy)]
+enum Mood {
+    Happy,
+}
+
+#[derive(Model)]
+#[table_name = "moods"]
+#[allow(dead_code)]
+struct MoodEntry {
+    #[column(primary_key)]
+    id: i32,
+    #[column(type = "Custom(mood)")]
+    mood: Mood,
+}
+
+#[test]
+fn model_derive_alone_accepts_any_field_type() {
+    let _ = MoodEntry { id: 1, mood: Mood::Happy };
+
+    let table = MoodEntry::table();
+    assert_eq!(table.column("mood").unwrap().data_type, DataType::Custom("mood".to_string()));
+}
+
+/// A legacy table whose column names differ from the field names.
+#[derive(Model, FromRow, IntoInsert, Debug, PartialEq)]
+#[table_name = "legacy_accounts"]
+struct LegacyAccount {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(name = "user_email")]
+    email: String,
+    #[column(name = "isEnabled")]
+    enabled: bool,
+}
+
+#[test]
+fn renamed_columns_use_their_database_names() {
+    assert_eq!(LegacyAccount::table().column_names(), ["id", "user_email", "isEnabled"]);
+
+    let account = LegacyAccount { id: 1, email: "ann@example.com".to_string(), enabled: true };
+    assert_eq!(
+        InsertQuery::from_model(&account).build(),
+        r#"INSERT INTO legacy_accounts (id, user_email, "isEnabled") VALUES (1, 'ann@example.com', true);"#
+    );
+    assert_eq!(
+        UpdateQuery::from_model(&account).build(),
+        r#"UPDATE legacy_accounts SET user_email = 'ann@example.com', "isEnabled" = true WHERE id = 1"#
+    );
+
+    let row =
+        Row::new().with("id", 1).with("user_email", "ann@example.com").with("isEnabled", true);
+    assert_eq!(LegacyAccount::from_row(&row).unwrap(), account);
+    assert_eq!(
+        LegacyAccount::from_row(&Row::new().with("id", 1).with("email", "ann@example.com")),
+        Err(RowError::MissingColumn { column: "user_email".to_string() })
+    );
+}
+
+/// Declares a model named `$name` with the same fields, renamed by `$rule`.
+macro_rules! renamed_model {
+    ($name:ident, $rule:literal) => {
+        #[allow(dead_code, non_snake_case)]
+        #[derive(Model, IntoInsert)]
+        #[table_name = "renamed"]
+        #[model(rename_all = $rule)]
+        struct $name {
+            user_id: i32,
+            address_line_2: String,
+            HTTPServer: String,
+            sha256_sum: String,
+            #[column(name = "kept_as_is")]
+            overridden: bool,
+        }
+    };
+}
+
+renamed_model!(SnakeCase, "snake_case");
+renamed_model!(CamelCase, "camelCase");
+renamed_model!(ScreamingSnakeCase, "SCREAMING_SNAKE_CASE");
+renamed_model!(PascalCase, "PascalCase");
+
+#[test]
+fn rename_all_converts_every_column_name() {
+    assert_eq!(
+        SnakeCase::table().column_names(),
+        ["user_id", "address_line_2", "http_server", "sha256_sum", "kept_as_is"]
+    );
+    assert_eq!(
+        CamelCase::table().column_names(),
+        ["userId", "addressLine2", "httpServer", "sha256Sum", "kept_as_is"]
+    );
+    assert_eq!(
+        ScreamingSnakeCase::table().column_names(),
+        ["USER_ID", "ADDRESS_LINE_2", "HTTP_SERVER", "SHA256_SUM", "kept_as_is"]
+    );
+    assert_eq!(
+        PascalCase::table().column_names(),
+        ["UserId", "AddressLine2", "HttpServer", "Sha256Sum", "kept_as_is"]
+    );
+}
+
+#[test]
+fn renamed_columns_flow_into_migrations_and_inserts() {
+    assert_eq!(
+        MigrationGenerator::generate_for::<CamelCase>(Dialect::Postgres).up,
+        [
+            r#"CREATE TABLE "renamed" ("userId" INTEGER NOT NULL, "addressLine2" VARCHAR(255) NOT NULL, "httpServer" VARCHAR(255) NOT NULL, "sha256Sum" VARCHAR(255) NOT NULL, "kept_as_is" BOOLEAN NOT NULL);"#
+        ]
+    );
+
+    let model = CamelCase {
+        user_id: 1,
+        address_line_2: "Flat 2".to_string(),
+        HTTPServer: "nginx".to_string(),
+        sha256_sum: "ab".to_string(),
+        overridden: false,
+    };
+    assert_eq!(
+        InsertQuery::from_model(&model).build(),
+        r#"INSERT INTO renamed ("userId", "addressLine2", "httpServer", "sha256Sum", kept_as_is) VALUES (1, 'Flat 2', 'nginx', 'ab', false);"#
+    );
+}
+
+/// A model carrying a transient field.
+#[derive(Model, FromRow, IntoInsert, Debug, PartialEq)]
+#[table_name = "authors"]
+struct Author {
+    #[column(primary_key = true)]
+    id: i32,
+    name: String,
+    #[column(skip)]
+    display_name: String,
+}
+
+#[test]
+fn skipped_fields_are_not_columns() {
+    assert_eq!(Author::table().column_names(), ["id", "name"]);
+    assert_eq!(
+        MigrationGenerator::generate::<Author>().up,
+        ["CREATE TABLE authors (id INTEGER PRIMARY KEY, name VARCHAR(255) NOT NULL);"]
+    );
+
+    let author = Author { id: 1, name: "Ann".to_string(), display_name: "Ann (admin)".to_string() };
+    assert_eq!(
+        InsertQuery::from_model(&author).build(),
+        "INSERT INTO authors (id, name) VALUES (1, 'Ann');"
+    );
+
+    // Reading a row fills the skipped field with its default
+    let row = Row::new().with("id", 1).with("name", "Ann");
+    assert_eq!(
+        Author::from_row(&row).unwrap(),
+        Author { id: 1, name: "Ann".to_string(), display_name: String::new() }
+    );
+}
+
+#[test]
+fn column_constants_match_the_table() {
+    assert_eq!(User::table().column_names(), [User::ID, User::NAME, User::EMAIL, User::IS_ACTIVE]);
+    assert_eq!(
+        LegacyAccount::table().column_names(),
+        [LegacyAccount::ID, LegacyAccount::EMAIL, LegacyAccount::ENABLED]
+    );
+    assert_eq!(LegacyAccount::EMAIL, "user_email");
+    assert_eq!(CamelCase::ADDRESS_LINE_2, "addressLine2");
+
+    let query =
+        SelectQuery::<User>::new().select(&[User::NAME, User::EMAIL]).filter_eq(User::ID, 1);
+    assert_eq!(query.build(), "SELECT name, email FROM users WHERE id = 1");
+}
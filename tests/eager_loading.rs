@@ -0,0 +1,662 @@
+use rusty_orm::dialect::Dialect;
+use rusty_orm::eager_loading::{EagerLoader, ManyToMany, RelationKind, Relationship};
+use rusty_orm::executor::MockConnection;
+use rusty_orm::expr::Expr;
+use rusty_orm::model::Value;
+use rusty_orm::query_builder::{JoinType, SelectQuery};
+use rusty_orm::row::Row;
+use rusty_orm_macros::Model;
+
+/// An author, referenced by a column other than its primary key.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "authors"]
+struct Author {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(unique = true)]
+    code: String,
+}
+
+/// A book, whose foreign key to its author is only declared on the column.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "books"]
+struct Book {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(references = "authors.code")]
+    written_by: String,
+}
+
+/// The author of a book, with the keys left to the foreign key metadata.
+struct BookAuthor;
+
+impl Relationship for BookAuthor {
+    type RelatedModel = Author;
+}
+
+#[test]
+fn join_condition_comes_from_the_foreign_key() {
+    let loader = EagerLoader::new(SelectQuery::<Book>::new()).with::<BookAuthor>();
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM books INNER JOIN authors ON books.written_by = authors.code"
+    );
+}
+
+/// A customer in the `crm` schema.
+#[allow(dead_code)]
+#[derive(Model)]
+#[schema = "crm"]
+#[table_name = "customers"]
+struct Customer {
+    #[column(primary_key = true)]
+    id: i32,
+}
+
+/// A purchase in the `sales` schema, belonging to a customer.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "sales.purchases"]
+#[belongs_to(model = "Customer")]
+struct Purchase {
+    #[column(primary_key = true)]
+    id: i32,
+    customer_id: i32,
+}
+
+#[test]
+fn joins_use_schema_qualified_names() {
+    let loader = EagerLoader::new(SelectQuery::<Purchase>::new()).with::<PurchaseCustomer>();
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM sales.purchases INNER JOIN crm.customers ON sales.purchases.customer_id = crm.customers.id"
+    );
+    assert_eq!(
+        loader.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "sales"."purchases" INNER JOIN "crm"."customers" ON "sales"."purchases"."customer_id" = "crm"."customers"."id""#
+    );
+}
+
+/// A writer with many articles.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "writers"]
+#[has_many(model = "Article")]
+struct Writer {
+    #[column(primary_key = true)]
+    id: i32,
+}
+
+/// An article belonging to a writer, and to an editor through another key.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "articles"]
+#[belongs_to(model = "Writer")]
+#[belongs_to(model = "Editor", foreign_key = "reviewed_by", related_key = "badge")]
+struct Article {
+    #[column(primary_key = true)]
+    id: i32,
+    writer_id: i32,
+    reviewed_by: String,
+}
+
+/// An editor, identified to articles by badge rather than by primary key.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "editors"]
+struct Editor {
+    #[column(primary_key = true)]
+    id: i32,
+    badge: String,
+}
+
+#[test]
+fn derived_relationships_generate_join_conditions() {
+    assert_eq!(ArticleWriter::foreign_key().as_deref(), Some("writer_id"));
+    assert_eq!(ArticleWriter::related_key(), None);
+
+    let belongs_to = EagerLoader::new(SelectQuery::<Article>::new()).with::<ArticleWriter>();
+    assert_eq!(
+        belongs_to.build(),
+        "SELECT * FROM articles INNER JOIN writers ON articles.writer_id = writers.id"
+    );
+
+    let has_many = EagerLoader::new(SelectQuery::<Writer>::new()).with_left::<WriterArticle>();
+    assert_eq!(
+        has_many.build(),
+        "SELECT * FROM writers LEFT JOIN articles ON writers.id = articles.writer_id"
+    );
+
+    let custom_keys = EagerLoader::new(SelectQuery::<Article>::new()).with::<ArticleEditor>();
+    assert_eq!(
+        custom_keys.build(),
+        "SELECT * FROM articles INNER JOIN editors ON articles.reviewed_by = editors.badge"
+    );
+}
+
+#[test]
+fn left_joins_keep_rows_without_a_related_record() {
+    let inner = EagerLoader::new(SelectQuery::<Article>::new()).with::<ArticleWriter>();
+    let left = EagerLoader::new(SelectQuery::<Article>::new()).with_left::<ArticleWriter>();
+    assert_eq!(
+        inner.build(),
+        "SELECT * FROM articles INNER JOIN writers ON articles.writer_id = writers.id"
+    );
+    assert_eq!(
+        left.build(),
+        "SELECT * FROM articles LEFT JOIN writers ON articles.writer_id = writers.id"
+    );
+}
+
+#[test]
+fn join_type_selects_the_join_keyword() {
+    let join = |join_type| {
+        EagerLoader::new(SelectQuery::<Article>::new())
+            .with_join::<ArticleWriter>(join_type)
+            .build()
+    };
+    assert_eq!(
+        join(JoinType::Inner),
+        "SELECT * FROM articles INNER JOIN writers ON articles.writer_id = writers.id"
+    );
+    assert_eq!(
+        join(JoinType::Left),
+        "SELECT * FROM articles LEFT JOIN writers ON articles.writer_id = writers.id"
+    );
+    assert_eq!(
+        join(JoinType::Right),
+        "SELECT * FROM articles RIGHT JOIN writers ON articles.writer_id = writers.id"
+    );
+    assert_eq!(
+        join(JoinType::Full),
+        "SELECT * FROM articles FULL JOIN writers ON articles.writer_id = writers.id"
+    );
+}
+
+#[test]
+fn full_joins_are_rejected_on_mysql() {
+    let loader =
+        EagerLoader::new(SelectQuery::<Article>::new()).with_join::<ArticleWriter>(JoinType::Full);
+    assert_eq!(
+        loader.try_build_for(Dialect::MySql).unwrap_err().to_string(),
+        "FULL JOIN is not supported by MySQL"
+    );
+}
+
+#[test]
+fn mixed_joins_render_in_call_order() {
+    let loader = EagerLoader::new(SelectQuery::<Article>::new())
+        .with_left::<ArticleEditor>()
+        .with::<ArticleWriter>();
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM articles LEFT JOIN editors ON articles.reviewed_by = editors.badge INNER JOIN writers ON articles.writer_id = writers.id"
+    );
+}
+
+/// A user with many posts.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "users"]
+#[has_many(model = "Post")]
+struct User {
+    #[column(primary_key = true)]
+    id: i32,
+}
+
+/// A post belonging to its user.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "posts"]
+#[belongs_to(model = "User")]
+struct Post {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(references = "users.id")]
+    user_id: i32,
+}
+
+/// The posts of a user, with the keys given explicitly.
+struct AuthoredPosts;
+
+impl Relationship for AuthoredPosts {
+    type RelatedModel = Post;
+    const KIND: RelationKind = RelationKind::HasMany;
+
+    fn foreign_key() -> Option<String> {
+        Some("user_id".to_string())
+    }
+
+    fn related_key() -> Option<String> {
+        Some("id".to_string())
+    }
+}
+
+#[test]
+fn derived_relationships_know_their_direction() {
+    assert_eq!(UserPost::KIND, RelationKind::HasMany);
+    assert_eq!(PostUser::KIND, RelationKind::BelongsTo);
+}
+
+#[test]
+fn join_conditions_follow_the_direction() {
+    let belongs_to = EagerLoader::new(SelectQuery::<Post>::new()).with::<PostUser>();
+    assert_eq!(
+        belongs_to.build(),
+        "SELECT * FROM posts INNER JOIN users ON posts.user_id = users.id"
+    );
+
+    let has_many = EagerLoader::new(SelectQuery::<User>::new()).with::<UserPost>();
+    assert_eq!(
+        has_many.build(),
+        "SELECT * FROM users INNER JOIN posts ON users.id = posts.user_id"
+    );
+
+    // Explicit keys are sided as the derived ones
+    let explicit = EagerLoader::new(SelectQuery::<User>::new()).with::<AuthoredPosts>();
+    assert_eq!(explicit.build(), has_many.build());
+}
+
+/// A tag, given to posts through `post_tags`.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "tags"]
+struct Tag {
+    #[column(primary_key = true)]
+    id: i32,
+    label: String,
+}
+
+/// The tags of a post.
+struct PostTags;
+
+impl ManyToMany for PostTags {
+    type RelatedModel = Tag;
+
+    fn join_table() -> String {
+        "post_tags".to_string()
+    }
+
+    fn base_keys() -> (String, String) {
+        ("post_id".to_string(), "id".to_string())
+    }
+
+    fn related_keys() -> (String, String) {
+        ("tag_id".to_string(), "id".to_string())
+    }
+}
+
+/// The tags suggested for a post, kept in the same join table.
+struct SuggestedTags;
+
+impl ManyToMany for SuggestedTags {
+    type RelatedModel = Tag;
+
+    fn join_table() -> String {
+        "post_tags".to_string()
+    }
+
+    fn base_keys() -> (String, String) {
+        ("suggested_for".to_string(), "id".to_string())
+    }
+
+    fn related_keys() -> (String, String) {
+        ("tag_id".to_string(), "id".to_string())
+    }
+}
+
+#[test]
+fn many_to_many_joins_through_the_join_table() {
+    let loader = EagerLoader::new(SelectQuery::<Post>::new()).with_many_to_many::<PostTags>();
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM posts INNER JOIN post_tags ON posts.id = post_tags.post_id INNER JOIN tags ON post_tags.tag_id = tags.id"
+    );
+
+    let left = EagerLoader::new(SelectQuery::<Post>::new())
+        .with_many_to_many_join::<PostTags>(JoinType::Left);
+    assert_eq!(
+        left.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "posts" LEFT JOIN "post_tags" ON "posts"."id" = "post_tags"."post_id" LEFT JOIN "tags" ON "post_tags"."tag_id" = "tags"."id""#
+    );
+}
+
+#[test]
+fn join_tables_used_twice_are_aliased() {
+    let loader = EagerLoader::new(SelectQuery::<Post>::new())
+        .with_many_to_many::<PostTags>()
+        .with_many_to_many_join::<SuggestedTags>(JoinType::Left);
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM posts INNER JOIN post_tags ON posts.id = post_tags.post_id INNER JOIN tags ON post_tags.tag_id = tags.id LEFT JOIN post_tags AS post_tags_2 ON posts.id = post_tags_2.suggested_for LEFT JOIN tags AS tags_2 ON post_tags_2.tag_id = tags_2.id"
+    );
+}
+
+#[test]
+fn related_columns_are_selected_under_unique_aliases() {
+    let loader = EagerLoader::new(SelectQuery::<Article>::new())
+        .with::<ArticleWriter>()
+        .with_left::<ArticleEditor>()
+        .select_related_columns();
+    let sql = loader.build();
+    assert_eq!(
+        sql,
+        "SELECT articles.id AS articles_id, articles.writer_id AS articles_writer_id, articles.reviewed_by AS articles_reviewed_by, writers.id AS writers_id, editors.id AS editors_id, editors.badge AS editors_badge FROM articles INNER JOIN writers ON articles.writer_id = writers.id LEFT JOIN editors ON articles.reviewed_by = editors.badge"
+    );
+
+    let select_list = sql.strip_prefix("SELECT ").unwrap().split(" FROM ").next().unwrap();
+    let aliases: Vec<&str> =
+        select_list.split(", ").map(|column| column.split(" AS ").nth(1).unwrap()).collect();
+    let mut unique = aliases.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), aliases.len());
+}
+
+#[test]
+fn related_column_aliases_take_a_separator() {
+    let loader = EagerLoader::new(SelectQuery::<Post>::new())
+        .with::<PostUser>()
+        .select_related_columns_with("__");
+    assert_eq!(
+        loader.build_for(Dialect::MySql),
+        "SELECT `posts`.`id` AS `posts__id`, `posts`.`user_id` AS `posts__user_id`, `users`.`id` AS `users__id` FROM `posts` INNER JOIN `users` ON `posts`.`user_id` = `users`.`id`"
+    );
+}
+
+#[test]
+fn explicit_select_lists_are_kept() {
+    let loader = EagerLoader::new(SelectQuery::<Post>::new().select(&["posts.id"]))
+        .with::<PostUser>()
+        .select_related_columns();
+    assert_eq!(
+        loader.build(),
+        "SELECT posts.id FROM posts INNER JOIN users ON posts.user_id = users.id"
+    );
+}
+
+#[test]
+fn preload_plans_a_query_for_the_related_rows() {
+    let plan =
+        EagerLoader::new(SelectQuery::<User>::new().filter_eq("id", 7)).preload::<UserPost>();
+    assert_eq!(plan.base_key, "id");
+    assert_eq!(plan.related_key, "user_id");
+    assert_eq!(plan.base_sql(), "SELECT * FROM users WHERE id = 7");
+    assert_eq!(
+        plan.related_query(&[1, 2, 3]).build_parameterized_for(Dialect::Postgres).0,
+        r#"SELECT * FROM "posts" WHERE "posts"."user_id" IN ($1, $2, $3)"#
+    );
+    assert_eq!(
+        plan.related_query(&[1, 2, 3]).build_parameterized_for(Dialect::Postgres).1,
+        [Value::Integer(1), Value::Integer(2), Value::Integer(3)]
+    );
+
+    let belongs_to = EagerLoader::new(SelectQuery::<Post>::new()).preload::<PostUser>();
+    assert_eq!((belongs_to.base_key.as_str(), belongs_to.related_key.as_str()), ("user_id", "id"));
+}
+
+#[test]
+fn preloading_without_keys_matches_nothing() {
+    let plan = EagerLoader::new(SelectQuery::<User>::new()).preload::<UserPost>();
+    assert_eq!(plan.related_query::<i32>(&[]).build(), "SELECT * FROM posts WHERE 1 = 0");
+}
+
+#[test]
+fn preloaded_rows_are_stitched_to_their_base_rows() {
+    let user = |id: i32| Row::new().with("id", id);
+    let post = |id: i32, user_id: i32| Row::new().with("id", id).with("user_id", user_id);
+    let mut connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![user(1), user(2), user(3)])
+        .with_rows(vec![post(10, 1), post(11, 3), post(12, 1)]);
+
+    let plan = EagerLoader::new(SelectQuery::<User>::new()).preload::<UserPost>();
+    let loaded = plan.load(&mut connection).unwrap();
+    assert_eq!(
+        loaded,
+        [
+            (user(1), vec![post(10, 1), post(12, 1)]),
+            (user(2), Vec::new()),
+            (user(3), vec![post(11, 3)]),
+        ]
+    );
+    assert_eq!(
+        connection.statements(),
+        [
+            r#"SELECT * FROM "users""#,
+            r#"SELECT * FROM "posts" WHERE "posts"."user_id" IN (?, ?, ?)"#,
+        ]
+    );
+}
+
+#[test]
+fn preloading_skips_the_related_query_without_base_rows() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let plan = EagerLoader::new(SelectQuery::<User>::new()).preload::<UserPost>();
+    assert!(plan.load(&mut connection).unwrap().is_empty());
+    assert_eq!(connection.statements(), [r#"SELECT * FROM "users""#]);
+}
+
+/// A comment on a post, written by a user.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "comments"]
+#[belongs_to(model = "Post")]
+#[belongs_to(model = "User", foreign_key = "author_id")]
+struct Comment {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(references = "posts.id")]
+    post_id: i32,
+    #[column(references = "users.id")]
+    author_id: i32,
+}
+
+#[test]
+fn nested_relations_join_off_the_related_table() {
+    let loader = EagerLoader::new(SelectQuery::<Comment>::new())
+        .with::<CommentPost>()
+        .with_nested::<CommentPost, PostUser>();
+    assert_eq!(
+        loader.try_build().unwrap(),
+        "SELECT * FROM comments INNER JOIN posts ON comments.post_id = posts.id INNER JOIN users ON posts.user_id = users.id"
+    );
+}
+
+#[test]
+fn diamonds_alias_the_table_joined_twice() {
+    // The comment's author and the post's author are both users
+    let loader = EagerLoader::new(SelectQuery::<Comment>::new())
+        .with::<CommentUser>()
+        .with::<CommentPost>()
+        .with_nested_join::<CommentPost, PostUser>(JoinType::Left);
+    assert_eq!(
+        loader.try_build().unwrap(),
+        "SELECT * FROM comments INNER JOIN users ON comments.author_id = users.id INNER JOIN posts ON comments.post_id = posts.id LEFT JOIN users AS users_2 ON posts.user_id = users_2.id"
+    );
+    assert_eq!(loader.qualifier_of::<CommentUser>(), Some("users"));
+    assert_eq!(loader.qualifier_of::<PostUser>(), Some("users_2"));
+}
+
+#[test]
+fn nesting_off_a_relation_that_was_not_joined_fails() {
+    let loader =
+        EagerLoader::new(SelectQuery::<Comment>::new()).with_nested::<CommentPost, PostUser>();
+    assert_eq!(
+        loader.try_build().unwrap_err().to_string(),
+        "relationship `eager_loading::CommentPost` must be joined before relations are nested off it"
+    );
+    // Nothing is joined
+    assert_eq!(loader.build(), "SELECT * FROM comments");
+}
+
+#[test]
+fn left_join_filters_differ_between_where_and_on() {
+    let senior = || Expr::col("badge").like("senior-%");
+
+    // In WHERE, articles without a senior editor are left out
+    let filtered = EagerLoader::new(SelectQuery::<Article>::new())
+        .with_filtered_join::<ArticleEditor>(JoinType::Left, senior());
+    assert_eq!(
+        filtered.build(),
+        r#"SELECT * FROM articles LEFT JOIN editors ON articles.reviewed_by = editors.badge WHERE editors.badge LIKE 'senior-%' ESCAPE '\'"#
+    );
+
+    // In ON, they are kept, with NULL editor columns
+    let on_extra = EagerLoader::new(SelectQuery::<Article>::new())
+        .with_on_extra_join::<ArticleEditor>(JoinType::Left, senior());
+    assert_eq!(
+        on_extra.build(),
+        r#"SELECT * FROM articles LEFT JOIN editors ON articles.reviewed_by = editors.badge AND editors.badge LIKE 'senior-%' ESCAPE '\'"#
+    );
+}
+
+#[test]
+fn join_filters_are_escaped_like_base_filters() {
+    let loader = EagerLoader::new(SelectQuery::<Article>::new().filter_eq("articles.id", 3))
+        .with_filtered::<ArticleWriter>(Expr::col("id").ne(7))
+        .with_on_extra::<ArticleEditor>(Expr::col("badge").eq("O'Brien"));
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM articles INNER JOIN writers ON articles.writer_id = writers.id INNER JOIN editors ON articles.reviewed_by = editors.badge AND editors.badge = 'O''Brien' WHERE articles.id = 3 AND writers.id <> 7"
+    );
+}
+
+/// An employee, managed by another employee.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "employees"]
+struct Employee {
+    #[column(primary_key = true)]
+    id: i32,
+    name: String,
+    #[column(references = "employees.id")]
+    manager_id: Option<i32>,
+}
+
+/// The manager of an employee.
+struct Manager;
+
+impl Relationship for Manager {
+    type RelatedModel = Employee;
+}
+
+#[test]
+fn self_referencing_joins_are_aliased() {
+    let loader = EagerLoader::new(SelectQuery::<Employee>::new())
+        .with_left::<Manager>()
+        .select_related_columns();
+    assert_eq!(loader.qualifier_of::<Manager>(), Some("employees_2"));
+    assert_eq!(
+        loader.build(),
+        "SELECT employees.id AS employees_id, employees.name AS employees_name, employees.manager_id AS employees_manager_id, employees_2.id AS employees_2_id, employees_2.name AS employees_2_name, employees_2.manager_id AS employees_2_manager_id FROM employees LEFT JOIN employees AS employees_2 ON employees.manager_id = employees_2.id"
+    );
+}
+
+#[test]
+fn aliases_can_be_filtered_on() {
+    let loader = EagerLoader::new(SelectQuery::<Employee>::new())
+        .with_filtered::<Manager>(Expr::col("name").eq("Ada"));
+    assert_eq!(loader.qualifier_of::<Manager>(), Some("employees_2"));
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM employees INNER JOIN employees AS employees_2 ON employees.manager_id = employees_2.id WHERE employees_2.name = 'Ada'"
+    );
+}
+
+#[test]
+fn joining_a_relation_twice_aliases_each_join() {
+    // An employee's manager, and the manager's manager
+    let chain = EagerLoader::new(SelectQuery::<Employee>::new())
+        .with::<Manager>()
+        .with_nested::<Manager, Manager>();
+    assert_eq!(
+        chain.build(),
+        "SELECT * FROM employees INNER JOIN employees AS employees_2 ON employees.manager_id = employees_2.id INNER JOIN employees AS employees_3 ON employees_2.manager_id = employees_3.id"
+    );
+    assert_eq!(chain.qualifier_of::<Manager>(), Some("employees_3"));
+
+    let twice =
+        EagerLoader::new(SelectQuery::<Employee>::new()).with::<Manager>().with::<Manager>();
+    assert_eq!(
+        twice.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "employees" INNER JOIN "employees" AS "employees_2" ON "employees"."manager_id" = "employees_2"."id" INNER JOIN "employees" AS "employees_3" ON "employees"."manager_id" = "employees_3"."id""#
+    );
+}
+
+/// A member of a tenant, identified by the tenant and an id within it.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "members"]
+struct Member {
+    #[column(primary_key = true)]
+    tenant_id: i32,
+    #[column(primary_key = true)]
+    id: i32,
+}
+
+/// An invoice of a tenant, issued to a member of the same tenant by a writer.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "invoices"]
+#[belongs_to(model = "Writer")]
+struct Invoice {
+    #[column(primary_key = true)]
+    id: i32,
+    tenant_id: i32,
+    member_id: i32,
+    writer_id: i32,
+}
+
+/// The member an invoice is issued to.
+struct InvoiceMember;
+
+impl Relationship for InvoiceMember {
+    type RelatedModel = Member;
+
+    fn key_pairs() -> Vec<(String, String)> {
+        vec![
+            ("tenant_id".to_string(), "tenant_id".to_string()),
+            ("member_id".to_string(), "id".to_string()),
+        ]
+    }
+}
+
+/// The invoices issued to a member.
+struct MemberInvoices;
+
+impl Relationship for MemberInvoices {
+    type RelatedModel = Invoice;
+    const KIND: RelationKind = RelationKind::HasMany;
+
+    fn key_pairs() -> Vec<(String, String)> {
+        InvoiceMember::key_pairs()
+    }
+}
+
+#[test]
+fn composite_keys_are_joined_with_and() {
+    let loader = EagerLoader::new(SelectQuery::<Invoice>::new()).with::<InvoiceMember>();
+    assert_eq!(
+        loader.build(),
+        "SELECT * FROM invoices INNER JOIN members ON invoices.tenant_id = members.tenant_id AND invoices.member_id = members.id"
+    );
+
+    let has_many = EagerLoader::new(SelectQuery::<Member>::new()).with::<MemberInvoices>();
+    assert_eq!(
+        has_many.build(),
+        "SELECT * FROM members INNER JOIN invoices ON members.tenant_id = invoices.tenant_id AND members.id = invoices.member_id"
+    );
+}
+
+#[test]
+fn composite_and_single_keys_mix_in_one_query() {
+    let loader = EagerLoader::new(SelectQuery::<Invoice>::new())
+        .with::<InvoiceMember>()
+        .with_left::<InvoiceWriter>();
+    assert_eq!(
+        loader.build_for(Dialect::MySql),
+        "SELECT * FROM `invoices` INNER JOIN `members` ON `invoices`.`tenant_id` = `members`.`tenant_id` AND `invoices`.`member_id` = `members`.`id` LEFT JOIN `writers` ON `invoices`.`writer_id` = `writers`.`id`"
+    );
+}
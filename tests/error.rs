@@ -0,0 +1,134 @@
+mod common;
+
+use common::User;
+use rusty_orm::dialect::Dialect;
+use rusty_orm::error::OrmError;
+use rusty_orm::executor::{DatabaseError, FetchError, MockConnection};
+use rusty_orm::migration::{Migration, MigrationError, MigrationGenerator, MigrationRunner};
+use rusty_orm::model::{ModelExt, SchemaError};
+use rusty_orm::query_builder::{QueryError, SelectQuery};
+use rusty_orm::row::{Row, RowError};
+use rusty_orm::schema::{import, ImportError, SchemaFormat};
+use std::error::Error;
+
+/// Returns the messages of the error and of its sources, outermost first.
+fn chain(error: &dyn Error) -> Vec<String> {
+    let mut messages = vec![error.to_string()];
+    let mut source = error.source();
+    while let Some(error) = source {
+        messages.push(error.to_string());
+        source = error.source();
+    }
+    messages
+}
+
+#[test]
+fn display_names_the_kind_of_error() {
+    let query = OrmError::from(QueryError::MissingPrimaryKey { table: "events".to_string() });
+    assert_eq!(query.to_string(), "invalid query: table `events` has no primary key");
+
+    let schema = OrmError::from(SchemaError::NoColumns { table: "users".to_string() });
+    assert_eq!(schema.to_string(), "invalid schema: table `users` has no columns");
+
+    let execution = OrmError::from(DatabaseError::new("no such table: users"));
+    assert_eq!(execution.to_string(), "database error: no such table: users");
+
+    let decode = OrmError::from(RowError::MissingColumn { column: "email".to_string() });
+    assert_eq!(decode.to_string(), "failed to decode a row: row has no column `email`");
+}
+
+#[test]
+fn display_of_transparent_variants_is_the_wrapped_error() {
+    let import = OrmError::from(ImportError::UnsupportedVersion { found: 3, expected: 1 });
+    assert_eq!(import.to_string(), "schema format version 3 is not supported; expected version 1");
+
+    let migration = OrmError::from(MigrationError::NotApplied { name: "0001_users".to_string() });
+    assert_eq!(migration.to_string(), "migration `0001_users` has not been applied");
+
+    let fetch = OrmError::from(FetchError::TooManyRows);
+    assert_eq!(fetch.to_string(), "query returned more than one row");
+}
+
+#[test]
+fn source_is_the_wrapped_error() {
+    let query = OrmError::from(QueryError::OffsetWithoutLimit);
+    assert_eq!(
+        chain(&query),
+        [
+            "invalid query: OFFSET without LIMIT is not supported by all databases",
+            "OFFSET without LIMIT is not supported by all databases",
+        ]
+    );
+
+    let execution = OrmError::from(DatabaseError::new("disk I/O error"));
+    assert_eq!(chain(&execution), ["database error: disk I/O error", "disk I/O error"]);
+
+    // Transparent variants forward to the source of the error they wrap
+    assert!(OrmError::from(FetchError::NoRows).source().is_none());
+}
+
+#[test]
+fn io_and_json_errors_are_classified_by_what_raised_them() {
+    let io = MigrationGenerator::load_migrations("does/not/exist").unwrap_err();
+    assert!(matches!(io, OrmError::Migration(MigrationError::Io(_))));
+    assert!(io.to_string().starts_with("failed to read migrations: "));
+
+    let migration = Migration::from_embedded(&[("0001_users.json", "{")]).unwrap_err();
+    assert!(matches!(migration, OrmError::Migration(MigrationError::InvalidMigration { .. })));
+
+    let schema = OrmError::from(import("{", SchemaFormat::Json).unwrap_err());
+    assert!(matches!(schema, OrmError::Import(ImportError::Json(_))));
+    assert!(schema.to_string().starts_with("invalid schema JSON: "));
+}
+
+#[test]
+fn fetching_reports_each_step_that_fails() {
+    let mut connection = MockConnection::new(Dialect::Sqlite).fail_on("FROM");
+    let error = User::all(&mut connection).unwrap_err();
+    assert!(matches!(error, OrmError::Execution(_)));
+    assert_eq!(chain(&error).last().unwrap(), "mock failure on `FROM`");
+
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let error = SelectQuery::<User>::new().fetch_one(&mut connection).unwrap_err();
+    assert!(matches!(error, OrmError::Fetch(FetchError::NoRows)));
+
+    let mut connection =
+        MockConnection::new(Dialect::Sqlite).with_rows(vec![Row::new().with("id", 1)]);
+    let error = SelectQuery::<User>::new().fetch_all(&mut connection).unwrap_err();
+    assert!(matches!(error, OrmError::Decode(RowError::MissingColumn { .. })));
+}
+
+#[test]
+fn migration_failures_keep_the_database_error_as_source() {
+    let migration = Migration {
+        up: vec!["CREATE TABLE users (id INTEGER)".to_string()],
+        down: vec!["DROP TABLE users".to_string()],
+        checksum: None,
+    };
+    let connection = MockConnection::new(Dialect::Sqlite).fail_on("CREATE TABLE users");
+    let mut runner =
+        MigrationRunner::with_migrations(connection, vec![("0001_users".to_string(), migration)]);
+
+    let error = runner.run().unwrap_err();
+    assert!(matches!(
+        &error,
+        OrmError::Migration(MigrationError::Failed { name, completed, .. })
+            if name == "0001_users" && completed.is_empty()
+    ));
+    assert_eq!(
+        chain(&error),
+        [
+            "migration `0001_users` failed: database error: mock failure on `CREATE TABLE users`",
+            "database error: mock failure on `CREATE TABLE users`",
+            "mock failure on `CREATE TABLE users`",
+        ]
+    );
+}
+
+#[test]
+fn missing_migration_directories_are_io_errors() {
+    let connection = MockConnection::new(Dialect::Sqlite);
+    let mut runner = MigrationRunner::new(connection, "does/not/exist");
+    let error = runner.run().unwrap_err();
+    assert!(matches!(error, OrmError::Migration(MigrationError::Io(_))));
+}
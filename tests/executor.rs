@@ -0,0 +1,296 @@
+mod common;
+
+use common::User;
+use rusty_orm::dialect::Dialect;
+use rusty_orm::error::OrmError;
+use rusty_orm::executor::{Connection, DatabaseError, FetchError, MockConnection};
+use rusty_orm::model::Value;
+use rusty_orm::query_builder::SelectQuery;
+use rusty_orm::row::{FromRow, Row, RowError};
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+
+fn user_row(id: i32, name: &str) -> Row {
+    Row::new()
+        .with("id", id)
+        .with("name", name)
+        .with("email", format!("{}@example.com", name.to_lowercase()))
+        .with("is_active", true)
+}
+
+#[test]
+fn mock_records_statements_with_their_values() {
+    let mut connection = MockConnection::new(Dialect::Postgres);
+    assert_eq!(connection.dialect(), Dialect::Postgres);
+    connection.execute("DELETE FROM users WHERE id = $1", &[Value::Integer(1)]).unwrap();
+    connection.query("SELECT * FROM users", &[]).unwrap();
+
+    assert_eq!(
+        connection.executed(),
+        [
+            ("DELETE FROM users WHERE id = $1".to_string(), vec![Value::Integer(1)]),
+            ("SELECT * FROM users".to_string(), Vec::new()),
+        ]
+    );
+    assert_eq!(connection.statements(), ["DELETE FROM users WHERE id = $1", "SELECT * FROM users"]);
+}
+
+#[test]
+fn mock_returns_queued_rows_in_order_then_none() {
+    let mut connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![user_row(1, "Ada")])
+        .with_rows(vec![user_row(2, "Grace"), user_row(3, "Linus")]);
+
+    assert_eq!(connection.query("SELECT 1", &[]).unwrap().len(), 1);
+    // Statements don't take the queued rows
+    connection.execute("UPDATE users SET is_active = FALSE", &[]).unwrap();
+    assert_eq!(connection.query("SELECT 2", &[]).unwrap().len(), 2);
+    assert!(connection.query("SELECT 3", &[]).unwrap().is_empty());
+}
+
+#[test]
+fn mock_fails_statements_containing_a_fragment() {
+    let mut connection = MockConnection::new(Dialect::Sqlite).fail_on("DROP");
+    let error = connection.execute("DROP TABLE users", &[]).unwrap_err();
+    assert_eq!(error.to_string(), "database error: mock failure on `DROP`");
+    // The driver error is kept as the source
+    let source = error.source().and_then(|source| source.downcast_ref::<DatabaseError>());
+    assert_eq!(source, Some(&DatabaseError::new("mock failure on `DROP`")));
+    // Failed statements are still recorded
+    assert_eq!(connection.statements(), ["DROP TABLE users"]);
+    assert!(connection.execute("CREATE TABLE users (id INTEGER)", &[]).is_ok());
+}
+
+#[test]
+fn select_queries_run_through_the_mock_and_map_rows() {
+    let query = SelectQuery::<User>::new().filter_eq("is_active", true).limit(10);
+    let (sql, params) = query.build_parameterized_for(Dialect::Postgres);
+    let mut connection = MockConnection::new(Dialect::Postgres)
+        .with_rows(vec![user_row(1, "Ada"), user_row(2, "Grace")]);
+
+    let rows = connection.query(&sql, &params).unwrap();
+    let users = rows.iter().map(User::from_row).collect::<Result<Vec<User>, RowError>>().unwrap();
+    assert_eq!(
+        users.iter().map(|user| user.name.as_str()).collect::<Vec<&str>>(),
+        ["Ada", "Grace"]
+    );
+    assert_eq!(users[1].email, "grace@example.com");
+    assert_eq!(connection.executed()[0].1, [Value::Bool(true)]);
+}
+
+#[test]
+fn rows_missing_a_column_or_of_another_type_fail_to_map() {
+    let missing = Row::new().with("id", 1).with("name", "Ada").with("is_active", true);
+    assert_eq!(
+        User::from_row(&missing).unwrap_err(),
+        RowError::MissingColumn { column: "email".to_string() }
+    );
+
+    let mismatched = Row::new()
+        .with("id", "one")
+        .with("name", "Ada")
+        .with("email", "ada@example.com")
+        .with("is_active", true);
+    assert!(matches!(
+        User::from_row(&mismatched).unwrap_err(),
+        RowError::TypeMismatch { column, .. } if column == "id"
+    ));
+}
+
+#[test]
+fn fetch_all_binds_the_filter_values() {
+    let mut connection = MockConnection::new(Dialect::Postgres)
+        .with_rows(vec![user_row(1, "Ada"), user_row(2, "Grace")]);
+    let query = SelectQuery::<User>::new().filter_eq("name", "Ada' OR 1 = 1").order_by(&["id"]);
+
+    let users = query.fetch_all(&mut connection).unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<i32>>(), [1, 2]);
+    assert_eq!(
+        connection.statements(),
+        [r#"SELECT * FROM "users" WHERE "name" = $1 ORDER BY "id""#]
+    );
+    assert_eq!(connection.executed()[0].1, [Value::Text("Ada' OR 1 = 1".to_string())]);
+}
+
+#[test]
+fn fetch_one_limits_the_query_to_one_row() {
+    let mut connection = MockConnection::new(Dialect::Sqlite).with_rows(vec![user_row(7, "Ada")]);
+    let user =
+        SelectQuery::<User>::new().filter_eq("name", "Ada").fetch_one(&mut connection).unwrap();
+    assert_eq!(user.id, 7);
+    assert_eq!(connection.statements(), [r#"SELECT * FROM "users" WHERE "name" = ? LIMIT 1"#]);
+
+    // A limit of the query's own is kept
+    let mut connection = MockConnection::new(Dialect::Sqlite).with_rows(vec![user_row(7, "Ada")]);
+    SelectQuery::<User>::new().limit(5).fetch_one(&mut connection).unwrap();
+    assert_eq!(connection.statements(), [r#"SELECT * FROM "users" LIMIT 5"#]);
+}
+
+#[test]
+fn fetch_one_fails_without_rows_and_takes_the_first_of_several() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let error = SelectQuery::<User>::new().fetch_one(&mut connection).unwrap_err();
+    assert!(matches!(error, OrmError::Fetch(FetchError::NoRows)));
+
+    let mut connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![user_row(1, "Ada"), user_row(2, "Grace")]);
+    assert_eq!(SelectQuery::<User>::new().limit(2).fetch_one(&mut connection).unwrap().id, 1);
+}
+
+#[test]
+fn fetch_optional_returns_none_one_or_fails_on_several() {
+    let mut connection = MockConnection::new(Dialect::MySql)
+        .with_rows(Vec::new())
+        .with_rows(vec![user_row(1, "Ada")])
+        .with_rows(vec![user_row(1, "Ada"), user_row(2, "Grace")]);
+    let query = SelectQuery::<User>::new().filter_eq("is_active", true);
+
+    assert_eq!(query.fetch_optional(&mut connection).unwrap(), None);
+    assert_eq!(query.fetch_optional(&mut connection).unwrap().map(|user| user.id), Some(1));
+    let error = query.fetch_optional(&mut connection).unwrap_err();
+    assert!(matches!(error, OrmError::Fetch(FetchError::TooManyRows)));
+    // Two rows are enough to tell one from several
+    assert_eq!(connection.statements()[0], "SELECT * FROM `users` WHERE `is_active` = ? LIMIT 2");
+}
+
+#[test]
+fn fetching_reports_rows_that_fail_to_map() {
+    let mut connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![Row::new().with("id", 1).with("name", "Ada")]);
+    let error = SelectQuery::<User>::new().fetch_all(&mut connection).unwrap_err();
+    assert!(
+        matches!(error, OrmError::Decode(RowError::MissingColumn { column }) if column == "email")
+    );
+}
+
+/// Deletes the user inside a transaction, returning early with an error if
+/// it's still referenced.
+fn delete_user(connection: &mut MockConnection, id: i32) -> Result<(), OrmError> {
+    let mut transaction = connection.begin()?;
+    transaction.execute("DELETE FROM posts WHERE user_id = ?", &[Value::Integer(id)])?;
+    transaction.execute("DELETE FROM users WHERE id = ?", &[Value::Integer(id)])?;
+    transaction.commit()?;
+    Ok(())
+}
+
+#[test]
+fn transactions_commit_explicitly() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    delete_user(&mut connection, 1).unwrap();
+    assert_eq!(
+        connection.statements(),
+        [
+            "BEGIN;",
+            "DELETE FROM posts WHERE user_id = ?",
+            "DELETE FROM users WHERE id = ?",
+            "COMMIT;"
+        ]
+    );
+}
+
+#[test]
+fn transactions_roll_back_on_early_return() {
+    let mut connection = MockConnection::new(Dialect::Sqlite).fail_on("DELETE FROM posts");
+    assert!(delete_user(&mut connection, 1).is_err());
+    assert_eq!(
+        connection.statements(),
+        ["BEGIN;", "DELETE FROM posts WHERE user_id = ?", "ROLLBACK;"]
+    );
+}
+
+#[test]
+fn transactions_roll_back_when_a_panic_unwinds() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut transaction = connection.begin().unwrap();
+        transaction.execute("UPDATE users SET is_active = FALSE", &[]).unwrap();
+        panic!("request handler failed");
+    }));
+    assert!(result.is_err());
+    assert_eq!(
+        connection.statements(),
+        ["BEGIN;", "UPDATE users SET is_active = FALSE", "ROLLBACK;"]
+    );
+}
+
+#[test]
+fn nested_transactions_use_savepoints() {
+    let mut connection = MockConnection::new(Dialect::Postgres);
+    let mut outer = connection.begin().unwrap();
+    assert_eq!(outer.depth(), 0);
+    {
+        let mut retry = outer.begin().unwrap();
+        assert_eq!(retry.depth(), 1);
+        retry
+            .execute("INSERT INTO users (name) VALUES ($1)", &[Value::Text("Ada".to_string())])
+            .unwrap();
+        // Dropped without a commit
+    }
+    let mut retry = outer.begin().unwrap();
+    let mut inner = retry.begin().unwrap();
+    inner
+        .execute("INSERT INTO users (name) VALUES ($1)", &[Value::Text("Ada".to_string())])
+        .unwrap();
+    inner.commit().unwrap();
+    retry.commit().unwrap();
+    outer.commit().unwrap();
+
+    assert_eq!(
+        connection.statements(),
+        [
+            "BEGIN;",
+            "SAVEPOINT sp_1;",
+            "INSERT INTO users (name) VALUES ($1)",
+            "ROLLBACK TO SAVEPOINT sp_1;",
+            "SAVEPOINT sp_1;",
+            "SAVEPOINT sp_2;",
+            "INSERT INTO users (name) VALUES ($1)",
+            "RELEASE SAVEPOINT sp_2;",
+            "RELEASE SAVEPOINT sp_1;",
+            "COMMIT;",
+        ]
+    );
+}
+
+/// Runs a statement in a transaction of its own, as generic callers do.
+fn deactivate_users<C: Connection>(mut connection: C) -> Result<(), OrmError> {
+    let mut transaction = connection.begin()?;
+    transaction.execute("UPDATE users SET is_active = FALSE", &[])?;
+    transaction.commit()?;
+    Ok(())
+}
+
+#[test]
+fn begins_through_a_reference_to_a_transaction_use_savepoints() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let mut outer = connection.begin().unwrap();
+    deactivate_users(&mut outer).unwrap();
+    let mut retry = outer.begin().unwrap();
+    deactivate_users(&mut retry).unwrap();
+    retry.commit().unwrap();
+    outer.commit().unwrap();
+
+    assert_eq!(
+        connection.statements(),
+        [
+            "BEGIN;",
+            "SAVEPOINT sp_1;",
+            "UPDATE users SET is_active = FALSE",
+            "RELEASE SAVEPOINT sp_1;",
+            "SAVEPOINT sp_1;",
+            "SAVEPOINT sp_2;",
+            "UPDATE users SET is_active = FALSE",
+            "RELEASE SAVEPOINT sp_2;",
+            "RELEASE SAVEPOINT sp_1;",
+            "COMMIT;",
+        ]
+    );
+}
+
+#[test]
+fn explicit_rollbacks_are_not_repeated_on_drop() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let transaction = connection.begin().unwrap();
+    transaction.rollback().unwrap();
+    assert_eq!(connection.statements(), ["BEGIN;", "ROLLBACK;"]);
+}
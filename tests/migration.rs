@@ -0,0 +1,1449 @@
+mod common;
+
+use common::User;
+use rusty_orm::dialect::Dialect;
+use rusty_orm::error::OrmError;
+use rusty_orm::migration::{
+    render_column_def, Migration, MigrationError, MigrationFormat, MigrationGenerator,
+    MigrationNaming, MigrationOptions, View, ViewBuilder,
+};
+use rusty_orm::model::{
+    Column, ColumnDefault, DataType, ForeignKey, Index, IndexMethod, Model, ReferentialAction,
+    SchemaError, Table, Value,
+};
+use rusty_orm::query_builder::{InsertQuery, SelectQuery};
+use rusty_orm_macros::{IntoInsert, Model};
+
+#[test]
+fn create_table_per_dialect() {
+    assert_eq!(
+        MigrationGenerator::generate::<User>().up,
+        [
+            "CREATE TABLE users (id INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY, name VARCHAR(100) NOT NULL, email VARCHAR(150) NOT NULL, is_active BOOLEAN NOT NULL);"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<User>(Dialect::Postgres).up,
+        [
+            r#"CREATE TABLE "users" ("id" INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY, "name" VARCHAR(100) NOT NULL, "email" VARCHAR(150) NOT NULL, "is_active" BOOLEAN NOT NULL);"#
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<User>(Dialect::MySql).up,
+        [
+            "CREATE TABLE `users` (`id` INTEGER AUTO_INCREMENT PRIMARY KEY, `name` VARCHAR(100) NOT NULL, `email` VARCHAR(150) NOT NULL, `is_active` BOOLEAN NOT NULL);"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<User>(Dialect::Sqlite).up,
+        [
+            r#"CREATE TABLE "users" ("id" INTEGER PRIMARY KEY AUTOINCREMENT, "name" VARCHAR(100) NOT NULL, "email" VARCHAR(150) NOT NULL, "is_active" BOOLEAN NOT NULL);"#
+        ]
+    );
+}
+
+/// A model with a column of each date, time and text type.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "events"]
+struct Event {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(type = "Text")]
+    body: String,
+    #[column(type = "Date")]
+    day: String,
+    #[column(type = "Time")]
+    starts_at: String,
+    #[column(type = "DateTime")]
+    created_at: String,
+    #[column(type = "Timestamp")]
+    updated_at: String,
+    #[column(type = "TimestampTz")]
+    published_at: String,
+}
+
+#[test]
+fn date_time_and_text_types() {
+    let types: Vec<DataType> = Event::table().columns.into_iter().map(|c| c.data_type).collect();
+    assert_eq!(
+        types,
+        [
+            DataType::Integer,
+            DataType::Text,
+            DataType::Date,
+            DataType::Time,
+            DataType::DateTime,
+            DataType::DateTime,
+            DataType::TimestampTz,
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate::<Event>().up,
+        [
+            "CREATE TABLE events (id INTEGER PRIMARY KEY, body TEXT NOT NULL, day DATE NOT NULL, starts_at TIME NOT NULL, created_at TIMESTAMP NOT NULL, updated_at TIMESTAMP NOT NULL, published_at TIMESTAMPTZ NOT NULL);"
+        ]
+    );
+}
+
+#[test]
+fn data_types_deserialize_from_saved_migrations() {
+    let saved = r#"[{"Varchar":100},"Integer","Text","TimestampTz",{"Decimal":[10,2]},{"Custom":"citext"}]"#;
+    let types: Vec<DataType> = serde_json::from_str(saved).unwrap();
+    assert_eq!(
+        types,
+        [
+            DataType::Varchar(100),
+            DataType::Integer,
+            DataType::Text,
+            DataType::TimestampTz,
+            DataType::Decimal(10, 2),
+            DataType::Custom("citext".to_string()),
+        ]
+    );
+    assert_eq!(serde_json::to_string(&types).unwrap(), saved);
+}
+
+/// A model with UUID, decimal and binary columns.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "payments"]
+struct Payment {
+    #[column(primary_key = true, type = "Uuid")]
+    id: String,
+    #[column(type = "Decimal(10, 2)")]
+    amount: f64,
+    receipt: Vec<u8>,
+    #[column(type = "Bytea")]
+    signature: Vec<u8>,
+}
+
+#[test]
+fn uuid_decimal_and_blob_types() {
+    let table = Payment::table();
+    let types: Vec<&DataType> = table.columns.iter().map(|c| &c.data_type).collect();
+    assert_eq!(
+        types,
+        [&DataType::Uuid, &DataType::Decimal(10, 2), &DataType::Blob, &DataType::Blob]
+    );
+
+    assert_eq!(
+        MigrationGenerator::generate_for::<Payment>(Dialect::Postgres).up,
+        [
+            r#"CREATE TABLE "payments" ("id" UUID PRIMARY KEY, "amount" NUMERIC(10, 2) NOT NULL, "receipt" BYTEA NOT NULL, "signature" BYTEA NOT NULL);"#
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Payment>(Dialect::MySql).up,
+        [
+            "CREATE TABLE `payments` (`id` CHAR(36) PRIMARY KEY, `amount` NUMERIC(10, 2) NOT NULL, `receipt` BLOB NOT NULL, `signature` BLOB NOT NULL);"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Payment>(Dialect::Sqlite).up,
+        [
+            r#"CREATE TABLE "payments" ("id" CHAR(36) PRIMARY KEY, "amount" NUMERIC(10, 2) NOT NULL, "receipt" BLOB NOT NULL, "signature" BLOB NOT NULL);"#
+        ]
+    );
+
+    // The generated table survives a round trip through its saved form
+    let saved = serde_json::to_string(&table).unwrap();
+    assert_eq!(serde_json::from_str::<Table>(&saved).unwrap(), table);
+}
+
+/// A model with JSON columns.
+#[allow(dead_code)]
+#[derive(Model, IntoInsert)]
+#[table_name = "documents"]
+struct Document {
+    #[column(primary_key = true, auto_increment = true)]
+    id: i32,
+    body: serde_json::Value,
+    #[column(type = "Jsonb")]
+    meta: serde_json::Value,
+}
+
+#[test]
+fn json_columns() {
+    let table = Document::table();
+    assert_eq!(table.columns[1].data_type, DataType::Json);
+    assert_eq!(table.columns[2].data_type, DataType::Jsonb);
+
+    assert_eq!(
+        MigrationGenerator::generate_for::<Document>(Dialect::Postgres).up,
+        [
+            r#"CREATE TABLE "documents" ("id" INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY, "body" JSON NOT NULL, "meta" JSONB NOT NULL);"#
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Document>(Dialect::MySql).up,
+        [
+            "CREATE TABLE `documents` (`id` INTEGER AUTO_INCREMENT PRIMARY KEY, `body` JSON NOT NULL, `meta` JSON NOT NULL);"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Document>(Dialect::Sqlite).up,
+        [
+            r#"CREATE TABLE "documents" ("id" INTEGER PRIMARY KEY AUTOINCREMENT, "body" TEXT NOT NULL, "meta" TEXT NOT NULL);"#
+        ]
+    );
+
+    let document = Document {
+        id: 0,
+        body: serde_json::json!({"title": "It's \"done\"", "tags": ["a"]}),
+        meta: serde_json::json!(null),
+    };
+    assert_eq!(
+        InsertQuery::from_model(&document).build(),
+        r#"INSERT INTO documents (body, meta) VALUES ('{"tags":["a"],"title":"It''s \"done\""}', 'null');"#
+    );
+    let (_, params) = InsertQuery::from_model(&document).build_parameterized();
+    assert_eq!(
+        params,
+        vec![
+            Value::Text(r#"{"tags":["a"],"title":"It's \"done\""}"#.to_string()),
+            Value::Text("null".to_string())
+        ]
+    );
+}
+
+/// A model with a field of each integer width.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "counters"]
+struct Counter {
+    #[column(primary_key = true)]
+    id: i64,
+    tiny: i8,
+    small: i16,
+    regular: i32,
+    byte: u8,
+    word: u16,
+    double_word: u32,
+    quad_word: u64,
+    size: usize,
+    #[column(type = "SmallInt")]
+    explicit_small: i32,
+    #[column(type = "BigInt")]
+    explicit_big: i32,
+}
+
+#[test]
+fn integer_widths() {
+    let types: Vec<DataType> = Counter::table().columns.into_iter().map(|c| c.data_type).collect();
+    assert_eq!(
+        types,
+        [
+            DataType::BigInt,
+            DataType::SmallInt,
+            DataType::SmallInt,
+            DataType::Integer,
+            DataType::SmallInt,
+            DataType::Integer,
+            DataType::BigInt,
+            DataType::BigInt,
+            DataType::BigInt,
+            DataType::SmallInt,
+            DataType::BigInt,
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate::<Counter>().up,
+        [
+            "CREATE TABLE counters (id BIGINT PRIMARY KEY, tiny SMALLINT NOT NULL, small SMALLINT NOT NULL, regular INTEGER NOT NULL, byte SMALLINT NOT NULL, word INTEGER NOT NULL, double_word BIGINT NOT NULL, quad_word BIGINT NOT NULL, size BIGINT NOT NULL, explicit_small SMALLINT NOT NULL, explicit_big BIGINT NOT NULL);"
+        ]
+    );
+}
+
+/// A model with Postgres enum and extension types.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "members"]
+struct Member {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(type = "Custom(user_role)")]
+    role: String,
+    #[column(raw_type = "citext")]
+    email: String,
+    #[column(raw_type = "inet", nullable)]
+    last_ip: Option<String>,
+}
+
+#[test]
+fn custom_types_are_emitted_verbatim() {
+    assert_eq!(Member::table().columns[1].data_type, DataType::Custom("user_role".to_string()));
+    assert_eq!(
+        MigrationGenerator::generate_for::<Member>(Dialect::Postgres).up,
+        [
+            r#"CREATE TABLE "members" ("id" INTEGER PRIMARY KEY, "role" user_role NOT NULL, "email" citext NOT NULL, "last_ip" inet);"#
+        ]
+    );
+}
+
+/// A model with optional fields.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "profiles"]
+struct Profile {
+    #[column(primary_key = true)]
+    id: i32,
+    age: Option<i32>,
+    bio: Option<String>,
+    #[column(nullable = true)]
+    nickname: String,
+    #[column(nullable = false)]
+    website: Option<String>,
+    name: String,
+}
+
+#[test]
+fn optional_fields_are_nullable() {
+    let table = Profile::table();
+    let columns: Vec<(&str, &DataType, bool)> =
+        table.columns.iter().map(|c| (c.name.as_str(), &c.data_type, c.is_nullable)).collect();
+    assert_eq!(
+        columns,
+        [
+            ("id", &DataType::Integer, false),
+            ("age", &DataType::Integer, true),
+            ("bio", &DataType::Varchar(255), true),
+            ("nickname", &DataType::Varchar(255), true),
+            ("website", &DataType::Varchar(255), false),
+            ("name", &DataType::Varchar(255), false),
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate::<Profile>().up,
+        [
+            "CREATE TABLE profiles (id INTEGER PRIMARY KEY, age INTEGER, bio VARCHAR(255), nickname VARCHAR(255), website VARCHAR(255) NOT NULL, name VARCHAR(255) NOT NULL);"
+        ]
+    );
+}
+
+#[test]
+fn columns_saved_before_nullability_deserialize() {
+    let saved =
+        r#"{"name":"users","columns":[{"name":"id","data_type":"Integer","is_primary_key":true}]}"#;
+    let table: Table = serde_json::from_str(saved).unwrap();
+    let column = &table.columns[0];
+    assert!(!column.is_nullable);
+    assert_eq!(column.default, None);
+    assert!(!column.is_unique && !column.is_auto_increment);
+    assert_eq!(column.foreign_key, None);
+}
+
+/// A model with a default of each kind.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "settings"]
+struct Setting {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(default = "light")]
+    theme: String,
+    #[column(default = "it's")]
+    motto: String,
+    #[column(default = 10)]
+    page_size: i32,
+    #[column(default = 1.5)]
+    zoom: f64,
+    #[column(default = true)]
+    notify: bool,
+    #[column(type = "TimestampTz", default_expr = "now()")]
+    created_at: String,
+}
+
+#[test]
+fn column_defaults() {
+    assert_eq!(
+        Setting::table().columns[1].default,
+        Some(ColumnDefault::Value(Value::Text("light".to_string())))
+    );
+    assert_eq!(Setting::table().columns[6].default, Some(ColumnDefault::Expr("now()".to_string())));
+    assert_eq!(
+        MigrationGenerator::generate::<Setting>().up,
+        [
+            "CREATE TABLE settings (id INTEGER PRIMARY KEY, theme VARCHAR(255) NOT NULL DEFAULT 'light', motto VARCHAR(255) NOT NULL DEFAULT 'it''s', page_size INTEGER NOT NULL DEFAULT 10, zoom FLOAT NOT NULL DEFAULT 1.5, notify BOOLEAN NOT NULL DEFAULT true, created_at TIMESTAMPTZ NOT NULL DEFAULT now());"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Setting>(Dialect::Sqlite).up,
+        [
+            r#"CREATE TABLE "settings" ("id" INTEGER PRIMARY KEY, "theme" VARCHAR(255) NOT NULL DEFAULT 'light', "motto" VARCHAR(255) NOT NULL DEFAULT 'it''s', "page_size" INTEGER NOT NULL DEFAULT 10, "zoom" FLOAT NOT NULL DEFAULT 1.5, "notify" BOOLEAN NOT NULL DEFAULT 1, "created_at" TIMESTAMPTZ NOT NULL DEFAULT now());"#
+        ]
+    );
+}
+
+/// A model with single and multi-column unique constraints.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "accounts"]
+#[model(unique = "email, tenant_id")]
+struct Account {
+    #[column(primary_key = true)]
+    id: i32,
+    tenant_id: i32,
+    email: String,
+    #[column(unique = true)]
+    username: String,
+}
+
+#[test]
+fn unique_columns_and_constraints() {
+    let table = Account::table();
+    assert!(table.columns[3].is_unique);
+    assert_eq!(table.unique_constraints, [["email", "tenant_id"]]);
+    assert_eq!(
+        MigrationGenerator::generate::<Account>().up,
+        [
+            "CREATE TABLE accounts (id INTEGER PRIMARY KEY, tenant_id INTEGER NOT NULL, email VARCHAR(255) NOT NULL, username VARCHAR(255) NOT NULL UNIQUE, UNIQUE (email, tenant_id));"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Account>(Dialect::MySql).up,
+        [
+            "CREATE TABLE `accounts` (`id` INTEGER PRIMARY KEY, `tenant_id` INTEGER NOT NULL, `email` VARCHAR(255) NOT NULL, `username` VARCHAR(255) NOT NULL UNIQUE, UNIQUE (`email`, `tenant_id`));"
+        ]
+    );
+}
+
+/// A model whose key the database generates.
+#[derive(Model, IntoInsert)]
+#[table_name = "tickets"]
+struct Ticket {
+    #[column(primary_key = true, auto_increment = true)]
+    id: i64,
+    title: String,
+}
+
+#[test]
+fn auto_increment_keys_per_dialect() {
+    assert!(Ticket::table().columns[0].is_auto_increment);
+    assert_eq!(
+        MigrationGenerator::generate_for::<Ticket>(Dialect::Postgres).up,
+        [
+            r#"CREATE TABLE "tickets" ("id" BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY, "title" VARCHAR(255) NOT NULL);"#
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Ticket>(Dialect::MySql).up,
+        [
+            "CREATE TABLE `tickets` (`id` BIGINT AUTO_INCREMENT PRIMARY KEY, `title` VARCHAR(255) NOT NULL);"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Ticket>(Dialect::Sqlite).up,
+        [
+            r#"CREATE TABLE "tickets" ("id" INTEGER PRIMARY KEY AUTOINCREMENT, "title" VARCHAR(255) NOT NULL);"#
+        ]
+    );
+}
+
+#[test]
+fn inserting_a_model_skips_auto_increment_columns() {
+    let ticket = Ticket { id: 0, title: "Broken link".to_string() };
+    assert_eq!(
+        InsertQuery::from_model(&ticket).build(),
+        "INSERT INTO tickets (title) VALUES ('Broken link');"
+    );
+    assert_eq!(
+        InsertQuery::from_model(&ticket).value("id", 7).build(),
+        "INSERT INTO tickets (title, id) VALUES ('Broken link', 7);"
+    );
+}
+
+/// A model with a foreign key for each referential action.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "comments"]
+struct Comment {
+    #[column(primary_key = true)]
+    id: i32,
+    #[column(references = "users.id", on_delete = "cascade")]
+    user_id: i32,
+    #[column(references = "comments.id", on_delete = "set_null", nullable)]
+    parent_id: Option<i32>,
+    #[column(
+        references = "tickets.id",
+        on_delete = "set_default",
+        on_update = "cascade",
+        default = 0
+    )]
+    ticket_id: i64,
+    #[column(references = "accounts.id", on_delete = "restrict", on_update = "no_action")]
+    account_id: i32,
+    #[column(references = "members.id")]
+    member_id: i32,
+}
+
+#[test]
+fn foreign_keys_and_referential_actions() {
+    assert_eq!(
+        Comment::table().columns[1].foreign_key,
+        Some(ForeignKey {
+            table: "users".to_string(),
+            column: "id".to_string(),
+            on_delete: Some(ReferentialAction::Cascade),
+            on_update: None,
+        })
+    );
+    assert_eq!(
+        MigrationGenerator::generate::<Comment>().up,
+        [
+            "CREATE TABLE comments (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, parent_id INTEGER, ticket_id BIGINT NOT NULL DEFAULT 0, account_id INTEGER NOT NULL, member_id INTEGER NOT NULL, FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE, FOREIGN KEY (parent_id) REFERENCES comments(id) ON DELETE SET NULL, FOREIGN KEY (ticket_id) REFERENCES tickets(id) ON DELETE SET DEFAULT ON UPDATE CASCADE, FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE RESTRICT ON UPDATE NO ACTION, FOREIGN KEY (member_id) REFERENCES members(id));"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Comment>(Dialect::MySql).up,
+        [
+            "CREATE TABLE `comments` (`id` INTEGER PRIMARY KEY, `user_id` INTEGER NOT NULL, `parent_id` INTEGER, `ticket_id` BIGINT NOT NULL DEFAULT 0, `account_id` INTEGER NOT NULL, `member_id` INTEGER NOT NULL, FOREIGN KEY (`user_id`) REFERENCES `users`(`id`) ON DELETE CASCADE, FOREIGN KEY (`parent_id`) REFERENCES `comments`(`id`) ON DELETE SET NULL, FOREIGN KEY (`ticket_id`) REFERENCES `tickets`(`id`) ON DELETE SET DEFAULT ON UPDATE CASCADE, FOREIGN KEY (`account_id`) REFERENCES `accounts`(`id`) ON DELETE RESTRICT ON UPDATE NO ACTION, FOREIGN KEY (`member_id`) REFERENCES `members`(`id`));"
+        ]
+    );
+}
+
+/// A model with indexes declared on a column and on the struct.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "logins"]
+#[model(index(name = "idx_logins_email_tenant", columns = "email, tenant_id", unique))]
+#[model(index(columns = "payload", method = "gin"))]
+struct Login {
+    #[column(primary_key = true)]
+    id: i32,
+    tenant_id: i32,
+    #[column(index)]
+    email: String,
+    #[column(type = "Jsonb")]
+    payload: serde_json::Value,
+}
+
+#[test]
+fn indexes_are_created_after_the_table() {
+    let table = Login::table();
+    assert_eq!(
+        table.indexes,
+        [
+            Index {
+                name: "idx_logins_email".to_string(),
+                columns: vec!["email".to_string()],
+                is_unique: false,
+                method: None,
+            },
+            Index {
+                name: "idx_logins_email_tenant".to_string(),
+                columns: vec!["email".to_string(), "tenant_id".to_string()],
+                is_unique: true,
+                method: None,
+            },
+            Index {
+                name: "idx_logins_payload".to_string(),
+                columns: vec!["payload".to_string()],
+                is_unique: false,
+                method: Some(IndexMethod::Gin),
+            },
+        ]
+    );
+
+    let migration = MigrationGenerator::generate::<Login>();
+    assert_eq!(
+        migration.up,
+        [
+            "CREATE TABLE logins (id INTEGER PRIMARY KEY, tenant_id INTEGER NOT NULL, email VARCHAR(255) NOT NULL, payload JSONB NOT NULL);",
+            "CREATE INDEX idx_logins_email ON logins (email);",
+            "CREATE UNIQUE INDEX idx_logins_email_tenant ON logins (email, tenant_id);",
+            "CREATE INDEX idx_logins_payload ON logins USING GIN (payload);",
+        ]
+    );
+    assert_eq!(
+        migration.down,
+        [
+            "DROP INDEX IF EXISTS idx_logins_payload;",
+            "DROP INDEX IF EXISTS idx_logins_email_tenant;",
+            "DROP INDEX IF EXISTS idx_logins_email;",
+            "DROP TABLE IF EXISTS logins;",
+        ]
+    );
+
+    // MySQL names the table an index is dropped from, and has no GIN indexes
+    let migration = MigrationGenerator::generate_for::<Login>(Dialect::MySql);
+    assert_eq!(
+        migration.up,
+        [
+            "CREATE TABLE `logins` (`id` INTEGER PRIMARY KEY, `tenant_id` INTEGER NOT NULL, `email` VARCHAR(255) NOT NULL, `payload` JSON NOT NULL);",
+            "CREATE INDEX `idx_logins_email` ON `logins` (`email`);",
+            "CREATE UNIQUE INDEX `idx_logins_email_tenant` ON `logins` (`email`, `tenant_id`);",
+            "CREATE INDEX `idx_logins_payload` ON `logins` (`payload`);",
+        ]
+    );
+    assert_eq!(
+        migration.down,
+        [
+            "DROP INDEX `idx_logins_payload` ON `logins`;",
+            "DROP INDEX `idx_logins_email_tenant` ON `logins`;",
+            "DROP INDEX `idx_logins_email` ON `logins`;",
+            "DROP TABLE IF EXISTS `logins`;",
+        ]
+    );
+}
+
+/// A join table keyed by both of its references.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "memberships"]
+struct Membership {
+    #[column(primary_key = true)]
+    team_id: i32,
+    role: String,
+    #[column(primary_key = true)]
+    user_id: i32,
+}
+
+/// A table without a primary key.
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "audit_log"]
+struct AuditEntry {
+    message: String,
+}
+
+#[test]
+fn composite_primary_keys_are_table_constraints() {
+    let table = Membership::table();
+    let key: Vec<&str> = table.primary_key().iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(key, ["team_id", "user_id"]);
+    assert_eq!(
+        MigrationGenerator::generate::<Membership>().up,
+        [
+            "CREATE TABLE memberships (team_id INTEGER NOT NULL, role VARCHAR(255) NOT NULL, user_id INTEGER NOT NULL, PRIMARY KEY (team_id, user_id));"
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Membership>(Dialect::Sqlite).up,
+        [
+            r#"CREATE TABLE "memberships" ("team_id" INTEGER NOT NULL, "role" VARCHAR(255) NOT NULL, "user_id" INTEGER NOT NULL, PRIMARY KEY ("team_id", "user_id"));"#
+        ]
+    );
+
+    // A single key column keeps its inline marker
+    assert_eq!(User::table().primary_key().len(), 1);
+    assert!(MigrationGenerator::generate::<User>().up[0]
+        .contains("id INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,"));
+}
+
+#[test]
+fn tables_without_a_primary_key() {
+    assert!(AuditEntry::table().primary_key().is_empty());
+    assert_eq!(
+        MigrationGenerator::generate::<AuditEntry>().up,
+        ["CREATE TABLE audit_log (message VARCHAR(255) NOT NULL);"]
+    );
+}
+
+/// Returns a `VARCHAR(255)` column with no constraints.
+fn varchar(name: &str) -> Column {
+    Column {
+        name: name.to_string(),
+        data_type: DataType::Varchar(255),
+        is_primary_key: false,
+        is_nullable: false,
+        default: None,
+        is_unique: false,
+        is_auto_increment: false,
+        foreign_key: None,
+    }
+}
+
+#[test]
+fn diff_adds_and_drops_columns() {
+    let old = User::table();
+    let mut new = User::table();
+    new.columns.retain(|column| column.name != "is_active");
+    new.columns.push(Column { is_nullable: true, ..varchar("nickname") });
+    new.columns.push(Column {
+        default: Some(ColumnDefault::Value(Value::Text("en".to_string()))),
+        ..varchar("locale")
+    });
+
+    let migration = MigrationGenerator::diff(&old, &new);
+    assert_eq!(
+        migration.up,
+        [
+            "ALTER TABLE users DROP COLUMN is_active;",
+            "ALTER TABLE users ADD COLUMN nickname VARCHAR(255);",
+            "ALTER TABLE users ADD COLUMN locale VARCHAR(255) NOT NULL DEFAULT 'en';",
+        ]
+    );
+    assert_eq!(
+        migration.down,
+        [
+            "ALTER TABLE users DROP COLUMN locale;",
+            "ALTER TABLE users DROP COLUMN nickname;",
+            "ALTER TABLE users ADD COLUMN is_active BOOLEAN NOT NULL;",
+        ]
+    );
+}
+
+#[test]
+fn diff_alters_changed_columns() {
+    let old = User::table();
+    let mut new = User::table();
+    // The type changes before the new default is set
+    new.columns[1].data_type = DataType::Text;
+    new.columns[1].default = Some(ColumnDefault::Value(Value::Text("anonymous".to_string())));
+    new.columns[2].is_nullable = true;
+
+    let migration = MigrationGenerator::diff(&old, &new);
+    assert_eq!(
+        migration.up,
+        [
+            "ALTER TABLE users ALTER COLUMN name TYPE TEXT;",
+            "ALTER TABLE users ALTER COLUMN name SET DEFAULT 'anonymous';",
+            "ALTER TABLE users ALTER COLUMN email DROP NOT NULL;",
+        ]
+    );
+    assert_eq!(
+        migration.down,
+        [
+            "ALTER TABLE users ALTER COLUMN email SET NOT NULL;",
+            "ALTER TABLE users ALTER COLUMN name DROP DEFAULT;",
+            "ALTER TABLE users ALTER COLUMN name TYPE VARCHAR(100);",
+        ]
+    );
+}
+
+#[test]
+fn diff_of_equal_tables_is_empty() {
+    let old = User::table();
+    let mut reordered = User::table();
+    reordered.columns.reverse();
+
+    let migration = MigrationGenerator::diff(&old, &reordered);
+    assert!(migration.up.is_empty());
+    assert!(migration.down.is_empty());
+}
+
+/// The first version of a model, before a column was added.
+mod v1 {
+    use rusty_orm_macros::Model;
+
+    #[allow(dead_code)]
+    #[derive(Model)]
+    #[table_name = "notes"]
+    pub struct Note {
+        #[column(primary_key = true)]
+        id: i32,
+        body: String,
+    }
+}
+
+/// The second version of the model.
+mod v2 {
+    use rusty_orm_macros::Model;
+
+    #[allow(dead_code)]
+    #[derive(Model)]
+    #[table_name = "notes"]
+    pub struct Note {
+        #[column(primary_key = true)]
+        id: i32,
+        body: String,
+        pinned: Option<bool>,
+    }
+}
+
+#[test]
+fn snapshots_turn_later_migrations_into_diffs() {
+    let dir = tempfile::tempdir().unwrap();
+    let snapshots = dir.path().to_str().unwrap();
+
+    let first = MigrationGenerator::generate_with_snapshot::<v1::Note>(snapshots).unwrap();
+    assert_eq!(
+        first.up,
+        ["CREATE TABLE notes (id INTEGER PRIMARY KEY, body VARCHAR(255) NOT NULL);"]
+    );
+
+    // The snapshot is the table as readable JSON, named after it
+    let json = std::fs::read_to_string(dir.path().join("notes.json")).unwrap();
+    assert!(json.contains("\n  \"name\": \"notes\""));
+    assert_eq!(serde_json::from_str::<Table>(&json).unwrap(), v1::Note::table());
+
+    let second = MigrationGenerator::generate_with_snapshot::<v2::Note>(snapshots).unwrap();
+    assert_eq!(second.up, ["ALTER TABLE notes ADD COLUMN pinned BOOLEAN;"]);
+    assert_eq!(second.down, ["ALTER TABLE notes DROP COLUMN pinned;"]);
+
+    let unchanged = MigrationGenerator::generate_with_snapshot::<v2::Note>(snapshots).unwrap();
+    assert!(unchanged.up.is_empty());
+}
+
+#[test]
+fn malformed_snapshots_fail_to_parse() {
+    let dir = tempfile::tempdir().unwrap();
+    let snapshot = dir.path().join("notes.json");
+    std::fs::write(&snapshot, "{\"name\": \"notes\", \"columns\": [").unwrap();
+
+    let error =
+        MigrationGenerator::generate_with_snapshot::<v2::Note>(dir.path().to_str().unwrap())
+            .unwrap_err();
+    assert!(matches!(
+        &error,
+        OrmError::Migration(MigrationError::InvalidSnapshot { path, .. })
+            if *path == snapshot.display().to_string()
+    ));
+    assert!(error
+        .to_string()
+        .starts_with(&format!("failed to parse snapshot {}: ", snapshot.display())));
+    // The snapshot is left for the user to fix
+    assert!(std::fs::read_to_string(&snapshot).unwrap().ends_with('['));
+}
+
+/// Tables referencing each other in a chain: comments to posts to users.
+mod blog {
+    use rusty_orm_macros::Model;
+
+    #[allow(dead_code)]
+    #[derive(Model)]
+    #[table_name = "users"]
+    pub struct User {
+        #[column(primary_key = true)]
+        id: i32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Model)]
+    #[table_name = "posts"]
+    pub struct Post {
+        #[column(primary_key = true)]
+        id: i32,
+        #[column(references = "users.id")]
+        user_id: i32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Model)]
+    #[table_name = "comments"]
+    pub struct Comment {
+        #[column(primary_key = true)]
+        id: i32,
+        #[column(references = "posts.id")]
+        post_id: i32,
+        #[column(references = "users.id")]
+        user_id: i32,
+    }
+}
+
+#[test]
+fn generate_all_creates_referenced_tables_first() {
+    let tables = [blog::Comment::table(), blog::Post::table(), blog::User::table()];
+    let migration = MigrationGenerator::generate_all(&tables).unwrap();
+    assert_eq!(
+        migration.up,
+        [
+            "CREATE TABLE users (id INTEGER PRIMARY KEY);",
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, FOREIGN KEY (user_id) REFERENCES users(id));",
+            "CREATE TABLE comments (id INTEGER PRIMARY KEY, post_id INTEGER NOT NULL, user_id INTEGER NOT NULL, FOREIGN KEY (post_id) REFERENCES posts(id), FOREIGN KEY (user_id) REFERENCES users(id));",
+        ]
+    );
+    assert_eq!(
+        migration.down,
+        [
+            "DROP TABLE IF EXISTS comments;",
+            "DROP TABLE IF EXISTS posts;",
+            "DROP TABLE IF EXISTS users;",
+        ]
+    );
+
+    // A table referencing itself, or tables outside the set, isn't a cycle
+    assert!(MigrationGenerator::generate_all(&[Comment::table()]).is_ok());
+}
+
+#[test]
+fn generate_all_reports_circular_references() {
+    let mut users = blog::User::table();
+    users.columns.push(Column {
+        foreign_key: Some(ForeignKey {
+            table: "comments".to_string(),
+            column: "id".to_string(),
+            on_delete: None,
+            on_update: None,
+        }),
+        ..varchar("pinned_comment_id")
+    });
+    let tables = [blog::Comment::table(), blog::Post::table(), users];
+
+    let error = MigrationGenerator::generate_all(&tables).unwrap_err();
+    assert_eq!(
+        error,
+        SchemaError::CircularReference {
+            tables: vec!["comments".to_string(), "posts".to_string(), "users".to_string()]
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "foreign keys between tables `comments`, `posts`, `users` form a cycle"
+    );
+}
+
+/// Returns the sorted names of the files in a directory.
+fn file_names(dir: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn migrations_are_saved_as_sql_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let migration = MigrationGenerator::generate::<Login>();
+
+    MigrationGenerator::save_migration_sql(&migration, "0001_logins", path).unwrap();
+    assert_eq!(file_names(dir.path()), ["0001_logins.down.sql", "0001_logins.up.sql"]);
+
+    // One statement per line, ending with a newline
+    let up = std::fs::read_to_string(dir.path().join("0001_logins.up.sql")).unwrap();
+    assert_eq!(up.lines().collect::<Vec<_>>(), migration.up);
+    assert!(up.ends_with(";\n"));
+    let down = std::fs::read_to_string(dir.path().join("0001_logins.down.sql")).unwrap();
+    assert_eq!(
+        down,
+        "DROP INDEX IF EXISTS idx_logins_payload;\nDROP INDEX IF EXISTS idx_logins_email_tenant;\nDROP INDEX IF EXISTS idx_logins_email;\nDROP TABLE IF EXISTS logins;\n"
+    );
+}
+
+#[test]
+fn migration_formats_select_the_files_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let migration = MigrationGenerator::generate::<User>();
+
+    MigrationGenerator::save_migration_as(
+        &migration,
+        "0001_users",
+        path,
+        MigrationFormat::Json,
+        false,
+    )
+    .unwrap();
+    assert_eq!(file_names(dir.path()), ["0001_users.json"]);
+
+    MigrationGenerator::save_migration_as(
+        &migration,
+        "0002_users",
+        path,
+        MigrationFormat::Both,
+        false,
+    )
+    .unwrap();
+    assert_eq!(
+        file_names(dir.path()),
+        ["0001_users.json", "0002_users.down.sql", "0002_users.json", "0002_users.up.sql"]
+    );
+}
+
+#[test]
+fn saved_migrations_are_not_overwritten_unless_asked() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let up_file = dir.path().join("0001_users.up.sql");
+    MigrationGenerator::save_migration_sql(
+        &MigrationGenerator::generate::<User>(),
+        "0001_users",
+        path,
+    )
+    .unwrap();
+
+    let changed = MigrationGenerator::generate::<Login>();
+    let error = MigrationGenerator::save_migration_sql(&changed, "0001_users", path).unwrap_err();
+    assert!(matches!(
+        &error,
+        OrmError::Migration(MigrationError::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists
+    ));
+    assert!(std::fs::read_to_string(&up_file).unwrap().contains("CREATE TABLE users"));
+
+    MigrationGenerator::save_migration_as(&changed, "0001_users", path, MigrationFormat::Sql, true)
+        .unwrap();
+    assert!(std::fs::read_to_string(&up_file).unwrap().contains("CREATE TABLE logins"));
+}
+
+#[test]
+fn sequence_names_follow_the_saved_migrations() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let name = |name| MigrationGenerator::prefixed_name(name, path, MigrationNaming::Sequence);
+
+    assert_eq!(name("create_users").unwrap(), "0001_create_users");
+    MigrationGenerator::save_migration(
+        &MigrationGenerator::generate::<User>(),
+        "0001_create_users",
+        path,
+    )
+    .unwrap();
+    MigrationGenerator::save_migration_sql(
+        &MigrationGenerator::generate::<Login>(),
+        "0007_create_logins",
+        path,
+    )
+    .unwrap();
+    assert_eq!(name("add_email").unwrap(), "0008_add_email");
+}
+
+#[test]
+fn timestamp_names_sort_after_the_saved_migrations() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let name = |name| MigrationGenerator::prefixed_name(name, path, MigrationNaming::Timestamp);
+
+    let first = name("create_users").unwrap();
+    let (prefix, rest) = first.split_once('_').unwrap();
+    assert_eq!(prefix.len(), 14);
+    assert!(prefix.starts_with("20"));
+    assert_eq!(rest, "create_users");
+
+    // A saved migration from the same second, or a clock running behind,
+    // bumps the prefix rather than colliding with it
+    MigrationGenerator::save_migration(
+        &MigrationGenerator::generate::<User>(),
+        "99990101000000_future",
+        path,
+    )
+    .unwrap();
+    assert_eq!(name("add_email").unwrap(), "99990101000001_add_email");
+}
+
+#[test]
+fn timestamp_bumps_roll_over_into_the_next_day() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let name = |name| MigrationGenerator::prefixed_name(name, path, MigrationNaming::Timestamp);
+    let users = MigrationGenerator::generate::<User>();
+
+    MigrationGenerator::save_migration(&users, "29991231235959_create_users", path).unwrap();
+    assert_eq!(name("add_email").unwrap(), "30000101000000_add_email");
+
+    // A prefix bumped past a valid time before is normalized
+    MigrationGenerator::save_migration(&users, "30000228235960_add_email", path).unwrap();
+    assert_eq!(name("add_logins").unwrap(), "30000301000001_add_logins");
+}
+
+#[test]
+fn sequence_numbers_widen_past_four_digits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let name = |name| MigrationGenerator::prefixed_name(name, path, MigrationNaming::Sequence);
+
+    MigrationGenerator::save_migration(&MigrationGenerator::generate::<User>(), "9999_users", path)
+        .unwrap();
+    let next = name("logins").unwrap();
+    assert_eq!(next, "10000_logins");
+    MigrationGenerator::save_migration(&MigrationGenerator::generate::<Login>(), &next, path)
+        .unwrap();
+
+    let loaded = MigrationGenerator::load_migrations(path).unwrap();
+    let names: Vec<&str> = loaded.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["9999_users", "10000_logins"]);
+}
+
+#[test]
+fn saved_migrations_load_in_prefix_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    let users = MigrationGenerator::generate::<User>();
+    let logins = MigrationGenerator::generate::<Login>();
+    let notes = MigrationGenerator::generate::<v2::Note>();
+    MigrationGenerator::save_migration(&notes, "0010_create_notes", path).unwrap();
+    MigrationGenerator::save_migration_sql(&logins, "0002_create_logins", path).unwrap();
+    MigrationGenerator::save_migration(&users, "0001_create_users", path).unwrap();
+    std::fs::write(dir.path().join("README.md"), "Not a migration").unwrap();
+
+    let loaded = MigrationGenerator::load_migrations(path).unwrap();
+    let names: Vec<&str> = loaded.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["0001_create_users", "0002_create_logins", "0010_create_notes"]);
+    assert_eq!(loaded[0].1.up, users.up);
+    // SQL files load as a single script each
+    assert_eq!(loaded[1].1.up, [logins.up_sql()]);
+    assert_eq!(loaded[1].1.down, [logins.down_sql()]);
+    assert_eq!(loaded[2].1.down, notes.down);
+}
+
+#[test]
+fn sqlite_integer_primary_keys_alias_the_rowid() {
+    let mut table = User::table();
+    table.columns[0].is_auto_increment = false;
+    assert_eq!(
+        MigrationGenerator::generate_all_for(&[table], Dialect::Sqlite).unwrap().up,
+        [
+            r#"CREATE TABLE "users" ("id" INTEGER PRIMARY KEY, "name" VARCHAR(100) NOT NULL, "email" VARCHAR(150) NOT NULL, "is_active" BOOLEAN NOT NULL);"#
+        ]
+    );
+}
+
+#[test]
+fn diff_alters_columns_per_dialect() {
+    let old = User::table();
+    let mut new = User::table();
+    new.columns[1].data_type = DataType::Text;
+
+    let postgres = MigrationGenerator::diff_for(&old, &new, Dialect::Postgres);
+    assert_eq!(postgres.up, [r#"ALTER TABLE "users" ALTER COLUMN "name" TYPE TEXT;"#]);
+    assert_eq!(postgres.down, [r#"ALTER TABLE "users" ALTER COLUMN "name" TYPE VARCHAR(100);"#]);
+
+    let mysql = MigrationGenerator::diff_for(&old, &new, Dialect::MySql);
+    assert_eq!(mysql.up, ["ALTER TABLE `users` MODIFY COLUMN `name` TEXT NOT NULL;"]);
+    assert_eq!(mysql.down, ["ALTER TABLE `users` MODIFY COLUMN `name` VARCHAR(100) NOT NULL;"]);
+}
+
+#[test]
+fn sqlite_diff_rebuilds_tables_with_altered_columns() {
+    let old = User::table();
+    let mut new = User::table();
+    new.columns[1].data_type = DataType::Text;
+    new.columns.retain(|column| column.name != "is_active");
+
+    let migration = MigrationGenerator::diff_for(&old, &new, Dialect::Sqlite);
+    assert_eq!(
+        migration.up,
+        [
+            r#"CREATE TABLE "new_users" ("id" INTEGER PRIMARY KEY AUTOINCREMENT, "name" TEXT NOT NULL, "email" VARCHAR(150) NOT NULL);"#,
+            r#"INSERT INTO "new_users" ("id", "name", "email") SELECT "id", "name", "email" FROM "users";"#,
+            r#"DROP TABLE "users";"#,
+            r#"ALTER TABLE "new_users" RENAME TO "users";"#,
+        ]
+    );
+    assert_eq!(
+        migration.down,
+        [
+            r#"CREATE TABLE "new_users" ("id" INTEGER PRIMARY KEY AUTOINCREMENT, "name" VARCHAR(100) NOT NULL, "email" VARCHAR(150) NOT NULL, "is_active" BOOLEAN NOT NULL);"#,
+            r#"INSERT INTO "new_users" ("id", "name", "email") SELECT "id", "name", "email" FROM "users";"#,
+            r#"DROP TABLE "users";"#,
+            r#"ALTER TABLE "new_users" RENAME TO "users";"#,
+        ]
+    );
+}
+
+#[test]
+fn sqlite_diff_adds_columns_without_rebuilding() {
+    let old = User::table();
+    let mut new = User::table();
+    new.columns.push(Column { is_nullable: true, ..varchar("nickname") });
+
+    let migration = MigrationGenerator::diff_for(&old, &new, Dialect::Sqlite);
+    assert_eq!(migration.up, [r#"ALTER TABLE "users" ADD COLUMN "nickname" VARCHAR(255);"#]);
+    assert_eq!(migration.down, [r#"ALTER TABLE "users" DROP COLUMN "nickname";"#]);
+}
+
+#[test]
+fn if_not_exists_applies_to_tables_and_indexes() {
+    let options = MigrationOptions::new().if_not_exists();
+    let migration = MigrationGenerator::generate_with_options::<Login>(&options);
+    assert_eq!(
+        migration.up,
+        [
+            "CREATE TABLE IF NOT EXISTS logins (id INTEGER PRIMARY KEY, tenant_id INTEGER NOT NULL, email VARCHAR(255) NOT NULL, payload JSONB NOT NULL);",
+            "CREATE INDEX IF NOT EXISTS idx_logins_email ON logins (email);",
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_logins_email_tenant ON logins (email, tenant_id);",
+            "CREATE INDEX IF NOT EXISTS idx_logins_payload ON logins USING GIN (payload);",
+        ]
+    );
+    assert_eq!(
+        migration.down,
+        [
+            "DROP INDEX IF EXISTS idx_logins_payload;",
+            "DROP INDEX IF EXISTS idx_logins_email_tenant;",
+            "DROP INDEX IF EXISTS idx_logins_email;",
+            "DROP TABLE IF EXISTS logins;",
+        ]
+    );
+}
+
+#[test]
+fn if_not_exists_applies_per_dialect() {
+    let options = MigrationOptions::new().dialect(Dialect::MySql).if_not_exists();
+    let all = MigrationGenerator::generate_all_with_options(&[User::table()], &options).unwrap();
+    assert_eq!(
+        all.up,
+        ["CREATE TABLE IF NOT EXISTS `users` (`id` INTEGER AUTO_INCREMENT PRIMARY KEY, `name` VARCHAR(100) NOT NULL, `email` VARCHAR(150) NOT NULL, `is_active` BOOLEAN NOT NULL);"]
+    );
+
+    let options = MigrationOptions::new().dialect(Dialect::Sqlite).if_not_exists();
+    let migration = MigrationGenerator::generate_with_options::<Login>(&options);
+    assert_eq!(
+        migration.up,
+        [
+            r#"CREATE TABLE IF NOT EXISTS "logins" ("id" INTEGER PRIMARY KEY, "tenant_id" INTEGER NOT NULL, "email" VARCHAR(255) NOT NULL, "payload" TEXT NOT NULL);"#,
+            r#"CREATE INDEX IF NOT EXISTS "idx_logins_email" ON "logins" ("email");"#,
+            r#"CREATE UNIQUE INDEX IF NOT EXISTS "idx_logins_email_tenant" ON "logins" ("email", "tenant_id");"#,
+            r#"CREATE INDEX IF NOT EXISTS "idx_logins_payload" ON "logins" ("payload");"#,
+        ]
+    );
+}
+
+#[test]
+fn tables_are_created_unconditionally_by_default() {
+    for migration in [
+        MigrationGenerator::generate::<Login>(),
+        MigrationGenerator::generate_for::<Login>(Dialect::Postgres),
+        MigrationGenerator::generate_all(&[Login::table()]).unwrap(),
+    ] {
+        assert!(migration.up.iter().all(|sql| !sql.contains("IF NOT EXISTS")));
+    }
+}
+
+#[test]
+fn column_definitions_render_each_combination_of_constraints() {
+    for is_nullable in [false, true] {
+        for has_default in [false, true] {
+            for is_unique in [false, true] {
+                let column = Column {
+                    is_nullable,
+                    default: has_default
+                        .then(|| ColumnDefault::Value(Value::Text("x".to_string()))),
+                    is_unique,
+                    ..varchar("code")
+                };
+                let mut expected = "code VARCHAR(255)".to_string();
+                if !is_nullable {
+                    expected.push_str(" NOT NULL");
+                }
+                if has_default {
+                    expected.push_str(" DEFAULT 'x'");
+                }
+                if is_unique {
+                    expected.push_str(" UNIQUE");
+                }
+                assert_eq!(render_column_def(&column, None), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn primary_key_definitions_per_dialect() {
+    let id = Column {
+        data_type: DataType::BigInt,
+        is_primary_key: true,
+        is_auto_increment: true,
+        // Implied by the primary key
+        is_unique: true,
+        ..varchar("id")
+    };
+    assert_eq!(render_column_def(&id, None), "id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY");
+    assert_eq!(
+        render_column_def(&id, Some(Dialect::Postgres)),
+        r#""id" BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY"#
+    );
+    assert_eq!(
+        render_column_def(&id, Some(Dialect::MySql)),
+        "`id` BIGINT AUTO_INCREMENT PRIMARY KEY"
+    );
+    assert_eq!(
+        render_column_def(&id, Some(Dialect::Sqlite)),
+        r#""id" INTEGER PRIMARY KEY AUTOINCREMENT"#
+    );
+
+    let code = Column { is_auto_increment: false, ..id };
+    assert_eq!(render_column_def(&code, Some(Dialect::Sqlite)), r#""id" BIGINT PRIMARY KEY"#);
+}
+
+#[test]
+fn expression_defaults_render_verbatim() {
+    let created_at = Column {
+        data_type: DataType::DateTime,
+        default: Some(ColumnDefault::Expr("CURRENT_TIMESTAMP".to_string())),
+        ..varchar("created_at")
+    };
+    assert_eq!(
+        render_column_def(&created_at, Some(Dialect::MySql)),
+        "`created_at` TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP"
+    );
+
+    let is_active = Column {
+        data_type: DataType::Boolean,
+        default: Some(ColumnDefault::Value(Value::Bool(true))),
+        ..varchar("is_active")
+    };
+    assert_eq!(
+        render_column_def(&is_active, Some(Dialect::Postgres)),
+        r#""is_active" BOOLEAN NOT NULL DEFAULT TRUE"#
+    );
+    assert_eq!(
+        render_column_def(&is_active, Some(Dialect::Sqlite)),
+        r#""is_active" BOOLEAN NOT NULL DEFAULT 1"#
+    );
+}
+
+#[test]
+fn table_constraints_follow_the_columns() {
+    let table = Table {
+        name: "enrollments".to_string(),
+        schema: None,
+        columns: vec![
+            Column {
+                data_type: DataType::Integer,
+                is_primary_key: true,
+                foreign_key: Some(ForeignKey {
+                    table: "students".to_string(),
+                    column: "id".to_string(),
+                    on_delete: Some(ReferentialAction::Cascade),
+                    on_update: None,
+                }),
+                ..varchar("student_id")
+            },
+            Column { data_type: DataType::Integer, is_primary_key: true, ..varchar("course_id") },
+            varchar("term"),
+            Column { data_type: DataType::Integer, ..varchar("seat") },
+        ],
+        unique_constraints: vec![vec!["term".to_string(), "seat".to_string()]],
+        indexes: Vec::new(),
+    };
+    assert_eq!(
+        MigrationGenerator::generate_all(&[table]).unwrap().up,
+        ["CREATE TABLE enrollments (student_id INTEGER NOT NULL, course_id INTEGER NOT NULL, term VARCHAR(255) NOT NULL, seat INTEGER NOT NULL, PRIMARY KEY (student_id, course_id), UNIQUE (term, seat), FOREIGN KEY (student_id) REFERENCES students(id) ON DELETE CASCADE);"]
+    );
+}
+
+/// Returns the view of the active users, with a bound value inlined.
+fn active_users() -> ViewBuilder {
+    ViewBuilder::new(
+        "active_users",
+        SelectQuery::<User>::new()
+            .select(&["id", "name"])
+            .filter("is_active = true")
+            .filter_ne("name", "O'Brien"),
+    )
+}
+
+#[test]
+fn view_is_created_from_a_query_and_dropped() {
+    let migration = MigrationGenerator::generate_view(&active_users().build());
+    assert_eq!(
+        migration.up,
+        ["CREATE VIEW active_users AS SELECT id, name FROM users WHERE (is_active = true) AND name <> 'O''Brien';"]
+    );
+    assert_eq!(migration.down, ["DROP VIEW IF EXISTS active_users;"]);
+
+    let migration = MigrationGenerator::generate_view_for(&active_users().build(), Dialect::MySql);
+    assert_eq!(
+        migration.up,
+        ["CREATE VIEW `active_users` AS SELECT `id`, `name` FROM `users` WHERE (is_active = true) AND `name` <> 'O''Brien';"]
+    );
+    assert_eq!(migration.down, ["DROP VIEW IF EXISTS `active_users`;"]);
+}
+
+#[test]
+fn materialized_views_are_only_created_on_postgres() {
+    let view: View = active_users().materialized().build();
+    let postgres = MigrationGenerator::generate_view_for(&view, Dialect::Postgres);
+    assert_eq!(
+        postgres.up,
+        [
+            r#"CREATE MATERIALIZED VIEW "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#
+        ]
+    );
+    assert_eq!(postgres.down, [r#"DROP MATERIALIZED VIEW IF EXISTS "active_users";"#]);
+
+    let sqlite = MigrationGenerator::generate_view_for(&view, Dialect::Sqlite);
+    assert_eq!(
+        sqlite.up,
+        [
+            r#"CREATE VIEW "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#
+        ]
+    );
+    assert_eq!(sqlite.down, [r#"DROP VIEW IF EXISTS "active_users";"#]);
+}
+
+#[test]
+fn or_replace_drops_views_that_cannot_be_replaced() {
+    let view = active_users().or_replace().build();
+    assert_eq!(
+        MigrationGenerator::generate_view_for(&view, Dialect::Postgres).up,
+        [
+            r#"CREATE OR REPLACE VIEW "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_view_for(&view, Dialect::Sqlite).up,
+        [
+            r#"DROP VIEW IF EXISTS "active_users";"#,
+            r#"CREATE VIEW "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#,
+        ]
+    );
+
+    let materialized = active_users().or_replace().materialized().build();
+    assert_eq!(
+        MigrationGenerator::generate_view_for(&materialized, Dialect::Postgres).up,
+        [
+            r#"DROP MATERIALIZED VIEW IF EXISTS "active_users";"#,
+            r#"CREATE MATERIALIZED VIEW "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#,
+        ]
+    );
+}
+
+#[test]
+fn if_not_exists_applies_to_views_that_support_it() {
+    let view = active_users().build();
+    let options = |dialect| MigrationOptions::new().dialect(dialect).if_not_exists();
+    let up = |dialect| MigrationGenerator::generate_view_with_options(&view, &options(dialect)).up;
+    assert_eq!(
+        up(Dialect::Sqlite),
+        [
+            r#"CREATE VIEW IF NOT EXISTS "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#
+        ]
+    );
+    // Neither Postgres plain views nor MySQL views take IF NOT EXISTS
+    assert_eq!(
+        up(Dialect::Postgres),
+        [
+            r#"CREATE VIEW "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#
+        ]
+    );
+    assert_eq!(
+        up(Dialect::MySql),
+        ["CREATE VIEW `active_users` AS SELECT `id`, `name` FROM `users` WHERE (is_active = true) AND `name` <> 'O''Brien';"]
+    );
+
+    let materialized = active_users().materialized().build();
+    assert_eq!(
+        MigrationGenerator::generate_view_with_options(&materialized, &options(Dialect::Postgres))
+            .up,
+        [
+            r#"CREATE MATERIALIZED VIEW IF NOT EXISTS "active_users" AS SELECT "id", "name" FROM "users" WHERE (is_active = true) AND "name" <> 'O''Brien';"#
+        ]
+    );
+}
+
+#[test]
+fn migrations_round_trip_through_json() {
+    let migration = MigrationGenerator::generate::<User>()
+        .with_up_statement("INSERT INTO users (name, email, is_active) VALUES ('admin', 'admin@example.com', TRUE);")
+        .with_down_statement("DELETE FROM users;");
+    let json = serde_json::to_string(&migration).unwrap();
+    let loaded: Migration = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.up, migration.up);
+    assert_eq!(loaded.down, migration.down);
+    assert_eq!(loaded.checksum, migration.checksum);
+}
+
+#[test]
+fn migrations_saved_with_a_single_string_still_load() {
+    let old: Migration = serde_json::from_str(
+        r#"{"up": "CREATE TABLE users (id INTEGER);\nCREATE INDEX idx ON users (id);", "down": "DROP TABLE users;"}"#,
+    )
+    .unwrap();
+    assert_eq!(old.up, ["CREATE TABLE users (id INTEGER);\nCREATE INDEX idx ON users (id);"]);
+    assert_eq!(old.down, ["DROP TABLE users;"]);
+
+    // Saving the statements as a list keeps the checksum
+    let new = Migration {
+        up: vec![
+            "CREATE TABLE users (id INTEGER);".to_string(),
+            "CREATE INDEX idx ON users (id);".to_string(),
+        ],
+        down: vec!["DROP TABLE users;".to_string()],
+        checksum: None,
+    };
+    assert_eq!(new.compute_checksum(), old.compute_checksum());
+}
@@ -0,0 +1,651 @@
+use rusty_orm::dialect::Dialect;
+use rusty_orm::error::OrmError;
+use rusty_orm::executor::{Connection, MockConnection};
+use rusty_orm::migration::{
+    is_destructive, Migration, MigrationError, MigrationGenerator, MigrationRunner,
+};
+use rusty_orm::model::Value;
+use rusty_orm::row::Row;
+use rusty_orm_macros::embed_migrations;
+
+/// Returns a migration creating and dropping a table.
+fn create_table(table: &str) -> Migration {
+    Migration {
+        up: vec![format!("CREATE TABLE {} (id INTEGER PRIMARY KEY);", table)],
+        down: vec![format!("DROP TABLE {};", table)],
+        checksum: None,
+    }
+}
+
+/// Returns the migrations creating each table, named `000N_{table}`.
+fn migrations(tables: &[&str]) -> Vec<(String, Migration)> {
+    tables
+        .iter()
+        .enumerate()
+        .map(|(i, table)| (format!("{:04}_{}", i + 1, table), create_table(table)))
+        .collect()
+}
+
+/// Returns the rows the tracking table reads for the applied migrations.
+fn applied(names: &[&str]) -> Vec<Row> {
+    names.iter().map(|name| Row::new().with("name", *name).with("checksum", Value::Null)).collect()
+}
+
+const CREATE_TRACKING_TABLE: &str = "CREATE TABLE IF NOT EXISTS _rusty_orm_migrations \
+    (name VARCHAR(255) PRIMARY KEY, checksum VARCHAR(64), \
+    applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP);";
+const SELECT_APPLIED: &str =
+    "SELECT name, checksum FROM _rusty_orm_migrations ORDER BY applied_at, name;";
+const RECORD: &str = "INSERT INTO _rusty_orm_migrations (name, checksum) VALUES (?, ?);";
+const UNRECORD: &str = "DELETE FROM _rusty_orm_migrations WHERE name = ?;";
+
+#[test]
+fn run_applies_each_migration_in_a_transaction_with_its_record() {
+    let connection = MockConnection::new(Dialect::Sqlite);
+    let mut runner = MigrationRunner::with_migrations(connection, migrations(&["users", "posts"]));
+
+    let report = runner.run().unwrap();
+    assert_eq!(report.applied, ["0001_users", "0002_posts"]);
+    assert!(report.skipped.is_empty());
+    assert_eq!(
+        runner.connection().statements(),
+        [
+            CREATE_TRACKING_TABLE,
+            SELECT_APPLIED,
+            "BEGIN;",
+            "CREATE TABLE users (id INTEGER PRIMARY KEY);",
+            RECORD,
+            "COMMIT;",
+            "BEGIN;",
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY);",
+            RECORD,
+            "COMMIT;",
+        ]
+    );
+    assert_eq!(
+        runner.connection().executed()[4].1,
+        [Value::Text("0001_users".to_string()), Value::Null]
+    );
+}
+
+#[test]
+fn run_in_a_transaction_applies_each_migration_in_a_savepoint() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let mut transaction = connection.begin().unwrap();
+    MigrationRunner::with_migrations(&mut transaction, migrations(&["users"])).run().unwrap();
+    transaction.commit().unwrap();
+
+    assert_eq!(
+        connection.statements(),
+        [
+            "BEGIN;",
+            CREATE_TRACKING_TABLE,
+            SELECT_APPLIED,
+            "SAVEPOINT sp_1;",
+            "CREATE TABLE users (id INTEGER PRIMARY KEY);",
+            RECORD,
+            "RELEASE SAVEPOINT sp_1;",
+            "COMMIT;",
+        ]
+    );
+}
+
+#[test]
+fn run_skips_applied_migrations() {
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(applied(&["0001_users"]));
+    let mut runner = MigrationRunner::with_migrations(connection, migrations(&["users", "posts"]));
+
+    let report = runner.run().unwrap();
+    assert_eq!(report.applied, ["0002_posts"]);
+    assert_eq!(report.skipped, ["0001_users"]);
+    assert!(!runner
+        .connection()
+        .statements()
+        .contains(&"CREATE TABLE users (id INTEGER PRIMARY KEY);"));
+}
+
+#[test]
+fn run_stops_at_the_first_failure() {
+    let connection = MockConnection::new(Dialect::Sqlite).fail_on("CREATE TABLE posts");
+    let mut runner =
+        MigrationRunner::with_migrations(connection, migrations(&["users", "posts", "tags"]));
+
+    let error = runner.run().unwrap_err();
+    assert!(matches!(
+        &error,
+        OrmError::Migration(MigrationError::Failed { name, completed, .. })
+            if name == "0002_posts" && completed == &["0001_users"]
+    ));
+    // The failed migration is rolled back without its record, and those
+    // after it never run
+    assert_eq!(
+        runner.connection().statements()[6..],
+        ["BEGIN;", "CREATE TABLE posts (id INTEGER PRIMARY KEY);", "ROLLBACK;"]
+    );
+}
+
+#[test]
+fn run_applies_the_migrations_of_a_directory_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    for (name, migration) in migrations(&["users", "posts"]).into_iter().rev() {
+        MigrationGenerator::save_migration(&migration, &name, path).unwrap();
+    }
+
+    let mut runner = MigrationRunner::new(MockConnection::new(Dialect::Postgres), path);
+    let report = runner.run().unwrap();
+    assert_eq!(report.applied, ["0001_users", "0002_posts"]);
+    assert_eq!(
+        runner.connection().statements()[4],
+        "INSERT INTO _rusty_orm_migrations (name, checksum) VALUES ($1, $2);"
+    );
+}
+
+#[test]
+fn rollback_reverts_the_most_recent_migrations_first() {
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(applied(&[
+        "0001_users",
+        "0002_posts",
+        "0003_tags",
+    ]));
+    let mut runner =
+        MigrationRunner::with_migrations(connection, migrations(&["users", "posts", "tags"]));
+
+    assert_eq!(runner.rollback(2).unwrap(), ["0003_tags", "0002_posts"]);
+    assert_eq!(
+        runner.connection().statements()[2..],
+        [
+            "BEGIN;",
+            "DROP TABLE tags;",
+            UNRECORD,
+            "COMMIT;",
+            "BEGIN;",
+            "DROP TABLE posts;",
+            UNRECORD,
+            "COMMIT;"
+        ]
+    );
+    let unrecorded: Vec<&[Value]> = runner
+        .connection()
+        .executed()
+        .iter()
+        .filter(|(sql, _)| sql == UNRECORD)
+        .map(|(_, params)| params.as_slice())
+        .collect();
+    assert_eq!(
+        unrecorded,
+        [[Value::Text("0003_tags".to_string())], [Value::Text("0002_posts".to_string())]]
+    );
+}
+
+#[test]
+fn rollback_to_keeps_the_named_migration_applied() {
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(applied(&[
+        "0001_users",
+        "0002_posts",
+        "0003_tags",
+    ]));
+    let mut runner =
+        MigrationRunner::with_migrations(connection, migrations(&["users", "posts", "tags"]));
+
+    assert_eq!(runner.rollback_to("0001_users").unwrap(), ["0003_tags", "0002_posts"]);
+    assert!(!runner.connection().statements().contains(&"DROP TABLE users;"));
+}
+
+#[test]
+fn rollback_to_an_unapplied_migration_fails() {
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(applied(&["0001_users"]));
+    let mut runner = MigrationRunner::with_migrations(connection, migrations(&["users", "posts"]));
+
+    let error = runner.rollback_to("0002_posts").unwrap_err();
+    assert!(matches!(
+        error,
+        OrmError::Migration(MigrationError::NotApplied { name }) if name == "0002_posts"
+    ));
+    assert_eq!(runner.connection().statements(), [CREATE_TRACKING_TABLE, SELECT_APPLIED]);
+}
+
+#[test]
+fn rolling_back_a_missing_migration_fails_before_reverting_anything() {
+    // `0002_posts` is applied, but its file has since been deleted
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(applied(&[
+        "0001_users",
+        "0002_posts",
+        "0003_tags",
+    ]));
+    let mut remaining = migrations(&["users", "posts", "tags"]);
+    remaining.remove(1);
+    let mut runner = MigrationRunner::with_migrations(connection, remaining);
+
+    let error = runner.rollback(2).unwrap_err();
+    assert!(matches!(
+        error,
+        OrmError::Migration(MigrationError::Missing { name }) if name == "0002_posts"
+    ));
+    assert_eq!(runner.connection().statements(), [CREATE_TRACKING_TABLE, SELECT_APPLIED]);
+}
+
+#[test]
+fn status_lists_applied_and_pending_migrations() {
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(applied(&["0001_users"]));
+    let mut runner =
+        MigrationRunner::with_migrations(connection, migrations(&["users", "posts", "tags"]));
+
+    let status = runner.status().unwrap();
+    assert_eq!(status.applied, ["0001_users"]);
+    assert_eq!(status.pending, ["0002_posts", "0003_tags"]);
+}
+
+#[test]
+fn applying_then_rolling_back_records_and_removes_each_migration() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let report = MigrationRunner::with_migrations(&mut connection, migrations(&["users", "posts"]))
+        .run()
+        .unwrap();
+    assert_eq!(report.applied, ["0001_users", "0002_posts"]);
+
+    // The mock returns the rows a database would after the run
+    connection.push_rows(applied(&["0001_users", "0002_posts"]));
+    let mut runner =
+        MigrationRunner::with_migrations(&mut connection, migrations(&["users", "posts"]));
+    assert_eq!(runner.rollback(5).unwrap(), ["0002_posts", "0001_users"]);
+
+    let tracking: Vec<(&str, &[Value])> = connection
+        .executed()
+        .iter()
+        .filter(|(sql, _)| {
+            sql.contains("_rusty_orm_migrations")
+                && !sql.starts_with("CREATE")
+                && !sql.starts_with("SELECT")
+        })
+        .map(|(sql, params)| (sql.as_str(), params.as_slice()))
+        .collect();
+    let name = |name: &str| Value::Text(name.to_string());
+    assert_eq!(
+        tracking,
+        [
+            (RECORD, &[name("0001_users"), Value::Null][..]),
+            (RECORD, &[name("0002_posts"), Value::Null][..]),
+            (UNRECORD, &[name("0002_posts")][..]),
+            (UNRECORD, &[name("0001_users")][..]),
+        ]
+    );
+}
+
+/// Returns the migration creating the table with its checksum, as saved.
+fn checksummed(table: &str) -> Migration {
+    let migration = create_table(table);
+    Migration { checksum: Some(migration.compute_checksum()), ..migration }
+}
+
+#[test]
+fn checksums_cover_up_and_down() {
+    let migration = checksummed("users");
+    assert_eq!(migration.compute_checksum().len(), 64);
+    assert_eq!(migration.compute_checksum(), create_table("users").compute_checksum());
+    assert_ne!(
+        migration.compute_checksum(),
+        create_table("users").with_down_statement("SELECT 1;").compute_checksum()
+    );
+    // Moving a statement from up to down changes the checksum
+    let moved = Migration {
+        up: Vec::new(),
+        down: vec![migration.up_sql(), migration.down_sql()],
+        checksum: None,
+    };
+    assert_ne!(migration.compute_checksum(), moved.compute_checksum());
+}
+
+#[test]
+fn saved_migrations_record_their_checksum() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    MigrationGenerator::save_migration(&create_table("users"), "0001_users", path).unwrap();
+
+    let mut runner = MigrationRunner::new(MockConnection::new(Dialect::Sqlite), path);
+    runner.run().unwrap();
+    let (_, params) = &runner.connection().executed()[4];
+    assert_eq!(params[1], Value::Text(create_table("users").compute_checksum()));
+}
+
+#[test]
+fn unchanged_applied_migrations_verify() {
+    let checksum = checksummed("users").checksum.unwrap();
+    let connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![Row::new().with("name", "0001_users").with("checksum", checksum)]);
+    let migrations = vec![
+        ("0001_users".to_string(), checksummed("users")),
+        ("0002_posts".to_string(), checksummed("posts")),
+    ];
+    let mut runner = MigrationRunner::with_migrations(connection, migrations);
+
+    assert_eq!(runner.run().unwrap().applied, ["0002_posts"]);
+}
+
+#[test]
+fn edited_applied_migrations_are_reported_as_drift() {
+    let recorded = |name: &str, table: &str| {
+        Row::new().with("name", name).with("checksum", checksummed(table).checksum.unwrap())
+    };
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(vec![
+        recorded("0001_users", "users"),
+        recorded("0002_posts", "posts"),
+        recorded("0003_tags", "tags"),
+    ]);
+    let edited = |table: &str| {
+        let migration = create_table(table).with_up_statement("CREATE INDEX idx ON t (id);");
+        Migration { checksum: Some(migration.compute_checksum()), ..migration }
+    };
+    let migrations = vec![
+        ("0001_users".to_string(), edited("users")),
+        ("0002_posts".to_string(), checksummed("posts")),
+        ("0003_tags".to_string(), edited("tags")),
+        ("0004_likes".to_string(), checksummed("likes")),
+    ];
+    let mut runner = MigrationRunner::with_migrations(connection, migrations);
+
+    let error = runner.run().unwrap_err();
+    assert!(matches!(
+        &error,
+        OrmError::Migration(MigrationError::Drift { names }) if names == &["0001_users", "0003_tags"]
+    ));
+    assert_eq!(
+        error.to_string(),
+        "migrations `0001_users`, `0003_tags` were edited after being applied"
+    );
+    // Nothing is applied
+    assert_eq!(runner.connection().statements(), [CREATE_TRACKING_TABLE, SELECT_APPLIED]);
+}
+
+#[test]
+fn repairing_checksums_accepts_edited_migrations() {
+    let recorded = Row::new()
+        .with("name", "0001_users")
+        .with("checksum", checksummed("users").checksum.unwrap());
+    let edited = create_table("users").with_up_statement("CREATE INDEX idx ON users (id);");
+    let edited = Migration { checksum: Some(edited.compute_checksum()), ..edited };
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(vec![recorded]);
+    let mut runner = MigrationRunner::with_migrations(
+        connection,
+        vec![("0001_users".to_string(), edited.clone())],
+    );
+
+    assert_eq!(runner.repair_checksums().unwrap(), ["0001_users"]);
+    let (sql, params) = runner.connection().executed().last().unwrap();
+    assert_eq!(sql, "UPDATE _rusty_orm_migrations SET checksum = ? WHERE name = ?;");
+    assert_eq!(
+        params,
+        &[Value::Text(edited.compute_checksum()), Value::Text("0001_users".to_string())]
+    );
+}
+
+#[test]
+fn migrations_without_a_checksum_are_unverified() {
+    // Saved before checksums existed
+    let old: Migration = serde_json::from_str(
+        r#"{"up": "CREATE TABLE posts (id INTEGER PRIMARY KEY);", "down": "DROP TABLE posts;"}"#,
+    )
+    .unwrap();
+    assert_eq!(old.checksum, None);
+
+    // An applied migration recorded without a checksum, or one without a
+    // checksum of its own, is never reported as edited
+    let connection = MockConnection::new(Dialect::Sqlite).with_rows(vec![
+        Row::new().with("name", "0001_users").with("checksum", Value::Null),
+        Row::new().with("name", "0002_posts").with("checksum", "0".repeat(64)),
+    ]);
+    let migrations =
+        vec![("0001_users".to_string(), checksummed("users")), ("0002_posts".to_string(), old)];
+    let mut runner = MigrationRunner::with_migrations(connection, migrations);
+    assert_eq!(runner.run().unwrap().skipped, ["0001_users", "0002_posts"]);
+}
+
+#[test]
+fn embedded_migrations_are_sorted_by_prefix() {
+    let files = [
+        (
+            "0010_tags.json",
+            r#"{"up": ["CREATE TABLE tags (id INTEGER);"], "down": ["DROP TABLE tags;"]}"#,
+        ),
+        ("0002_posts.up.sql", "CREATE TABLE posts (id INTEGER);\n"),
+        ("0002_posts.down.sql", "DROP TABLE posts;\n"),
+        (
+            "0001_users.json",
+            r#"{"up": "CREATE TABLE users (id INTEGER);", "down": "DROP TABLE users;"}"#,
+        ),
+        ("notes.txt", "Not a migration"),
+    ];
+    let migrations = Migration::from_embedded(&files).unwrap();
+    let names: Vec<&str> = migrations.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["0001_users", "0002_posts", "0010_tags"]);
+    assert_eq!(migrations[1].1.up, ["CREATE TABLE posts (id INTEGER);"]);
+    assert_eq!(migrations[1].1.down, ["DROP TABLE posts;"]);
+}
+
+#[test]
+fn embedded_json_takes_precedence_over_sql_files() {
+    let files = [
+        ("0001_users.up.sql", "CREATE TABLE users (id INTEGER);"),
+        ("0001_users.down.sql", "DROP TABLE users;"),
+        (
+            "0001_users.json",
+            r#"{"up": ["CREATE TABLE users (id BIGINT);"], "down": ["DROP TABLE users;"]}"#,
+        ),
+    ];
+    let migrations = Migration::from_embedded(&files).unwrap();
+    assert_eq!(migrations.len(), 1);
+    assert_eq!(migrations[0].1.up, ["CREATE TABLE users (id BIGINT);"]);
+}
+
+#[test]
+fn embedded_sql_migrations_need_a_down_file() {
+    let files = [("0001_users.up.sql", "CREATE TABLE users (id INTEGER);")];
+    let error = Migration::from_embedded(&files).unwrap_err();
+    assert!(matches!(
+        &error,
+        OrmError::Migration(MigrationError::MissingDownFile { name }) if name == "0001_users"
+    ));
+    assert_eq!(error.to_string(), "migration `0001_users` has no 0001_users.down.sql file");
+}
+
+#[test]
+fn malformed_embedded_json_fails_to_parse() {
+    let files = [("0001_users.json", r#"{"up": ["CREATE TABLE users (id INTEGER);"]"#)];
+    let error = Migration::from_embedded(&files).unwrap_err();
+    assert!(matches!(
+        &error,
+        OrmError::Migration(MigrationError::InvalidMigration { name, .. }) if name == "0001_users"
+    ));
+    assert!(error.to_string().starts_with("failed to parse migration `0001_users`: "));
+}
+
+#[test]
+fn embedded_migrations_apply_without_the_directory() {
+    let migrations = embed_migrations!("tests/migrations").unwrap();
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let report = MigrationRunner::with_migrations(&mut connection, migrations).run().unwrap();
+
+    assert_eq!(report.applied, ["0001_create_users", "0002_index_user_names"]);
+    assert_eq!(
+        connection.statements()[2..],
+        [
+            "BEGIN;",
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR(100) NOT NULL);",
+            RECORD,
+            "COMMIT;",
+            "BEGIN;",
+            // Comments are kept with the statement they precede
+            "-- Looked up by name on sign-in\nCREATE INDEX idx_users_name ON users (name);",
+            RECORD,
+            "COMMIT;",
+        ]
+    );
+}
+
+#[test]
+fn data_statements_run_in_order_after_the_generated_ones() {
+    let migration = create_table("users")
+        .with_up_statement("INSERT INTO users (id) VALUES (1);")
+        .with_up_statement("UPDATE users SET id = 2 WHERE id = 1;")
+        .with_down_statement("DELETE FROM users;");
+    assert_eq!(migration.down, ["DELETE FROM users;", "DROP TABLE users;"]);
+
+    let connection = MockConnection::new(Dialect::Sqlite);
+    let mut runner =
+        MigrationRunner::with_migrations(connection, vec![("0001_users".to_string(), migration)]);
+    runner.run().unwrap();
+    assert_eq!(
+        runner.connection().statements()[2..],
+        [
+            "BEGIN;",
+            "CREATE TABLE users (id INTEGER PRIMARY KEY);",
+            "INSERT INTO users (id) VALUES (1);",
+            "UPDATE users SET id = 2 WHERE id = 1;",
+            RECORD,
+            "COMMIT;",
+        ]
+    );
+}
+
+#[test]
+fn entries_holding_several_statements_run_one_by_one() {
+    let migration = Migration {
+        up: vec!["CREATE TABLE users (id INTEGER); -- the users\nINSERT INTO users VALUES (';');"
+            .to_string()],
+        down: vec!["DROP TABLE users;".to_string()],
+        checksum: None,
+    };
+    let connection = MockConnection::new(Dialect::Sqlite);
+    let mut runner =
+        MigrationRunner::with_migrations(connection, vec![("0001_users".to_string(), migration)]);
+    runner.run().unwrap();
+    assert_eq!(
+        runner.connection().statements()[3..5],
+        ["CREATE TABLE users (id INTEGER);", "-- the users\nINSERT INTO users VALUES (';');"]
+    );
+}
+
+#[test]
+fn a_failing_data_statement_rolls_back_the_whole_migration() {
+    let migration = create_table("users").with_up_statement("UPDATE users SET id = 2;");
+    let connection = MockConnection::new(Dialect::Sqlite).fail_on("UPDATE users");
+    let mut runner =
+        MigrationRunner::with_migrations(connection, vec![("0001_users".to_string(), migration)]);
+
+    assert!(runner.run().is_err());
+    assert_eq!(
+        runner.connection().statements()[2..],
+        [
+            "BEGIN;",
+            "CREATE TABLE users (id INTEGER PRIMARY KEY);",
+            "UPDATE users SET id = 2;",
+            "ROLLBACK;"
+        ]
+    );
+}
+
+/// Saves a mix of safe and destructive migrations in the directory.
+fn save_mixed_migrations(path: &str) {
+    let save = |name: &str, up: &[&str]| {
+        let migration = Migration {
+            up: up.iter().map(|sql| sql.to_string()).collect(),
+            down: Vec::new(),
+            checksum: None,
+        };
+        MigrationGenerator::save_migration(&migration, name, path).unwrap();
+    };
+    save("0001_users", &["CREATE TABLE users (id INTEGER PRIMARY KEY, nickname TEXT);"]);
+    save(
+        "0002_drop_nicknames",
+        &["UPDATE users SET nickname = NULL;", "ALTER TABLE users DROP COLUMN nickname;"],
+    );
+    save("0003_reset", &["TRUNCATE TABLE sessions; DROP TABLE legacy_users;"]);
+}
+
+#[test]
+fn plan_lists_pending_statements_and_flags_destructive_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    save_mixed_migrations(path);
+
+    // The tracking table exists, and the first migration is applied
+    let connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![Row::new().with("name", "_rusty_orm_migrations")])
+        .with_rows(applied(&["0001_users"]));
+    let mut runner = MigrationRunner::new(connection, path);
+    let plan = runner.plan().unwrap();
+
+    let names: Vec<&str> =
+        plan.migrations.iter().map(|migration| migration.name.as_str()).collect();
+    assert_eq!(names, ["0002_drop_nicknames", "0003_reset"]);
+    assert_eq!(
+        plan.destructive(),
+        [
+            ("0002_drop_nicknames", "ALTER TABLE users DROP COLUMN nickname;"),
+            ("0003_reset", "TRUNCATE TABLE sessions;"),
+            ("0003_reset", "DROP TABLE legacy_users;"),
+        ]
+    );
+    assert_eq!(
+        plan.render(),
+        "-- migration: 0002_drop_nicknames\n\
+         UPDATE users SET nickname = NULL;\n\
+         -- destructive\n\
+         ALTER TABLE users DROP COLUMN nickname;\n\
+         \n\
+         -- migration: 0003_reset\n\
+         -- destructive\n\
+         TRUNCATE TABLE sessions;\n\
+         -- destructive\n\
+         DROP TABLE legacy_users;\n"
+    );
+}
+
+#[test]
+fn plan_only_reads_the_tracking_table() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+    save_mixed_migrations(path);
+
+    // Without a tracking table, every migration is pending and it isn't created
+    let mut runner = MigrationRunner::new(MockConnection::new(Dialect::Sqlite), path);
+    let plan = runner.plan().unwrap();
+    assert_eq!(plan.migrations.len(), 3);
+    assert_eq!(
+        runner.connection().statements(),
+        ["SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?;"]
+    );
+}
+
+#[test]
+fn plan_of_applied_migrations_is_empty() {
+    let connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![Row::new().with("name", "_rusty_orm_migrations")])
+        .with_rows(applied(&["0001_users"]));
+    let mut runner = MigrationRunner::with_migrations(connection, migrations(&["users"]));
+    let plan = runner.plan().unwrap();
+    assert!(plan.is_empty());
+    assert_eq!(plan.render(), "");
+}
+
+#[test]
+fn destructive_statements_are_detected() {
+    for sql in [
+        "DROP TABLE users;",
+        "drop table if exists users",
+        "TRUNCATE users;",
+        "ALTER TABLE users DROP COLUMN email;",
+        "-- no longer needed\nDROP TABLE sessions;",
+    ] {
+        assert!(is_destructive(sql), "{}", sql);
+    }
+    for sql in [
+        "CREATE TABLE users (id INTEGER);",
+        "DROP INDEX idx_users_email;",
+        "DROP VIEW active_users;",
+        "ALTER TABLE users ADD COLUMN dropped_at TIMESTAMP;",
+        "UPDATE users SET note = 'DROP TABLE users';",
+    ] {
+        assert!(!is_destructive(sql), "{}", sql);
+    }
+}
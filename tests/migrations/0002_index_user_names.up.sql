@@ -0,0 +1,2 @@
+-- Looked up by name on sign-in
+CREATE INDEX idx_users_name ON users (name);
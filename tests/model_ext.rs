@@ -0,0 +1,262 @@
+mod common;
+
+use common::User;
+use rusty_orm::dialect::Dialect;
+use rusty_orm::error::OrmError;
+use rusty_orm::executor::{FetchError, MockConnection};
+use rusty_orm::expr::Expr;
+use rusty_orm::model::{IntoInsert, ModelExt, Value};
+use rusty_orm::query_builder::QueryError;
+use rusty_orm::row::Row;
+use rusty_orm_macros::{FromRow, IntoInsert, Model};
+
+/// A model keyed by a natural key the database doesn't generate.
+#[derive(Model, FromRow, IntoInsert, Debug, Clone, PartialEq)]
+#[table_name = "settings"]
+struct Setting {
+    #[column(primary_key)]
+    key: String,
+    value: String,
+}
+
+/// A model telling whether it's new with a flag of its own.
+#[derive(Model, FromRow, IntoInsert, Debug, Clone, PartialEq)]
+#[table_name = "documents"]
+#[model(is_new = "Self::is_draft")]
+struct Document {
+    #[column(primary_key)]
+    slug: String,
+    title: String,
+    #[column(skip)]
+    draft: bool,
+}
+
+impl Document {
+    fn is_draft(&self) -> bool {
+        self.draft
+    }
+}
+
+/// A model without a primary key.
+#[derive(Model, FromRow, IntoInsert, Debug, Clone, PartialEq)]
+#[table_name = "events"]
+struct Event {
+    name: String,
+}
+
+/// A model keyed by two columns.
+#[derive(Model, FromRow, IntoInsert, Debug, Clone, PartialEq)]
+#[table_name = "memberships"]
+struct Membership {
+    #[column(primary_key)]
+    team_id: i32,
+    #[column(primary_key)]
+    user_id: i32,
+}
+
+fn user_row(id: i32, name: &str) -> Row {
+    Row::new()
+        .with("id", id)
+        .with("name", name)
+        .with("email", "ann@example.com")
+        .with("is_active", true)
+}
+
+#[test]
+fn save_inserts_a_new_instance_and_reads_back_its_key() {
+    let mut connection = MockConnection::new(Dialect::Postgres).with_rows(vec![user_row(7, "Ann")]);
+    let mut user = User {
+        id: 0,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        is_active: true,
+    };
+
+    assert!(user.is_new());
+    user.save(&mut connection).unwrap();
+    assert_eq!(user.id, 7);
+    assert_eq!(
+        connection.statements(),
+        [r#"INSERT INTO "users" ("name", "email", "is_active") VALUES ($1, $2, $3) RETURNING *;"#]
+    );
+}
+
+#[test]
+fn save_updates_a_row_that_exists() {
+    let mut connection =
+        MockConnection::new(Dialect::Postgres).with_rows(vec![Row::new().with("1", 1)]);
+    let mut setting = Setting { key: "theme".to_string(), value: "dark".to_string() };
+
+    assert!(!setting.is_new());
+    setting.save(&mut connection).unwrap();
+    // The mock reports no affected rows, so the key is looked up
+    assert_eq!(
+        connection.statements(),
+        [
+            r#"UPDATE "settings" SET "value" = $1 WHERE "key" = $2"#,
+            r#"SELECT 1 FROM "settings" WHERE "key" = $1 LIMIT 1"#,
+        ]
+    );
+}
+
+#[test]
+fn save_inserts_when_the_update_matches_no_row() {
+    let mut connection = MockConnection::new(Dialect::Postgres);
+    connection.push_rows(Vec::new());
+    connection.push_rows(vec![Row::new().with("key", "theme").with("value", "dark")]);
+    let mut setting = Setting { key: "theme".to_string(), value: "dark".to_string() };
+
+    setting.save(&mut connection).unwrap();
+    assert_eq!(
+        connection.statements(),
+        [
+            r#"UPDATE "settings" SET "value" = $1 WHERE "key" = $2"#,
+            r#"SELECT 1 FROM "settings" WHERE "key" = $1 LIMIT 1"#,
+            r#"INSERT INTO "settings" ("key", "value") VALUES ($1, $2) RETURNING *;"#,
+        ]
+    );
+    assert_eq!(
+        connection.executed()[2].1,
+        [Value::Text("theme".into()), Value::Text("dark".into())]
+    );
+}
+
+#[test]
+fn is_new_attribute_decides_between_insert_and_update() {
+    let mut document =
+        Document { slug: "intro".to_string(), title: "Intro".to_string(), draft: true };
+    assert!(document.is_new());
+
+    let inserted = Row::new().with("slug", "intro").with("title", "Intro");
+    let mut connection = MockConnection::new(Dialect::Sqlite).with_rows(vec![inserted]);
+    document.save(&mut connection).unwrap();
+    assert_eq!(
+        connection.statements(),
+        [r#"INSERT INTO "documents" ("slug", "title") VALUES (?, ?) RETURNING *;"#]
+    );
+
+    // Read back from the row, the skipped flag is back to false
+    assert!(!document.is_new());
+}
+
+#[test]
+fn writing_a_model_without_a_primary_key_fails() {
+    let mut connection = MockConnection::new(Dialect::Postgres);
+    let mut event = Event { name: "login".to_string() };
+    let missing_key = |error| matches!(error, OrmError::Query(QueryError::MissingPrimaryKey { table }) if table == "events");
+
+    assert!(missing_key(event.save(&mut connection).unwrap_err()));
+    assert!(missing_key(event.update(&mut connection).unwrap_err()));
+    assert!(missing_key(event.delete(&mut connection).unwrap_err()));
+    assert!(connection.statements().is_empty());
+}
+
+#[tokio::test]
+async fn save_async_inserts_when_the_update_matches_no_row() {
+    let mut connection = MockConnection::new(Dialect::Postgres);
+    connection.push_rows(Vec::new());
+    connection.push_rows(vec![Row::new().with("key", "theme").with("value", "dark")]);
+    let mut setting = Setting { key: "theme".to_string(), value: "dark".to_string() };
+
+    setting.save_async(&mut connection).await.unwrap();
+    assert_eq!(connection.statements().len(), 3);
+    assert!(connection.statements()[2].starts_with(r#"INSERT INTO "settings""#));
+}
+
+#[test]
+fn save_inserts_a_missing_row_with_its_key_on_mysql() {
+    let mut connection = MockConnection::new(Dialect::MySql);
+    let mut user = User {
+        id: 5,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        is_active: true,
+    };
+
+    user.save(&mut connection).unwrap();
+    assert_eq!(
+        connection.statements(),
+        [
+            "UPDATE `users` SET `name` = ?, `email` = ?, `is_active` = ? WHERE `id` = ?",
+            "SELECT 1 FROM `users` WHERE `id` = ? LIMIT 1",
+            "INSERT INTO `users` (`name`, `email`, `is_active`, `id`) VALUES (?, ?, ?, ?);",
+        ]
+    );
+    assert_eq!(user.id, 5);
+}
+
+#[test]
+fn find_looks_up_the_primary_key() {
+    let mut connection = MockConnection::new(Dialect::Postgres).with_rows(vec![user_row(3, "Ann")]);
+    let user = User::find(&mut connection, 3).unwrap().unwrap();
+    assert_eq!(user.name, "Ann");
+    assert_eq!(User::find(&mut connection, 4).unwrap(), None);
+    assert_eq!(
+        connection.statements(),
+        [
+            r#"SELECT * FROM "users" WHERE "id" = $1 LIMIT 2"#,
+            r#"SELECT * FROM "users" WHERE "id" = $1 LIMIT 2"#,
+        ]
+    );
+    assert_eq!(connection.executed()[0].1, [Value::Integer(3)]);
+}
+
+#[test]
+fn find_by_key_matches_every_key_column() {
+    let mut connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![Row::new().with("team_id", 1).with("user_id", 2)]);
+    let key = [Value::Integer(1), Value::Integer(2)];
+    let membership = Membership::find_by_key(&mut connection, &key).unwrap();
+    assert_eq!(membership, Some(Membership { team_id: 1, user_id: 2 }));
+    assert_eq!(
+        connection.statements(),
+        [r#"SELECT * FROM "memberships" WHERE "team_id" = ? AND "user_id" = ? LIMIT 2"#]
+    );
+
+    let error = Membership::find(&mut connection, 1).unwrap_err();
+    assert!(matches!(
+        error,
+        OrmError::Fetch(FetchError::KeyMismatch { expected: 2, found: 1, .. })
+    ));
+}
+
+#[test]
+fn find_without_a_primary_key_fails() {
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    let error = Event::find(&mut connection, "login").unwrap_err();
+    assert!(
+        matches!(error, OrmError::Query(QueryError::MissingPrimaryKey { table }) if table == "events")
+    );
+    assert!(connection.statements().is_empty());
+}
+
+#[test]
+fn all_selects_every_row() {
+    let mut connection =
+        MockConnection::new(Dialect::MySql).with_rows(vec![user_row(1, "Ann"), user_row(2, "Bo")]);
+    let users = User::all(&mut connection).unwrap();
+    assert_eq!(users.iter().map(|user| user.name.as_str()).collect::<Vec<_>>(), ["Ann", "Bo"]);
+    assert_eq!(connection.statements(), ["SELECT * FROM `users`"]);
+}
+
+#[test]
+fn count_reads_the_count_column() {
+    let mut connection =
+        MockConnection::new(Dialect::Postgres).with_rows(vec![Row::new().with("count", 42i64)]);
+    assert_eq!(User::count(&mut connection).unwrap(), 42);
+    assert_eq!(connection.statements(), [r#"SELECT COUNT(*) AS "count" FROM "users""#]);
+
+    let error = User::count(&mut connection).unwrap_err();
+    assert!(matches!(error, OrmError::Fetch(FetchError::NoRows)));
+}
+
+#[test]
+fn exists_selects_at_most_one_row() {
+    let mut connection =
+        MockConnection::new(Dialect::Postgres).with_rows(vec![Row::new().with("1", 1)]);
+    let condition = || Expr::col("email").eq("ann@example.com");
+    assert!(User::exists(&mut connection, condition()).unwrap());
+    assert!(!User::exists(&mut connection, condition()).unwrap());
+    assert_eq!(connection.statements()[0], r#"SELECT 1 FROM "users" WHERE "email" = $1 LIMIT 1"#);
+    assert_eq!(connection.executed()[0].1, [Value::Text("ann@example.com".to_string())]);
+}
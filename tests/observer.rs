@@ -0,0 +1,132 @@
+use rusty_orm::dialect::Dialect;
+use rusty_orm::executor::{Connection, DatabaseError, MockConnection};
+use rusty_orm::model::Value;
+use rusty_orm::observer::{CollectingObserver, LogObserver, QueryEvent};
+use rusty_orm::row::Row;
+use std::sync::Mutex;
+
+/// The events with their durations left out, which vary from run to run.
+fn events(observer: &CollectingObserver) -> Vec<(String, String)> {
+    observer
+        .events()
+        .into_iter()
+        .map(|event| match event {
+            QueryEvent::Start { sql, params } => {
+                (format!("start {}", sql), format!("{:?}", params))
+            }
+            QueryEvent::End { sql, result, .. } => {
+                (format!("end {}", sql), format!("{:?}", result))
+            }
+        })
+        .collect()
+}
+
+fn pair(event: &str, detail: &str) -> (String, String) {
+    (event.to_string(), detail.to_string())
+}
+
+#[test]
+fn each_statement_is_reported_when_it_starts_and_ends() {
+    let observer = CollectingObserver::new();
+    let mut connection = MockConnection::new(Dialect::Sqlite)
+        .with_rows(vec![Row::new().with("id", 1), Row::new().with("id", 2)])
+        .fail_on("DROP");
+    connection.set_observer(Box::new(observer.clone()));
+
+    connection.query("SELECT id FROM users WHERE is_active = ?", &[Value::Bool(true)]).unwrap();
+    connection.execute("DROP TABLE users", &[]).unwrap_err();
+
+    assert_eq!(
+        events(&observer),
+        [
+            pair("start SELECT id FROM users WHERE is_active = ?", "Some([Bool(true)])"),
+            pair("end SELECT id FROM users WHERE is_active = ?", "Ok(2)"),
+            pair("start DROP TABLE users", "Some([])"),
+            pair(
+                "end DROP TABLE users",
+                r#"Err(DatabaseError { message: "mock failure on `DROP`", code: None })"#
+            ),
+        ]
+    );
+}
+
+#[test]
+fn statements_of_transactions_are_observed() {
+    let observer = CollectingObserver::new();
+    let mut connection = MockConnection::new(Dialect::Sqlite);
+    connection.set_observer(Box::new(observer.clone()));
+
+    let mut transaction = connection.begin().unwrap();
+    transaction.execute("DELETE FROM users", &[]).unwrap();
+    transaction.commit().unwrap();
+
+    let started: Vec<String> = events(&observer)
+        .into_iter()
+        .filter_map(|(event, _)| event.strip_prefix("start ").map(str::to_string))
+        .collect();
+    assert_eq!(started, ["BEGIN;", "DELETE FROM users", "COMMIT;"]);
+}
+
+#[test]
+fn collected_params_can_be_redacted() {
+    let observer = CollectingObserver::new().redact_params(true);
+    let mut connection = MockConnection::new(Dialect::Postgres);
+    connection.set_observer(Box::new(observer.clone()));
+
+    let email = Value::Text("ann@example.com".to_string());
+    connection.execute("DELETE FROM users WHERE email = $1", &[email]).unwrap();
+    assert_eq!(
+        observer.events()[0],
+        QueryEvent::Start { sql: "DELETE FROM users WHERE email = $1".to_string(), params: None }
+    );
+
+    observer.clear();
+    assert!(observer.events().is_empty());
+}
+
+/// A logger keeping the messages logged, for the one test using it.
+struct CapturingLogger(Mutex<Vec<(log::Level, String)>>);
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.0.lock().unwrap().push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+
+#[test]
+fn log_observer_logs_statements_and_redacts_params() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+    let email = [Value::Text("ann@example.com".to_string())];
+
+    let mut connection = MockConnection::new(Dialect::Postgres).fail_on("DROP");
+    connection.set_observer(Box::new(LogObserver::new()));
+    connection.execute("DELETE FROM users WHERE email = $1", &email).unwrap();
+    connection.set_observer(Box::new(LogObserver::new().redact_params(true)));
+    connection.execute("DELETE FROM users WHERE email = $1", &email).unwrap();
+    connection.execute("DROP TABLE users", &[]).unwrap_err();
+
+    let logged = std::mem::take(&mut *LOGGER.0.lock().unwrap());
+    let messages: Vec<&str> = logged.iter().map(|(_, message)| message.as_str()).collect();
+    assert_eq!(
+        messages[0],
+        r#"running `DELETE FROM users WHERE email = $1` with [Text("ann@example.com")]"#
+    );
+    assert_eq!(messages[2], "running `DELETE FROM users WHERE email = $1` with [?]");
+    assert!(messages.iter().skip(1).all(|message| !message.contains("ann@example.com")));
+    assert!(messages[3].starts_with("ran `DELETE FROM users WHERE email = $1` in "));
+    assert!(messages[3].ends_with(": 0 rows"));
+
+    let (level, failed) = logged.last().unwrap();
+    assert_eq!(*level, log::Level::Warn);
+    assert!(failed.starts_with("`DROP TABLE users` failed after "));
+    assert!(failed.ends_with(&format!(": {}", DatabaseError::new("mock failure on `DROP`"))));
+}
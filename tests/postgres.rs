@@ -0,0 +1,199 @@
+//! Runs against the server given by `RUSTY_ORM_POSTGRES`, such as
+//! `host=localhost user=postgres`; the tests pass without running otherwise.
+
+mod common;
+
+use common::User;
+use rusty_orm::dialect::Dialect;
+use rusty_orm::error::OrmError;
+use rusty_orm::executor::Connection;
+use rusty_orm::migration::{Migration, MigrationGenerator, MigrationRunner};
+use rusty_orm::model::{ModelExt, Value};
+use rusty_orm::postgres::PostgresConnection;
+use rusty_orm::query_builder::InsertQuery;
+
+/// Connects to the test server, in a schema of the test's own dropped
+/// first, or returns `None` without one.
+fn connect(schema: &str) -> Option<PostgresConnection> {
+    let params = std::env::var("RUSTY_ORM_POSTGRES").ok()?;
+    let mut connection = PostgresConnection::connect(&params).unwrap();
+    connection.execute(&format!("DROP SCHEMA IF EXISTS {} CASCADE", schema), &[]).unwrap();
+    connection.execute(&format!("CREATE SCHEMA {}", schema), &[]).unwrap();
+    connection.execute(&format!("SET search_path TO {}", schema), &[]).unwrap();
+    Some(connection)
+}
+
+fn connection_with_users(schema: &str, names: &[&str]) -> Option<PostgresConnection> {
+    let mut connection = connect(schema)?;
+    let migration = MigrationGenerator::generate_for::<User>(Dialect::Postgres);
+    for statement in &migration.up {
+        connection.execute(statement, &[]).unwrap();
+    }
+    for name in names {
+        let (sql, params) = InsertQuery::<User>::new()
+            .value("name", *name)
+            .value("email", format!("{}@example.com", name.len()))
+            .value("is_active", true)
+            .build_parameterized_for(Dialect::Postgres);
+        connection.execute(&sql, &params).unwrap();
+    }
+    Some(connection)
+}
+
+#[test]
+fn repeated_fetches_hit_the_statement_cache() {
+    let Some(mut connection) = connection_with_users("statement_cache_hits", &["Ann"]) else {
+        return;
+    };
+    let before = connection.stats();
+    for _ in 0..3 {
+        User::find(&mut connection, 1).unwrap();
+    }
+    assert_eq!(connection.stats().misses - before.misses, 1);
+    assert_eq!(connection.stats().hits - before.hits, 2);
+}
+
+#[test]
+fn running_a_migration_clears_the_statement_cache() {
+    let Some(mut connection) = connection_with_users("statement_cache_migration", &["Ann"]) else {
+        return;
+    };
+    User::find(&mut connection, 1).unwrap();
+
+    let migration = Migration {
+        up: vec!["ALTER TABLE users ADD COLUMN nickname TEXT".to_string()],
+        down: vec!["ALTER TABLE users DROP COLUMN nickname".to_string()],
+        checksum: None,
+    };
+    MigrationRunner::with_migrations(
+        &mut connection,
+        vec![("0002_nickname".to_string(), migration)],
+    )
+    .run()
+    .unwrap();
+
+    // A statement prepared before would fail, as the server rejects
+    // cached plans whose result columns changed
+    let misses = connection.stats().misses;
+    assert!(User::find(&mut connection, 1).unwrap().is_some());
+    assert_eq!(connection.stats().misses, misses + 1);
+}
+
+#[test]
+fn database_errors_keep_the_sqlstate() {
+    let Some(mut connection) = connect("sqlstate") else {
+        return;
+    };
+    connection.execute("CREATE TABLE accounts (email TEXT UNIQUE)", &[]).unwrap();
+    let email = [Value::Text("ann@example.com".to_string())];
+    connection.execute("INSERT INTO accounts VALUES ($1)", &email).unwrap();
+    let error = connection.execute("INSERT INTO accounts VALUES ($1)", &email).unwrap_err();
+    let OrmError::Execution(error) = error else { panic!("{:?}", error) };
+    assert_eq!(error.code.as_deref(), Some("23505"));
+    assert!(error.message.contains("accounts_email_key"), "{}", error.message);
+
+    let error = connection.query("SELECT * FROM missing", &[]).unwrap_err();
+    assert!(matches!(error, OrmError::Execution(error) if error.code.as_deref() == Some("42P01")));
+}
+
+#[test]
+fn insert_returning_id_returns_the_generated_key() {
+    let Some(mut connection) = connection_with_users("returning_id", &["Ann", "Bo"]) else {
+        return;
+    };
+    let query = InsertQuery::<User>::new()
+        .value("name", "Cy")
+        .value("email", "cy@example.com")
+        .value("is_active", false);
+    let id = connection.insert_returning_id(query).unwrap();
+    assert_eq!(id, Some(Value::Integer(3)));
+    assert_eq!(User::find(&mut connection, 3).unwrap().unwrap().name, "Cy");
+}
+
+#[test]
+fn models_insert_with_their_own_keys() {
+    let Some(mut connection) = connection_with_users("explicit_keys", &[]) else {
+        return;
+    };
+    let user = User {
+        id: 42,
+        name: "Di".to_string(),
+        email: "di@example.com".to_string(),
+        is_active: true,
+    };
+    let (sql, params) =
+        InsertQuery::from_model_with_keys(&user).build_parameterized_for(Dialect::Postgres);
+    connection.execute(&sql, &params).unwrap();
+    assert_eq!(User::find(&mut connection, 42).unwrap().unwrap().name, "Di");
+}
+
+#[test]
+fn values_round_trip_through_each_column_type() {
+    let Some(mut connection) = connect("value_round_trip") else {
+        return;
+    };
+    connection
+        .execute(
+            "CREATE TABLE samples (small SMALLINT, big BIGINT, ratio REAL, flag BOOLEAN, \
+             data BYTEA, doc JSONB, note TEXT)",
+            &[],
+        )
+        .unwrap();
+    let values = [
+        Value::BigInt(7),
+        Value::BigInt(1 << 40),
+        Value::Float(0.5),
+        Value::Bool(true),
+        Value::Bytes(vec![0, 1, 255]),
+        Value::Text("{\"a\": [1, 2]}".to_string()),
+        Value::Null,
+    ];
+    connection.execute("INSERT INTO samples VALUES ($1, $2, $3, $4, $5, $6, $7)", &values).unwrap();
+
+    let rows = connection.query("SELECT * FROM samples", &[]).unwrap();
+    assert_eq!(rows[0].get("small"), Some(&Value::Integer(7)));
+    assert_eq!(rows[0].get("big"), Some(&Value::BigInt(1 << 40)));
+    assert_eq!(rows[0].get("ratio"), Some(&Value::Float(0.5)));
+    assert_eq!(rows[0].get("flag"), Some(&Value::Bool(true)));
+    assert_eq!(rows[0].get("data"), Some(&Value::Bytes(vec![0, 1, 255])));
+    assert_eq!(rows[0].get("doc"), Some(&Value::Text("{\"a\":[1,2]}".to_string())));
+    assert_eq!(rows[0].get("note"), Some(&Value::Null));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn timestamps_round_trip_as_text() {
+    let Some(mut connection) = connect("timestamp_round_trip") else {
+        return;
+    };
+    connection
+        .execute("CREATE TABLE events (day DATE, at TIMESTAMP, at_tz TIMESTAMPTZ)", &[])
+        .unwrap();
+    let values = [
+        Value::Text("2024-02-29".to_string()),
+        Value::Text("2024-02-29 12:30:00".to_string()),
+        Value::Text("2024-02-29T12:30:00+02:00".to_string()),
+    ];
+    connection.execute("INSERT INTO events VALUES ($1, $2, $3)", &values).unwrap();
+
+    let rows = connection.query("SELECT * FROM events", &[]).unwrap();
+    assert_eq!(rows[0].get("day"), Some(&Value::Text("2024-02-29".to_string())));
+    assert_eq!(rows[0].get("at"), Some(&Value::Text("2024-02-29 12:30:00".to_string())));
+    // Time zones are read back in UTC
+    assert_eq!(rows[0].get("at_tz"), Some(&Value::Text("2024-02-29T10:30:00+00:00".to_string())));
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uuids_round_trip_as_text() {
+    let Some(mut connection) = connect("uuid_round_trip") else {
+        return;
+    };
+    connection.execute("CREATE TABLE tokens (id UUID PRIMARY KEY)", &[]).unwrap();
+    let id = Value::Text("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string());
+    connection.execute("INSERT INTO tokens VALUES ($1)", std::slice::from_ref(&id)).unwrap();
+
+    let rows =
+        connection.query("SELECT id FROM tokens WHERE id = $1", std::slice::from_ref(&id)).unwrap();
+    assert_eq!(rows[0].get("id"), Some(&id));
+}
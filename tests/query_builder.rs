@@ -0,0 +1,1512 @@
+mod common;
+
+use common::User;
+use rusty_orm::dialect::{Dialect, PlaceholderStyle};
+use rusty_orm::escape::{escape_like, escape_string, quote_string, BackslashMode};
+use rusty_orm::expr::Expr;
+use rusty_orm::migration::MigrationGenerator;
+use rusty_orm::model::{Model, ToValue, Value};
+use rusty_orm::query_builder::{
+    DeleteQuery, InsertQuery, NullsOrder, Order, QueryError, SelectQuery, TruncateQuery,
+    UpdateQuery,
+};
+use rusty_orm_macros::{IntoInsert, Model};
+
+/// A model only queries are built for.
+#[allow(dead_code)]
+#[derive(Model, Debug, Clone)]
+#[table_name = "invoices"]
+struct Invoice {
+    #[column(primary_key = true, auto_increment = true)]
+    id: i32,
+    status: String,
+    amount: f64,
+}
+
+/// A model without a primary key.
+#[allow(dead_code)]
+#[derive(Model, IntoInsert, Debug, Clone)]
+#[table_name = "tags"]
+struct Tag {
+    label: String,
+}
+
+/// The table inactive users are copied to.
+#[allow(dead_code)]
+#[derive(Model, Debug, Clone)]
+#[table_name = "archived_users"]
+struct ArchivedUser {
+    #[column(primary_key = true)]
+    id: i32,
+    name: String,
+}
+
+/// A table and columns named after reserved words, or with mixed case.
+#[allow(dead_code)]
+#[derive(Model, Debug, Clone)]
+#[table_name = "group"]
+struct Group {
+    #[column(primary_key = true)]
+    id: i32,
+    order: i32,
+    #[column(name = "displayName")]
+    display_name: String,
+}
+
+/// A join table keyed by both of its references.
+#[derive(Model, IntoInsert, Debug, Clone)]
+#[table_name = "memberships"]
+struct Membership {
+    #[column(primary_key = true)]
+    team_id: i32,
+    #[column(primary_key = true)]
+    user_id: i32,
+    role: String,
+}
+
+/// A table in the `crm` schema, declared with a separate attribute.
+#[allow(dead_code)]
+#[derive(Model, IntoInsert, Debug, Clone)]
+#[schema = "crm"]
+#[table_name = "contacts"]
+struct Contact {
+    #[column(primary_key = true, auto_increment = true)]
+    id: i32,
+    name: String,
+}
+
+/// A table in the `billing` schema, declared in its qualified name.
+#[allow(dead_code)]
+#[derive(Model, Debug, Clone)]
+#[table_name = "billing.statements"]
+struct Statement {
+    #[column(primary_key = true)]
+    id: i32,
+    contact_id: i32,
+}
+
+#[test]
+fn parameterized_filters_collect_their_values_in_order() {
+    let query = SelectQuery::<User>::new().filter_eq("id", 5).filter_eq("name", "Ann");
+
+    let (sql, params) = query.build_parameterized();
+    assert_eq!(sql, "SELECT * FROM users WHERE id = ? AND name = ?");
+    assert_eq!(params, vec![Value::Integer(5), Value::Text("Ann".to_string())]);
+    assert_eq!(query.build(), "SELECT * FROM users WHERE id = 5 AND name = 'Ann'");
+}
+
+#[test]
+fn raw_filters_interleave_with_bound_values() {
+    let query = SelectQuery::<User>::new()
+        .filter_eq("id", 5)
+        .filter("email IS NOT NULL")
+        .filter_eq("name", "Ann");
+
+    let (sql, params) = query.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"SELECT * FROM "users" WHERE "id" = $1 AND (email IS NOT NULL) AND "name" = $2"#
+    );
+    assert_eq!(params, vec![Value::Integer(5), Value::Text("Ann".to_string())]);
+}
+
+#[test]
+fn insert_renders_each_value_kind() {
+    let cases: [(Value, &str); 7] = [
+        (42.to_value(), "42"),
+        (9_000_000_000i64.to_value(), "9000000000"),
+        (2.5.to_value(), "2.5"),
+        ("Ann".to_value(), "'Ann'"),
+        (true.to_value(), "true"),
+        (false.to_value(), "false"),
+        (None::<i32>.to_value(), "NULL"),
+    ];
+    for (value, literal) in cases {
+        assert_eq!(
+            InsertQuery::<User>::new().value("name", value).build(),
+            format!("INSERT INTO users (name) VALUES ({});", literal)
+        );
+    }
+    assert_eq!(
+        InsertQuery::<User>::new().value("is_active", true).build_for(Dialect::Sqlite),
+        r#"INSERT INTO "users" ("is_active") VALUES (1);"#
+    );
+    assert_eq!(Some("Ann").to_value(), Value::Text("Ann".to_string()));
+    assert_eq!(String::from("Ann").to_value(), Value::Text("Ann".to_string()));
+}
+
+#[test]
+fn mixed_insert_quotes_only_text() {
+    let query = InsertQuery::<User>::new()
+        .value("id", 42)
+        .value("name", "Ann")
+        .value("email", None::<String>)
+        .value("is_active", true)
+        .value_raw("created_at", "CURRENT_TIMESTAMP");
+
+    assert_eq!(
+        query.build(),
+        "INSERT INTO users (id, name, email, is_active, created_at) VALUES (42, 'Ann', NULL, true, CURRENT_TIMESTAMP);"
+    );
+    let (sql, params) = query.build_parameterized();
+    assert_eq!(
+        sql,
+        "INSERT INTO users (id, name, email, is_active, created_at) VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP);"
+    );
+    assert_eq!(
+        params,
+        vec![Value::Integer(42), Value::Text("Ann".to_string()), Value::Null, Value::Bool(true)]
+    );
+}
+
+#[test]
+fn escape_string_doubles_quotes_and_mysql_backslashes() {
+    assert_eq!(escape_string("O'Brien", BackslashMode::Literal), "O''Brien");
+    assert_eq!(escape_string("''", BackslashMode::Literal), "''''");
+    assert_eq!(escape_string(r"C:\temp", BackslashMode::Literal), r"C:\temp");
+    assert_eq!(escape_string(r"C:\temp", BackslashMode::Escape), r"C:\\temp");
+    assert_eq!(escape_string(r"\'", BackslashMode::Escape), r"\\''");
+    assert_eq!(quote_string("", BackslashMode::Literal), "''");
+    assert_eq!(quote_string("", BackslashMode::Escape), "''");
+}
+
+#[test]
+fn string_literals_are_escaped_in_inserts_and_updates() {
+    let insert = InsertQuery::<User>::new()
+        .value("name", "O'Brien")
+        .value("email", r"a\'b")
+        .value("bio", "");
+    assert_eq!(
+        insert.build_for(Dialect::Postgres),
+        r#"INSERT INTO "users" ("name", "email", "bio") VALUES ('O''Brien', 'a\''b', '');"#
+    );
+    assert_eq!(
+        insert.build_for(Dialect::MySql),
+        r"INSERT INTO `users` (`name`, `email`, `bio`) VALUES ('O''Brien', 'a\\''b', '');"
+    );
+
+    let update =
+        UpdateQuery::<User>::new().set("name", "'; DROP TABLE users; --").filter_eq("id", 1);
+    assert_eq!(
+        update.build_for(Dialect::Sqlite),
+        r#"UPDATE "users" SET "name" = '''; DROP TABLE users; --' WHERE "id" = 1"#
+    );
+}
+
+#[test]
+fn raw_values_bypass_escaping() {
+    let insert = InsertQuery::<User>::new().value_raw("name", "UPPER('ann')");
+    assert_eq!(insert.build(), "INSERT INTO users (name) VALUES (UPPER('ann'));");
+
+    let update = UpdateQuery::<User>::new().set_expr("name", "'O''Brien'").filter_eq("id", 1);
+    assert_eq!(update.build(), "UPDATE users SET name = 'O''Brien' WHERE id = 1");
+}
+
+#[test]
+fn single_raw_filter_is_written_as_is() {
+    assert_eq!(
+        SelectQuery::<User>::new().filter("a = 1").build(),
+        "SELECT * FROM users WHERE a = 1"
+    );
+    assert_eq!(
+        DeleteQuery::<User>::new().filter("a = 1 OR b = 2").build(),
+        "DELETE FROM users WHERE a = 1 OR b = 2"
+    );
+}
+
+#[test]
+fn raw_filters_combine_with_and_keeping_precedence() {
+    let query = SelectQuery::<User>::new().filter("a = 1 OR b = 2").filter("c = 3");
+    assert_eq!(query.build(), "SELECT * FROM users WHERE (a = 1 OR b = 2) AND (c = 3)");
+
+    let update = UpdateQuery::<User>::new().set("name", "Ann").filter("a = 1").filter("b = 2");
+    assert_eq!(update.build(), "UPDATE users SET name = 'Ann' WHERE (a = 1) AND (b = 2)");
+
+    let delete = DeleteQuery::<User>::new().filter("a = 1").filter("b = 2 OR c = 3");
+    assert_eq!(delete.build(), "DELETE FROM users WHERE (a = 1) AND (b = 2 OR c = 3)");
+}
+
+#[test]
+fn or_filter_joins_the_accumulated_conditions() {
+    let query = SelectQuery::<User>::new().filter("a = 1").filter("b = 2").or_filter("c = 3");
+    assert_eq!(query.build(), "SELECT * FROM users WHERE ((a = 1) AND (b = 2)) OR (c = 3)");
+
+    let update = UpdateQuery::<User>::new().set("name", "Ann").filter("a = 1").or_filter("b = 2");
+    assert_eq!(update.build(), "UPDATE users SET name = 'Ann' WHERE (a = 1) OR (b = 2)");
+
+    let delete = DeleteQuery::<User>::new().filter("a = 1").or_filter("b = 2");
+    assert_eq!(delete.build(), "DELETE FROM users WHERE (a = 1) OR (b = 2)");
+}
+
+#[test]
+fn nested_expressions_keep_their_precedence() {
+    let expr = Expr::col("age")
+        .ge(18)
+        .and(Expr::col("name").eq("Ann").or(Expr::col("name").eq("Bob")))
+        .or(Expr::col("is_active").eq(true).not());
+    let query = SelectQuery::<User>::new().filter_expr(expr);
+
+    assert_eq!(
+        query.build(),
+        "SELECT * FROM users WHERE age >= 18 AND (name = 'Ann' OR name = 'Bob') OR NOT (is_active = true)"
+    );
+    let (sql, params) = query.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"SELECT * FROM "users" WHERE "age" >= $1 AND ("name" = $2 OR "name" = $3) OR NOT ("is_active" = $4)"#
+    );
+    assert_eq!(
+        params,
+        vec![
+            Value::Integer(18),
+            Value::Text("Ann".to_string()),
+            Value::Text("Bob".to_string()),
+            Value::Bool(true)
+        ]
+    );
+}
+
+#[test]
+fn expressions_filter_updates_and_deletes() {
+    let expr = Expr::col("id").lt(10).or(Expr::col("id").gt(20)).and(Expr::col("name").ne("root"));
+
+    assert_eq!(
+        UpdateQuery::<User>::new().set("is_active", false).filter_expr(expr.clone()).build(),
+        "UPDATE users SET is_active = false WHERE (id < 10 OR id > 20) AND name <> 'root'"
+    );
+    assert_eq!(
+        DeleteQuery::<User>::new().filter_expr(expr).build(),
+        "DELETE FROM users WHERE (id < 10 OR id > 20) AND name <> 'root'"
+    );
+}
+
+#[test]
+fn in_lists_format_values_by_type() {
+    let query = SelectQuery::<User>::new()
+        .filter_eq("is_active", true)
+        .filter_in("id", &[1, 2, 3])
+        .filter_not_in("name", &["root", "O'Brien"]);
+
+    assert_eq!(
+        query.build(),
+        "SELECT * FROM users WHERE is_active = true AND id IN (1, 2, 3) AND name NOT IN ('root', 'O''Brien')"
+    );
+    let (sql, params) = query.build_parameterized();
+    assert_eq!(
+        sql,
+        "SELECT * FROM users WHERE is_active = ? AND id IN (?, ?, ?) AND name NOT IN (?, ?)"
+    );
+    assert_eq!(params.len(), 6);
+}
+
+#[test]
+fn empty_in_lists_are_constant_conditions() {
+    let empty: [i32; 0] = [];
+    assert_eq!(
+        SelectQuery::<User>::new().filter_in("id", &empty).build(),
+        "SELECT * FROM users WHERE 1 = 0"
+    );
+    assert_eq!(
+        UpdateQuery::<User>::new().set("is_active", false).filter_not_in("id", &empty).build(),
+        "UPDATE users SET is_active = false WHERE 1 = 1"
+    );
+    assert_eq!(
+        DeleteQuery::<User>::new().filter_eq("is_active", false).filter_in("id", &empty).build(),
+        "DELETE FROM users WHERE is_active = false AND 1 = 0"
+    );
+}
+
+#[test]
+fn between_with_numeric_string_and_mixed_bounds() {
+    let numeric =
+        SelectQuery::<User>::new().filter_eq("is_active", true).filter_between("id", 10, 20);
+    assert_eq!(
+        numeric.build(),
+        "SELECT * FROM users WHERE is_active = true AND id BETWEEN 10 AND 20"
+    );
+
+    let strings = SelectQuery::<User>::new().filter_between("name", "A", "M'");
+    assert_eq!(strings.build(), "SELECT * FROM users WHERE name BETWEEN 'A' AND 'M'''");
+
+    let mixed = SelectQuery::<User>::new().filter_between("id", 1, 9_000_000_000i64);
+    let (sql, params) = mixed.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(sql, r#"SELECT * FROM "users" WHERE "id" BETWEEN $1 AND $2"#);
+    assert_eq!(params, vec![Value::Integer(1), Value::BigInt(9_000_000_000)]);
+}
+
+#[test]
+fn between_keeps_inverted_bounds() {
+    let query =
+        SelectQuery::<User>::new().filter_between("id", 20, 10).filter_not_between("id", 5, 1);
+    assert_eq!(
+        query.build(),
+        "SELECT * FROM users WHERE id BETWEEN 20 AND 10 AND id NOT BETWEEN 5 AND 1"
+    );
+}
+
+#[test]
+fn like_pattern_with_quotes_and_wildcards() {
+    let pattern = format!("%{}%", escape_like("O'Brien 50%_off\\"));
+    let query = SelectQuery::<User>::new().filter_like("name", &pattern);
+
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "users" WHERE "name" LIKE '%O''Brien 50\%\_off\\%' ESCAPE '\'"#
+    );
+    assert_eq!(
+        query.build_for(Dialect::Sqlite),
+        r#"SELECT * FROM "users" WHERE "name" LIKE '%O''Brien 50\%\_off\\%' ESCAPE '\'"#
+    );
+    // MySQL reads backslashes in literals as escapes, so both are doubled
+    assert_eq!(
+        query.build_for(Dialect::MySql),
+        r"SELECT * FROM `users` WHERE `name` LIKE '%O''Brien 50\\%\\_off\\\\%' ESCAPE '\\'"
+    );
+}
+
+#[test]
+fn like_pattern_is_bound_with_its_escape_clause() {
+    let query = SelectQuery::<User>::new().filter_like("name", "%it's\\_%");
+
+    let (sql, params) = query.build_parameterized();
+    assert_eq!(sql, r"SELECT * FROM users WHERE name LIKE ? ESCAPE '\'");
+    assert_eq!(params, vec![Value::Text("%it's\\_%".to_string())]);
+}
+
+#[test]
+fn not_like_declares_the_escape_character() {
+    let query = SelectQuery::<User>::new().filter_not_like("email", "%@example.com");
+
+    assert_eq!(
+        query.build_for(Dialect::Sqlite),
+        r#"SELECT * FROM "users" WHERE "email" NOT LIKE '%@example.com' ESCAPE '\'"#
+    );
+}
+
+#[test]
+fn ilike_falls_back_to_lower_without_ilike() {
+    let query = SelectQuery::<User>::new().filter_ilike("name", "%ann%");
+
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "users" WHERE "name" ILIKE '%ann%' ESCAPE '\'"#
+    );
+    assert_eq!(
+        query.build_for(Dialect::Sqlite),
+        r#"SELECT * FROM "users" WHERE LOWER("name") LIKE LOWER('%ann%') ESCAPE '\'"#
+    );
+    assert_eq!(
+        query.build_for(Dialect::MySql),
+        r"SELECT * FROM `users` WHERE LOWER(`name`) LIKE LOWER('%ann%') ESCAPE '\\'"
+    );
+}
+
+#[test]
+fn escape_like_escapes_wildcards_and_itself() {
+    assert_eq!(escape_like("100%"), r"100\%");
+    assert_eq!(escape_like("snake_case"), r"snake\_case");
+    assert_eq!(escape_like(r"C:\temp"), r"C:\\temp");
+    assert_eq!(escape_like("O'Brien"), "O'Brien");
+}
+
+#[test]
+fn clauses_are_written_in_order() {
+    // Built in the reverse of the order the clauses are written in
+    let query = SelectQuery::<User>::new()
+        .offset(20)
+        .limit(10)
+        .order_by(&["name"])
+        .having("COUNT(*) > 1")
+        .group_by(&["name"])
+        .group_by(&["email"])
+        .filter("is_active = true")
+        .select(&["name", "email"]);
+
+    assert_eq!(
+        query.build(),
+        "SELECT name, email FROM users WHERE is_active = true GROUP BY name, email HAVING COUNT(*) > 1 ORDER BY name LIMIT 10 OFFSET 20"
+    );
+}
+
+#[test]
+fn having_conditions_combine_with_and() {
+    let query = SelectQuery::<User>::new()
+        .select(&["name"])
+        .group_by(&["name"])
+        .having("COUNT(*) > 1")
+        .having_expr(Expr::raw("MAX(id)").lt(100))
+        .order_by_desc(&["name"])
+        .limit(5);
+
+    assert_eq!(
+        query.build(),
+        "SELECT name FROM users GROUP BY name HAVING (COUNT(*) > 1) AND (MAX(id)) < 100 ORDER BY name DESC LIMIT 5"
+    );
+    let (sql, params) = query.build_parameterized();
+    assert_eq!(
+        sql,
+        "SELECT name FROM users GROUP BY name HAVING (COUNT(*) > 1) AND (MAX(id)) < ? ORDER BY name DESC LIMIT 5"
+    );
+    assert_eq!(params, vec![Value::Integer(100)]);
+}
+
+#[test]
+fn having_without_group_by_is_written() {
+    let query = SelectQuery::<User>::new().select(&["COUNT(*)"]).having("COUNT(*) > 5");
+    assert_eq!(query.try_build().unwrap(), "SELECT COUNT(*) FROM users HAVING COUNT(*) > 5");
+}
+
+#[test]
+fn distinct_selects_distinct_rows() {
+    assert_eq!(SelectQuery::<User>::new().distinct().build(), "SELECT DISTINCT * FROM users");
+    assert_eq!(
+        SelectQuery::<User>::new().select(&["name", "email"]).distinct().build(),
+        "SELECT DISTINCT name, email FROM users"
+    );
+}
+
+#[test]
+fn distinct_on_lists_its_columns() {
+    let query = SelectQuery::<User>::new()
+        .select(&["name", "email", "id"])
+        .distinct_on(&["name", "email"])
+        .order_by(&["name", "email", "id"]);
+
+    assert_eq!(
+        query.try_build_for(Dialect::Postgres).unwrap(),
+        r#"SELECT DISTINCT ON ("name", "email") "name", "email", "id" FROM "users" ORDER BY "name", "email", "id""#
+    );
+    assert_eq!(
+        query.try_build_for(Dialect::MySql),
+        Err(QueryError::Unsupported { feature: "DISTINCT ON", dialect: Dialect::MySql })
+    );
+}
+
+#[test]
+fn last_distinct_call_wins() {
+    let query = SelectQuery::<User>::new().distinct_on(&["name"]).distinct();
+    assert_eq!(query.try_build_for(Dialect::Sqlite).unwrap(), r#"SELECT DISTINCT * FROM "users""#);
+
+    let query = SelectQuery::<User>::new().distinct().distinct_on(&["name"]);
+    assert_eq!(query.build(), "SELECT DISTINCT ON (name) * FROM users");
+}
+
+#[test]
+fn aliased_columns_keep_the_call_order() {
+    let query = SelectQuery::<User>::new()
+        .select(&["id"])
+        .select_as(&[("name", "full_name"), ("LOWER(email)", "login")])
+        .select(&["is_active"])
+        .select_as(&[("id", "user id")]);
+
+    assert_eq!(
+        query.build(),
+        r#"SELECT id, name AS full_name, LOWER(email) AS login, is_active, id AS "user id" FROM users"#
+    );
+    assert_eq!(
+        query.build_for(Dialect::MySql),
+        "SELECT `id`, `name` AS `full_name`, LOWER(email) AS `login`, `is_active`, `id` AS `user id` FROM `users`"
+    );
+}
+
+#[test]
+fn aggregates_combine_with_group_by_and_columns() {
+    let query = SelectQuery::<Invoice>::new()
+        .select(&["status"])
+        .select_count("*", Some("n"))
+        .group_by(&["status"]);
+    assert_eq!(query.build(), "SELECT status, COUNT(*) AS n FROM invoices GROUP BY status");
+
+    let totals = SelectQuery::<Invoice>::new()
+        .select(&["status"])
+        .select_sum("amount", None)
+        .select_avg("amount", Some("average"))
+        .select_min("amount", Some("low"))
+        .select_max("amount", Some("high"))
+        .group_by(&["status"]);
+    assert_eq!(
+        totals.build_for(Dialect::Postgres),
+        r#"SELECT "status", SUM(amount), AVG(amount) AS "average", MIN(amount) AS "low", MAX(amount) AS "high" FROM "invoices" GROUP BY "status""#
+    );
+}
+
+#[test]
+fn count_replaces_the_select_list_and_drops_the_ordering() {
+    let query = SelectQuery::<Invoice>::new()
+        .select(&["status", "amount"])
+        .filter_eq("status", "paid")
+        .order_by(&["amount"])
+        .count();
+    assert_eq!(query.build(), "SELECT COUNT(*) FROM invoices WHERE status = 'paid'");
+}
+
+#[test]
+fn order_directions_interleave_in_call_order() {
+    let query = SelectQuery::<User>::new()
+        .order_by(&["is_active"])
+        .order_by_dir(&[("name", Order::Asc), ("id", Order::Desc)])
+        .order_by_desc(&["email"]);
+
+    assert_eq!(
+        query.build(),
+        "SELECT * FROM users ORDER BY is_active, name ASC, id DESC, email DESC"
+    );
+    assert_eq!(
+        SelectQuery::<User>::new().order_by(&["name", "id"]).build(),
+        "SELECT * FROM users ORDER BY name, id"
+    );
+}
+
+#[test]
+fn nulls_placement_mixes_with_plain_columns() {
+    let query = SelectQuery::<User>::new()
+        .order_by(&["is_active"])
+        .order_by_with(&[("email", Order::Desc, Some(NullsOrder::Last))])
+        .order_by_with(&[("name", Order::Asc, None)])
+        .order_by_with(&[("id", Order::Asc, Some(NullsOrder::First))]);
+
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "users" ORDER BY "is_active", "email" DESC NULLS LAST, "name" ASC, "id" ASC NULLS FIRST"#
+    );
+    // MySQL sorts on the IS NULL flag first instead
+    assert_eq!(
+        query.build_for(Dialect::MySql),
+        "SELECT * FROM `users` ORDER BY `is_active`, `email` IS NULL, `email` DESC, `name` ASC, `id` IS NULL DESC, `id` ASC"
+    );
+}
+
+#[test]
+fn joins_render_between_from_and_where_in_call_order() {
+    let query = SelectQuery::<User>::new()
+        .filter_eq("users.is_active", true)
+        .inner_join("posts", "posts.user_id = users.id")
+        .left_join("comments", "comments.post_id = posts.id")
+        .right_join("teams", "teams.id = users.team_id")
+        .cross_join("settings")
+        .inner_join_model::<Invoice>("invoices.user_id = users.id");
+
+    assert_eq!(
+        query.build(),
+        "SELECT * FROM users INNER JOIN posts ON posts.user_id = users.id LEFT JOIN comments ON comments.post_id = posts.id RIGHT JOIN teams ON teams.id = users.team_id CROSS JOIN settings INNER JOIN invoices ON invoices.user_id = users.id WHERE users.is_active = true"
+    );
+}
+
+#[test]
+fn self_join_with_two_aliases() {
+    let query = SelectQuery::<User>::new()
+        .alias("u")
+        .select_as(&[("u.name", "employee"), ("m.name", "manager")])
+        .inner_join_as("users", "m", "u.manager_id = m.id")
+        .filter_eq("m.is_active", true);
+
+    assert_eq!(query.qualifier(), "u");
+    assert_eq!(
+        query.build(),
+        "SELECT u.name AS employee, m.name AS manager FROM users AS u INNER JOIN users AS m ON u.manager_id = m.id WHERE m.is_active = true"
+    );
+    assert_eq!(SelectQuery::<User>::new().qualifier(), "users");
+}
+
+#[test]
+fn nested_subqueries_merge_their_parameters_in_place() {
+    let active_invoices = SelectQuery::<Invoice>::new()
+        .select(&["user_id"])
+        .filter_eq("status", "paid")
+        .filter_in_subquery(
+            "user_id",
+            SelectQuery::<User>::new().select(&["id"]).filter_eq("is_active", true),
+        );
+    let query = SelectQuery::<User>::new()
+        .filter_eq("name", "Ann")
+        .filter_in_subquery("id", active_invoices)
+        .filter_gt("id", 10);
+
+    let (sql, params) = query.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"SELECT * FROM "users" WHERE "name" = $1 AND "id" IN (SELECT "user_id" FROM "invoices" WHERE "status" = $2 AND "user_id" IN (SELECT "id" FROM "users" WHERE "is_active" = $3)) AND "id" > $4"#
+    );
+    assert_eq!(
+        params,
+        vec![
+            Value::Text("Ann".to_string()),
+            Value::Text("paid".to_string()),
+            Value::Bool(true),
+            Value::Integer(10)
+        ]
+    );
+}
+
+#[test]
+fn exists_subqueries_are_parenthesized() {
+    let invoices =
+        SelectQuery::<Invoice>::new().select(&["1"]).filter("invoices.user_id = users.id");
+    let query = SelectQuery::<User>::new()
+        .filter_exists(invoices.clone().filter_eq("status", "paid"))
+        .filter_not_exists(invoices.filter_eq("status", "overdue"));
+
+    assert_eq!(
+        query.build(),
+        "SELECT * FROM users WHERE EXISTS (SELECT 1 FROM invoices WHERE (invoices.user_id = users.id) AND status = 'paid') AND NOT EXISTS (SELECT 1 FROM invoices WHERE (invoices.user_id = users.id) AND status = 'overdue')"
+    );
+}
+
+#[test]
+fn unions_of_differently_filtered_queries_with_an_outer_limit() {
+    let active = SelectQuery::<User>::new().select(&["name"]).filter_eq("is_active", true);
+    let recent = SelectQuery::<User>::new().select(&["name"]).filter_gt("id", 100);
+    let billed = SelectQuery::<Invoice>::new().select(&["status"]).filter_eq("status", "paid");
+    let query = active.union(recent).union_all(billed).order_by(&["name"]).limit(10);
+
+    assert_eq!(
+        query.build(),
+        "(SELECT name FROM users WHERE is_active = true) UNION (SELECT name FROM users WHERE id > 100) UNION ALL (SELECT status FROM invoices WHERE status = 'paid') ORDER BY name LIMIT 10"
+    );
+    let (sql, params) = query.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"(SELECT "name" FROM "users" WHERE "is_active" = $1) UNION (SELECT "name" FROM "users" WHERE "id" > $2) UNION ALL (SELECT "status" FROM "invoices" WHERE "status" = $3) ORDER BY "name" LIMIT 10"#
+    );
+    assert_eq!(
+        params,
+        vec![Value::Bool(true), Value::Integer(100), Value::Text("paid".to_string())]
+    );
+}
+
+#[test]
+fn typed_filters_format_their_values() {
+    let query = SelectQuery::<User>::new()
+        .filter_eq("name", "O'Brien")
+        .filter_ne("is_active", false)
+        .filter_gt("id", 1)
+        .filter_lt("id", 100)
+        .filter_ge("id", 2)
+        .filter_le("id", 99);
+
+    assert_eq!(
+        query.build(),
+        "SELECT * FROM users WHERE name = 'O''Brien' AND is_active <> false AND id > 1 AND id < 100 AND id >= 2 AND id <= 99"
+    );
+}
+
+#[test]
+fn typed_filters_reject_unknown_columns() {
+    let query = SelectQuery::<User>::new().filter_eq("emial", "ann@example.com");
+    assert_eq!(
+        query.try_build(),
+        Err(QueryError::UnknownColumn { table: "users".to_string(), column: "emial".to_string() })
+    );
+    // Qualified names may refer to joined tables, so they're left to the database
+    assert!(SelectQuery::<User>::new().filter_eq("posts.title", "Hi").try_build().is_ok());
+}
+
+#[test]
+fn each_invalid_builder_state_has_its_error() {
+    let table = || "users".to_string();
+    assert_eq!(
+        UpdateQuery::<User>::new().filter_eq("id", 1).try_build(),
+        Err(QueryError::EmptySetClause { table: table() })
+    );
+    assert_eq!(
+        InsertQuery::<User>::new().try_build(),
+        Err(QueryError::NoValues { table: table() })
+    );
+    assert_eq!(
+        UpdateQuery::<User>::new().set("nmae", "Ann").filter_eq("id", 1).try_build(),
+        Err(QueryError::UnknownColumn { table: table(), column: "nmae".to_string() })
+    );
+    assert_eq!(
+        SelectQuery::<User>::new().offset(10).try_build(),
+        Err(QueryError::OffsetWithoutLimit)
+    );
+}
+
+#[test]
+fn multi_row_insert_lists_each_row() {
+    let query = InsertQuery::<User>::new()
+        .columns(&["name", "email"])
+        .row(&["Alice", "a@x.com"])
+        .row(&["Bob", "b@x.com"]);
+    assert_eq!(
+        query.build(),
+        "INSERT INTO users (name, email) VALUES ('Alice', 'a@x.com'), ('Bob', 'b@x.com');"
+    );
+
+    let mixed = InsertQuery::<User>::new()
+        .columns(&["id", "name"])
+        .row::<&dyn ToValue>(&[&1, &"Alice"])
+        .row::<&dyn ToValue>(&[&2, &"Bob"]);
+    let (sql, params) = mixed.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"INSERT INTO "users" ("id", "name") OVERRIDING SYSTEM VALUE VALUES ($1, $2), ($3, $4);"#
+    );
+    assert_eq!(params.len(), 4);
+}
+
+#[test]
+fn rows_of_the_wrong_length_are_rejected() {
+    let query = InsertQuery::<User>::new()
+        .columns(&["name", "email"])
+        .row(&["Alice", "a@x.com"])
+        .row(&["Bob"]);
+    assert_eq!(query.try_build(), Err(QueryError::RowLength { row: 1, expected: 2, found: 1 }));
+}
+
+#[test]
+fn chunked_rows_split_into_statements() {
+    let query = ["Ann", "Bob", "Cid", "Dee", "Eve"]
+        .iter()
+        .fold(InsertQuery::<User>::new().columns(&["name"]), |query, name| query.row(&[*name]));
+
+    assert_eq!(
+        query.rows_chunked(2).unwrap(),
+        [
+            "INSERT INTO users (name) VALUES ('Ann'), ('Bob');",
+            "INSERT INTO users (name) VALUES ('Cid'), ('Dee');",
+            "INSERT INTO users (name) VALUES ('Eve');",
+        ]
+    );
+    assert_eq!(query.rows_chunked(10).unwrap(), [query.build()]);
+    assert_eq!(
+        query.rows_chunked_for(3, Dialect::MySql).unwrap(),
+        [
+            "INSERT INTO `users` (`name`) VALUES ('Ann'), ('Bob'), ('Cid');",
+            "INSERT INTO `users` (`name`) VALUES ('Dee'), ('Eve');",
+        ]
+    );
+}
+
+#[test]
+fn chunked_rows_bind_their_own_values() {
+    let query = ["Ann", "Bob", "Cid"]
+        .iter()
+        .fold(InsertQuery::<User>::new().columns(&["name"]), |query, name| query.row(&[*name]));
+
+    let chunks = query.rows_chunked_parameterized_for(2, Dialect::Postgres).unwrap();
+    assert_eq!(
+        chunks,
+        [
+            (
+                r#"INSERT INTO "users" ("name") VALUES ($1), ($2);"#.to_string(),
+                vec![Value::Text("Ann".to_string()), Value::Text("Bob".to_string())]
+            ),
+            (
+                r#"INSERT INTO "users" ("name") VALUES ($1);"#.to_string(),
+                vec![Value::Text("Cid".to_string())]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn chunking_validates_the_rows() {
+    let query = InsertQuery::<User>::new()
+        .columns(&["name", "email"])
+        .row(&["Alice", "a@x.com"])
+        .row(&["Bob"]);
+    let error = QueryError::RowLength { row: 1, expected: 2, found: 1 };
+    assert_eq!(query.rows_chunked(1), Err(error.clone()));
+    assert_eq!(query.rows_chunked_for(1, Dialect::Sqlite), Err(error.clone()));
+    assert_eq!(query.rows_chunked_parameterized_for(1, Dialect::Sqlite), Err(error));
+
+    let empty = InsertQuery::<User>::new().columns(&["name"]);
+    assert_eq!(empty.rows_chunked(10), Err(QueryError::NoValues { table: "users".to_string() }));
+
+    let returning = InsertQuery::<User>::new().columns(&["name"]).row(&["Ann"]).returning_all();
+    assert!(matches!(
+        returning.rows_chunked_for(10, Dialect::MySql),
+        Err(QueryError::Unsupported { feature: "RETURNING", .. })
+    ));
+}
+
+#[test]
+fn on_conflict_do_nothing_defaults_to_the_primary_key() {
+    let query = InsertQuery::<User>::new().value("id", 1).value("name", "Ann");
+
+    assert_eq!(
+        query.clone().on_conflict_do_nothing(&[]).build(),
+        "INSERT INTO users (id, name) VALUES (1, 'Ann') ON CONFLICT (id) DO NOTHING;"
+    );
+    assert_eq!(
+        query.on_conflict_do_nothing(&["email"]).build(),
+        "INSERT INTO users (id, name) VALUES (1, 'Ann') ON CONFLICT (email) DO NOTHING;"
+    );
+}
+
+#[test]
+fn on_conflict_updates_a_subset_of_columns() {
+    let query = InsertQuery::<User>::new()
+        .value("id", 1)
+        .value("name", "Ann")
+        .value("email", "ann@example.com")
+        .on_conflict_update(&["id"], &[("email", "excluded.email")]);
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"INSERT INTO "users" ("id", "name", "email") OVERRIDING SYSTEM VALUE VALUES (1, 'Ann', 'ann@example.com') ON CONFLICT ("id") DO UPDATE SET "email" = excluded.email;"#
+    );
+
+    let mysql = InsertQuery::<User>::new()
+        .value("id", 1)
+        .value("email", "ann@example.com")
+        .on_duplicate_key_update(&[("email", "VALUES(email)")]);
+    assert_eq!(
+        mysql.build_for(Dialect::MySql),
+        "INSERT INTO `users` (`id`, `email`) VALUES (1, 'ann@example.com') ON DUPLICATE KEY UPDATE `email` = VALUES(email);"
+    );
+}
+
+#[test]
+fn conflict_target_needs_a_primary_key() {
+    let query = InsertQuery::<Tag>::new().value("label", "rust").on_conflict_do_nothing(&[]);
+    assert_eq!(query.try_build(), Err(QueryError::MissingPrimaryKey { table: "tags".to_string() }));
+}
+
+#[test]
+fn returning_follows_on_conflict_and_where() {
+    let insert = InsertQuery::<User>::new()
+        .value("name", "Ann")
+        .on_conflict_do_nothing(&["name"])
+        .returning(&["id"]);
+    assert_eq!(
+        insert.build_for(Dialect::Sqlite),
+        r#"INSERT INTO "users" ("name") VALUES ('Ann') ON CONFLICT ("name") DO NOTHING RETURNING "id";"#
+    );
+
+    let update =
+        UpdateQuery::<User>::new().set("is_active", false).filter_eq("id", 1).returning_all();
+    assert_eq!(
+        update.build_for(Dialect::Postgres),
+        r#"UPDATE "users" SET "is_active" = FALSE WHERE "id" = 1 RETURNING *"#
+    );
+
+    let delete = DeleteQuery::<User>::new().filter_eq("id", 1).returning(&["id", "name"]);
+    assert_eq!(delete.build(), "DELETE FROM users WHERE id = 1 RETURNING id, name");
+}
+
+#[test]
+fn returning_is_rejected_on_mysql() {
+    let unsupported =
+        Err(QueryError::Unsupported { feature: "RETURNING", dialect: Dialect::MySql });
+    assert_eq!(
+        InsertQuery::<User>::new()
+            .value("name", "Ann")
+            .returning_all()
+            .try_build_for(Dialect::MySql),
+        unsupported
+    );
+    assert_eq!(
+        UpdateQuery::<User>::new()
+            .set("name", "Ann")
+            .filter_eq("id", 1)
+            .returning(&["id"])
+            .try_build_for(Dialect::MySql),
+        unsupported
+    );
+    assert_eq!(
+        DeleteQuery::<User>::new().filter_eq("id", 1).returning_all().try_build_for(Dialect::MySql),
+        unsupported
+    );
+}
+
+#[test]
+fn insert_from_a_filtered_and_limited_select() {
+    let select = SelectQuery::<User>::new()
+        .select(&["id", "name"])
+        .filter_eq("is_active", false)
+        .order_by(&["id"])
+        .limit(100);
+    let query = InsertQuery::<ArchivedUser>::new().from_select(&["id", "name"], select);
+
+    assert_eq!(
+        query.build(),
+        "INSERT INTO archived_users (id, name) SELECT id, name FROM users WHERE is_active = false ORDER BY id LIMIT 100;"
+    );
+    let (sql, params) = query.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"INSERT INTO "archived_users" ("id", "name") SELECT "id", "name" FROM "users" WHERE "is_active" = $1 ORDER BY "id" LIMIT 100;"#
+    );
+    assert_eq!(params, vec![Value::Bool(false)]);
+}
+
+#[test]
+fn insert_from_select_checks_its_columns() {
+    let select = SelectQuery::<User>::new().select(&["id", "name", "email"]);
+    assert_eq!(
+        InsertQuery::<ArchivedUser>::new().from_select(&["id", "name"], select.clone()).try_build(),
+        Err(QueryError::SelectColumnCount { expected: 2, found: 3 })
+    );
+    assert_eq!(
+        InsertQuery::<ArchivedUser>::new()
+            .value("id", 1)
+            .from_select(&["name"], select)
+            .try_build(),
+        Err(QueryError::MixedInsertSource)
+    );
+    // Without an explicit column list, the count is left to the database
+    let all =
+        InsertQuery::<ArchivedUser>::new().from_select(&["id", "name"], SelectQuery::<User>::new());
+    assert!(all.try_build().is_ok());
+}
+
+#[test]
+fn insert_from_model_skips_generated_keys() {
+    let user = User {
+        id: 0,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        is_active: true,
+    };
+
+    assert_eq!(
+        InsertQuery::from_model(&user).build(),
+        "INSERT INTO users (name, email, is_active) VALUES ('Ann', 'ann@example.com', true);"
+    );
+}
+
+#[test]
+fn insert_from_model_with_keys_keeps_field_order() {
+    let user = User {
+        id: 7,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        is_active: true,
+    };
+
+    let insert = InsertQuery::from_model_with_keys(&user);
+    assert_eq!(
+        insert.build(),
+        "INSERT INTO users (id, name, email, is_active) VALUES (7, 'Ann', 'ann@example.com', true);"
+    );
+    // Identity columns are generated always on PostgreSQL
+    assert_eq!(
+        insert.build_for(Dialect::Postgres),
+        r#"INSERT INTO "users" ("id", "name", "email", "is_active") OVERRIDING SYSTEM VALUE VALUES (7, 'Ann', 'ann@example.com', TRUE);"#
+    );
+    assert_eq!(
+        insert.build_for(Dialect::Sqlite),
+        r#"INSERT INTO "users" ("id", "name", "email", "is_active") VALUES (7, 'Ann', 'ann@example.com', 1);"#
+    );
+}
+
+#[test]
+fn update_from_model_sets_columns_by_primary_key() {
+    let user = User {
+        id: 7,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        is_active: false,
+    };
+
+    let (sql, params) = UpdateQuery::from_model(&user).build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"UPDATE "users" SET "name" = $1, "email" = $2, "is_active" = $3 WHERE "id" = $4"#
+    );
+    assert_eq!(
+        params,
+        vec![
+            Value::Text("Ann".to_string()),
+            Value::Text("ann@example.com".to_string()),
+            Value::Bool(false),
+            Value::Integer(7)
+        ]
+    );
+    assert_eq!(
+        UpdateQuery::from_model(&user).only(&["email"]).build(),
+        "UPDATE users SET email = 'ann@example.com' WHERE id = 7"
+    );
+}
+
+#[test]
+fn update_from_model_needs_a_primary_key() {
+    let tag = Tag { label: "rust".to_string() };
+    assert_eq!(
+        UpdateQuery::from_model(&tag).try_build(),
+        Err(QueryError::MissingPrimaryKey { table: "tags".to_string() })
+    );
+}
+
+#[test]
+fn unfiltered_updates_and_deletes_are_rejected() {
+    assert_eq!(
+        UpdateQuery::<User>::new().set("is_active", false).try_build(),
+        Err(QueryError::MissingWhereClause { table: "users".to_string() })
+    );
+    assert_eq!(
+        DeleteQuery::<User>::new().try_build(),
+        Err(QueryError::MissingWhereClause { table: "users".to_string() })
+    );
+}
+
+#[test]
+fn all_rows_opts_into_unfiltered_updates_and_deletes() {
+    assert_eq!(
+        UpdateQuery::<User>::new().set("is_active", false).all_rows().try_build().unwrap(),
+        "UPDATE users SET is_active = false"
+    );
+    assert_eq!(DeleteQuery::<User>::new().all_rows().try_build().unwrap(), "DELETE FROM users");
+    // The permissive build still writes them
+    assert_eq!(DeleteQuery::<User>::new().build(), "DELETE FROM users");
+}
+
+#[test]
+fn literal_and_expression_sets_keep_their_order() {
+    let query = UpdateQuery::<User>::new()
+        .set("name", "Ann")
+        .set_expr("email", "LOWER(email)")
+        .increment("id", 1)
+        .set("is_active", false)
+        .filter_eq("id", 7);
+
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"UPDATE "users" SET "name" = 'Ann', "email" = LOWER(email), "id" = "id" + 1, "is_active" = FALSE WHERE "id" = 7"#
+    );
+}
+
+#[test]
+fn increment_and_decrement_bind_their_amount() {
+    let query = UpdateQuery::<User>::new().increment("id", 5).decrement("order", 2);
+
+    let (sql, params) = query.build_parameterized_for(Dialect::Postgres);
+    assert_eq!(sql, r#"UPDATE "users" SET "id" = "id" + $1, "order" = "order" - $2"#);
+    assert_eq!(params, vec![Value::Integer(5), Value::Integer(2)]);
+
+    assert_eq!(
+        query.build_for(Dialect::MySql),
+        "UPDATE `users` SET `id` = `id` + 5, `order` = `order` - 2"
+    );
+}
+
+#[test]
+fn set_null_mixes_with_regular_sets() {
+    let query = UpdateQuery::<User>::new()
+        .set("name", "NULL")
+        .set_null("email")
+        .set("is_active", false)
+        .filter_eq("id", 1);
+
+    assert_eq!(
+        query.build(),
+        "UPDATE users SET name = 'NULL', email = NULL, is_active = false WHERE id = 1"
+    );
+    let (sql, params) = query.build_parameterized();
+    assert_eq!(sql, "UPDATE users SET name = ?, email = ?, is_active = ? WHERE id = ?");
+    assert_eq!(
+        params,
+        vec![Value::Text("NULL".to_string()), Value::Null, Value::Bool(false), Value::Integer(1)]
+    );
+}
+
+#[test]
+fn optional_values_set_null_when_none() {
+    let email: Option<&str> = None;
+    let query = UpdateQuery::<User>::new()
+        .set("email", email)
+        .set("name", Some("Ann"))
+        .set("is_active", Value::Null)
+        .filter_eq("id", 1);
+
+    assert_eq!(
+        query.build(),
+        "UPDATE users SET email = NULL, name = 'Ann', is_active = NULL WHERE id = 1"
+    );
+}
+
+#[test]
+fn delete_with_using_writes_clauses_in_order() {
+    let query = DeleteQuery::<Invoice>::new()
+        .using("users", "invoices.user_id = users.id")
+        .filter_eq("users.is_active", false)
+        .returning(&["invoices.id"]);
+
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"DELETE FROM "invoices" USING "users" WHERE (invoices.user_id = users.id) AND "users"."is_active" = FALSE RETURNING "invoices"."id""#
+    );
+    // MySQL's multi-table form lists the target table as well
+    let mysql = DeleteQuery::<Invoice>::new()
+        .using("users", "invoices.user_id = users.id")
+        .filter_eq("users.is_active", false);
+    assert_eq!(
+        mysql.try_build_for(Dialect::MySql).unwrap(),
+        "DELETE FROM `invoices` USING `invoices`, `users` WHERE (invoices.user_id = users.id) AND `users`.`is_active` = FALSE"
+    );
+    assert_eq!(
+        DeleteQuery::<Invoice>::new()
+            .using_model::<User>("invoices.user_id = users.id")
+            .all_rows()
+            .build(),
+        "DELETE FROM invoices USING users WHERE invoices.user_id = users.id"
+    );
+}
+
+#[test]
+fn delete_without_using_returns_deleted_rows() {
+    let query = DeleteQuery::<Invoice>::new().filter_eq("status", "void").returning_all();
+    assert_eq!(query.build(), "DELETE FROM invoices WHERE status = 'void' RETURNING *");
+}
+
+#[test]
+fn truncate_option_combinations() {
+    let cases = [
+        (false, false, false, "TRUNCATE TABLE users"),
+        (true, false, false, "TRUNCATE TABLE ONLY users"),
+        (false, true, false, "TRUNCATE TABLE users RESTART IDENTITY"),
+        (false, false, true, "TRUNCATE TABLE users CASCADE"),
+        (true, true, false, "TRUNCATE TABLE ONLY users RESTART IDENTITY"),
+        (true, false, true, "TRUNCATE TABLE ONLY users CASCADE"),
+        (false, true, true, "TRUNCATE TABLE users RESTART IDENTITY CASCADE"),
+        (true, true, true, "TRUNCATE TABLE ONLY users RESTART IDENTITY CASCADE"),
+    ];
+    for (only, restart_identity, cascade, expected) in cases {
+        let mut query = TruncateQuery::<User>::new();
+        if only {
+            query = query.only();
+        }
+        if restart_identity {
+            query = query.restart_identity();
+        }
+        if cascade {
+            query = query.cascade();
+        }
+
+        assert_eq!(query.build(), expected);
+        assert_eq!(query.try_build().unwrap(), expected);
+        assert_eq!(
+            query.try_build_for(Dialect::Postgres).unwrap(),
+            expected.replace("users", r#""users""#)
+        );
+        assert_eq!(query.try_build_for(Dialect::Sqlite).unwrap(), r#"DELETE FROM "users""#);
+        // MySQL can't cascade a TRUNCATE, so it deletes the rows instead
+        let mysql = if cascade { "DELETE FROM `users`" } else { "TRUNCATE TABLE `users`" };
+        assert_eq!(query.build_for(Dialect::MySql), mysql);
+        assert_eq!(query.try_build_for(Dialect::MySql).unwrap(), mysql);
+    }
+}
+
+#[test]
+fn paginate_sets_limit_and_offset() {
+    let query = SelectQuery::<User>::new().order_by(&["id"]);
+
+    assert_eq!(
+        query.clone().paginate(3, 20).build(),
+        "SELECT * FROM users ORDER BY id LIMIT 20 OFFSET 40"
+    );
+    assert_eq!(query.clone().paginate(1, 20).build(), query.clone().paginate(0, 20).build());
+}
+
+#[test]
+fn count_query_preserves_joins_and_filters() {
+    let query = SelectQuery::<User>::new()
+        .select(&["users.name", "posts.title"])
+        .inner_join("posts", "posts.user_id = users.id")
+        .filter_eq("users.is_active", true)
+        .filter_gt("posts.id", 10)
+        .order_by(&["users.name"])
+        .paginate(2, 25);
+
+    let (sql, params) = query.count_query().build_parameterized_for(Dialect::Postgres);
+    assert_eq!(
+        sql,
+        r#"SELECT COUNT(*) FROM "users" INNER JOIN "posts" ON posts.user_id = users.id WHERE "users"."is_active" = $1 AND "posts"."id" > $2"#
+    );
+    assert_eq!(params, vec![Value::Bool(true), Value::Integer(10)]);
+}
+
+#[test]
+fn count_query_wraps_grouped_and_distinct_queries() {
+    let grouped = SelectQuery::<User>::new()
+        .select(&["users.id"])
+        .inner_join("posts", "posts.user_id = users.id")
+        .filter_eq("users.is_active", true)
+        .group_by(&["users.id"])
+        .having("COUNT(posts.id) > 1")
+        .order_by(&["users.id"])
+        .limit(10);
+    assert_eq!(
+        grouped.count_query().build_for(Dialect::Postgres),
+        r#"SELECT COUNT(*) FROM (SELECT "users"."id" FROM "users" INNER JOIN "posts" ON posts.user_id = users.id WHERE "users"."is_active" = TRUE GROUP BY "users"."id" HAVING COUNT(posts.id) > 1) AS "t""#
+    );
+
+    let distinct =
+        SelectQuery::<User>::new().select(&["name"]).distinct().filter_eq("is_active", true);
+    let (sql, params) = distinct.count_query().build_parameterized_for(Dialect::MySql);
+    assert_eq!(
+        sql,
+        "SELECT COUNT(*) FROM (SELECT DISTINCT `name` FROM `users` WHERE `is_active` = ?) AS `t`"
+    );
+    assert_eq!(params, vec![Value::Bool(true)]);
+}
+
+#[test]
+fn keyset_after_single_column_cursor() {
+    let (sql, params) = SelectQuery::<User>::new()
+        .order_by(&["name"])
+        .after(&[("id", 42)])
+        .limit(20)
+        .build_parameterized_for(Dialect::Postgres);
+    assert_eq!(sql, r#"SELECT * FROM "users" WHERE "id" > $1 ORDER BY "id" ASC LIMIT 20"#);
+    assert_eq!(params, vec![Value::Integer(42)]);
+}
+
+#[test]
+fn keyset_composite_cursors_ascending_and_descending() {
+    let query = SelectQuery::<User>::new().filter_eq("is_active", true).limit(20);
+
+    let (sql, params) = query
+        .clone()
+        .after(&[("name", "ada".to_value()), ("id", 7.to_value())])
+        .build_parameterized_for(Dialect::Sqlite);
+    assert_eq!(
+        sql,
+        r#"SELECT * FROM "users" WHERE "is_active" = ? AND ("name", "id") > (?, ?) ORDER BY "name" ASC, "id" ASC LIMIT 20"#
+    );
+    assert_eq!(params, vec![Value::Bool(true), "ada".to_value(), Value::Integer(7)]);
+
+    assert_eq!(
+        query.before(&[("name", "ada".to_value()), ("id", 7.to_value())]).build_for(Dialect::MySql),
+        "SELECT * FROM `users` WHERE `is_active` = TRUE AND (`name`, `id`) < ('ada', 7) ORDER BY `name` DESC, `id` DESC LIMIT 20"
+    );
+}
+
+#[test]
+fn keyset_refuses_an_offset() {
+    let query = SelectQuery::<User>::new().after(&[("id", 42)]).limit(20).offset(40);
+    assert_eq!(query.try_build(), Err(QueryError::KeysetWithOffset));
+}
+
+#[test]
+fn cloned_queries_are_modified_independently() {
+    let base = SelectQuery::<User>::new()
+        .inner_join("posts", "posts.user_id = users.id")
+        .filter_eq("users.is_active", true)
+        .order_by(&["users.name"]);
+    let original = base.build();
+
+    let page = base.clone().limit(10);
+    let filtered = base.clone().filter_like("users.name", "a%");
+    let count = base.count_query();
+    assert_ne!(page.build(), original);
+    assert_ne!(filtered.build(), original);
+    assert_ne!(count.build(), original);
+    assert_eq!(base.build(), original);
+
+    let insert = InsertQuery::<User>::new().value("name", "ada");
+    let _ = insert.clone().value("email", "ada@example.com");
+    assert_eq!(insert.build(), "INSERT INTO users (name) VALUES ('ada');");
+
+    let update = UpdateQuery::<User>::new().set("name", "ada").filter_eq("id", 1);
+    let _ = update.clone().set("email", "ada@example.com");
+    assert_eq!(update.build(), "UPDATE users SET name = 'ada' WHERE id = 1");
+
+    let delete = DeleteQuery::<User>::new().filter_eq("id", 1);
+    let _ = delete.clone().filter_eq("is_active", false);
+    assert_eq!(delete.build(), "DELETE FROM users WHERE id = 1");
+
+    assert!(format!("{:?}", base).contains("users"));
+}
+
+#[test]
+fn one_query_across_all_dialects() {
+    let query = SelectQuery::<User>::new()
+        .select(&["id", "name"])
+        .filter_eq("is_active", true)
+        .filter_gt("id", 10)
+        .order_by(&["name"])
+        .limit(20)
+        .offset(40);
+
+    assert_eq!(
+        query.build(),
+        "SELECT id, name FROM users WHERE is_active = true AND id > 10 ORDER BY name LIMIT 20 OFFSET 40"
+    );
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"SELECT "id", "name" FROM "users" WHERE "is_active" = TRUE AND "id" > 10 ORDER BY "name" LIMIT 20 OFFSET 40"#
+    );
+    assert_eq!(
+        query.build_for(Dialect::MySql),
+        "SELECT `id`, `name` FROM `users` WHERE `is_active` = TRUE AND `id` > 10 ORDER BY `name` LIMIT 20 OFFSET 40"
+    );
+    assert_eq!(
+        query.build_for(Dialect::Sqlite),
+        r#"SELECT "id", "name" FROM "users" WHERE "is_active" = 1 AND "id" > 10 ORDER BY "name" LIMIT 20 OFFSET 40"#
+    );
+
+    assert_eq!(
+        query.build_parameterized_for(Dialect::Postgres).0,
+        r#"SELECT "id", "name" FROM "users" WHERE "is_active" = $1 AND "id" > $2 ORDER BY "name" LIMIT 20 OFFSET 40"#
+    );
+    assert_eq!(
+        query.build_parameterized_for(Dialect::MySql).0,
+        "SELECT `id`, `name` FROM `users` WHERE `is_active` = ? AND `id` > ? ORDER BY `name` LIMIT 20 OFFSET 40"
+    );
+}
+
+#[test]
+fn offset_without_limit_per_dialect() {
+    let query = SelectQuery::<User>::new().order_by(&["id"]).offset(40);
+    assert_eq!(query.try_build(), Err(QueryError::OffsetWithoutLimit));
+    assert_eq!(query.try_build_for(Dialect::Sqlite), Err(QueryError::OffsetWithoutLimit));
+
+    // Unvalidated builds fill in the LIMIT each database needs
+    assert_eq!(
+        query.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "users" ORDER BY "id" OFFSET 40"#
+    );
+    assert_eq!(
+        query.build_for(Dialect::MySql),
+        "SELECT * FROM `users` ORDER BY `id` LIMIT 18446744073709551615 OFFSET 40"
+    );
+    assert_eq!(
+        query.build_for(Dialect::Sqlite),
+        r#"SELECT * FROM "users" ORDER BY "id" LIMIT -1 OFFSET 40"#
+    );
+}
+
+#[test]
+fn reserved_and_mixed_case_names_are_quoted() {
+    let select = SelectQuery::<Group>::new()
+        .select(&["order", "displayName"])
+        .filter_eq("order", 1)
+        .filter_eq("group.displayName", "Admins")
+        .order_by(&["order"]);
+    assert_eq!(
+        select.build(),
+        r#"SELECT "order", "displayName" FROM "group" WHERE "order" = 1 AND "group"."displayName" = 'Admins' ORDER BY "order""#
+    );
+    assert_eq!(
+        select.build_for(Dialect::MySql),
+        "SELECT `order`, `displayName` FROM `group` WHERE `order` = 1 AND `group`.`displayName` = 'Admins' ORDER BY `order`"
+    );
+
+    let insert = InsertQuery::<Group>::new().value("order", 1).value("displayName", "Admins");
+    assert_eq!(
+        insert.build(),
+        r#"INSERT INTO "group" ("order", "displayName") VALUES (1, 'Admins');"#
+    );
+
+    let update = UpdateQuery::<Group>::new().set("displayName", "Staff").filter_eq("order", 1);
+    assert_eq!(update.build(), r#"UPDATE "group" SET "displayName" = 'Staff' WHERE "order" = 1"#);
+
+    let delete = DeleteQuery::<Group>::new().filter_eq("order", 1);
+    assert_eq!(delete.build_for(Dialect::Sqlite), r#"DELETE FROM "group" WHERE "order" = 1"#);
+
+    assert_eq!(
+        MigrationGenerator::generate::<Group>().up,
+        [
+            r#"CREATE TABLE "group" (id INTEGER PRIMARY KEY, "order" INTEGER NOT NULL, "displayName" VARCHAR(255) NOT NULL);"#
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate_for::<Group>(Dialect::MySql).up,
+        [
+            "CREATE TABLE `group` (`id` INTEGER PRIMARY KEY, `order` INTEGER NOT NULL, `displayName` VARCHAR(255) NOT NULL);"
+        ]
+    );
+}
+
+#[test]
+fn embedded_quote_characters_are_doubled() {
+    assert_eq!(Dialect::Postgres.quote_identifier("a\"b"), r#""a""b""#);
+    assert_eq!(Dialect::MySql.quote_identifier("a`b"), "`a``b`");
+}
+
+#[test]
+fn placeholders_stay_contiguous_across_subqueries() {
+    let archived = SelectQuery::<ArchivedUser>::new().select(&["id"]).filter_eq("name", "ada");
+    let query = SelectQuery::<User>::new()
+        .filter_eq("name", "ada")
+        .filter_in_subquery("id", archived)
+        .filter_gt("id", 10);
+
+    let (sql, params) = query.build_parameterized_for(PlaceholderStyle::Dollar);
+    assert_eq!(
+        sql,
+        "SELECT * FROM users WHERE name = $1 AND id IN (SELECT id FROM archived_users WHERE name = $2) AND id > $3"
+    );
+    assert_eq!(params, vec!["ada".to_value(), "ada".to_value(), Value::Integer(10)]);
+
+    let (sql, params) = query.build_parameterized_for(PlaceholderStyle::Question);
+    assert_eq!(
+        sql,
+        "SELECT * FROM users WHERE name = ? AND id IN (SELECT id FROM archived_users WHERE name = ?) AND id > ?"
+    );
+    assert_eq!(params.len(), 3);
+
+    // Repeated values share a name, so each is bound once
+    let (sql, params) = query.build_parameterized_for(PlaceholderStyle::Named);
+    assert_eq!(
+        sql,
+        "SELECT * FROM users WHERE name = :p1 AND id IN (SELECT id FROM archived_users WHERE name = :p1) AND id > :p2"
+    );
+    assert_eq!(params, vec!["ada".to_value(), Value::Integer(10)]);
+
+    let (sql, _) =
+        query.build_parameterized_for(Dialect::MySql.with_placeholders(PlaceholderStyle::Named));
+    assert_eq!(
+        sql,
+        "SELECT * FROM `users` WHERE `name` = :p1 AND `id` IN (SELECT `id` FROM `archived_users` WHERE `name` = :p1) AND `id` > :p2"
+    );
+}
+
+#[test]
+fn model_updates_and_deletes_filter_on_every_key_column() {
+    let membership = Membership { team_id: 3, user_id: 7, role: "owner".to_string() };
+    assert_eq!(
+        UpdateQuery::from_model(&membership).build(),
+        "UPDATE memberships SET role = 'owner' WHERE team_id = 3 AND user_id = 7"
+    );
+    assert_eq!(
+        DeleteQuery::from_model(&membership).build_for(Dialect::Postgres),
+        r#"DELETE FROM "memberships" WHERE "team_id" = 3 AND "user_id" = 7"#
+    );
+    assert_eq!(
+        DeleteQuery::from_model(&Tag { label: "rust".to_string() }).try_build(),
+        Err(QueryError::MissingPrimaryKey { table: "tags".to_string() })
+    );
+}
+
+#[test]
+fn schema_qualified_tables() {
+    assert_eq!(Contact::table().qualified_name(), "crm.contacts");
+    assert_eq!(Statement::table().schema.as_deref(), Some("billing"));
+    assert_eq!(Statement::table().name, "statements");
+
+    let select = SelectQuery::<Statement>::new()
+        .inner_join_model::<Contact>("crm.contacts.id = billing.statements.contact_id")
+        .filter_eq("billing.statements.id", 1);
+    assert_eq!(
+        select.build(),
+        "SELECT * FROM billing.statements INNER JOIN crm.contacts ON crm.contacts.id = billing.statements.contact_id WHERE billing.statements.id = 1"
+    );
+    assert_eq!(
+        select.build_for(Dialect::Postgres),
+        r#"SELECT * FROM "billing"."statements" INNER JOIN "crm"."contacts" ON crm.contacts.id = billing.statements.contact_id WHERE "billing"."statements"."id" = 1"#
+    );
+
+    let contact = Contact { id: 0, name: "Ann".to_string() };
+    assert_eq!(
+        InsertQuery::from_model(&contact).build(),
+        "INSERT INTO crm.contacts (name) VALUES ('Ann');"
+    );
+    assert_eq!(
+        InsertQuery::from_model(&contact).build_for(Dialect::MySql),
+        "INSERT INTO `crm`.`contacts` (`name`) VALUES ('Ann');"
+    );
+
+    assert_eq!(
+        MigrationGenerator::generate_for::<Contact>(Dialect::Postgres).up,
+        [
+            r#"CREATE TABLE "crm"."contacts" ("id" INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY, "name" VARCHAR(255) NOT NULL);"#
+        ]
+    );
+    assert_eq!(
+        MigrationGenerator::generate::<Statement>().up,
+        ["CREATE TABLE billing.statements (id INTEGER PRIMARY KEY, contact_id INTEGER NOT NULL);"]
+    );
+}
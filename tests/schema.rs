@@ -0,0 +1,74 @@
+mod common;
+
+use common::User;
+use rusty_orm::model::{Model, Table};
+use rusty_orm::schema::{export, import, ImportError, SchemaFormat, SCHEMA_VERSION};
+use rusty_orm_macros::Model;
+
+#[allow(dead_code)]
+#[derive(Model)]
+#[table_name = "posts"]
+#[model(unique = "user_id, slug")]
+struct Post {
+    #[column(primary_key, auto_increment)]
+    id: i64,
+    #[column(references = "users.id", on_delete = "cascade", index)]
+    user_id: i32,
+    #[column(length = 120)]
+    slug: String,
+    #[column(type = "Text")]
+    body: Option<String>,
+    #[column(type = "Jsonb", default_expr = "'{}'")]
+    metadata: serde_json::Value,
+    #[column(default = "draft")]
+    status: String,
+}
+
+fn tables() -> Vec<Table> {
+    vec![User::table(), Post::table()]
+}
+
+#[test]
+fn schemas_round_trip_through_json() {
+    let exported = export(&tables(), SchemaFormat::Json);
+    assert_eq!(import(&exported, SchemaFormat::Json).unwrap(), tables());
+}
+
+#[test]
+fn exported_schemas_record_their_version() {
+    let exported = export(&tables(), SchemaFormat::Json);
+    let document: serde_json::Value = serde_json::from_str(&exported).unwrap();
+    assert_eq!(document["version"], SCHEMA_VERSION);
+    assert_eq!(document["tables"][0]["name"], "users");
+    assert_eq!(document["tables"][1]["name"], "posts");
+}
+
+#[test]
+fn unknown_versions_are_rejected() {
+    let document = r#"{"version": 2, "tables": [{"name": "users", "columns": "not read"}]}"#;
+    let error = import(document, SchemaFormat::Json).unwrap_err();
+    assert!(matches!(error, ImportError::UnsupportedVersion { found: 2, expected: 1 }));
+    assert_eq!(error.to_string(), "schema format version 2 is not supported; expected version 1");
+}
+
+#[test]
+fn documents_without_an_envelope_are_rejected() {
+    let tables = serde_json::to_string(&tables()).unwrap();
+    let error = import(&tables, SchemaFormat::Json).unwrap_err();
+    assert!(matches!(error, ImportError::Json(_)));
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn schemas_round_trip_through_yaml() {
+    let exported = export(&tables(), SchemaFormat::Yaml);
+    assert!(exported.lines().any(|line| line == "version: 1"));
+    assert_eq!(import(&exported, SchemaFormat::Yaml).unwrap(), tables());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn unknown_yaml_versions_are_rejected() {
+    let error = import("version: 0\ntables: []\n", SchemaFormat::Yaml).unwrap_err();
+    assert!(matches!(error, ImportError::UnsupportedVersion { found: 0, expected: 1 }));
+}
@@ -0,0 +1,296 @@
+mod common;
+
+use common::User;
+use rusty_orm::dialect::Dialect;
+use rusty_orm::escape::escape_like;
+use rusty_orm::executor::Connection;
+use rusty_orm::migration::{Migration, MigrationGenerator, MigrationRunner};
+use rusty_orm::model::ModelExt;
+use rusty_orm::model::Value;
+use rusty_orm::query_builder::{InsertQuery, SelectQuery, UpdateQuery};
+use rusty_orm::sqlite::SqliteConnection;
+use rusty_orm::statement_cache::CacheStats;
+use rusty_orm_macros::{FromRow, Model};
+
+fn connection_with_users(names: &[&str]) -> SqliteConnection {
+    let mut connection = SqliteConnection::open_in_memory().unwrap();
+    let migration = MigrationGenerator::generate_for::<User>(Dialect::Sqlite);
+    connection.execute(&migration.up_sql(), &[]).unwrap();
+    for name in names {
+        let (sql, params) = InsertQuery::<User>::new()
+            .value("name", *name)
+            .value("email", format!("{}@example.com", name.len()))
+            .value("is_active", true)
+            .build_parameterized_for(Dialect::Sqlite);
+        connection.execute(&sql, &params).unwrap();
+    }
+    connection
+}
+
+fn names(users: Vec<User>) -> Vec<String> {
+    users.into_iter().map(|user| user.name).collect()
+}
+
+#[test]
+fn escaped_like_pattern_matches_literally() {
+    let mut connection = connection_with_users(&["50% off", "500 off", "50_50", "5000", "O'Brien"]);
+
+    let percent = SelectQuery::<User>::new()
+        .filter_like("name", &format!("{}%", escape_like("50%")))
+        .order_by(&["id"]);
+    assert_eq!(names(percent.fetch_all(&mut connection).unwrap()), ["50% off"]);
+
+    let underscore = SelectQuery::<User>::new()
+        .filter_like("name", &format!("%{}%", escape_like("_")))
+        .order_by(&["id"]);
+    assert_eq!(names(underscore.fetch_all(&mut connection).unwrap()), ["50_50"]);
+
+    let quote =
+        SelectQuery::<User>::new().filter_like("name", &format!("%{}", escape_like("'Brien")));
+    assert_eq!(names(quote.fetch_all(&mut connection).unwrap()), ["O'Brien"]);
+}
+
+#[test]
+fn ilike_fallback_ignores_case() {
+    let mut connection = connection_with_users(&["Ann", "JOANNA", "Bob"]);
+
+    let query = SelectQuery::<User>::new().filter_ilike("name", "%ann%").order_by(&["id"]);
+    assert_eq!(names(query.fetch_all(&mut connection).unwrap()), ["Ann", "JOANNA"]);
+}
+
+#[test]
+fn count_query_counts_distinct_rows() {
+    let mut connection = connection_with_users(&["Ann", "Ann", "Bob", "Cid"]);
+    let query = SelectQuery::<User>::new().select(&["name"]).distinct().paginate(1, 2);
+
+    let (sql, params) = query.count_query().build_parameterized_for(Dialect::Sqlite);
+    let rows = connection.query(&sql, &params).unwrap();
+    assert_eq!(rows[0].try_get::<i64>("COUNT(*)").unwrap(), 3);
+
+    let (sql, params) = query.build_parameterized_for(Dialect::Sqlite);
+    assert_eq!(connection.query(&sql, &params).unwrap().len(), 2);
+}
+
+#[test]
+fn user_is_inserted_then_updated_then_deleted() {
+    let mut connection = connection_with_users(&["Bob"]);
+    let mut user = User {
+        id: 0,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        is_active: true,
+    };
+
+    user.save(&mut connection).unwrap();
+    assert_eq!(user.id, 2);
+    assert_eq!(User::find(&mut connection, 2).unwrap(), Some(user.clone()));
+
+    user.name = "Anna".to_string();
+    user.save(&mut connection).unwrap();
+    assert_eq!(User::count(&mut connection).unwrap(), 2);
+    assert_eq!(User::find(&mut connection, 2).unwrap().unwrap().name, "Anna");
+
+    assert_eq!(user.delete(&mut connection).unwrap(), 1);
+    assert_eq!(User::find(&mut connection, 2).unwrap(), None);
+    assert_eq!(names(User::all(&mut connection).unwrap()), ["Bob"]);
+}
+
+#[test]
+fn save_inserts_a_deleted_instance_again() {
+    let mut connection = connection_with_users(&["Ann"]);
+    let mut user = User::find(&mut connection, 1).unwrap().unwrap();
+
+    user.delete(&mut connection).unwrap();
+    user.save(&mut connection).unwrap();
+    assert_eq!(User::find(&mut connection, 1).unwrap(), Some(user));
+}
+
+#[test]
+fn repeated_fetches_hit_the_statement_cache() {
+    let mut connection = connection_with_users(&["Ann", "Bob"]);
+    let before = connection.stats();
+
+    for name in ["Ann", "Bob", "Cid"] {
+        SelectQuery::<User>::new().filter_eq("name", name).fetch_optional(&mut connection).unwrap();
+    }
+    let stats = connection.stats();
+    assert_eq!(stats.misses - before.misses, 1);
+    assert_eq!(stats.hits - before.hits, 2);
+}
+
+#[test]
+fn statements_failing_to_prepare_are_not_counted_as_cached() {
+    let mut connection = SqliteConnection::open_in_memory().unwrap();
+    assert!(connection.query("SELECT * FROM missing", &[]).is_err());
+    assert!(connection.query("SELECT * FROM missing", &[]).is_err());
+    assert_eq!(connection.stats(), CacheStats::default());
+
+    connection.execute("CREATE TABLE missing (id INTEGER)", &[]).unwrap();
+    connection.query("SELECT * FROM missing", &[]).unwrap();
+    assert_eq!(connection.stats(), CacheStats { hits: 0, misses: 2 });
+}
+
+#[test]
+fn disabled_statement_cache_never_hits() {
+    let mut connection = connection_with_users(&["Ann"]).with_statement_cache_capacity(0);
+    let before = connection.stats();
+    for _ in 0..3 {
+        User::find(&mut connection, 1).unwrap();
+    }
+    assert_eq!(connection.stats().hits, before.hits);
+    assert_eq!(connection.stats().misses - before.misses, 3);
+}
+
+#[test]
+fn running_a_migration_clears_the_statement_cache() {
+    let mut connection = connection_with_users(&["Ann"]);
+    User::find(&mut connection, 1).unwrap();
+    User::find(&mut connection, 1).unwrap();
+    let before = connection.stats();
+
+    let migration = Migration {
+        up: vec!["ALTER TABLE users ADD COLUMN nickname TEXT".to_string()],
+        down: vec!["ALTER TABLE users DROP COLUMN nickname".to_string()],
+        checksum: None,
+    };
+    MigrationRunner::with_migrations(
+        &mut connection,
+        vec![("0002_nickname".to_string(), migration)],
+    )
+    .run()
+    .unwrap();
+
+    // The user's statement is prepared anew against the altered table
+    let misses = connection.stats().misses;
+    User::find(&mut connection, 1).unwrap();
+    assert_eq!(connection.stats().misses, misses + 1);
+    assert!(connection.stats().hits >= before.hits);
+}
+
+#[test]
+fn schema_changes_through_query_clear_the_statement_cache() {
+    let mut connection = connection_with_users(&["Ann"]);
+    User::find(&mut connection, 1).unwrap();
+
+    connection.query("DROP TABLE IF EXISTS archive", &[]).unwrap();
+    let misses = connection.stats().misses;
+    User::find(&mut connection, 1).unwrap();
+    assert_eq!(connection.stats().misses, misses + 1);
+}
+
+#[test]
+fn grouped_counts_round_trip() {
+    let mut connection = connection_with_users(&["Ann", "Bob", "Cid"]);
+    connection.execute("UPDATE users SET is_active = 0 WHERE name = 'Bob'", &[]).unwrap();
+
+    let query = SelectQuery::<User>::new()
+        .select(&["is_active"])
+        .select_count("*", Some("n"))
+        .group_by(&["is_active"])
+        .order_by(&["is_active"]);
+    let (sql, params) = query.build_parameterized_for(Dialect::Sqlite);
+    let rows = connection.query(&sql, &params).unwrap();
+
+    let counts: Vec<(i64, i64)> = rows
+        .iter()
+        .map(|row| (row.try_get("is_active").unwrap(), row.try_get("n").unwrap()))
+        .collect();
+    assert_eq!(counts, [(0, 1), (1, 2)]);
+}
+
+#[test]
+fn model_instance_round_trips_through_insert_and_update() {
+    let mut connection = connection_with_users(&[]);
+    let mut user = User {
+        id: 0,
+        name: "Ann".to_string(),
+        email: "ann@example.com".to_string(),
+        is_active: true,
+    };
+
+    let (sql, params) = InsertQuery::from_model(&user).build_parameterized_for(Dialect::Sqlite);
+    connection.execute(&sql, &params).unwrap();
+    user.id = i32::try_from(connection.last_insert_rowid()).unwrap();
+
+    user.email = "ann@example.org".to_string();
+    user.is_active = false;
+    let (sql, params) = UpdateQuery::from_model(&user).build_parameterized_for(Dialect::Sqlite);
+    assert_eq!(connection.execute(&sql, &params).unwrap(), 1);
+
+    let stored = SelectQuery::<User>::new().filter_eq("id", user.id).fetch_one(&mut connection);
+    assert_eq!(stored.unwrap(), user);
+}
+
+#[test]
+fn inserted_rows_read_back_with_their_generated_id() {
+    let mut connection = connection_with_users(&["Ada", "Grace"]);
+    assert_eq!(connection.last_insert_rowid(), 2);
+
+    let (sql, params) = InsertQuery::<User>::new()
+        .value("name", "Linus")
+        .value("email", "linus@example.com")
+        .value("is_active", false)
+        .build_parameterized_for(Dialect::Sqlite);
+    assert_eq!(connection.execute(&sql, &params).unwrap(), 1);
+    let id = connection.last_insert_rowid();
+    assert_eq!(id, 3);
+
+    let user = SelectQuery::<User>::new().filter_eq("id", id).fetch_one(&mut connection).unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 3,
+            name: "Linus".to_string(),
+            email: "linus@example.com".to_string(),
+            is_active: false
+        }
+    );
+}
+
+/// A model with the types SQLite stores loosely.
+#[derive(Model, FromRow, Debug, PartialEq)]
+#[table_name = "readings"]
+struct Reading {
+    #[column(primary_key = true, auto_increment = true)]
+    id: i64,
+    is_valid: bool,
+    value: f64,
+    note: Option<String>,
+    raw: Vec<u8>,
+}
+
+#[test]
+fn dynamically_typed_values_read_back_as_their_rust_type() {
+    let mut connection = SqliteConnection::open_in_memory().unwrap();
+    let migration = MigrationGenerator::generate_for::<Reading>(Dialect::Sqlite);
+    connection.execute(&migration.up_sql(), &[]).unwrap();
+    for (is_valid, value, note) in [(true, 2.0, None), (false, 0.5, Some("drifted"))] {
+        let (sql, params) = InsertQuery::<Reading>::new()
+            .value("is_valid", is_valid)
+            .value("value", value)
+            .value("note", note)
+            .value("raw", vec![0u8, 255])
+            .build_parameterized_for(Dialect::Sqlite);
+        connection.execute(&sql, &params).unwrap();
+    }
+
+    // Booleans are stored as integers
+    let rows = connection.query("SELECT is_valid, value FROM readings ORDER BY id", &[]).unwrap();
+    assert_eq!(rows[0].get("is_valid"), Some(&Value::BigInt(1)));
+
+    let readings =
+        SelectQuery::<Reading>::new().order_by(&["id"]).fetch_all(&mut connection).unwrap();
+    assert_eq!(
+        readings,
+        [
+            Reading { id: 1, is_valid: true, value: 2.0, note: None, raw: vec![0, 255] },
+            Reading {
+                id: 2,
+                is_valid: false,
+                value: 0.5,
+                note: Some("drifted".to_string()),
+                raw: vec![0, 255]
+            },
+        ]
+    );
+}
@@ -0,0 +1,95 @@
+//! Runs against an in-memory database through sqlx's SQLite driver.
+
+use rusty_orm::dialect::Dialect;
+use rusty_orm::error::OrmError;
+use rusty_orm::executor::AsyncConnection;
+use rusty_orm::expr::Expr;
+use rusty_orm::migration::MigrationGenerator;
+use rusty_orm::model::{ModelExt, Value};
+use rusty_orm::query_builder::SelectQuery;
+use rusty_orm::sqlx::SqlxConnection;
+use rusty_orm_macros::{FromRow, IntoInsert, Model};
+
+#[derive(Model, FromRow, IntoInsert, Debug, Clone, PartialEq)]
+#[table_name = "notes"]
+struct Note {
+    #[column(type = "Integer", primary_key = true, auto_increment = true)]
+    id: i32,
+    #[column(type = "Text")]
+    title: String,
+    #[column(type = "BigInt")]
+    views: i64,
+    #[column(type = "Float")]
+    score: f64,
+    #[column(type = "Text")]
+    body: Option<String>,
+}
+
+async fn connection_with_notes() -> SqlxConnection {
+    let mut connection = SqlxConnection::connect("sqlite::memory:").await.unwrap();
+    let migration = MigrationGenerator::generate_for::<Note>(Dialect::Sqlite);
+    for statement in &migration.up {
+        connection.execute(statement, &[]).await.unwrap();
+    }
+    connection
+}
+
+#[tokio::test]
+async fn models_round_trip() {
+    let mut connection = connection_with_notes().await;
+    let mut note =
+        Note { id: 0, title: "Groceries".to_string(), views: 1 << 40, score: 0.5, body: None };
+    note.save_async(&mut connection).await.unwrap();
+    assert_eq!(note.id, 1);
+    assert_eq!(Note::find_async(&mut connection, 1).await.unwrap(), Some(note.clone()));
+
+    note.body = Some("Milk, eggs".to_string());
+    note.save_async(&mut connection).await.unwrap();
+    assert_eq!(Note::all_async(&mut connection).await.unwrap(), [note.clone()]);
+    assert_eq!(Note::count_async(&mut connection).await.unwrap(), 1);
+
+    note.delete_async(&mut connection).await.unwrap();
+    assert_eq!(Note::find_async(&mut connection, 1).await.unwrap(), None);
+    assert!(!Note::exists_async(&mut connection, Expr::col("id").eq(1)).await.unwrap());
+}
+
+#[tokio::test]
+async fn select_queries_fetch_through_the_pool() {
+    let mut connection = connection_with_notes().await;
+    for title in ["a", "b", "c"] {
+        let mut note = Note { id: 0, title: title.to_string(), views: 0, score: 0.0, body: None };
+        note.save_async(&mut connection).await.unwrap();
+    }
+
+    let query = SelectQuery::<Note>::new().filter_gt("id", 1).order_by(&["id"]);
+    let titles: Vec<_> = query.fetch_all_async(&mut connection).await.unwrap();
+    assert_eq!(titles.iter().map(|note| note.title.as_str()).collect::<Vec<_>>(), ["b", "c"]);
+
+    let query = SelectQuery::<Note>::new().filter_eq("title", "z");
+    assert_eq!(query.fetch_optional_async(&mut connection).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn rows_read_the_storage_class_of_each_column() {
+    let mut connection = SqlxConnection::connect("sqlite::memory:").await.unwrap();
+    let rows = connection
+        .query("SELECT 1 AS one, 2.5 AS half, 'x' AS text, x'00ff' AS blob, NULL AS absent", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows[0].get("one"), Some(&Value::BigInt(1)));
+    assert_eq!(rows[0].get("half"), Some(&Value::Float(2.5)));
+    assert_eq!(rows[0].get("text"), Some(&Value::Text("x".to_string())));
+    assert_eq!(rows[0].get("blob"), Some(&Value::Bytes(vec![0, 255])));
+    assert_eq!(rows[0].get("absent"), Some(&Value::Null));
+}
+
+#[tokio::test]
+async fn database_errors_are_reported() {
+    let mut connection = SqlxConnection::connect("sqlite::memory:").await.unwrap();
+    let error = connection.query("SELECT * FROM missing", &[]).await.unwrap_err();
+    assert!(matches!(&error, OrmError::Execution(_)));
+    assert!(error.to_string().contains("no such table: missing"), "{}", error);
+
+    let error = SqlxConnection::connect("redis://localhost").await.unwrap_err();
+    assert_eq!(error.to_string(), "database error: unsupported database URL `redis://localhost`");
+}
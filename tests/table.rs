@@ -0,0 +1,70 @@
+mod common;
+
+use common::User;
+use rusty_orm::migration::MigrationGenerator;
+use rusty_orm::model::{DataType, Model, SchemaError};
+use rusty_orm::query_builder::{QueryError, SelectQuery};
+
+#[test]
+fn columns_are_looked_up_by_name() {
+    let table = User::table();
+    assert_eq!(table.column("email").map(|c| &c.data_type), Some(&DataType::Varchar(150)));
+    assert!(table.column("missing").is_none());
+    assert_eq!(table.column_names(), ["id", "name", "email", "is_active"]);
+    assert_eq!(table.primary_key().iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["id"]);
+    assert_eq!(table, User::table());
+}
+
+#[test]
+fn valid_tables_pass_validation() {
+    assert_eq!(User::table().validate(), Ok(()));
+}
+
+#[test]
+fn empty_table_names_are_rejected() {
+    let mut table = User::table();
+    table.name.clear();
+    assert_eq!(table.validate(), Err(SchemaError::EmptyTableName));
+}
+
+#[test]
+fn tables_without_columns_are_rejected() {
+    let mut table = User::table();
+    table.columns.clear();
+    assert_eq!(table.validate(), Err(SchemaError::NoColumns { table: "users".to_string() }));
+}
+
+#[test]
+fn duplicate_columns_are_rejected() {
+    let mut table = User::table();
+    let email = table.columns[2].clone();
+    table.columns.push(email);
+    assert_eq!(
+        table.validate(),
+        Err(SchemaError::DuplicateColumn {
+            table: "users".to_string(),
+            column: "email".to_string()
+        })
+    );
+}
+
+#[test]
+fn empty_varchars_are_rejected() {
+    let mut table = User::table();
+    table.columns[1].data_type = DataType::Varchar(0);
+    let error =
+        SchemaError::EmptyVarchar { table: "users".to_string(), column: "name".to_string() };
+    assert_eq!(table.validate(), Err(error.clone()));
+
+    // The migration generator reports the same errors
+    assert_eq!(MigrationGenerator::generate_all(&[table]).unwrap_err(), error);
+}
+
+#[test]
+fn validated_queries_check_columns_against_the_table() {
+    let query = SelectQuery::<User>::new().filter_eq("nmae", "Ann");
+    assert_eq!(
+        query.try_build(),
+        Err(QueryError::UnknownColumn { table: "users".to_string(), column: "nmae".to_string() })
+    );
+}
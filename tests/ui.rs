@@ -0,0 +1,10 @@
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+    // Types from optional dependencies, only available with their features
+    if cfg!(all(feature = "chrono", feature = "uuid")) {
+        t.pass("tests/ui/chrono_uuid/*.rs");
+    }
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rusty_orm::model::{DataType, Model};
+use rusty_orm_macros::Model;
+use uuid::Uuid;
+
+#[derive(Model)]
+#[table_name = "bookings"]
+struct Booking {
+    #[column(primary_key)]
+    id: Uuid,
+    day: NaiveDate,
+    starts_at: NaiveTime,
+    created_at: NaiveDateTime,
+    confirmed_at: DateTime<Utc>,
+    cancelled_at: Option<chrono::DateTime<Utc>>,
+    #[column(type = "Text")]
+    reference: Uuid,
+}
+
+fn main() {
+    let types: Vec<DataType> = Booking::table().columns.into_iter().map(|c| c.data_type).collect();
+    assert_eq!(
+        types,
+        [
+            DataType::Uuid,
+            DataType::Date,
+            DataType::Time,
+            DataType::DateTime,
+            DataType::TimestampTz,
+            DataType::TimestampTz,
+            DataType::Text,
+        ]
+    );
+    assert!(Booking::table().column("cancelled_at").unwrap().is_nullable);
+}
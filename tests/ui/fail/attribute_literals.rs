@@ -0,0 +1,27 @@
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+struct IntegerFlag {
+    #[column(primary_key = 1)]
+    id: i32,
+}
+
+#[derive(Model)]
+struct MisspelledFlag {
+    #[column(unique = "yes")]
+    email: String,
+}
+
+#[derive(Model)]
+struct BooleanLength {
+    #[column(length = true)]
+    name: String,
+}
+
+#[derive(Model)]
+struct NegativeLength {
+    #[column(length = "-5")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use rusty_orm::model::IntoInsert;
+use rusty_orm_macros::{IntoInsert, Model};
+
+#[derive(Clone)]
+struct Opaque;
+
+#[derive(Model, IntoInsert)]
+struct Audit<T> {
+    #[column(primary_key)]
+    id: i64,
+    payload: T,
+}
+
+fn main() {
+    let audit = Audit { id: 1, payload: Opaque };
+    let _ = audit.insert_values();
+}
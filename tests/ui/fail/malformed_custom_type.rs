@@ -0,0 +1,15 @@
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+struct Unclosed {
+    #[column(type = "Custom(user_role")]
+    role: String,
+}
+
+#[derive(Model)]
+struct Empty {
+    #[column(raw_type = " ")]
+    address: String,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+struct MissingParenthesis {
+    #[column(type = "Varchar100")]
+    name: String,
+}
+
+#[derive(Model)]
+struct NotANumber {
+    #[column(type = "Varchar(abc)")]
+    name: String,
+}
+
+#[derive(Model)]
+struct UnknownType {
+    #[column(type = "Strnig")]
+    name: String,
+}
+
+fn main() {}
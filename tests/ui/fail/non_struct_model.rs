@@ -0,0 +1,9 @@
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+enum Status {
+    Active,
+    Inactive,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+struct Author {
+    #[column(primary_key)]
+    id: i32,
+    #[column(skip, name = "display")]
+    display_name: String,
+}
+
+fn main() {}
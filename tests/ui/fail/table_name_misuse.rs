@@ -0,0 +1,15 @@
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+#[table_name(users)]
+struct User {
+    id: i32,
+}
+
+#[derive(Model)]
+#[table_name = 42]
+struct Account {
+    id: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+struct User {
+    #[column(primary_ky = "true")]
+    id: i32,
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use rusty_orm::model::{DataType, Model};
+use rusty_orm_macros::Model;
+
+#[derive(Model)]
+#[table_name = "accounts"]
+struct Account {
+    #[column(primary_key = true, auto_increment = true)]
+    id: i64,
+    #[column(length = 100, unique = true)]
+    email: String,
+    #[column(length = "50", unique = "true")]
+    handle: String,
+    #[column(nullable = false)]
+    bio: Option<String>,
+    #[column(primary_key = "false", nullable)]
+    website: String,
+}
+
+fn main() {
+    let table = Account::table();
+    let id = table.column("id").unwrap();
+    assert!(id.is_primary_key && id.is_auto_increment);
+
+    let email = table.column("email").unwrap();
+    assert_eq!(email.data_type, DataType::Varchar(100));
+    assert!(email.is_unique);
+
+    let handle = table.column("handle").unwrap();
+    assert_eq!(handle.data_type, DataType::Varchar(50));
+    assert!(handle.is_unique);
+
+    assert!(!table.column("bio").unwrap().is_nullable);
+    let website = table.column("website").unwrap();
+    assert!(!website.is_primary_key && website.is_nullable);
+}
@@ -0,0 +1,10 @@
+// The source `codegen::generate_model_source` generates for the table of
+// tests/codegen.rs, compiled on its own
+use rusty_orm::model::Model;
+use rusty_orm_macros::Model;
+
+include!("../../codegen/order.rs");
+
+fn main() {
+    assert_eq!(Order::table().qualified_name(), "shop.orders");
+}
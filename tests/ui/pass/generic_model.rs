@@ -0,0 +1,27 @@
+use rusty_orm::model::{DataType, IntoInsert, Model, Value};
+use rusty_orm::row::{FromRow, Row};
+use rusty_orm_macros::{FromRow, IntoInsert, Model};
+
+#[derive(Model, FromRow, IntoInsert, Debug, PartialEq)]
+#[table_name = "audits"]
+struct Audit<T>
+where
+    T: Clone,
+{
+    #[column(primary_key)]
+    id: i64,
+    #[column(type = "Text")]
+    payload: T,
+}
+
+fn main() {
+    assert_eq!(Audit::<String>::table().column("payload").unwrap().data_type, DataType::Text);
+    assert_eq!(Audit::<i64>::table().name, "audits");
+
+    let audit = Audit { id: 1, payload: 42i64 };
+    assert_eq!(audit.insert_values()[1], ("payload".to_string(), Value::BigInt(42)));
+
+    let row = Row::new().with("id", 1i64).with("payload", "changed");
+    let read = Audit::<String>::from_row(&row).unwrap();
+    assert_eq!(read, Audit { id: 1, payload: "changed".to_string() });
+}
@@ -0,0 +1,19 @@
+use rusty_orm::model::{IntoInsert, Model, Value};
+use rusty_orm_macros::{IntoInsert, Model};
+
+#[derive(Model, IntoInsert)]
+#[table_name = "notes"]
+struct Note<'a> {
+    #[column(primary_key)]
+    id: i32,
+    body: &'a str,
+}
+
+fn main() {
+    let body = String::from("hello");
+    let note = Note { id: 1, body: &body };
+
+    assert_eq!(Note::table().name, "notes");
+    assert_eq!(Note::BODY, "body");
+    assert_eq!(note.insert_values()[1], ("body".to_string(), Value::Text("hello".to_string())));
+}